@@ -0,0 +1,105 @@
+//! Benchmarks pareto fronts on random n-dimensional points.
+//!
+//! run with `cargo bench --bench pareto_fronts`
+
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use do_util::priority_queue::{PriorityQueue, ParetoFront};
+use do_util::priority_queue::pareto_list::ListParetoFront;
+use do_util::priority_queue::kd_tree::KDTreeFront;
+use do_util::priority_queue::morton::MortonFront;
+use do_util::priority_queue::rtree::RTreeFront;
+use do_util::priority_queue::range_tree::RangeTreeFront;
+use do_util::priority_queue::util::CartesianParetoElement;
+
+/// generates random points
+fn random_points<const NB_DIM:usize>(n:usize, max:u32, seed:u64) -> Vec<CartesianParetoElement<NB_DIM>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| {
+        let mut coords = [0;NB_DIM];
+        for c in coords.iter_mut() { *c = rng.gen_range(0..max); }
+        CartesianParetoElement::new(coords)
+    }).collect()
+}
+
+/// generates random points of equal coordinate sums (no point dominates another)
+fn antichain_points<const NB_DIM:usize>(n:usize, max:u32, seed:u64) -> Vec<CartesianParetoElement<NB_DIM>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| {
+        let mut coords = [0;NB_DIM];
+        for c in coords[1..].iter_mut() { *c = rng.gen_range(0..max); }
+        coords[0] = (NB_DIM as u32 - 1) * max - coords[1..].iter().sum::<u32>();
+        CartesianParetoElement::new(coords)
+    }).collect()
+}
+
+/// inserts all points in the front, then pops all of them. Returns the time taken
+fn bench_front<F, const NB_DIM:usize>(points:&[CartesianParetoElement<NB_DIM>]) -> Duration
+where F:ParetoFront<u32,CartesianParetoElement<NB_DIM>>+PriorityQueue<u32,CartesianParetoElement<NB_DIM>> {
+    let start = Instant::now();
+    let mut front = F::default();
+    for p in points {
+        front.insert(p.clone());
+    }
+    while front.pop_min().is_some() {}
+    start.elapsed()
+}
+
+/// inserts all points in the front, then checks for each query whether it is dominated.
+/// Returns the time taken by the queries
+fn bench_queries<F, const NB_DIM:usize>(points:&[CartesianParetoElement<NB_DIM>], queries:&[CartesianParetoElement<NB_DIM>]) -> Duration
+where F:ParetoFront<u32,CartesianParetoElement<NB_DIM>>+PriorityQueue<u32,CartesianParetoElement<NB_DIM>> {
+    let mut front = F::default();
+    for p in points {
+        front.insert(p.clone());
+    }
+    let start = Instant::now();
+    let nb_dominated = queries.iter().filter(|q| front.find_dominating(q).is_some()).count();
+    std::hint::black_box(nb_dominated);
+    start.elapsed()
+}
+
+/// returns the time taken by f (its result is kept alive until the end of the measure)
+fn bench_bulk_loading<R>(f:impl FnOnce() -> R) -> Duration {
+    let start = Instant::now();
+    std::hint::black_box(f());
+    start.elapsed()
+}
+
+fn run<const NB_DIM:usize>(n:usize) {
+    let points = random_points::<NB_DIM>(n, 10_000, 42);
+    println!("{} points, {} dimensions", n, NB_DIM);
+    println!("\tlist:    {:?}", bench_front::<ListParetoFront<u32,_>,NB_DIM>(&points));
+    println!("\tkd-tree: {:?}", bench_front::<KDTreeFront<u32,_,NB_DIM>,NB_DIM>(&points));
+    println!("\tmorton:  {:?}", bench_front::<MortonFront<u32,_,NB_DIM>,NB_DIM>(&points));
+    println!("\tr-tree:  {:?}", bench_front::<RTreeFront<u32,_,NB_DIM>,NB_DIM>(&points));
+    println!("\trange-tree: {:?}", bench_front::<RangeTreeFront<u32,_,NB_DIM>,NB_DIM>(&points));
+    // query-heavy workload: large front (no dominated point), 10 dominance queries per point
+    // close to the front (hard for the space-partitioning fronts)
+    let front = antichain_points::<NB_DIM>(n, 10_000, 42);
+    let queries = antichain_points::<NB_DIM>(10*n, 10_000, 7);
+    println!("front of {} points, {} dimensions, {} dominance queries", n, NB_DIM, queries.len());
+    println!("\tlist:    {:?}", bench_queries::<ListParetoFront<u32,_>,NB_DIM>(&front, &queries));
+    println!("\tkd-tree: {:?}", bench_queries::<KDTreeFront<u32,_,NB_DIM>,NB_DIM>(&front, &queries));
+    println!("\tmorton:  {:?}", bench_queries::<MortonFront<u32,_,NB_DIM>,NB_DIM>(&front, &queries));
+    println!("\tr-tree:  {:?}", bench_queries::<RTreeFront<u32,_,NB_DIM>,NB_DIM>(&front, &queries));
+    println!("\trange-tree: {:?}", bench_queries::<RangeTreeFront<u32,_,NB_DIM>,NB_DIM>(&front, &queries));
+    // bulk loading of a large front (the dominated points are filtered first)
+    let mut points = antichain_points::<NB_DIM>(10*n, 10_000, 42);
+    points.extend(random_points::<NB_DIM>(10*n, 10_000, 7));
+    println!("bulk loading of {} points, {} dimensions", points.len(), NB_DIM);
+    println!("\tlist:    {:?}", bench_bulk_loading(|| ListParetoFront::<u32,_>::from_vec(points.clone())));
+    println!("\tkd-tree: {:?}", bench_bulk_loading(|| KDTreeFront::<u32,_,NB_DIM>::from_vec(points.clone())));
+    println!("\tmorton:  {:?}", bench_bulk_loading(|| MortonFront::<u32,_,NB_DIM>::from_vec(points.clone())));
+    println!("\tr-tree:  {:?}", bench_bulk_loading(|| RTreeFront::<u32,_,NB_DIM>::from_vec(points.clone())));
+    println!("\trange-tree: {:?}", bench_bulk_loading(|| RangeTreeFront::<u32,_,NB_DIM>::from_vec(points.clone())));
+}
+
+fn main() {
+    run::<2>(10_000);
+    run::<3>(10_000);
+    run::<4>(10_000);
+}
@@ -0,0 +1,71 @@
+//! Benchmarks set stores on random sets made of runs of consecutive elements.
+//! Reports the number of trie nodes with and without path compression, and the time taken
+//! to bulk load the trie.
+//!
+//! run with `cargo bench --bench set_stores`
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use do_util::set_store::SetStore;
+use do_util::set_store::list::ListSetStore;
+use do_util::set_store::set_trie::TrieSetStore;
+
+/// generates random sets made of nb_runs runs of consecutive elements
+fn random_sets(n:usize, nb_runs:usize, run_len:usize, seed:u64) -> Vec<Vec<usize>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| {
+        let mut set:Vec<usize> = Vec::new();
+        for _ in 0..nb_runs {
+            let start = rng.gen_range(0..1000);
+            set.extend(start..start+1+rng.gen_range(0..run_len));
+        }
+        set.sort_unstable();
+        set.dedup();
+        set
+    }).collect()
+}
+
+/// number of nodes of a trie without path compression (number of distinct prefixes)
+fn nb_prefixes(sets:&[Vec<usize>]) -> usize {
+    let prefixes:HashSet<&[usize]> = sets.iter()
+        .flat_map(|s| (0..=s.len()).map(move |i| &s[..i]))
+        .collect();
+    prefixes.len()
+}
+
+/// inserts all sets, then performs a sub-set and a super-set query for each of them.
+/// Returns the time taken
+fn bench_store<S:SetStore<usize>+Default>(sets:&[Vec<usize>]) -> (S,Duration) {
+    let start = Instant::now();
+    let mut store = S::default();
+    for s in sets { store.insert(s); }
+    let mut nb_found = 0;
+    for s in sets {
+        nb_found += store.count_subsets(s);
+        nb_found += store.count_supersets(&s[..s.len().min(2)]);
+    }
+    assert!(nb_found >= sets.len());
+    (store, start.elapsed())
+}
+
+fn run(n:usize, nb_runs:usize, run_len:usize) {
+    let sets = random_sets(n, nb_runs, run_len, 42);
+    println!("{} sets, {} runs of at most {} elements", n, nb_runs, run_len);
+    println!("\tlist: {:?}", bench_store::<ListSetStore<usize>>(&sets).1);
+    let (trie, duration) = bench_store::<TrieSetStore>(&sets);
+    println!("\ttrie: {:?}", duration);
+    println!("\ttrie nodes: {} (without path compression: {})", trie.node_count(), nb_prefixes(&sets));
+    let start = Instant::now();
+    let built = TrieSetStore::from_sets(&sets);
+    println!("\ttrie bulk loading: {:?} ({} nodes)", start.elapsed(), built.node_count());
+}
+
+fn main() {
+    run(2_000, 1, 50);
+    run(2_000, 3, 20);
+    run(2_000, 10, 5);
+}
@@ -0,0 +1,215 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::priority_queue::{ExactSizeQueue, FrontEvent, GuidedElement, ParetoElement, ParetoFront};
+use crate::priority_queue::pareto_list::ListParetoFront;
+
+use super::store::DominanceStore;
+
+/// Identifier of a label of a LabelStore (dense, in creation order). Labels are never deleted,
+/// thus identifiers stay valid (e.g. to store the predecessor of a label and rebuild paths).
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct LabelId(usize);
+
+impl LabelId {
+    /// returns the index of the identifier (in 0..nb_labels)
+    pub fn index(&self) -> usize { self.0 }
+}
+
+/// Status of a label
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum LabelStatus {
+    /// in the front of its node, waiting to be extended
+    Open,
+    /// in the front of its node, already popped (extended)
+    Closed,
+    /// evicted from the front of its node by a dominating label
+    Dominated,
+}
+
+/// Label as stored in the node fronts: shares the element with the label arena
+#[derive(Debug)]
+pub struct StoredLabel<Elt> {
+    /// identifier of the label
+    id:LabelId,
+    /// element of the label
+    elt:Rc<Elt>,
+}
+
+impl<Elt> StoredLabel<Elt> {
+    /// returns the identifier of the label
+    pub fn id(&self) -> LabelId { self.id }
+
+    /// returns the element of the label
+    pub fn elt(&self) -> &Elt { &self.elt }
+}
+
+impl<T:Ord,Elt:ParetoElement<T>> ParetoElement<T> for StoredLabel<Elt> {
+    type CoordIterator = Elt::CoordIterator;
+
+    fn coordinates(&self) -> Self::CoordIterator { self.elt.coordinates() }
+
+    fn dominates(&self, other:&Self) -> bool { self.elt.dominates(&other.elt) }
+
+    fn nb_dimensions(&self) -> usize { self.elt.nb_dimensions() }
+
+    fn kth(&self, k:usize) -> T { self.elt.kth(k) }
+}
+
+impl<T:Ord,Elt:GuidedElement<T>> GuidedElement<T> for StoredLabel<Elt> {
+    fn guide(&self) -> T { self.elt.guide() }
+}
+
+/// Label store for multi-objective shortest path algorithms (label-setting or
+/// label-correcting).
+///
+/// Coordinates the fronts of the nodes (a DominanceStore) with a global priority queue of open
+/// labels (ordered by guide). A label pushed at a node is rejected if a label of the node
+/// dominates it, and evicts the labels of the node it dominates. Evicted labels that are still
+/// queued are invalidated lazily: they are skipped when they reach the top of the queue.
+/// - push: insertion in the front of the node + O(log q) (q: queued labels)
+/// - pop: O(log q) amortized
+#[derive(Debug)]
+pub struct LabelStore<K,T,Elt,F=ListParetoFront<T,StoredLabel<Elt>>> {
+    /// fronts of the nodes
+    fronts:DominanceStore<K,T,StoredLabel<Elt>,F>,
+    /// every label created (node, element, status). Rejected labels are not stored
+    labels:Vec<(K,Rc<Elt>,LabelStatus)>,
+    /// queued labels (guide, label index), possibly dominated
+    queue:BinaryHeap<Reverse<(T,usize)>>,
+    /// number of open labels
+    nb_open:usize,
+}
+
+impl<K,T,Elt,F> Default for LabelStore<K,T,Elt,F> where T:Ord {
+    fn default() -> Self {
+        Self { fronts:DominanceStore::default(), labels:Vec::new(), queue:BinaryHeap::new(), nb_open:0 }
+    }
+}
+
+impl<K,T,Elt,F> LabelStore<K,T,Elt,F>
+where K:Hash+Eq+Clone, T:Ord, Elt:ParetoElement<T>+GuidedElement<T>,
+F:ParetoFront<T,StoredLabel<Elt>>+ExactSizeQueue<T,StoredLabel<Elt>> {
+    /// creates an empty store
+    pub fn new() -> Self { Self::default() }
+
+    /// creates a label at a node. Returns its identifier if it is not dominated by a label of
+    /// the node (it is then open), None otherwise
+    pub fn push(&mut self, node:K, elt:Elt) -> Option<LabelId> {
+        let id = LabelId(self.labels.len());
+        let shared = Rc::new(elt);
+        let guide = shared.guide();
+        let stored = StoredLabel { id, elt:shared.clone() };
+        let mut evicted = Vec::new();
+        let inserted = self.fronts.insert_observed(node.clone(), stored, |event| {
+            if let FrontEvent::EvictedBy { element, .. } = event { evicted.push(element.id); }
+        });
+        for other in evicted {
+            if self.labels[other.0].2 == LabelStatus::Open { self.nb_open -= 1; }
+            self.labels[other.0].2 = LabelStatus::Dominated;
+        }
+        if !inserted { return None; }
+        self.labels.push((node, shared, LabelStatus::Open));
+        self.queue.push(Reverse((guide, id.0)));
+        self.nb_open += 1;
+        Some(id)
+    }
+
+    /// pops the open label with the smallest guide (it becomes closed), skipping the dominated
+    /// ones
+    pub fn pop(&mut self) -> Option<LabelId> {
+        while let Some(Reverse((_,i))) = self.queue.pop() {
+            if self.labels[i].2 == LabelStatus::Open {
+                self.labels[i].2 = LabelStatus::Closed;
+                self.nb_open -= 1;
+                return Some(LabelId(i));
+            }
+        }
+        None
+    }
+
+    /// returns the smallest guide of an open label
+    pub fn peek_min_guide(&mut self) -> Option<&T> {
+        self.skip_dominated();
+        self.queue.peek().map(|Reverse((guide,_))| guide)
+    }
+
+    /// returns the node of a label
+    pub fn node(&self, id:LabelId) -> &K { &self.labels[id.0].0 }
+
+    /// returns the element of a label
+    pub fn get(&self, id:LabelId) -> &Elt { &self.labels[id.0].1 }
+
+    /// returns the status of a label
+    pub fn status(&self, id:LabelId) -> LabelStatus { self.labels[id.0].2 }
+
+    /// returns the front of a node (open and closed labels)
+    pub fn front(&self, node:&K) -> Option<&F> { self.fronts.front(node) }
+
+    /// returns the number of open labels
+    pub fn nb_open(&self) -> usize { self.nb_open }
+
+    /// returns the number of labels created (including the ones evicted since)
+    pub fn nb_labels(&self) -> usize { self.labels.len() }
+
+    /// returns the number of non-dominated labels (over all the nodes)
+    pub fn nb_non_dominated(&self) -> usize { self.fronts.len() }
+
+    /// removes the dominated labels from the top of the queue
+    fn skip_dominated(&mut self) {
+        while let Some(Reverse((_,i))) = self.queue.peek() {
+            if self.labels[*i].2 == LabelStatus::Open { break; }
+            self.queue.pop();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::PeekKQueue;
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    type Costs = CartesianParetoElement<2>;
+
+    #[test]
+    fn test_lazy_invalidation() {
+        let mut store:LabelStore<usize,u32,Costs> = LabelStore::new();
+        let a = store.push(0, Costs::new([5,5])).unwrap();
+        assert!(store.push(0, Costs::new([6,6])).is_none());
+        let b = store.push(1, Costs::new([3,3])).unwrap();
+        let c = store.push(0, Costs::new([4,4])).unwrap();
+        assert_eq!(store.status(a), LabelStatus::Dominated);
+        assert_eq!((store.nb_open(), store.nb_labels(), store.nb_non_dominated()), (2, 3, 2));
+        assert_eq!(store.peek_min_guide(), Some(&6));
+        assert_eq!(store.pop(), Some(b));
+        assert_eq!(store.pop(), Some(c));
+        assert_eq!(store.pop(), None);
+        assert_eq!((store.status(c), store.node(c), store.get(c)), (LabelStatus::Closed, &0, &Costs::new([4,4])));
+    }
+
+    #[test]
+    fn test_bi_objective_shortest_path() {
+        let arcs:[(usize,usize,[u32;2]);5] = [
+            (0, 1, [1,5]), (0, 2, [4,1]), (1, 3, [1,5]), (2, 3, [1,1]), (1, 2, [1,1])
+        ];
+        let mut store:LabelStore<usize,u32,Costs> = LabelStore::new();
+        store.push(0, Costs::new([0,0]));
+        while let Some(id) = store.pop() {
+            let (node, costs) = (*store.node(id), store.get(id).clone());
+            for (_,to,c) in arcs.iter().filter(|(from,_,_)| *from == node) {
+                store.push(*to, Costs::new([costs.kth(0)+c[0], costs.kth(1)+c[1]]));
+            }
+        }
+        let mut front:Vec<[u32;2]> = store.front(&3).unwrap().peek_k(10).into_iter()
+            .map(|label| [label.elt().kth(0), label.elt().kth(1)])
+            .collect();
+        front.sort_unstable();
+        assert_eq!(front, vec![[2,10], [3,7], [5,2]]);
+        assert_eq!(store.front(&2).unwrap().len(), 2);
+    }
+}
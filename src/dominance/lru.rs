@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// index of no node (end of the recency list)
+const NIL:usize = usize::MAX;
+
+/// Entry of the table, linked in the recency list
+#[derive(Debug,Clone)]
+struct Node<K,V> {
+    /// key of the entry
+    key:K,
+    /// value of the entry
+    value:V,
+    /// more recently used node (NIL for the most recent)
+    prev:usize,
+    /// less recently used node (NIL for the least recent)
+    next:usize,
+}
+
+/// Bounded memoization table (hash map + intrusive LRU list).
+///
+/// Stores at most capacity entries. When full, inserting a new key evicts the least recently
+/// used entry (an entry is used when it is inserted, updated, or read through get).
+/// Typical use: state → best known value, to prune dominated states in memory-bounded searches
+/// (see insert_if_better). Forgetting an entry never prunes wrongly, it only prunes less.
+/// - memory: O(capacity)
+/// - get/insert/remove: O(1) expected
+#[derive(Debug,Clone)]
+pub struct LruMemo<K,V> {
+    /// entries (the recency list is threaded through them)
+    nodes:Vec<Node<K,V>>,
+    /// position of each key in nodes
+    positions:HashMap<K,usize>,
+    /// most recently used node
+    head:usize,
+    /// least recently used node
+    tail:usize,
+    /// maximum number of entries
+    capacity:usize,
+    /// number of entries evicted since the creation of the table
+    nb_evictions:usize,
+}
+
+impl<K:Hash+Eq+Clone,V> LruMemo<K,V> {
+    /// creates an empty table holding at most capacity entries. Panics if capacity is 0
+    pub fn new(capacity:usize) -> Self {
+        assert!(capacity > 0, "LruMemo: the capacity must be positive");
+        Self {
+            nodes:Vec::with_capacity(capacity),
+            positions:HashMap::with_capacity(capacity),
+            head:NIL,
+            tail:NIL,
+            capacity,
+            nb_evictions:0,
+        }
+    }
+
+    /// returns the maximum number of entries
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// returns the number of entries
+    pub fn len(&self) -> usize { self.nodes.len() }
+
+    /// returns true iff the table is empty
+    pub fn is_empty(&self) -> bool { self.nodes.is_empty() }
+
+    /// returns the number of entries evicted because the table was full
+    pub fn nb_evictions(&self) -> usize { self.nb_evictions }
+
+    /// returns true iff the key is stored (does not mark it as used)
+    pub fn contains_key(&self, key:&K) -> bool { self.positions.contains_key(key) }
+
+    /// returns the value of a key and marks it as the most recently used
+    pub fn get(&mut self, key:&K) -> Option<&V> {
+        let i = *self.positions.get(key)?;
+        self.move_to_front(i);
+        Some(&self.nodes[i].value)
+    }
+
+    /// returns the value of a key (does not mark it as used)
+    pub fn peek(&self, key:&K) -> Option<&V> {
+        self.positions.get(key).map(|i| &self.nodes[*i].value)
+    }
+
+    /// inserts or updates an entry and marks it as the most recently used.
+    /// Returns the removed entry if any: the previous entry of the key, or the least recently
+    /// used one if the table was full
+    pub fn insert(&mut self, key:K, value:V) -> Option<(K,V)> {
+        if let Some(&i) = self.positions.get(&key) {
+            self.move_to_front(i);
+            let old_value = std::mem::replace(&mut self.nodes[i].value, value);
+            return Some((key, old_value));
+        }
+        let evicted = if self.nodes.len() == self.capacity {
+            self.nb_evictions += 1;
+            self.remove_at(self.tail)
+        } else { None };
+        let i = self.nodes.len();
+        self.nodes.push(Node { key:key.clone(), value, prev:NIL, next:NIL });
+        self.positions.insert(key, i);
+        self.link_front(i);
+        evicted
+    }
+
+    /// removes an entry and returns its value
+    pub fn remove(&mut self, key:&K) -> Option<V> {
+        let i = *self.positions.get(key)?;
+        self.remove_at(i).map(|(_,v)| v)
+    }
+
+    /// removes all the entries (the eviction count is kept)
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.positions.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// iterates over the entries, from the most recently used to the least recently used
+    pub fn iter(&self) -> LruMemoIterator<'_,K,V> {
+        LruMemoIterator { memo:self, current:self.head }
+    }
+
+    /// unlinks the node i from the recency list
+    fn unlink(&mut self, i:usize) {
+        let (prev, next) = (self.nodes[i].prev, self.nodes[i].next);
+        if prev == NIL { self.head = next; } else { self.nodes[prev].next = next; }
+        if next == NIL { self.tail = prev; } else { self.nodes[next].prev = prev; }
+    }
+
+    /// links the (unlinked) node i as the most recently used
+    fn link_front(&mut self, i:usize) {
+        self.nodes[i].prev = NIL;
+        self.nodes[i].next = self.head;
+        if self.head == NIL { self.tail = i; } else { self.nodes[self.head].prev = i; }
+        self.head = i;
+    }
+
+    /// marks the node i as the most recently used
+    fn move_to_front(&mut self, i:usize) {
+        if self.head == i { return; }
+        self.unlink(i);
+        self.link_front(i);
+    }
+
+    /// removes the node i (the last node takes its position)
+    fn remove_at(&mut self, i:usize) -> Option<(K,V)> {
+        if i == NIL { return None; }
+        self.unlink(i);
+        let last = self.nodes.len() - 1;
+        if i != last {
+            // the last node moves to position i: update its neighbours and its key
+            let (prev, next) = (self.nodes[last].prev, self.nodes[last].next);
+            if prev == NIL { self.head = i; } else { self.nodes[prev].next = i; }
+            if next == NIL { self.tail = i; } else { self.nodes[next].prev = i; }
+            *self.positions.get_mut(&self.nodes[last].key).unwrap() = i;
+        }
+        let node = self.nodes.swap_remove(i);
+        self.positions.remove(&node.key);
+        Some((node.key, node.value))
+    }
+}
+
+impl<K:Hash+Eq+Clone,V:Ord> LruMemo<K,V> {
+    /// stores the value of a state if it is strictly better (lower) than the stored one.
+    ///
+    /// Returns false if the state is dominated: a value lower or equal is stored (the entry
+    /// is then marked as used). Otherwise, stores the value and returns true
+    pub fn insert_if_better(&mut self, key:K, value:V) -> bool {
+        if let Some(&i) = self.positions.get(&key) {
+            self.move_to_front(i);
+            if self.nodes[i].value <= value { return false; }
+            self.nodes[i].value = value;
+            return true;
+        }
+        self.insert(key, value);
+        true
+    }
+}
+
+/// Iterator over the entries of a LruMemo (most recently used first)
+#[derive(Debug)]
+pub struct LruMemoIterator<'a,K,V> {
+    /// iterated table
+    memo:&'a LruMemo<K,V>,
+    /// next node
+    current:usize,
+}
+
+impl<'a,K,V> Iterator for LruMemoIterator<'a,K,V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == NIL { return None; }
+        let node = &self.memo.nodes[self.current];
+        self.current = node.next;
+        Some((&node.key, &node.value))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// returns the keys from the most recently used
+    fn keys(memo:&LruMemo<u32,i32>) -> Vec<u32> { memo.iter().map(|(k,_)| *k).collect() }
+
+    #[test]
+    fn evictions() {
+        let mut memo = LruMemo::new(3);
+        for k in 0..3 { assert_eq!(memo.insert(k, 10*k as i32), None); }
+        assert_eq!(keys(&memo), vec![2, 1, 0]);
+        assert_eq!(memo.get(&0), Some(&0));
+        assert_eq!(memo.insert(3, 30), Some((1, 10)));
+        assert_eq!(keys(&memo), vec![3, 0, 2]);
+        assert_eq!(memo.insert(2, 25), Some((2, 20)));
+        assert_eq!(memo.peek(&0), Some(&0));
+        assert_eq!(memo.insert(4, 40), Some((0, 0)));
+        assert_eq!(keys(&memo), vec![4, 2, 3]);
+        assert_eq!((memo.len(), memo.nb_evictions()), (3, 2));
+        assert_eq!(memo.remove(&2), Some(25));
+        assert_eq!(memo.remove(&2), None);
+        assert_eq!(keys(&memo), vec![4, 3]);
+        memo.insert(5, 50);
+        memo.insert(6, 60);
+        assert_eq!(keys(&memo), vec![6, 5, 4]);
+        assert!(!memo.contains_key(&3));
+        memo.clear();
+        assert!(memo.is_empty() && memo.iter().next().is_none());
+    }
+
+    #[test]
+    fn dominance() {
+        let mut memo = LruMemo::new(2);
+        assert!(memo.insert_if_better(1, 10));
+        assert!(!memo.insert_if_better(1, 10));
+        assert!(memo.insert_if_better(1, 7));
+        assert!(memo.insert_if_better(2, 5));
+        assert!(!memo.insert_if_better(1, 8)); // 1 becomes the most recent
+        assert!(memo.insert_if_better(3, 9)); // evicts 2
+        assert_eq!(keys(&memo), vec![3, 1]);
+        assert!(memo.insert_if_better(2, 6)); // forgotten: accepted again
+    }
+
+    #[test]
+    fn random_operations() {
+        // compares with a naive list ordered by recency
+        let mut memo = LruMemo::new(8);
+        let mut naive:Vec<(u32,i32)> = Vec::new();
+        let mut x:u32 = 12345;
+        for step in 0..2000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let key = (x >> 16) % 20;
+            if step % 5 == 0 {
+                memo.remove(&key);
+                naive.retain(|(k,_)| *k != key);
+            } else {
+                memo.insert(key, step);
+                naive.retain(|(k,_)| *k != key);
+                naive.insert(0, (key, step));
+                naive.truncate(8);
+            }
+            let expected:Vec<u32> = naive.iter().map(|(k,_)| *k).collect();
+            assert_eq!(keys(&memo), expected);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// LRU-bounded memoization table.
+///
+/// Maps states to their best known value with a bounded capacity, evicting the least recently
+/// used entries when full (memory-bounded dominance caching).
+pub mod lru;
+
+/// State-keyed dominance store.
+///
+/// Maps discrete states to the Pareto front of the elements reaching them (multi-objective
+/// labeling algorithms).
+pub mod store;
+
+/// Label store for multi-objective shortest paths.
+///
+/// Coordinates the fronts of the nodes with a global priority queue of open labels (dominated
+/// labels are invalidated lazily).
+pub mod labeling;
+
+/// Dominance staircase of (weight, profit) states.
+///
+/// Keeps the non-dominated states of a knapsack-like dynamic program, merged with shifted
+/// copies of themselves in linear time.
+pub mod staircase;
+
+/// Bounded-memory streaming Pareto filter.
+///
+/// Keeps an approximate non-dominated set of a stream of elements under a hard memory cap
+/// (epsilon-box coarsening when full).
+pub mod streaming;
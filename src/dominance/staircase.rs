@@ -0,0 +1,132 @@
+use std::cmp::Reverse;
+use std::ops::Add;
+
+/// Dominance staircase of (weight, profit) states (knapsack dynamic programming with
+/// dominance).
+///
+/// A state dominates another one if it has a lower or equal weight and a greater or equal
+/// profit. The non-dominated states are stored sorted by increasing weight, thus by strictly
+/// increasing profit. Adding an item merges the list with a copy of itself shifted by the
+/// weight and profit of the item, in linear time.
+/// If *n* is the number of states:
+/// - memory: O(n)
+/// - insertion: O(n) (binary search, then shift)
+/// - add an item (merge with a shifted copy): O(n)
+/// - best profit under a capacity: O(log n)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Staircase<W,P> {
+    /// non-dominated states sorted by increasing weight (and increasing profit)
+    states:Vec<(W,P)>,
+}
+
+impl<W,P> Default for Staircase<W,P> {
+    fn default() -> Self { Self { states:Vec::new() } }
+}
+
+impl<W,P> Staircase<W,P>
+where W:Ord+Copy+Add<Output=W>, P:Ord+Copy+Add<Output=P> {
+    /// creates a staircase without states
+    pub fn new() -> Self { Self::default() }
+
+    /// creates a staircase containing the single state (weight, profit), for instance the
+    /// empty knapsack (0, 0)
+    pub fn from_state(weight:W, profit:P) -> Self { Self { states:vec![(weight, profit)] } }
+
+    /// returns the number of non-dominated states
+    pub fn len(&self) -> usize { self.states.len() }
+
+    /// returns true iff there is no state
+    pub fn is_empty(&self) -> bool { self.states.is_empty() }
+
+    /// returns the states sorted by increasing weight
+    pub fn states(&self) -> &[(W,P)] { &self.states }
+
+    /// returns true iff a state dominates (weight, profit)
+    pub fn is_dominated(&self, weight:W, profit:P) -> bool {
+        self.best_profit(weight).is_some_and(|p| p >= profit)
+    }
+
+    /// inserts a state. Returns false if it is dominated, otherwise removes the states it
+    /// dominates
+    pub fn insert(&mut self, weight:W, profit:P) -> bool {
+        if self.is_dominated(weight, profit) { return false; }
+        let start = self.states.partition_point(|(w,_)| *w < weight);
+        let end = start + self.states[start..].partition_point(|(_,p)| *p <= profit);
+        self.states.splice(start..end, std::iter::once((weight, profit)));
+        true
+    }
+
+    /// returns the best profit of a state of weight lower or equal to the capacity
+    pub fn best_profit(&self, capacity:W) -> Option<P> {
+        let nb_lighter = self.states.partition_point(|(w,_)| *w <= capacity);
+        nb_lighter.checked_sub(1).map(|i| self.states[i].1)
+    }
+
+    /// returns the non-dominated states of the union of the staircase and of its copy shifted
+    /// by (weight, profit), without the states heavier than the capacity
+    pub fn merge_shifted(&self, weight:W, profit:P, capacity:W) -> Self {
+        let mut res = Vec::with_capacity(2*self.states.len());
+        let mut shifted = self.states.iter()
+            .map(|(w,p)| (*w + weight, *p + profit))
+            .take_while(|(w,_)| *w <= capacity)
+            .peekable();
+        let mut original = self.states.iter().copied()
+            .take_while(|(w,_)| *w <= capacity)
+            .peekable();
+        loop {
+            // next state by increasing weight (the most profitable first on ties)
+            let next = match (original.peek(), shifted.peek()) {
+                (Some(a), Some(b)) => if (a.0, Reverse(a.1)) <= (b.0, Reverse(b.1)) {
+                    original.next()
+                } else { shifted.next() },
+                (Some(_), None) => original.next(),
+                (None, _) => shifted.next(),
+            };
+            let Some((w,p)) = next else { break; };
+            if res.last().is_none_or(|(_,last)| p > *last) { res.push((w, p)); }
+        }
+        Self { states:res }
+    }
+
+    /// adds an optional item of the given weight and profit (0-1 knapsack transition)
+    pub fn add_item(&mut self, weight:W, profit:P, capacity:W) {
+        *self = self.merge_shifted(weight, profit, capacity);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertions() {
+        let mut staircase = Staircase::new();
+        assert!(staircase.insert(5, 10));
+        assert!(staircase.insert(2, 4));
+        assert!(staircase.insert(8, 11));
+        assert!(!staircase.insert(6, 9));
+        assert!(!staircase.insert(5, 10));
+        assert!(staircase.insert(4, 11)); // dominates (5,10) and (8,11)
+        assert_eq!(staircase.states(), &[(2, 4), (4, 11)]);
+        assert_eq!((staircase.best_profit(3), staircase.best_profit(1)), (Some(4), None));
+    }
+
+    #[test]
+    fn knapsack() {
+        let items = [(3, 4), (4, 5), (5, 6), (2, 3), (3, 3), (7, 9)];
+        let capacity = 12;
+        let mut staircase = Staircase::from_state(0, 0);
+        for (w,p) in items { staircase.add_item(w, p, capacity); }
+        assert!(staircase.states().windows(2).all(|s| s[0].0 < s[1].0 && s[0].1 < s[1].1));
+        // brute force over the subsets of items
+        for c in 0..=capacity {
+            let best = (0..1u32 << items.len()).filter_map(|mask| {
+                let chosen = || items.iter().enumerate().filter(|(i,_)| mask >> i & 1 == 1).map(|(_,it)| *it);
+                let (w, p) = chosen().fold((0, 0), |(w,p),(iw,ip)| (w+iw, p+ip));
+                (w <= c).then_some(p)
+            }).max();
+            assert_eq!(staircase.best_profit(c), best);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::priority_queue::{ExactSizeQueue, FrontEvent, GuidedElement, ParetoElement, ParetoFront};
+use crate::priority_queue::pareto_list::ListParetoFront;
+
+/// State-keyed dominance store.
+///
+/// Maps discrete state keys (for instance the node of a resource constrained shortest path
+/// problem) to a Pareto front of the elements (labels) reaching this state. An element is only
+/// compared with the elements of its own state. Fronts are created on demand (by default, a
+/// ListParetoFront).
+/// - insert: one front lookup (O(1) expected) plus the insertion in the front
+/// - len: O(1) (the number of elements is maintained using the front events)
+#[derive(Debug)]
+pub struct DominanceStore<K,T,Elt,F=ListParetoFront<T,Elt>> {
+    /// front of each state
+    fronts:HashMap<K,F>,
+    /// total number of elements in the fronts
+    nb_elements:usize,
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<K,T,Elt,F> Default for DominanceStore<K,T,Elt,F> {
+    fn default() -> Self { Self { fronts:HashMap::new(), nb_elements:0, phantom:PhantomData } }
+}
+
+impl<K,T,Elt,F> DominanceStore<K,T,Elt,F>
+where K:Hash+Eq, T:Ord, Elt:ParetoElement<T>+GuidedElement<T>, F:ParetoFront<T,Elt>+ExactSizeQueue<T,Elt> {
+    /// creates an empty store
+    pub fn new() -> Self { Self::default() }
+
+    /// inserts an element in the front of its state (created if needed). Removes the elements
+    /// of this front it dominates.
+    ///
+    /// returns false if the element is dominated by an element of the same state
+    pub fn insert(&mut self, key:K, elt:Elt) -> bool {
+        self.insert_observed(key, elt, |_| {})
+    }
+
+    /// inserts an element in the front of its state, and reports the front events (rejection,
+    /// evictions, insertion) to the observer (see [ParetoFront::insert_observed]).
+    ///
+    /// returns false if the element is dominated by an element of the same state
+    pub fn insert_observed<O>(&mut self, key:K, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        let front = self.fronts.entry(key).or_default();
+        let mut nb_evicted = 0;
+        let inserted = front.insert_observed(elt, |event| {
+            if matches!(event, FrontEvent::EvictedBy { .. }) { nb_evicted += 1; }
+            observer(event);
+        });
+        self.nb_elements = self.nb_elements + usize::from(inserted) - nb_evicted;
+        inserted
+    }
+
+    /// returns an element of the state dominating elt if it exists
+    pub fn find_dominating(&self, key:&K, elt:&Elt) -> Option<&Elt> {
+        self.fronts.get(key).and_then(|front| front.find_dominating(elt))
+    }
+
+    /// returns true iff elt is dominated by an element of the state
+    pub fn is_dominated(&self, key:&K, elt:&Elt) -> bool { self.find_dominating(key, elt).is_some() }
+
+    /// returns the front of a state (None if no element was inserted in it)
+    pub fn front(&self, key:&K) -> Option<&F> { self.fronts.get(key) }
+
+    /// removes the front of a state and returns it
+    pub fn remove_front(&mut self, key:&K) -> Option<F> {
+        let front = self.fronts.remove(key)?;
+        self.nb_elements -= front.len();
+        Some(front)
+    }
+
+    /// returns the total number of elements (over all the states)
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the store contains no element
+    pub fn is_empty(&self) -> bool { self.nb_elements == 0 }
+
+    /// returns the number of states having a front
+    pub fn nb_states(&self) -> usize { self.fronts.len() }
+
+    /// iterates over the states and their fronts (arbitrary order)
+    pub fn iter(&self) -> impl Iterator<Item=(&K,&F)> { self.fronts.iter() }
+
+    /// removes all the fronts
+    pub fn clear(&mut self) {
+        self.fronts.clear();
+        self.nb_elements = 0;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::kd_tree::KDTreeFront;
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    type Label = CartesianParetoElement<2>;
+
+    #[test]
+    fn test_per_state_dominance() {
+        let mut store:DominanceStore<usize,u32,Label> = DominanceStore::new();
+        assert!(store.insert(0, Label::new([5,5])));
+        assert!(store.insert(1, Label::new([6,6]))); // other state: not dominated
+        assert!(!store.insert(0, Label::new([6,6])));
+        assert!(store.insert(0, Label::new([2,9])));
+        assert_eq!((store.len(), store.nb_states()), (3, 2));
+        assert!(store.insert(0, Label::new([1,1]))); // evicts both labels of state 0
+        assert_eq!(store.len(), 2);
+        assert!(store.is_dominated(&0, &Label::new([3,3])));
+        assert!(!store.is_dominated(&2, &Label::new([3,3])));
+        assert_eq!(store.find_dominating(&1, &Label::new([7,6])), Some(&Label::new([6,6])));
+        assert_eq!(store.front(&0).map(|f| f.len()), Some(1));
+        assert_eq!(store.remove_front(&1).map(|f| f.len()), Some(1));
+        assert_eq!((store.len(), store.nb_states()), (1, 1));
+        store.clear();
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_other_front_and_observer() {
+        let mut store:DominanceStore<(u8,bool),u32,Label,KDTreeFront<u32,Label,2>> = DominanceStore::default();
+        let mut nb_evicted = 0;
+        for (key,coords) in [((0,true),[4,4]), ((0,true),[3,6]), ((0,false),[1,1]), ((0,true),[2,2])] {
+            store.insert_observed(key, Label::new(coords), |event| {
+                if let FrontEvent::EvictedBy { .. } = event { nb_evicted += 1; }
+            });
+        }
+        assert_eq!(nb_evicted, 2);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.iter().map(|(_,f)| f.len()).sum::<usize>(), 2);
+    }
+}
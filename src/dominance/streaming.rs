@@ -0,0 +1,165 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::ParetoElement;
+
+/// Bounded-memory streaming Pareto filter.
+///
+/// Consumes a stream of elements and keeps an approximate non-dominated set of at most
+/// capacity elements. Elements are compared through their epsilon-boxes (coordinate c is in box
+/// floor(c/epsilon), minimized): an element is rejected if the box of a kept element dominates
+/// (or equals) its box. Epsilon starts at 0 (exact dominance). Each time the set exceeds its
+/// capacity, epsilon grows (initial epsilon, then doubled) and the set is filtered again.
+///
+/// Every element of the stream is box-dominated by a kept element for the current epsilon.
+/// Evicted elements (dominated by a new element, or coarsened out) can be observed
+/// (see [StreamingParetoFilter::push_observed]).
+/// If *k* is the capacity and *d* the number of dimensions:
+/// - memory: O(k)
+/// - push: O(k.d) (plus O(k².d) per coarsening)
+#[derive(Debug,Clone)]
+pub struct StreamingParetoFilter<T,Elt> {
+    /// kept elements (pairwise non box-dominated)
+    elements:Vec<Elt>,
+    /// maximum number of kept elements
+    capacity:usize,
+    /// current box size (0: exact dominance)
+    epsilon:f64,
+    /// box size used at the first coarsening
+    initial_epsilon:f64,
+    phantom:PhantomData<T>,
+}
+
+impl<T,Elt> StreamingParetoFilter<T,Elt>
+where T:Ord+Copy+Into<f64>, Elt:ParetoElement<T> {
+    /// creates a filter keeping at most capacity elements, using initial_epsilon at the first
+    /// coarsening. Panics if capacity is 0 or initial_epsilon is not positive
+    pub fn new(capacity:usize, initial_epsilon:f64) -> Self {
+        assert!(capacity > 0, "StreamingParetoFilter: the capacity must be positive");
+        assert!(initial_epsilon > 0., "StreamingParetoFilter: the initial epsilon must be positive");
+        Self { elements:Vec::with_capacity(capacity+1), capacity, epsilon:0., initial_epsilon, phantom:PhantomData }
+    }
+
+    /// returns the maximum number of kept elements
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// returns the current box size (0 while the dominance is exact)
+    pub fn epsilon(&self) -> f64 { self.epsilon }
+
+    /// returns the number of kept elements
+    pub fn len(&self) -> usize { self.elements.len() }
+
+    /// returns true iff no element is kept
+    pub fn is_empty(&self) -> bool { self.elements.is_empty() }
+
+    /// iterates over the kept elements
+    pub fn iter(&self) -> std::slice::Iter<'_,Elt> { self.elements.iter() }
+
+    /// consumes the filter and returns the kept elements
+    pub fn into_vec(self) -> Vec<Elt> { self.elements }
+
+    /// returns true iff the box of a dominates (or equals) the box of b
+    pub fn box_dominates(&self, a:&Elt, b:&Elt) -> bool {
+        if self.epsilon == 0. { return a.dominates(b); }
+        let cell = |c:T| (c.into() / self.epsilon).floor();
+        (0..a.nb_dimensions()).all(|k| cell(a.kth(k)) <= cell(b.kth(k)))
+    }
+
+    /// offers an element. Returns true iff it is kept (it may be evicted later)
+    pub fn push(&mut self, elt:Elt) -> bool { self.push_observed(elt, |_| {}) }
+
+    /// offers an element, and gives the evicted elements to the observer.
+    /// Returns true iff it is kept
+    pub fn push_observed<O:FnMut(Elt)>(&mut self, elt:Elt, mut observer:O) -> bool {
+        let mut same_box_dominated = None;
+        for (i,e) in self.elements.iter().enumerate() {
+            if self.box_dominates(e, &elt) {
+                // in the same box, an element exactly dominating the kept one replaces it
+                if self.box_dominates(&elt, e) && elt.dominates(e) && !e.dominates(&elt) {
+                    same_box_dominated = Some(i);
+                    break;
+                }
+                return false;
+            }
+        }
+        if let Some(i) = same_box_dominated { observer(self.elements.swap_remove(i)); }
+        self.evict_dominated_by(&elt, &mut observer);
+        self.elements.push(elt);
+        while self.elements.len() > self.capacity { self.coarsen(&mut observer); }
+        true
+    }
+
+    /// removes the elements whose box is dominated by the box of elt
+    fn evict_dominated_by<O:FnMut(Elt)>(&mut self, elt:&Elt, observer:&mut O) {
+        let mut i = 0;
+        while i < self.elements.len() {
+            if self.box_dominates(elt, &self.elements[i]) {
+                observer(self.elements.swap_remove(i));
+            } else { i += 1; }
+        }
+    }
+
+    /// increases epsilon and filters the kept elements again
+    fn coarsen<O:FnMut(Elt)>(&mut self, observer:&mut O) {
+        self.epsilon = if self.epsilon == 0. { self.initial_epsilon } else { 2. * self.epsilon };
+        let elements = std::mem::take(&mut self.elements);
+        for elt in elements {
+            if self.elements.iter().any(|e| self.box_dominates(e, &elt)) {
+                observer(elt);
+            } else {
+                self.evict_dominated_by(&elt, observer);
+                self.elements.push(elt);
+            }
+        }
+    }
+}
+
+impl<T,Elt> Extend<Elt> for StreamingParetoFilter<T,Elt>
+where T:Ord+Copy+Into<f64>, Elt:ParetoElement<T> {
+    fn extend<I:IntoIterator<Item=Elt>>(&mut self, iter:I) {
+        for elt in iter { self.push(elt); }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    type Point = CartesianParetoElement<2>;
+
+    #[test]
+    fn exact_while_small() {
+        let mut filter = StreamingParetoFilter::new(10, 1.);
+        assert!(filter.push(Point::new([5,5])));
+        assert!(!filter.push(Point::new([6,5])));
+        assert!(filter.push(Point::new([2,8])));
+        let mut evicted = Vec::new();
+        assert!(filter.push_observed(Point::new([4,4]), |e| evicted.push(e)));
+        assert_eq!(evicted, vec![Point::new([5,5])]);
+        assert_eq!((filter.len(), filter.epsilon()), (2, 0.));
+    }
+
+    #[test]
+    fn memory_cap() {
+        let stream:Vec<Point> = (0..200).map(|i| Point::new([i, 200-i])).chain((0..50).map(|i| Point::new([3*i, 3*i]))).collect();
+        let mut filter = StreamingParetoFilter::new(8, 1.);
+        let mut nb_evicted = 0;
+        let mut nb_kept = 0;
+        for p in stream.iter().cloned() {
+            if filter.push_observed(p, |_| nb_evicted += 1) { nb_kept += 1; }
+            assert!(filter.len() <= 8);
+        }
+        assert!(filter.epsilon() > 0.);
+        assert_eq!(nb_kept - nb_evicted, filter.len());
+        // every element of the stream is box-dominated by a kept element
+        for p in &stream {
+            assert!(filter.iter().any(|e| filter.box_dominates(e, p)));
+        }
+        // kept elements do not box-dominate each other
+        for (i,a) in filter.iter().enumerate() {
+            assert!(filter.iter().enumerate().all(|(j,b)| i == j || !filter.box_dominates(a, b)));
+        }
+    }
+}
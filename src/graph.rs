@@ -0,0 +1,130 @@
+/// Immutable graph in compressed sparse row (CSR) format.
+///
+/// The arcs leaving node u are stored contiguously (targets\[offsets\[u\]..offsets\[u+1\]\]),
+/// in the order in which their edges were given. Each arc carries a weight (W = () for
+/// unweighted graphs). Undirected edges are stored as two arcs (one for a self-loop).
+/// If *n* is the number of nodes and *m* the number of arcs:
+/// - memory: O(n + m)
+/// - construction: O(n + m) (counting sort of the edges)
+/// - degree: O(1), neighbor iteration: O(degree)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CsrGraph<W=()> {
+    /// offsets\[u\] is the position of the first arc leaving u (offsets\[n\] = number of arcs)
+    offsets:Vec<usize>,
+    /// target of each arc
+    targets:Vec<usize>,
+    /// weight of each arc
+    weights:Vec<W>,
+    /// true iff the edges are directed
+    directed:bool,
+}
+
+impl<W:Clone> CsrGraph<W> {
+    /// builds a graph over the nodes 0..nb_nodes from weighted edges (from, to, weight).
+    /// Panics if an edge has an endpoint out of the nodes
+    pub fn from_weighted_edges(nb_nodes:usize, directed:bool, edges:impl IntoIterator<Item=(usize,usize,W)>) -> Self {
+        let mut arcs:Vec<(usize,usize,W)> = Vec::new();
+        for (u,v,w) in edges {
+            assert!(u < nb_nodes && v < nb_nodes, "CsrGraph: edge ({u},{v}) out of the {nb_nodes} nodes");
+            if !directed && u != v { arcs.push((v, u, w.clone())); }
+            arcs.push((u, v, w));
+        }
+        // counting sort of the arcs by origin (stable)
+        let mut offsets = vec![0; nb_nodes+1];
+        for (u,_,_) in &arcs { offsets[u+1] += 1; }
+        for u in 0..nb_nodes { offsets[u+1] += offsets[u]; }
+        let mut next = offsets.clone();
+        let mut slots:Vec<Option<(usize,W)>> = vec![None; arcs.len()];
+        for (u,v,w) in arcs {
+            slots[next[u]] = Some((v, w));
+            next[u] += 1;
+        }
+        let (targets, weights) = slots.into_iter().map(|slot| slot.unwrap()).unzip();
+        Self { offsets, targets, weights, directed }
+    }
+
+    /// returns the graph with every arc reversed (incoming arcs become outgoing ones)
+    pub fn reversed(&self) -> Self {
+        let arcs = self.arcs().map(|(u,v,w)| (v, u, w.clone()));
+        // undirected graphs already store both directions
+        if self.directed { Self::from_weighted_edges(self.nb_nodes(), true, arcs) } else { self.clone() }
+    }
+}
+
+impl CsrGraph<()> {
+    /// builds an unweighted graph over the nodes 0..nb_nodes from edges (from, to).
+    /// Panics if an edge has an endpoint out of the nodes
+    pub fn from_edges(nb_nodes:usize, directed:bool, edges:impl IntoIterator<Item=(usize,usize)>) -> Self {
+        Self::from_weighted_edges(nb_nodes, directed, edges.into_iter().map(|(u,v)| (u, v, ())))
+    }
+}
+
+impl<W> CsrGraph<W> {
+    /// returns the number of nodes
+    pub fn nb_nodes(&self) -> usize { self.offsets.len() - 1 }
+
+    /// returns the number of arcs (undirected edges count twice, except self-loops)
+    pub fn nb_arcs(&self) -> usize { self.targets.len() }
+
+    /// returns true iff the edges are directed
+    pub fn is_directed(&self) -> bool { self.directed }
+
+    /// returns the number of arcs leaving u
+    pub fn degree(&self, u:usize) -> usize { self.offsets[u+1] - self.offsets[u] }
+
+    /// returns the targets of the arcs leaving u
+    pub fn neighbors(&self, u:usize) -> &[usize] { &self.targets[self.offsets[u]..self.offsets[u+1]] }
+
+    /// returns the weights of the arcs leaving u (in the order of the neighbors)
+    pub fn weights(&self, u:usize) -> &[W] { &self.weights[self.offsets[u]..self.offsets[u+1]] }
+
+    /// iterates over the arcs leaving u (target, weight)
+    pub fn weighted_neighbors(&self, u:usize) -> impl Iterator<Item=(usize,&W)> {
+        self.neighbors(u).iter().copied().zip(self.weights(u))
+    }
+
+    /// iterates over all the arcs (origin, target, weight), by origin
+    pub fn arcs(&self) -> impl Iterator<Item=(usize,usize,&W)> {
+        (0..self.nb_nodes()).flat_map(move |u| self.weighted_neighbors(u).map(move |(v,w)| (u, v, w)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn unweighted() {
+        let g = CsrGraph::from_edges(4, true, [(0, 1), (2, 0), (0, 3), (1, 2)]);
+        assert_eq!((g.nb_nodes(), g.nb_arcs(), g.is_directed()), (4, 4, true));
+        assert_eq!(g.neighbors(0), &[1, 3]);
+        assert_eq!((g.degree(0), g.degree(3)), (2, 0));
+        assert_eq!(g.reversed().neighbors(0), &[2]);
+        let u = CsrGraph::from_edges(3, false, [(0, 1), (1, 2), (2, 2)]);
+        assert_eq!(u.nb_arcs(), 5);
+        assert_eq!(u.neighbors(1), &[0, 2]);
+        assert_eq!(u.neighbors(2), &[1, 2]);
+        assert_eq!(u.reversed(), u);
+    }
+
+    #[test]
+    fn dijkstra() {
+        let g = CsrGraph::from_weighted_edges(5, true, [
+            (0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5), (3, 4, 3)
+        ]);
+        assert_eq!(g.weighted_neighbors(0).collect::<Vec<_>>(), vec![(1, &4), (2, &1)]);
+        assert_eq!(g.arcs().count(), 6);
+        let mut dist = vec![u32::MAX; g.nb_nodes()];
+        let mut open = BinaryHeap::from([Reverse((0, 0))]);
+        while let Some(Reverse((d,u))) = open.pop() {
+            if d >= dist[u] { continue; }
+            dist[u] = d;
+            for (v,w) in g.weighted_neighbors(u) { open.push(Reverse((d + w, v))); }
+        }
+        assert_eq!(dist, vec![0, 3, 1, 4, 7]);
+    }
+}
@@ -31,3 +31,24 @@ pub mod set;
 
 /// defines set store data-structures
 pub mod set_store;
+
+/// defines dominance stores (memoization of the best known value of states)
+pub mod dominance;
+
+/// defines decision diagrams (exact, restricted and relaxed layered MDDs)
+pub mod mdd;
+
+/// defines graph data-structures (compressed sparse row graphs)
+pub mod graph;
+
+/// defines random sampling data-structures (reservoirs, roulette wheels)
+pub mod sampling;
+
+/// defines scheduling data-structures (timelines, theta-trees, resource profiles)
+pub mod scheduling;
+
+/// defines tours (cyclic permutations for TSP-like local search)
+pub mod tour;
+
+/// generic trail (state restoration for backtracking search)
+pub mod trail;
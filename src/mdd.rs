@@ -0,0 +1,313 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Problem compiled into a decision diagram (top-down compilation hooks).
+///
+/// Variables are assigned in order (0..nb_variables). Each layer i of the diagram contains the
+/// states reached after assigning the first i variables. Nodes with the same state are merged
+/// (exact reduction). The longest path maximizes the sum of the arc weights.
+pub trait MddProblem {
+    /// state of a node (nodes of a layer with equal states are merged)
+    type State: Clone+Eq+Hash;
+
+    /// returns the number of variables (the diagram has nb_variables+1 layers)
+    fn nb_variables(&self) -> usize;
+
+    /// returns the state of the root node
+    fn root(&self) -> Self::State;
+
+    /// pushes in arcs the feasible decisions of the variable from a state:
+    /// (value, state reached, arc weight)
+    fn transitions(&self, variable:usize, state:&Self::State, arcs:&mut Vec<(usize,Self::State,i64)>);
+
+    /// returns a state relaxing all the given states (used by the relaxed compilation).
+    /// The transitions of the merged state must be a superset of the transitions of each state,
+    /// with greater or equal weights
+    fn merge(&self, states:&[&Self::State]) -> Self::State;
+
+    /// returns the rank of a node (nodes with the lowest ranks are the ones deleted or merged
+    /// when a layer is too wide). By default, the longest path value of the node
+    fn rank(&self, _state:&Self::State, value:i64) -> i64 { value }
+}
+
+/// Compilation mode of a decision diagram
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Compilation {
+    /// every node is kept (the diagram may be exponentially large)
+    Exact,
+    /// at most the given number of nodes per layer, the worst nodes are deleted. Every path is
+    /// a feasible solution (the longest path gives a lower bound)
+    Restricted(usize),
+    /// at most the given number of nodes per layer, the worst nodes are merged. Every solution
+    /// is a path (the longest path gives an upper bound)
+    Relaxed(usize),
+}
+
+/// Arc of a decision diagram (incoming arc of a node)
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct MddArc {
+    /// index of the origin node in the previous layer
+    pub from:usize,
+    /// value assigned to the variable
+    pub value:usize,
+    /// weight of the arc
+    pub weight:i64,
+}
+
+/// Node of a decision diagram
+#[derive(Debug,Clone)]
+pub struct MddNode<S> {
+    /// state of the node
+    state:S,
+    /// length of the longest path from the root
+    value:i64,
+    /// incoming arc on the longest path (unused for the root)
+    best_arc:usize,
+    /// incoming arcs
+    arcs:Vec<MddArc>,
+}
+
+impl<S> MddNode<S> {
+    /// creates a node without incoming arcs
+    fn new(state:S, value:i64) -> Self { Self { state, value, best_arc:0, arcs:Vec::new() } }
+
+    /// returns the state of the node
+    pub fn state(&self) -> &S { &self.state }
+
+    /// returns the length of the longest path from the root to the node
+    pub fn value(&self) -> i64 { self.value }
+
+    /// returns the incoming arcs of the node
+    pub fn arcs(&self) -> &[MddArc] { &self.arcs }
+
+    /// adds an incoming arc, the longest path through it having the given length
+    fn add_arc(&mut self, arc:MddArc, value:i64) {
+        if self.arcs.is_empty() || value > self.value {
+            self.value = value;
+            self.best_arc = self.arcs.len();
+        }
+        self.arcs.push(arc);
+    }
+}
+
+/// Layered multi-valued decision diagram.
+///
+/// Compiled top-down from an MddProblem, exactly or with a maximum width (restricted or
+/// relaxed diagrams). The longest path value of each node is maintained during the compilation.
+/// If *w* is the width of the diagram, *n* the number of variables and *d* the number of
+/// transitions per node:
+/// - compilation: O(n.w.d) transitions (plus O(w.log w) per reduced layer)
+/// - longest path: O(n)
+#[derive(Debug,Clone)]
+pub struct Mdd<S> {
+    /// nodes of each layer (layer 0 contains the root)
+    layers:Vec<Vec<MddNode<S>>>,
+    /// false iff a layer was reduced during the compilation
+    exact:bool,
+}
+
+impl<S:Clone+Eq+Hash> Mdd<S> {
+    /// compiles the decision diagram of a problem
+    pub fn compile<P:MddProblem<State=S>>(problem:&P, compilation:Compilation) -> Self {
+        let mut layers = vec![vec![MddNode::new(problem.root(), 0)]];
+        let mut exact = true;
+        let mut transitions = Vec::new();
+        for variable in 0..problem.nb_variables() {
+            let current = &layers[variable];
+            let mut next:Vec<MddNode<S>> = Vec::new();
+            let mut positions:HashMap<S,usize> = HashMap::new();
+            for (from,node) in current.iter().enumerate() {
+                problem.transitions(variable, &node.state, &mut transitions);
+                for (value,state,weight) in transitions.drain(..) {
+                    let i = match positions.get(&state) {
+                        Some(i) => *i,
+                        None => {
+                            positions.insert(state.clone(), next.len());
+                            next.push(MddNode::new(state, 0));
+                            next.len()-1
+                        }
+                    };
+                    next[i].add_arc(MddArc { from, value, weight }, node.value + weight);
+                }
+            }
+            match compilation {
+                Compilation::Restricted(width) if next.len() > width.max(1) => {
+                    exact = false;
+                    Self::sort_by_rank(problem, &mut next);
+                    next.truncate(width.max(1));
+                }
+                Compilation::Relaxed(width) if next.len() > width.max(1) => {
+                    exact = false;
+                    Self::sort_by_rank(problem, &mut next);
+                    let worst = next.split_off(width.max(1)-1);
+                    let states:Vec<&S> = worst.iter().map(|n| &n.state).collect();
+                    let state = problem.merge(&states);
+                    // the merged state may be the one of a kept node
+                    let i = match next.iter().position(|n| n.state == state) {
+                        Some(i) => i,
+                        None => {
+                            next.push(MddNode::new(state, 0));
+                            next.len()-1
+                        }
+                    };
+                    for arc in worst.into_iter().flat_map(|n| n.arcs) {
+                        next[i].add_arc(arc, current[arc.from].value + arc.weight);
+                    }
+                }
+                _ => {}
+            }
+            layers.push(next);
+        }
+        Self { layers, exact }
+    }
+
+    /// sorts the nodes of a layer by decreasing rank (ties kept in creation order)
+    fn sort_by_rank<P:MddProblem<State=S>>(problem:&P, nodes:&mut [MddNode<S>]) {
+        nodes.sort_by_cached_key(|n| Reverse(problem.rank(&n.state, n.value)));
+    }
+}
+
+impl<S> Mdd<S> {
+    /// returns true iff no layer was reduced (the diagram represents exactly the solutions)
+    pub fn is_exact(&self) -> bool { self.exact }
+
+    /// returns the number of layers (number of variables plus one)
+    pub fn nb_layers(&self) -> usize { self.layers.len() }
+
+    /// returns the nodes of a layer
+    pub fn layer(&self, i:usize) -> &[MddNode<S>] { &self.layers[i] }
+
+    /// returns the maximum number of nodes in a layer
+    pub fn width(&self) -> usize { self.layers.iter().map(|l| l.len()).max().unwrap_or(0) }
+
+    /// returns the number of nodes
+    pub fn nb_nodes(&self) -> usize { self.layers.iter().map(|l| l.len()).sum() }
+
+    /// returns the number of arcs
+    pub fn nb_arcs(&self) -> usize {
+        self.layers.iter().flatten().map(|n| n.arcs.len()).sum()
+    }
+
+    /// returns the longest path from the root to the last layer: its length and the value
+    /// of each variable. None if the last layer is empty (no feasible solution)
+    pub fn longest_path(&self) -> Option<(i64,Vec<usize>)> {
+        let (mut i, best) = self.layers.last()?.iter().enumerate()
+            .max_by_key(|(_,n)| n.value)?;
+        let mut values = Vec::with_capacity(self.layers.len()-1);
+        for layer in self.layers.iter().skip(1).rev() {
+            let arc = layer[i].arcs[layer[i].best_arc];
+            values.push(arc.value);
+            i = arc.from;
+        }
+        values.reverse();
+        Some((best.value, values))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0-1 knapsack: the state is the remaining capacity
+    struct Knapsack {
+        weights:Vec<usize>,
+        profits:Vec<i64>,
+        capacity:usize,
+    }
+
+    impl MddProblem for Knapsack {
+        type State = usize;
+
+        fn nb_variables(&self) -> usize { self.weights.len() }
+
+        fn root(&self) -> usize { self.capacity }
+
+        fn transitions(&self, variable:usize, state:&usize, arcs:&mut Vec<(usize,usize,i64)>) {
+            arcs.push((0, *state, 0));
+            if self.weights[variable] <= *state {
+                arcs.push((1, state - self.weights[variable], self.profits[variable]));
+            }
+        }
+
+        fn merge(&self, states:&[&usize]) -> usize { states.iter().map(|s| **s).max().unwrap() }
+    }
+
+    fn knapsack() -> Knapsack {
+        Knapsack { weights:vec![3, 4, 5, 2, 3], profits:vec![4, 5, 6, 3, 3], capacity:9 }
+    }
+
+    /// returns the (weight, profit) of a solution
+    fn evaluate(problem:&Knapsack, values:&[usize]) -> (usize, i64) {
+        let items = || values.iter().enumerate().filter(|(_,v)| **v == 1).map(|(i,_)| i);
+        (items().map(|i| problem.weights[i]).sum(), items().map(|i| problem.profits[i]).sum())
+    }
+
+    #[test]
+    fn exact() {
+        let problem = knapsack();
+        let mdd = Mdd::compile(&problem, Compilation::Exact);
+        assert!(mdd.is_exact());
+        assert_eq!(mdd.nb_layers(), 6);
+        assert_eq!(mdd.layer(1).len(), 2);
+        let (value, values) = mdd.longest_path().unwrap();
+        assert_eq!(value, 12);
+        assert_eq!(evaluate(&problem, &values), (9, 12));
+        // every layer contains at most one node per remaining capacity
+        assert!(mdd.width() <= 10);
+        assert!(mdd.nb_arcs() >= mdd.nb_nodes() - 1);
+    }
+
+    #[test]
+    fn bounds() {
+        let problem = knapsack();
+        for width in 1..4 {
+            let restricted = Mdd::compile(&problem, Compilation::Restricted(width));
+            let relaxed = Mdd::compile(&problem, Compilation::Relaxed(width));
+            assert!(restricted.width() <= width && relaxed.width() <= width);
+            let (lower, values) = restricted.longest_path().unwrap();
+            let (weight, profit) = evaluate(&problem, &values);
+            assert!(weight <= problem.capacity && profit == lower && lower <= 12);
+            assert!(relaxed.longest_path().unwrap().0 >= 12);
+        }
+        assert!(!Mdd::compile(&problem, Compilation::Relaxed(2)).is_exact());
+        assert!(Mdd::compile(&problem, Compilation::Restricted(100)).is_exact());
+    }
+
+    #[test]
+    fn merged_into_kept_state() {
+        /// counter saturating at 2: assigning v adds v to the counter (weight v)
+        struct Saturating;
+        impl MddProblem for Saturating {
+            type State = u8;
+            fn nb_variables(&self) -> usize { 2 }
+            fn root(&self) -> u8 { 0 }
+            fn transitions(&self, _variable:usize, state:&u8, arcs:&mut Vec<(usize,u8,i64)>) {
+                for v in 0..3 { arcs.push((v, (*state + v as u8).min(2), v as i64)); }
+            }
+            fn merge(&self, _states:&[&u8]) -> u8 { 2 }
+        }
+        // layer 1 keeps the state 2, and merges the states 0 and 1 into the state 2
+        let mdd = Mdd::compile(&Saturating, Compilation::Relaxed(2));
+        assert_eq!(mdd.layer(1).len(), 1);
+        assert_eq!((mdd.layer(1)[0].value(), mdd.layer(1)[0].arcs().len()), (2, 3));
+        assert_eq!(mdd.longest_path(), Some((4, vec![2, 2])));
+    }
+
+    #[test]
+    fn infeasible() {
+        struct NoSolution;
+        impl MddProblem for NoSolution {
+            type State = u8;
+            fn nb_variables(&self) -> usize { 2 }
+            fn root(&self) -> u8 { 0 }
+            fn transitions(&self, variable:usize, _state:&u8, arcs:&mut Vec<(usize,u8,i64)>) {
+                if variable == 0 { arcs.push((0, 1, 1)); }
+            }
+            fn merge(&self, states:&[&u8]) -> u8 { *states[0] }
+        }
+        let mdd = Mdd::compile(&NoSolution, Compilation::Exact);
+        assert_eq!(mdd.longest_path(), None);
+    }
+}
@@ -0,0 +1,267 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+use crate::priority_queue::pareto_list::ListParetoFront;
+
+/// Bounded beam queue.
+///
+/// Keeps only the w best elements (with the lowest guides). Inserting an element in a full
+/// queue evicts the current maximum (or rejects the inserted element if it is not better).
+/// It is backed by a min-max heap, thus both ends of the beam are accessible in O(log n).
+#[derive(Debug,Clone)]
+pub struct BeamQueue<T,Elt> {
+    /// elements of the beam
+    heap:BinaryHeapQueue<T,Elt>,
+    /// maximum number of elements
+    width:usize,
+}
+
+impl<T,Elt> BeamQueue<T,Elt> {
+    /// creates an empty beam keeping at most width elements
+    pub fn with_width(width:usize) -> Self {
+        Self { heap:BinaryHeapQueue::with_capacity(width), width }
+    }
+
+    /// returns the width of the beam
+    pub fn width(&self) -> usize { self.width }
+
+    /// returns the number of elements in the beam
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// returns true iff the beam is empty
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// returns true iff the beam contains width elements
+    pub fn is_full(&self) -> bool { self.heap.len() >= self.width }
+
+    /// returns an iterator over the elements of the beam (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> { self.heap.iter() }
+
+    /// removes all the elements of the beam
+    pub fn clear(&mut self) { self.heap.clear() }
+}
+
+impl<T,Elt> BeamQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// inserts an element in the beam.
+    ///
+    /// If the beam is full, returns the element that does not fit: either the evicted maximum,
+    /// or the inserted element itself if its guide is not lower than the current maximum.
+    pub fn push(&mut self, elt:Elt) -> Option<Elt> {
+        if self.width == 0 { return Some(elt); }
+        if !self.is_full() {
+            self.heap.insert(elt);
+            return None;
+        }
+        if self.heap.peek_max_guide().is_some_and(|g| elt.guide() >= g) {
+            return Some(elt);
+        }
+        let res = self.heap.pop_max();
+        self.heap.insert(elt);
+        res
+    }
+
+    /// returns the guide an element needs to be lower than to enter the beam
+    /// (None if the beam is not full)
+    pub fn threshold(&self) -> Option<T> {
+        if self.is_full() { self.heap.peek_max_guide() } else { None }
+    }
+
+    /// consumes the beam and returns its elements sorted by guide
+    pub fn into_sorted_vec(mut self) -> Vec<Elt> {
+        std::iter::from_fn(|| self.heap.pop_min()).collect()
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for BeamQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> { self.heap.peek_min() }
+
+    fn peek_max(&self) -> Option<&Elt> { self.heap.peek_max() }
+
+    fn reprioritize(&mut self) { self.heap.reprioritize() }
+
+    fn pop_min(&mut self) -> Option<Elt> { self.heap.pop_min() }
+
+    fn pop_max(&mut self) -> Option<Elt> { self.heap.pop_max() }
+
+    /// inserts the element. Returns false if it does not enter the beam
+    fn insert(&mut self, elt:Elt) -> bool {
+        if self.width == 0 { return false; }
+        if self.threshold().is_some_and(|g| elt.guide() >= g) { return false; }
+        self.push(elt);
+        true
+    }
+
+    /// inserts the element, and reports the maximum it evicts from a full beam
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, mut evicted:F) -> bool {
+        if self.width == 0 { return false; }
+        if self.threshold().is_some_and(|g| elt.guide() >= g) { return false; }
+        if let Some(e) = self.push(elt) { evicted(&e); }
+        true
+    }
+
+    fn peek_min_guide(&self) -> Option<T> { self.heap.peek_min_guide() }
+
+    fn peek_max_guide(&self) -> Option<T> { self.heap.peek_max_guide() }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for BeamQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn len(&self) -> usize { self.heap.len() }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for BeamQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> { self.heap.peek_k(k) }
+}
+
+
+/// Beam-search frontier.
+///
+/// Collects the candidates of the next level in a queue, keeping the width best ones (with the
+/// lowest guides), then advance_level moves them to the current level. With a pareto queue
+/// (see [BeamFrontier::pareto]), dominated candidates are also filtered out.
+///
+/// Typical use:
+/// ```text
+/// frontier.insert(root);
+/// loop {
+///     let level = frontier.advance_level();
+///     if level.is_empty() { break; }
+///     for node in level { for child in children(node) { frontier.insert(child); } }
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct BeamFrontier<T,Elt,Q=BeamQueue<T,Elt>> {
+    /// candidates of the next level
+    next:Q,
+    /// maximum number of elements per level
+    width:usize,
+    /// number of levels advanced
+    level:usize,
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt> BeamFrontier<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// creates a frontier keeping the width best candidates per level
+    pub fn new(width:usize) -> Self { Self::with_queue(width, BeamQueue::with_width(width)) }
+}
+
+impl<T,Elt> BeamFrontier<T,Elt,ListParetoFront<T,Elt>>
+where T:Ord, Elt:ParetoElement<T>+GuidedElement<T> {
+    /// creates a frontier keeping the width best non-dominated candidates per level
+    pub fn pareto(width:usize) -> Self { Self::with_queue(width, ListParetoFront::default()) }
+}
+
+impl<T,Elt,Q> BeamFrontier<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    /// creates a frontier collecting the candidates in the given (empty) queue
+    pub fn with_queue(width:usize, queue:Q) -> Self {
+        Self { next:queue, width, level:0, phantom:PhantomData }
+    }
+
+    /// returns the maximum number of elements per level
+    pub fn width(&self) -> usize { self.width }
+
+    /// returns the number of levels advanced
+    pub fn level(&self) -> usize { self.level }
+
+    /// returns the number of candidates collected for the next level
+    pub fn nb_candidates(&self) -> usize { self.next.len() }
+
+    /// returns the guide a candidate needs to be lower than to enter the next level
+    /// (None if less than width candidates were collected)
+    pub fn threshold(&self) -> Option<T> {
+        if self.next.len() >= self.width { self.next.peek_max_guide() } else { None }
+    }
+
+    /// inserts a candidate for the next level. Returns false if it is rejected (not better
+    /// than the width best candidates, or dominated)
+    pub fn insert(&mut self, elt:Elt) -> bool {
+        if self.width == 0 { return false; }
+        if self.threshold().is_some_and(|g| elt.guide() >= g) { return false; }
+        if !self.next.insert(elt) { return false; }
+        while self.next.len() > self.width { self.next.pop_max(); }
+        true
+    }
+
+    /// moves to the next level, and returns its elements sorted by guide (the candidates
+    /// collected since the last call)
+    pub fn advance_level(&mut self) -> Vec<Elt> {
+        self.level += 1;
+        self.next.drain_sorted().collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::fixtures::Value;
+
+    #[test]
+    fn test_push_evicts_maximum() {
+        let mut beam = BeamQueue::with_width(3);
+        assert_eq!(beam.push(Value(5)), None);
+        assert_eq!(beam.push(Value(8)), None);
+        assert_eq!(beam.threshold(), None);
+        assert_eq!(beam.push(Value(3)), None);
+        assert_eq!(beam.threshold(), Some(8));
+        assert_eq!(beam.push(Value(1)), Some(Value(8)));
+        assert_eq!(beam.push(Value(9)), Some(Value(9)));
+        assert_eq!(beam.into_sorted_vec(), vec![Value(1), Value(3), Value(5)]);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut beam = BeamQueue::with_width(2);
+        assert!(beam.insert(Value(4)));
+        assert!(beam.insert(Value(6)));
+        assert!(!beam.insert(Value(7)));
+        assert!(beam.insert(Value(2)));
+        assert_eq!(beam.pop_max(), Some(Value(4)));
+        assert_eq!(beam.len(), 1);
+    }
+
+    #[test]
+    fn test_frontier() {
+        let mut frontier = BeamFrontier::new(3);
+        assert!(frontier.insert(Value(1)));
+        let mut sizes = Vec::new();
+        loop {
+            let level = frontier.advance_level();
+            if level.is_empty() { break; }
+            sizes.push(level.len());
+            // children 2v and 2v+1, up to 20
+            for v in level {
+                for child in [2*v.0, 2*v.0+1].into_iter().filter(|c| *c <= 20) {
+                    frontier.insert(Value(child));
+                }
+            }
+        }
+        assert_eq!(sizes, vec![1, 2, 3, 3, 3]);
+        assert_eq!(frontier.level(), 6);
+        for v in [5, 3, 9, 4] { frontier.insert(Value(v)); }
+        assert_eq!((frontier.nb_candidates(), frontier.threshold()), (3, Some(5)));
+        assert!(!frontier.insert(Value(6)));
+        assert_eq!(frontier.advance_level(), vec![Value(3), Value(4), Value(5)]);
+    }
+
+    #[test]
+    fn test_pareto_frontier() {
+        use crate::priority_queue::util::CartesianParetoElement;
+        let mut frontier = BeamFrontier::pareto(2);
+        assert!(frontier.insert(CartesianParetoElement::<2>::new([4,4])));
+        assert!(!frontier.insert(CartesianParetoElement::<2>::new([5,4])));
+        assert!(frontier.insert(CartesianParetoElement::<2>::new([9,0])));
+        assert!(!frontier.insert(CartesianParetoElement::<2>::new([0,10])));
+        assert!(frontier.insert(CartesianParetoElement::<2>::new([3,3])));
+        assert_eq!(frontier.advance_level(), vec![
+            CartesianParetoElement::<2>::new([3,3]), CartesianParetoElement::<2>::new([9,0])
+        ]);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::priority_queue::{best_first_k, ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+
+/// Binary (min-max) heap priority queue.
+///
+/// Implements a priority queue over guided elements (no pareto logic) using an array heap in
+/// which even levels are min-levels and odd levels are max-levels. Thus, both the minimum and
+/// the maximum can be extracted efficiently. Guides are computed once at insertion and cached.
+/// - insertion: O(log n)
+/// - peek_min/peek_max: O(1)
+/// - pop_min/pop_max: O(log n)
+#[derive(Debug,Clone)]
+pub struct BinaryHeapQueue<T,Elt> {
+    /// heap of (guide, element)
+    heap:Vec<(T,Elt)>,
+}
+
+impl<T,Elt> Default for BinaryHeapQueue<T,Elt> {
+    fn default() -> Self {
+        Self { heap: Vec::new() }
+    }
+}
+
+impl<T,Elt> BinaryHeapQueue<T,Elt> {
+    /// creates an empty queue able to store capacity elements without reallocating
+    pub fn with_capacity(capacity:usize) -> Self {
+        Self { heap: Vec::with_capacity(capacity) }
+    }
+
+    /// returns the number of elements in the queue
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// returns true iff the queue is empty
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// returns an iterator over the elements of the queue (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> { self.heap.iter().map(|(_,e)| e) }
+
+    /// removes all the elements of the queue
+    pub fn clear(&mut self) { self.heap.clear() }
+}
+
+impl<T,Elt> BinaryHeapQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// builds a queue from a vector of elements (bottom-up heapify). O(n)
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let mut res = Self { heap:elts.into_iter().map(|e| (e.guide(), e)).collect() };
+        res.heapify();
+        res
+    }
+
+    /// keeps only the elements satisfying the predicate. O(n)
+    pub fn retain<F:FnMut(&Elt)->bool>(&mut self, mut f:F) {
+        self.heap.retain(|(_,e)| f(e));
+        self.heapify();
+    }
+
+    /// restores the heap order bottom-up. O(n)
+    fn heapify(&mut self) {
+        for i in (0..self.heap.len()/2).rev() {
+            self.trickle_down(i);
+        }
+    }
+
+    /// returns true iff the position is on a min-level
+    fn is_min_level(i:usize) -> bool {
+        (i+1).ilog2() & 1 == 0
+    }
+
+    /// returns true iff a is "better" than b (lower on min levels, greater on max levels)
+    fn better(a:T, b:T, min_level:bool) -> bool {
+        if min_level { a < b } else { a > b }
+    }
+
+    /// moves up the element at position i
+    fn bubble_up(&mut self, i:usize) {
+        if i == 0 { return; }
+        let parent = (i-1)/2;
+        let min_level = Self::is_min_level(i);
+        if Self::better(self.heap[parent].0, self.heap[i].0, min_level) {
+            self.heap.swap(i, parent);
+            self.bubble_up_grandparents(parent, !min_level);
+        } else {
+            self.bubble_up_grandparents(i, min_level);
+        }
+    }
+
+    /// moves up the element at position i, comparing it to its grand-parents
+    fn bubble_up_grandparents(&mut self, mut i:usize, min_level:bool) {
+        while i >= 3 {
+            let grandparent = ((i-1)/2-1)/2;
+            if Self::better(self.heap[i].0, self.heap[grandparent].0, min_level) {
+                self.heap.swap(i, grandparent);
+                i = grandparent;
+            } else { break; }
+        }
+    }
+
+    /// moves down the element at position i
+    fn trickle_down(&mut self, mut i:usize) {
+        let min_level = Self::is_min_level(i);
+        loop {
+            // find the best child or grand-child
+            let first_child = 2*i+1;
+            if first_child >= self.heap.len() { return; }
+            let mut m = first_child;
+            let candidates = [first_child+1, 4*i+3, 4*i+4, 4*i+5, 4*i+6];
+            for c in candidates {
+                if c < self.heap.len() && Self::better(self.heap[c].0, self.heap[m].0, min_level) {
+                    m = c;
+                }
+            }
+            if !Self::better(self.heap[m].0, self.heap[i].0, min_level) { return; }
+            self.heap.swap(m, i);
+            if m <= first_child+1 { return; } // m is a child
+            // m is a grand-child, check its parent
+            let parent = (m-1)/2;
+            if Self::better(self.heap[parent].0, self.heap[m].0, min_level) {
+                self.heap.swap(m, parent);
+            }
+            i = m;
+        }
+    }
+
+    /// returns the position of the maximum element
+    fn max_position(&self) -> Option<usize> {
+        match self.heap.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.heap[1].0 >= self.heap[2].0 { 1 } else { 2 }),
+        }
+    }
+
+    /// removes the element at position i
+    fn remove_at(&mut self, i:usize) -> Elt {
+        let (_,res) = self.heap.swap_remove(i);
+        if i < self.heap.len() {
+            self.trickle_down(i);
+            self.bubble_up(i);
+        }
+        res
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for BinaryHeapQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.heap.first().map(|(_,e)| e)
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.max_position().map(|i| &self.heap[i].1)
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        if self.heap.is_empty() { return None; }
+        Some(self.remove_at(0))
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let i = self.max_position()?;
+        Some(self.remove_at(i))
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.heap.push((elt.guide(), elt));
+        self.bubble_up(self.heap.len()-1);
+        true
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.heap.first().map(|(g,_)| *g)
+    }
+
+    /// recomputes the cached guides, then heapifies. O(n)
+    fn reprioritize(&mut self) {
+        for (g,e) in self.heap.iter_mut() { *g = e.guide(); }
+        self.heapify();
+    }
+
+    fn peek_max_guide(&self) -> Option<T> {
+        self.max_position().map(|i| self.heap[i].0)
+    }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for BinaryHeapQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn len(&self) -> usize { self.heap.len() }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for BinaryHeapQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// best-first search: an element is not lower than its closest ancestor on a min level
+    /// (its parent or grand-parent). O(k.log(k))
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        let roots = if self.heap.is_empty() { None } else { Some(0) };
+        best_first_k(roots, k, |i| self.heap[*i].0, |i, res| {
+            if Self::is_min_level(*i) {
+                let first_grandchild = 4*i+3;
+                res.extend((2*i+1..=2*i+2).chain(first_grandchild..first_grandchild+4)
+                    .filter(|j| *j < self.heap.len()));
+            }
+        }, |i| Some(&self.heap[*i].1))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    use crate::priority_queue::fixtures::{Tunable, Value};
+
+    /// pseudo-random sequence of values
+    fn values(n:u32) -> Vec<u32> {
+        (0..n).map(|i| (i*7919+13) % 101).collect()
+    }
+
+    #[test]
+    fn test_pop_min() {
+        let mut queue = BinaryHeapQueue::default();
+        for v in values(100) { queue.insert(Value(v)); }
+        let mut expected = values(100);
+        expected.sort();
+        for v in expected { assert_eq!(queue.pop_min(), Some(Value(v))); }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_peek_k_pop_k() {
+        let mut queue = BinaryHeapQueue::default();
+        for v in values(100) { queue.insert(Value(v)); }
+        let mut expected = values(100);
+        expected.sort();
+        let peeked:Vec<u32> = queue.peek_k(10).into_iter().map(|v| v.0).collect();
+        assert_eq!(peeked, expected[..10]);
+        let popped:Vec<u32> = queue.pop_k(10).into_iter().map(|v| v.0).collect();
+        assert_eq!(popped, expected[..10]);
+        assert_eq!(queue.peek_k(200).len(), 90);
+        assert_eq!(queue.pop_k(200).len(), 90);
+        assert!(queue.peek_k(3).is_empty());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut queue = BinaryHeapQueue::from_vec(values(100).into_iter().map(Value).collect());
+        let mut expected = values(100);
+        expected.sort();
+        assert_eq!(queue.pop_max(), Some(Value(expected[99])));
+        for v in &expected[..99] { assert_eq!(queue.pop_min(), Some(Value(*v))); }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_drain_sorted() {
+        let mut queue = BinaryHeapQueue::from_vec(values(50).into_iter().map(Value).collect());
+        let mut expected = values(50);
+        expected.sort();
+        let mut drain = queue.drain_sorted();
+        assert_eq!(drain.len(), 50);
+        let first:Vec<u32> = drain.by_ref().take(20).map(|v| v.0).collect();
+        assert_eq!(first, expected[..20]);
+        assert_eq!(drain.size_hint(), (30, Some(30)));
+        assert_eq!(queue.len(), 30);
+        let rest:Vec<u32> = queue.into_sorted_iter().map(|v| v.0).collect();
+        assert_eq!(rest, expected[20..]);
+    }
+
+    #[test]
+    fn test_reprioritize() {
+        let cells:Vec<Rc<Cell<u32>>> = values(40).into_iter().map(|v| Rc::new(Cell::new(v))).collect();
+        let mut queue = BinaryHeapQueue::default();
+        for c in &cells { queue.insert(Tunable(c.clone())); }
+        for (i,c) in cells.iter().enumerate() { c.set(1000 - i as u32); }
+        queue.reprioritize();
+        let guides:Vec<u32> = queue.drain_sorted().map(|e| e.guide()).collect();
+        assert_eq!(guides, (961..=1000).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_pop_max() {
+        let mut queue = BinaryHeapQueue::default();
+        for v in values(100) { queue.insert(Value(v)); }
+        let mut expected = values(100);
+        expected.sort();
+        for v in expected.into_iter().rev() { assert_eq!(queue.pop_max(), Some(Value(v))); }
+        assert!(queue.pop_max().is_none());
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let mut queue = BinaryHeapQueue::default();
+        let mut expected = values(60);
+        for v in &expected { queue.insert(Value(*v)); }
+        expected.sort();
+        let (mut lo, mut hi) = (0, expected.len());
+        while lo < hi {
+            assert_eq!(queue.peek_min_guide(), Some(expected[lo]));
+            assert_eq!(queue.peek_max_guide(), Some(expected[hi-1]));
+            if (lo+hi) & 1 == 0 {
+                assert_eq!(queue.pop_min(), Some(Value(expected[lo])));
+                lo += 1;
+            } else {
+                assert_eq!(queue.pop_max(), Some(Value(expected[hi-1])));
+                hi -= 1;
+            }
+        }
+        assert_eq!(queue.len(), 0);
+    }
+}
@@ -0,0 +1,190 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use rand::Rng;
+
+use crate::priority_queue::{ConcurrentPriorityQueue, ExactSizeQueue, GuidedElement};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
+/// locks the mutex, ignoring poisoning (a panicking thread leaves the queue usable)
+fn lock<Q>(m:&Mutex<Q>) -> MutexGuard<'_,Q> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A queue behind a mutex is a concurrent queue with exact pops (but every operation
+/// contends on the same lock).
+impl<T,Elt,Q> ConcurrentPriorityQueue<T,Elt> for Mutex<Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt>+Send {
+    fn insert(&self, elt:Elt) -> bool { lock(self).insert(elt) }
+
+    fn pop_min(&self) -> Option<Elt> { lock(self).pop_min() }
+
+    fn len(&self) -> usize { lock(self).len() }
+}
+
+
+/// Sharded concurrent priority queue.
+///
+/// Elements are spread over several queues (shards), each protected by its own lock.
+/// Insertions go to a random shard. pop_min picks two random shards, and pops from the one
+/// having the lowest minimum (relaxed pops: the returned element is close to the global
+/// minimum, but not necessarily equal). If both are empty, all shards are scanned, thus
+/// pop_min only returns None if the queue is (momentarily) empty.
+///
+/// Using a few times more shards than threads keeps the contention low.
+#[derive(Debug)]
+pub struct ShardedQueue<T,Elt,Q=BinaryHeapQueue<T,Elt>> {
+    /// shards
+    shards:Vec<Mutex<Q>>,
+    /// number of elements in the queue
+    nb_elements:AtomicUsize,
+    /// phantom for types T and Elt
+    phantom:PhantomData<fn() -> (T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> ShardedQueue<T,Elt,Q> {
+    /// creates an empty queue with the given number of shards (at least one)
+    pub fn with_shards(nb_shards:usize) -> Self {
+        Self {
+            shards:(0..nb_shards.max(1)).map(|_| Mutex::new(Q::default())).collect(),
+            nb_elements:AtomicUsize::new(0),
+            phantom:PhantomData,
+        }
+    }
+
+    /// returns the number of shards
+    pub fn nb_shards(&self) -> usize { self.shards.len() }
+
+    /// consumes the queue and returns its shards
+    pub fn into_shards(self) -> Vec<Q> {
+        self.shards.into_iter()
+            .map(|m| m.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect()
+    }
+}
+
+impl<T,Elt,Q> ShardedQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt>+Send {
+    /// returns the minimum guide of the queue (each shard is locked in turn, thus it may
+    /// be outdated if other threads modify the queue)
+    pub fn peek_min_guide(&self) -> Option<T> {
+        self.shards.iter().filter_map(|s| lock(s).peek_min_guide()).min()
+    }
+
+    /// pops from the shard i if it is not empty
+    fn pop_from(&self, i:usize) -> Option<Elt> {
+        let res = lock(&self.shards[i]).pop_min();
+        if res.is_some() { self.nb_elements.fetch_sub(1, Ordering::Relaxed); }
+        res
+    }
+}
+
+impl<T,Elt,Q> ConcurrentPriorityQueue<T,Elt> for ShardedQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt>+Send {
+    fn insert(&self, elt:Elt) -> bool {
+        let i = rand::thread_rng().gen_range(0..self.shards.len());
+        // the shard may drop elements because of the insertion (e.g. dominated ones)
+        let mut nb_evicted = 0;
+        let res = lock(&self.shards[i]).insert_evicting(elt, |_| nb_evicted += 1);
+        if res { self.nb_elements.fetch_add(1, Ordering::Relaxed); }
+        if nb_evicted > 0 { self.nb_elements.fetch_sub(nb_evicted, Ordering::Relaxed); }
+        res
+    }
+
+    fn pop_min(&self) -> Option<Elt> {
+        let n = self.shards.len();
+        let mut rng = rand::thread_rng();
+        let i = rng.gen_range(0..n);
+        if n > 1 {
+            let j = (i + rng.gen_range(1..n)) % n;
+            // shards are locked one at a time (no deadlock)
+            let gi = lock(&self.shards[i]).peek_min_guide();
+            let gj = lock(&self.shards[j]).peek_min_guide();
+            let best = match (gi, gj) {
+                (Some(a), Some(b)) => Some(if b < a { j } else { i }),
+                (Some(_), None) => Some(i),
+                (None, Some(_)) => Some(j),
+                (None, None) => None,
+            };
+            if let Some(res) = best.and_then(|k| self.pop_from(k)) { return Some(res); }
+        }
+        (0..n).find_map(|k| self.pop_from((i+k) % n))
+    }
+
+    fn len(&self) -> usize { self.nb_elements.load(Ordering::Relaxed) }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::thread;
+
+    use crate::priority_queue::fixtures::Value;
+
+    /// inserts 0..4000 from 4 threads, then pops everything from 4 threads
+    fn insert_pop_all<Q:ConcurrentPriorityQueue<u32,Value>>(queue:&Q) -> Vec<u32> {
+        thread::scope(|s| {
+            for t in 0..4 {
+                s.spawn(move || for i in 0..1000 { queue.insert(Value(4*i + t)); });
+            }
+        });
+        assert_eq!(queue.len(), 4000);
+        let mut res:Vec<u32> = thread::scope(|s| {
+            let workers:Vec<_> = (0..4).map(|_| s.spawn(move || {
+                std::iter::from_fn(|| queue.pop_min()).map(|v| v.0).collect::<Vec<u32>>()
+            })).collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+        assert!(queue.is_empty());
+        res.sort();
+        res
+    }
+
+    #[test]
+    fn test_mutex_queue() {
+        let queue:Mutex<BinaryHeapQueue<u32,Value>> = Mutex::default();
+        assert_eq!(insert_pop_all(&queue), (0..4000).collect::<Vec<u32>>());
+        queue.insert(Value(3));
+        queue.insert(Value(1));
+        assert_eq!(ConcurrentPriorityQueue::pop_min(&queue), Some(Value(1)));
+    }
+
+    #[test]
+    fn test_sharded_queue() {
+        let queue:ShardedQueue<u32,Value> = ShardedQueue::with_shards(8);
+        assert_eq!(insert_pop_all(&queue), (0..4000).collect::<Vec<u32>>());
+        assert_eq!(queue.pop_min(), None);
+    }
+
+    #[test]
+    fn test_evicting_shards() {
+        use crate::priority_queue::kd_tree::KDTreeFront;
+        use crate::priority_queue::util::CartesianParetoElement;
+        let queue:ShardedQueue<u32,CartesianParetoElement<2>,KDTreeFront<u32,CartesianParetoElement<2>,2>> =
+            ShardedQueue::with_shards(1);
+        for i in 0..10 { assert!(queue.insert(CartesianParetoElement::new([i,10-i]))); }
+        assert_eq!(queue.len(), 10);
+        // dominates 5 elements
+        assert!(queue.insert(CartesianParetoElement::new([2,4])));
+        assert_eq!(queue.len(), 6);
+        assert_eq!(std::iter::from_fn(|| queue.pop_min()).count(), 6);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_relaxed_pops() {
+        let queue:ShardedQueue<u32,Value> = ShardedQueue::with_shards(4);
+        for i in 0..1000 { queue.insert(Value(i)); }
+        assert_eq!(queue.peek_min_guide(), Some(0));
+        // each pop returns the minimum of some shard: popped values are roughly increasing
+        let first:Vec<u32> = (0..100).filter_map(|_| queue.pop_min()).map(|v| v.0).collect();
+        assert!(first.iter().all(|v| *v < 500));
+        assert_eq!(queue.len(), 900);
+        let shards = queue.into_shards();
+        assert_eq!(shards.len(), 4);
+        assert_eq!(shards.iter().map(|s| s.len()).sum::<usize>(), 900);
+    }
+}
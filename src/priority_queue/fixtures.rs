@@ -0,0 +1,21 @@
+use std::{cell::Cell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::priority_queue::GuidedElement;
+
+/// element guided by its value
+#[derive(Debug,Clone,PartialEq,Eq,Serialize,Deserialize)]
+pub struct Value(pub u32);
+
+impl GuidedElement<u32> for Value {
+    fn guide(&self) -> u32 { self.0 }
+}
+
+/// element whose guide can be changed after its insertion
+#[derive(Debug,Clone)]
+pub struct Tunable(pub Rc<Cell<u32>>);
+
+impl GuidedElement<u32> for Tunable {
+    fn guide(&self) -> u32 { self.0.get() }
+}
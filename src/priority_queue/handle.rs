@@ -0,0 +1,187 @@
+use crate::priority_queue::{GuidedElement, ParetoElement};
+
+/// Stable handle to an element stored in a data-structure.
+///
+/// A handle stays valid as long as the element it refers to is stored. Once the element is
+/// removed (popped, removed by handle, or evicted by dominance), the handle is invalidated
+/// and never refers to another element, even if its slot is reused (slots count their reuses
+/// with 64-bit generations, which do not wrap around in practice).
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Handle {
+    /// slot index in the handle table
+    index:usize,
+    /// generation of the slot when the handle was created
+    generation:u64,
+}
+
+impl Handle {
+    /// returns the slot index of the handle
+    pub fn index(&self) -> usize { self.index }
+}
+
+/// slot of the handle table
+#[derive(Debug,Clone)]
+struct Slot {
+    /// current generation of the slot
+    generation:u64,
+    /// position of the element in the data-structure (None if the slot is free)
+    pos:Option<usize>,
+}
+
+/// Maps handles to positions (slot-map).
+///
+/// Data-structures storing their elements in a vector use it to provide stable handles:
+/// each time an element moves, its position is updated in the table.
+/// - create: O(1)
+/// - position: O(1)
+/// - invalidate: O(1)
+#[derive(Debug,Clone,Default)]
+pub struct HandleTable {
+    /// slots of the table
+    slots:Vec<Slot>,
+    /// free slot indices
+    free:Vec<usize>,
+}
+
+impl HandleTable {
+    /// creates a new handle pointing to the given position
+    pub fn create(&mut self, pos:usize) -> Handle {
+        match self.free.pop() {
+            Some(index) => {
+                self.slots[index].pos = Some(pos);
+                Handle { index, generation:self.slots[index].generation }
+            },
+            None => {
+                self.slots.push(Slot { generation:0, pos:Some(pos) });
+                Handle { index:self.slots.len()-1, generation:0 }
+            }
+        }
+    }
+
+    /// returns the handle the next call to create returns
+    pub fn next_handle(&self) -> Handle {
+        match self.free.last() {
+            Some(&index) => Handle { index, generation:self.slots[index].generation },
+            None => Handle { index:self.slots.len(), generation:0 },
+        }
+    }
+
+    /// returns true iff the handle refers to a stored element
+    pub fn is_valid(&self, h:Handle) -> bool { self.position(h).is_some() }
+
+    /// returns the position of the element referred by the handle (None if invalid)
+    pub fn position(&self, h:Handle) -> Option<usize> {
+        match self.slots.get(h.index) {
+            Some(slot) if slot.generation == h.generation => slot.pos,
+            _ => None,
+        }
+    }
+
+    /// updates the position of the element referred by the handle
+    pub fn set_position(&mut self, h:Handle, pos:usize) {
+        debug_assert!(self.is_valid(h));
+        self.slots[h.index].pos = Some(pos);
+    }
+
+    /// invalidates the handle. Returns the last known position of its element
+    pub fn invalidate(&mut self, h:Handle) -> Option<usize> {
+        let res = self.position(h)?;
+        let slot = &mut self.slots[h.index];
+        slot.pos = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(h.index);
+        Some(res)
+    }
+
+    /// returns the number of valid handles
+    pub fn len(&self) -> usize { self.slots.len() - self.free.len() }
+
+    /// returns true iff no handle is valid
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// invalidates all handles
+    pub fn clear(&mut self) {
+        self.free.clear();
+        for (i,slot) in self.slots.iter_mut().enumerate() {
+            if slot.pos.is_some() {
+                slot.pos = None;
+                slot.generation = slot.generation.wrapping_add(1);
+            }
+            self.free.push(i);
+        }
+    }
+}
+
+
+/// Element tagged with a handle.
+///
+/// Allows storing handles in queues that are not aware of them. The guide (and the pareto
+/// coordinates) of the element are forwarded.
+#[derive(Debug,Clone)]
+pub struct Handled<Elt> {
+    /// handle of the element
+    handle:Handle,
+    /// element
+    elt:Elt,
+}
+
+impl<Elt> Handled<Elt> {
+    /// tags the element with the handle
+    pub fn new(handle:Handle, elt:Elt) -> Self { Self { handle, elt } }
+
+    /// returns the handle of the element
+    pub fn handle(&self) -> Handle { self.handle }
+
+    /// returns the element
+    pub fn elt(&self) -> &Elt { &self.elt }
+
+    /// returns the element, consuming the tagged element
+    pub fn into_elt(self) -> Elt { self.elt }
+}
+
+impl<T:Ord,Elt:GuidedElement<T>> GuidedElement<T> for Handled<Elt> {
+    fn guide(&self) -> T { self.elt.guide() }
+}
+
+impl<T:Ord,Elt:ParetoElement<T>> ParetoElement<T> for Handled<Elt> {
+    type CoordIterator = Elt::CoordIterator;
+
+    fn coordinates(&self) -> Self::CoordIterator { self.elt.coordinates() }
+
+    fn dominates(&self, other:&Self) -> bool { self.elt.dominates(&other.elt) }
+
+    fn nb_dimensions(&self) -> usize { self.elt.nb_dimensions() }
+
+    fn kth(&self, k:usize) -> T { self.elt.kth(k) }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_create_and_invalidate() {
+        let mut table = HandleTable::default();
+        let h1 = table.create(0);
+        let h2 = table.create(1);
+        assert_eq!(table.position(h1), Some(0));
+        assert_eq!(table.position(h2), Some(1));
+        assert_eq!(table.invalidate(h1), Some(0));
+        assert!(!table.is_valid(h1));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_reused_slot() {
+        let mut table = HandleTable::default();
+        let h1 = table.create(0);
+        table.invalidate(h1);
+        let next = table.next_handle();
+        let h2 = table.create(3);
+        assert_eq!(next, h2);
+        assert_eq!(h1.index(), h2.index());
+        assert!(!table.is_valid(h1));
+        assert_eq!(table.position(h2), Some(3));
+    }
+}
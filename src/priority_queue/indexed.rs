@@ -0,0 +1,241 @@
+use crate::priority_queue::GuidedElement;
+
+/// marks an identifier that is not in the queue
+const ABSENT:usize = usize::MAX;
+
+/// Indexed (addressable) priority queue.
+///
+/// Each element is associated with a user-provided identifier (usize). The queue contains at
+/// most one element per identifier, and the element of an identifier can be updated or removed.
+/// It is implemented as a binary heap with a position table indexed by identifiers, thus
+/// identifiers should be small (dense) integers: the table takes O(max id) memory, and
+/// identifier usize::MAX is not supported.
+/// - insert_or_update: O(log n)
+/// - remove: O(log n)
+/// - contains: O(1)
+/// - peek_min: O(1)
+/// - pop_min: O(log n)
+#[derive(Debug,Clone)]
+pub struct IndexedQueue<T,Elt> {
+    /// heap of (guide, id, element)
+    heap:Vec<(T,usize,Elt)>,
+    /// pos\[id\] = i <=> heap\[i\] has identifier id
+    pos:Vec<usize>,
+}
+
+impl<T,Elt> Default for IndexedQueue<T,Elt> {
+    fn default() -> Self {
+        Self { heap:Vec::new(), pos:Vec::new() }
+    }
+}
+
+impl<T,Elt> IndexedQueue<T,Elt> {
+    /// creates an empty queue for identifiers in 0..nb_ids (larger identifiers are supported,
+    /// but require growing the position table)
+    pub fn with_capacity(nb_ids:usize) -> Self {
+        Self { heap:Vec::new(), pos:vec![ABSENT;nb_ids] }
+    }
+
+    /// returns the number of elements in the queue
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// returns true iff the queue is empty
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// returns true iff the queue contains an element with the given identifier
+    pub fn contains(&self, id:usize) -> bool {
+        self.pos.get(id).is_some_and(|p| *p != ABSENT)
+    }
+
+    /// returns the element with the given identifier
+    pub fn get(&self, id:usize) -> Option<&Elt> {
+        if !self.contains(id) { return None; }
+        Some(&self.heap[self.pos[id]].2)
+    }
+
+    /// returns an iterator over the (identifier, element) pairs (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=(usize,&Elt)> {
+        self.heap.iter().map(|(_,id,e)| (*id,e))
+    }
+}
+
+impl<T,Elt> IndexedQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// inserts the element with the given identifier, or replaces the element already
+    /// associated with it. The position table grows up to the identifier.
+    ///
+    /// returns true iff the identifier was not in the queue. Panics if id is usize::MAX
+    pub fn insert_or_update(&mut self, id:usize, elt:Elt) -> bool {
+        let guide = elt.guide();
+        if self.contains(id) {
+            let i = self.pos[id];
+            let previous_guide = self.heap[i].0;
+            self.heap[i] = (guide, id, elt);
+            if guide < previous_guide { self.sift_up(i); } else { self.sift_down(i); }
+            false
+        } else {
+            if id >= self.pos.len() {
+                let nb_ids = id.checked_add(1).expect("IndexedQueue identifiers must be lower than usize::MAX");
+                self.pos.resize(nb_ids, ABSENT);
+            }
+            self.heap.push((guide, id, elt));
+            self.pos[id] = self.heap.len()-1;
+            self.sift_up(self.heap.len()-1);
+            true
+        }
+    }
+
+    /// inserts the element with the given identifier if the identifier is not in the queue,
+    /// or if the new element has a strictly better (lower) guide than the stored one.
+    ///
+    /// returns true iff the element was inserted
+    pub fn insert_if_better(&mut self, id:usize, elt:Elt) -> bool {
+        if self.contains(id) && self.heap[self.pos[id]].0 <= elt.guide() { return false; }
+        self.insert_or_update(id, elt);
+        true
+    }
+
+    /// restores the heap order after the guide of the element with the given identifier
+    /// changed. Returns false if the identifier is not in the queue
+    pub fn update(&mut self, id:usize) -> bool {
+        if !self.contains(id) { return false; }
+        let i = self.pos[id];
+        let previous_guide = self.heap[i].0;
+        self.heap[i].0 = self.heap[i].2.guide();
+        if self.heap[i].0 < previous_guide { self.sift_up(i); } else { self.sift_down(i); }
+        true
+    }
+
+    /// removes the element with the given identifier. Returns it if it existed
+    pub fn remove(&mut self, id:usize) -> Option<Elt> {
+        if !self.contains(id) { return None; }
+        Some(self.remove_at(self.pos[id]).1)
+    }
+
+    /// returns the element with the minimum guide with its identifier
+    pub fn peek_min(&self) -> Option<(usize,&Elt)> {
+        self.heap.first().map(|(_,id,e)| (*id,e))
+    }
+
+    /// returns the minimum guide
+    pub fn peek_min_guide(&self) -> Option<T> {
+        self.heap.first().map(|(g,_,_)| *g)
+    }
+
+    /// removes the element with the minimum guide. Returns it with its identifier
+    pub fn pop_min(&mut self) -> Option<(usize,Elt)> {
+        if self.heap.is_empty() { return None; }
+        Some(self.remove_at(0))
+    }
+
+    /// removes the element at position i of the heap
+    fn remove_at(&mut self, i:usize) -> (usize,Elt) {
+        let (_,id,elt) = self.heap.swap_remove(i);
+        self.pos[id] = ABSENT;
+        if i < self.heap.len() {
+            self.pos[self.heap[i].1] = i;
+            self.sift_down(i);
+            self.sift_up(i);
+        }
+        (id,elt)
+    }
+
+    /// swaps two positions of the heap
+    fn swap(&mut self, i:usize, j:usize) {
+        self.heap.swap(i,j);
+        self.pos[self.heap[i].1] = i;
+        self.pos[self.heap[j].1] = j;
+    }
+
+    /// moves up the element at position i
+    fn sift_up(&mut self, mut i:usize) {
+        while i > 0 {
+            let parent = (i-1)/2;
+            if self.heap[i].0 < self.heap[parent].0 {
+                self.swap(i, parent);
+                i = parent;
+            } else { break; }
+        }
+    }
+
+    /// moves down the element at position i
+    fn sift_down(&mut self, mut i:usize) {
+        loop {
+            let (l,r) = (2*i+1, 2*i+2);
+            let mut best = i;
+            if l < self.heap.len() && self.heap[l].0 < self.heap[best].0 { best = l; }
+            if r < self.heap.len() && self.heap[r].0 < self.heap[best].0 { best = r; }
+            if best == i { break; }
+            self.swap(i, best);
+            i = best;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    use crate::priority_queue::fixtures::{Tunable, Value};
+
+    #[test]
+    fn test_insert_or_update() {
+        let mut queue = IndexedQueue::default();
+        assert!(queue.insert_or_update(3, Value(30)));
+        assert!(queue.insert_or_update(1, Value(10)));
+        assert!(queue.insert_or_update(7, Value(20)));
+        assert!(!queue.insert_or_update(3, Value(5)));
+        assert!(!queue.insert_or_update(1, Value(40)));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.pop_min(), Some((3, Value(5))));
+        assert_eq!(queue.pop_min(), Some((7, Value(20))));
+        assert_eq!(queue.pop_min(), Some((1, Value(40))));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_contains() {
+        let mut queue = IndexedQueue::with_capacity(10);
+        for id in 0..10 { queue.insert_or_update(id, Value(100 - id as u32)); }
+        assert_eq!(queue.remove(4), Some(Value(96)));
+        assert_eq!(queue.remove(4), None);
+        assert!(!queue.contains(4));
+        assert!(queue.contains(5));
+        assert_eq!(queue.get(9), Some(&Value(91)));
+        let ids:Vec<usize> = std::iter::from_fn(|| queue.pop_min()).map(|(id,_)| id).collect();
+        assert_eq!(ids, vec![9,8,7,6,5,3,2,1,0]);
+    }
+
+    #[test]
+    fn test_update() {
+        let cells:Vec<Rc<Cell<u32>>> = (0..10).map(|i| Rc::new(Cell::new(i))).collect();
+        let mut queue = IndexedQueue::default();
+        for (id,c) in cells.iter().enumerate() { queue.insert_or_update(id, Tunable(c.clone())); }
+        cells[0].set(20);
+        assert!(queue.update(0));
+        cells[8].set(0);
+        assert!(queue.update(8));
+        assert!(!queue.update(12));
+        let ids:Vec<usize> = std::iter::from_fn(|| queue.pop_min()).map(|(id,_)| id).collect();
+        assert_eq!(ids, vec![8,1,2,3,4,5,6,7,9,0]);
+    }
+
+    #[test]
+    fn test_insert_if_better() {
+        let mut queue = IndexedQueue::default();
+        assert!(queue.insert_if_better(0, Value(10)));
+        assert!(!queue.insert_if_better(0, Value(10)));
+        assert!(queue.insert_if_better(0, Value(3)));
+        assert_eq!(queue.peek_min_guide(), Some(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "IndexedQueue identifiers must be lower than usize::MAX")]
+    fn test_max_identifier() {
+        let mut queue = IndexedQueue::default();
+        queue.insert_or_update(usize::MAX, Value(0));
+    }
+}
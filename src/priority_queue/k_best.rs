@@ -0,0 +1,99 @@
+use std::collections::BinaryHeap;
+
+/// k-best container.
+///
+/// Retains the k best (lowest) elements seen so far. The k-th best element is the admission
+/// threshold of new elements: an element not lower than it can be pruned. Use
+/// std::cmp::Reverse to retain the largest elements instead.
+/// - memory: O(k)
+/// - threshold: O(1)
+/// - push: O(log k)
+/// - sorted results: O(k.log(k))
+#[derive(Debug,Clone)]
+pub struct KBest<T> {
+    /// retained elements (max-heap: the top is the k-th best)
+    heap:BinaryHeap<T>,
+    /// number of elements retained
+    k:usize,
+}
+
+impl<T:Ord> KBest<T> {
+    /// creates a container retaining the k best elements
+    pub fn new(k:usize) -> Self { Self { heap:BinaryHeap::with_capacity(k), k } }
+
+    /// returns the number of elements retained when full
+    pub fn k(&self) -> usize { self.k }
+
+    /// returns the number of retained elements
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// returns true iff no element is retained
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// returns true iff k elements are retained
+    pub fn is_full(&self) -> bool { self.heap.len() >= self.k }
+
+    /// returns the k-th best element: new elements must be lower to be retained
+    /// (None if less than k elements were pushed)
+    pub fn threshold(&self) -> Option<&T> {
+        if self.is_full() { self.heap.peek() } else { None }
+    }
+
+    /// returns true iff the element would be retained
+    pub fn admits(&self, elt:&T) -> bool {
+        self.k > 0 && self.threshold().is_none_or(|t| elt < t)
+    }
+
+    /// offers an element. Returns true iff it is retained (the k-th best element is then
+    /// discarded if the container was full)
+    pub fn push(&mut self, elt:T) -> bool {
+        if !self.admits(&elt) { return false; }
+        if self.is_full() { self.heap.pop(); }
+        self.heap.push(elt);
+        true
+    }
+
+    /// iterates over the retained elements (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&T> { self.heap.iter() }
+
+    /// consumes the container and returns the retained elements from the best one
+    pub fn into_sorted_vec(self) -> Vec<T> { self.heap.into_sorted_vec() }
+}
+
+impl<T:Ord> Extend<T> for KBest<T> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for elt in iter { self.push(elt); }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test_k_best() {
+        let mut best = KBest::new(3);
+        assert!(best.push(7) && best.push(2) && best.push(9));
+        assert_eq!(best.threshold(), Some(&9));
+        assert!(!best.push(9));
+        assert!(best.push(4));
+        assert_eq!(best.threshold(), Some(&7));
+        assert!(best.admits(&6) && !best.admits(&7));
+        best.extend([1, 8, 3]);
+        assert_eq!(best.len(), 3);
+        assert_eq!(best.into_sorted_vec(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_largest_and_empty() {
+        let mut largest = KBest::new(2);
+        largest.extend([5, 1, 8, 3].map(Reverse));
+        assert_eq!(largest.into_sorted_vec(), vec![Reverse(8), Reverse(5)]);
+        let mut none:KBest<u32> = KBest::new(0);
+        assert!(!none.push(1) && none.is_empty());
+        assert_eq!(none.threshold(), None);
+    }
+}
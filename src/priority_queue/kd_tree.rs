@@ -1,6 +1,6 @@
-use std::{marker::PhantomData, mem::swap};
+use std::{cmp::Reverse, marker::PhantomData, mem::swap};
 
-use crate::priority_queue::{GuidedElement, ParetoElement, PriorityQueue, ParetoFront};
+use crate::priority_queue::{best_first_k, filter_dominated, on_evictions, FrontEvent, ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue, ParetoFront};
 
 type Link<T, Elt, const NB_DIM:usize> = Option<Box<Node<T, Elt,NB_DIM>>>;
 
@@ -9,6 +9,8 @@ type Link<T, Elt, const NB_DIM:usize> = Option<Box<Node<T, Elt,NB_DIM>>>;
 struct Node<T, Elt, const NB_DIM:usize> {
     /// element of the node
     e:Elt,
+    /// guide of the element (cached, as it may be expensive to compute)
+    guide:T,
     /// left child
     l:Link<T, Elt,NB_DIM>,
     /// right child
@@ -26,6 +28,19 @@ where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
     /// returns the node element
     pub fn elt(&self) -> &Elt { &self.e }
 
+    /// returns the (cached) guide of the node element
+    pub fn guide(&self) -> T { self.guide }
+
+    /// replaces the node element, and returns the previous one. Bounds are not updated
+    pub fn replace_elt(&mut self, mut e:Elt) -> Elt {
+        swap(&mut e, &mut self.e);
+        self.guide = self.e.guide();
+        e
+    }
+
+    /// recomputes the cached guide of the node element. Bounds are not updated
+    pub fn refresh_guide(&mut self) { self.guide = self.e.guide(); }
+
     /// returns the left child
     pub fn left(&self) -> &Link<T, Elt,NB_DIM> { &self.l }
 
@@ -45,10 +60,7 @@ where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
     pub fn attach_left(&mut self, t:Link<T,Elt,NB_DIM>) -> Link<T,Elt,NB_DIM> {
         let mut res = t;
         swap(&mut res, &mut self.l);
-        let (b, lb, ub) = Self::compute_bounds(&self.e, &self.l, &self.r);
-        self.b = b;
-        self.guide_lb = lb;
-        self.guide_ub = ub;
+        self.update_bounds();
         res
     }
 
@@ -56,16 +68,14 @@ where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
     pub fn attach_right(&mut self, t:Link<T,Elt,NB_DIM>) -> Link<T,Elt,NB_DIM> {
         let mut res = t;
         swap(&mut res, &mut self.r);
-        let (b, lb, ub) = Self::compute_bounds(&self.e, &self.l, &self.r);
-        self.b = b;
-        self.guide_lb = lb;
-        self.guide_ub = ub;
+        self.update_bounds();
         res
     }
 
     pub fn new(e:Elt, l:Link<T,Elt,NB_DIM>, r:Link<T,Elt,NB_DIM>) -> Self {
-        let (b, lb, ub) = Self::compute_bounds(&e, &l, &r);
-        Self { e, l, r, b, guide_lb:lb, guide_ub:ub }
+        let guide = e.guide();
+        let (b, lb, ub) = Self::compute_bounds(&e, guide, &l, &r);
+        Self { e, guide, l, r, b, guide_lb:lb, guide_ub:ub }
     }
 
     /// decompose the node into (elt,left,right)
@@ -75,22 +85,21 @@ where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
 
     /// update bounds of the node
     pub fn update_bounds(&mut self) {
-        let (b,lb,ub) = Self::compute_bounds(&self.e, &self.l, &self.r);
+        let (b,lb,ub) = Self::compute_bounds(&self.e, self.guide, &self.l, &self.r);
         self.b = b;
         self.guide_lb = lb;
         self.guide_ub = ub;
     }
 
-    /// compute the bounds given e, left, right
-    pub fn compute_bounds(e:&Elt, l:&Link<T,Elt,NB_DIM>, r:&Link<T,Elt,NB_DIM>) -> ([(T,T);NB_DIM],T,T) {
+    /// compute the bounds given e (and its guide), left, right
+    pub fn compute_bounds(e:&Elt, guide:T, l:&Link<T,Elt,NB_DIM>, r:&Link<T,Elt,NB_DIM>) -> ([(T,T);NB_DIM],T,T) {
         let dummy_t = e.kth(0);
         let mut res:[(T,T);NB_DIM] = [(dummy_t,dummy_t);NB_DIM];
-        for (i,(a,b)) in e.coordinates().zip(e.coordinates())
-        .map(|(a,b)| (a,b)).enumerate() {
-            res[i] = (a,b);
+        for (i,a) in e.coordinates().enumerate() {
+            res[i] = (a,a);
         }
-        let mut lb = e.guide();
-        let mut ub = e.guide();
+        let mut lb = guide;
+        let mut ub = guide;
         if let Some(n) = l {
             for (i,(lower,upper)) in n.bounds().iter().enumerate() {
                 res[i] = (
@@ -117,73 +126,161 @@ where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
 }
 
 
+/// side of a node containing a minimum: the node itself, or one of its sub-trees
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Side {
+    Here,
+    Left,
+    Right,
+}
+
+impl Side {
+    /// returns the side containing the minimum, given the value of the node and the lower
+    /// bounds of its sub-trees. The node is preferred in case of ties
+    fn of_minimum<T:Ord>(v_e:T, v_l:Option<T>, v_r:Option<T>) -> Self {
+        match (v_l, v_r) {
+            (Some(vl), Some(vr)) if vl < v_e && vl < vr => Side::Left,
+            (Some(vl), None) if vl < v_e => Side::Left,
+            (_, Some(vr)) if vr < v_e => Side::Right,
+            _ => Side::Here,
+        }
+    }
+}
+
+
 /// Kd-tree based pareto front structure
 #[derive(Debug)]
 pub struct KDTreeFront<T, Elt, const NB_DIM:usize> {
     /// root node
     root:Link<T,Elt,NB_DIM>,
+    /// number of elements in the front
+    nb_elements:usize,
     /// phantom for type T
     phantom_t:PhantomData<T>,
 }
 
 impl<T, Elt, const NB_DIM:usize> Default for KDTreeFront<T, Elt, NB_DIM> {
     fn default() -> Self {
-        Self { root: None, phantom_t: PhantomData::default() }
+        Self { root: None, nb_elements: 0, phantom_t: PhantomData }
     }
 }
 
 impl<T, Elt, const NB_DIM:usize> PriorityQueue<T, Elt> for KDTreeFront<T, Elt, NB_DIM>
 where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
     fn peek_min(&self) -> Option<&Elt> {
-        let (link,_) = Self::rec_search_min_guide(&self.root, 0);
+        let (link,_) = Self::rec_search_guide(&self.root, 0, false);
         link.as_ref().map(|n| n.elt())
     }
 
     fn peek_max(&self) -> Option<&Elt> {
-        todo!()
+        let (link,_) = Self::rec_search_guide(&self.root, 0, true);
+        link.as_ref().map(|n| n.elt())
     }
 
     fn pop_min(&mut self) -> Option<Elt> {
-        let (link,dim) = Self::mut_rec_search_min_guide(&mut self.root, 0);
-        match dim {
-            Some(d) => {
-                let res = Self::remove_link(link, d);
-                if let Some(elt) = &res {
-                    Self::rec_update_bounds(&mut self.root, elt.guide());
-                }
-                res
-            },
-            None => None,
-        }
+        let res = Self::rec_pop_guide(&mut self.root, 0, false);
+        if res.is_some() { self.nb_elements -= 1; }
+        res
     }
 
     fn pop_max(&mut self) -> Option<Elt> {
-        todo!()
+        let res = Self::rec_pop_guide(&mut self.root, 0, true);
+        if res.is_some() { self.nb_elements -= 1; }
+        res
     }
 
     fn insert(&mut self, elt:Elt) -> bool {
-        if self.find_dominating(&elt).is_some() { return false; } // dominated, stop here
-        // find all elements dominated by elt
-        Self::rec_remove_dominated_by(&mut self.root, &elt, 0);
-        // finally insert the element
-        self.insert_without_check(elt);
-        true
+        self.insert_observed(elt, |_| {})
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.insert_observed(elt, on_evictions(evicted))
     }
 
     fn peek_min_guide(&self) -> Option<T> {
         self.root.as_ref().map(|node| node.guide_lb)
     }
 
+    /// recomputes the cached guides and guide bounds of all the nodes. O(n)
+    fn reprioritize(&mut self) {
+        Self::rec_update_bounds(&mut self.root);
+    }
+
     fn peek_max_guide(&self) -> Option<T> {
         self.root.as_ref().map(|node| node.guide_ub)
     }
 }
 
+impl<T, Elt, const NB_DIM:usize> ExactSizeQueue<T, Elt> for KDTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T, Elt, const NB_DIM:usize> PeekKQueue<T, Elt> for KDTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
+    /// best-first search over the sub-trees (guided by their guide lower bound)
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        // (node, true) stands for the element of the node, (node, false) for its sub-tree
+        best_first_k(self.root.as_deref().map(|n| (n,false)), k, |(n,is_elt)| {
+            if *is_elt { n.guide() } else { n.guide_lb }
+        }, |(n,is_elt), res| if !is_elt {
+            res.push((*n,true));
+            res.extend(n.left().as_deref().map(|c| (c,false)));
+            res.extend(n.right().as_deref().map(|c| (c,false)));
+        }, |(n,is_elt)| if *is_elt { Some(n.elt()) } else { None })
+    }
+}
+
 impl<T, Elt, const NB_DIM:usize> ParetoFront<T, Elt> for KDTreeFront<T, Elt, NB_DIM>
 where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
     fn find_dominating(&self, elt:&Elt) -> Option<&Elt> {
         Self::rec_exists_dominating(&self.root, elt)
     }
+
+    fn insert_observed<O>(&mut self, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        if let Some(by) = self.find_dominating(&elt) { // dominated, stop here
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return false;
+        }
+        // find all elements dominated by elt
+        self.nb_elements -= Self::rec_remove_dominated_by(&mut self.root, &elt, 0, &mut observer);
+        // finally insert the element
+        observer(FrontEvent::Inserted(&elt));
+        self.insert_without_check(elt);
+        self.nb_elements += 1;
+        true
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> KDTreeFront<T, Elt, NB_DIM> {
+    /// returns an iterator over the elements of the front (in no particular order)
+    pub fn iter(&self) -> KDTreeIterator<'_, T, Elt, NB_DIM> {
+        KDTreeIterator { stack: self.root.iter().map(|n| &**n).collect() }
+    }
+
+    /// returns the number of elements in the front
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the front is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+}
+
+/// Iterator over the elements of a kd-tree.
+#[derive(Debug)]
+pub struct KDTreeIterator<'a, T, Elt, const NB_DIM:usize> {
+    /// nodes to visit
+    stack:Vec<&'a Node<T,Elt,NB_DIM>>,
+}
+
+impl<'a, T, Elt, const NB_DIM:usize> Iterator for KDTreeIterator<'a, T, Elt, NB_DIM> {
+    type Item = &'a Elt;
+    fn next(&mut self) -> Option<&'a Elt> {
+        let node = self.stack.pop()?;
+        if let Some(n) = &node.l { self.stack.push(n); }
+        if let Some(n) = &node.r { self.stack.push(n); }
+        Some(&node.e)
+    }
 }
 
 impl<T, Elt, const NB_DIM:usize> KDTreeFront<T, Elt, NB_DIM>
@@ -217,154 +314,124 @@ where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
         node.update_bounds();
     }
 
+    /// builds a balanced front from a vector of elements (splitting on medians).
+    ///
+    /// Dominated elements are filtered out before building the tree.
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let front = filter_dominated(elts);
+        let nb_elements = front.len();
+        Self { root:Self::rec_build(front, 0), nb_elements, phantom_t:PhantomData }
+    }
+
+    /// recursively builds a balanced sub-tree splitting on the median of dimension dim
+    fn rec_build(mut elts:Vec<Elt>, dim:usize) -> Link<T,Elt,NB_DIM> {
+        if elts.is_empty() { return None; }
+        let mid = elts.len()/2;
+        elts.select_nth_unstable_by_key(mid, |e| e.kth(dim));
+        let median = elts.swap_remove(mid);
+        // same convention as insertions: strictly lower elements on the left
+        let (left, right):(Vec<Elt>,Vec<Elt>) = elts.into_iter()
+            .partition(|e| e.kth(dim) < median.kth(dim));
+        Some(Box::new(Node::new(
+            median,
+            Self::rec_build(left, (dim+1)%NB_DIM),
+            Self::rec_build(right, (dim+1)%NB_DIM),
+        )))
+    }
+
     /// adds the element to a node without any dominance checks
     fn insert_without_check(&mut self, elt:Elt) {
-        if self.root.is_none() {
-            self.root = Some(Box::new(Node::new(elt, None, None)));
-        } else {
-            Self::rec_insert_without_check(self.root.as_mut().unwrap(), elt, 0);
+        match self.root.as_mut() {
+            None => { self.root = Some(Box::new(Node::new(elt, None, None))); },
+            Some(root) => { Self::rec_insert_without_check(root, elt, 0); }
         }
     }
 
     /// remove elements dominated by the given element
-    fn rec_remove_dominated_by(link: &mut Link<T,Elt,NB_DIM>, elt:&Elt, dim:usize) {
+    /// (the observer is notified of each eviction)
+    /// returns the number of removed elements
+    fn rec_remove_dominated_by<O>(link: &mut Link<T,Elt,NB_DIM>, elt:&Elt, dim:usize, observer:&mut O) -> usize
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        let mut nb_removed = 0;
         if let Some(node) = link {
             // if the element has a coordinate larger than the bound, return None
             for (i,d) in elt.coordinates().enumerate() {
-                if d > node.bounds()[i].1 { return; }
+                if d > node.bounds()[i].1 { return 0; }
             }
-            Self::rec_remove_dominated_by(node.left_mut(), elt, (dim+1)%NB_DIM);
-            Self::rec_remove_dominated_by(node.right_mut(), elt, (dim+1)%NB_DIM);
+            nb_removed += Self::rec_remove_dominated_by(node.left_mut(), elt, (dim+1)%NB_DIM, observer);
+            nb_removed += Self::rec_remove_dominated_by(node.right_mut(), elt, (dim+1)%NB_DIM, observer);
             if elt.dominates(node.elt()) {
-                Self::remove_link(link, dim);
+                if let Some(evicted) = Self::remove_link(link, dim) {
+                    observer(FrontEvent::EvictedBy { element:&evicted, by:elt });
+                    nb_removed += 1;
+                }
+            } else {
+                node.update_bounds(); // children may have been removed
             }
         }
+        nb_removed
     }
 
-    /// finds the link with the minimum guide
+    /// finds the link with the minimum (resp. maximum) guide
     /// returns the link, with the current dimension
-    fn rec_search_min_guide(link:&Link<T,Elt,NB_DIM>, dim:usize) -> (&Link<T,Elt,NB_DIM>, Option<usize>) {
+    fn rec_search_guide(link:&Link<T,Elt,NB_DIM>, dim:usize, max:bool) -> (&Link<T,Elt,NB_DIM>, Option<usize>) {
         match link {
             None => (link, None),
-            Some(node) => {
-                let ge = node.elt().guide();
-                let g_l = node.left().as_ref().map(|n| n.guide_lb);
-                let g_r = node.right().as_ref().map(|n| n.guide_lb);
-                match (g_l,g_r) {
-                    (None,None) => (link, Some(dim)),
-                    (None,Some(gr)) => {
-                        if gr < ge {
-                            Self::rec_search_min_guide(
-                                link.as_ref().unwrap().right(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    },
-                    (Some(gl),None) => {
-                        if gl < ge {
-                            Self::rec_search_min_guide(
-                                link.as_ref().unwrap().left(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    }, 
-                    (Some(gl),Some(gr)) => {
-                        if gl < ge && gl < gr {
-                            Self::rec_search_min_guide(
-                                link.as_ref().unwrap().left(), (dim+1)%NB_DIM
-                            )
-                        } else if gr < ge {
-                            Self::rec_search_min_guide(
-                                link.as_ref().unwrap().right(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    }
-                }
+            Some(node) => match Self::guide_side(node, max) {
+                Side::Here => (link, Some(dim)),
+                Side::Left => Self::rec_search_guide(node.left(), (dim+1)%NB_DIM, max),
+                Side::Right => Self::rec_search_guide(node.right(), (dim+1)%NB_DIM, max),
             }
         }
     }
 
-    /// finds the link with the minimum guide
-    /// returns the link, with the current dimension
-    fn mut_rec_search_min_guide(link:&mut Link<T,Elt,NB_DIM>, dim:usize) -> (&mut Link<T,Elt,NB_DIM>, Option<usize>) {
-        match link {
-            None => (link, None),
-            Some(node) => {
-                let ge = node.elt().guide();
-                let g_l = node.left().as_ref().map(|n| n.guide_lb);
-                let g_r = node.right().as_ref().map(|n| n.guide_lb);
-                match (g_l,g_r) {
-                    (None,None) => (link, Some(dim)),
-                    (None,Some(gr)) => {
-                        if gr < ge {
-                            Self::mut_rec_search_min_guide(
-                                link.as_mut().unwrap().right_mut(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    },
-                    (Some(gl),None) => {
-                        if gl < ge {
-                            Self::mut_rec_search_min_guide(
-                                link.as_mut().unwrap().left_mut(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    }, 
-                    (Some(gl),Some(gr)) => {
-                        if gl < ge && gl < gr {
-                            Self::mut_rec_search_min_guide(
-                                link.as_mut().unwrap().left_mut(), (dim+1)%NB_DIM
-                            )
-                        } else if gr < ge {
-                            Self::mut_rec_search_min_guide(
-                                link.as_mut().unwrap().right_mut(), (dim+1)%NB_DIM
-                            )
-                        } else { (link, Some(dim)) }
-                    }
-                }
-            }
+    /// returns the side of the node containing the minimum (resp. maximum) guide
+    fn guide_side(node:&Node<T,Elt,NB_DIM>, max:bool) -> Side {
+        if max {
+            Side::of_minimum(
+                Reverse(node.guide()),
+                node.left().as_ref().map(|n| Reverse(n.guide_ub)),
+                node.right().as_ref().map(|n| Reverse(n.guide_ub)),
+            )
+        } else {
+            Side::of_minimum(
+                node.guide(),
+                node.left().as_ref().map(|n| n.guide_lb),
+                node.right().as_ref().map(|n| n.guide_lb),
+            )
         }
     }
 
-    /// mutable recursive function to search a minimum node given a dimension
-    fn mut_rec_search_minimum(link:&mut Link<T,Elt,NB_DIM>, dim:usize, target_dim:usize,)
-    -> (&mut Link<T,Elt,NB_DIM>, Option<usize>, Option<T>) {
-        match link {
-            None => (link, None, None),
-            Some(node) => {
-                // identify the direction to search
-                let v_e = node.elt().kth(target_dim);
-                let v_l = node.left().as_ref().map(|n| n.bounds()[target_dim].0);
-                let v_r = node.right().as_ref().map(|n| n.bounds()[target_dim].0);
-                match (v_l, v_r) {
-                    (None, None) => (link, Some(dim), Some(v_e)),
-                    (None, Some(vr)) => {
-                        if vr < v_e {
-                            Self::mut_rec_search_minimum(
-                                link.as_mut().unwrap().right_mut(), (dim+1)%NB_DIM, target_dim
-                            )
-                        } else { (link, Some(dim), Some(v_e)) }
-                    },
-                    (Some(vl), None) => {
-                        if vl < v_e {
-                            Self::mut_rec_search_minimum(
-                                link.as_mut().unwrap().left_mut(), (dim+1)%NB_DIM, target_dim
-                            )
-                        } else { (link, Some(dim), Some(v_e)) }
-                    },
-                    (Some(vl), Some(vr)) => {
-                        if vl < v_e && vl < vr {
-                            Self::mut_rec_search_minimum(
-                                link.as_mut().unwrap().left_mut(), (dim+1)%NB_DIM, target_dim
-                            )
-                        } else if vr < v_e {
-                            Self::mut_rec_search_minimum(
-                                link.as_mut().unwrap().right_mut(), (dim+1)%NB_DIM, target_dim
-                            )
-                        } else { (link, Some(dim), Some(v_e)) }
-                    },
-                }
-            }
-        }
+    /// removes the element with the minimum (resp. maximum) guide in the sub-tree.
+    /// Bounds are only updated along the path to the removed node
+    fn rec_pop_guide(link:&mut Link<T,Elt,NB_DIM>, dim:usize, max:bool) -> Option<Elt> {
+        let node = link.as_mut()?;
+        let res = match Self::guide_side(node, max) {
+            Side::Here => return Self::remove_link(link, dim),
+            Side::Left => Self::rec_pop_guide(node.left_mut(), (dim+1)%NB_DIM, max),
+            Side::Right => Self::rec_pop_guide(node.right_mut(), (dim+1)%NB_DIM, max),
+        };
+        node.update_bounds();
+        res
     }
 
+    /// removes the element with the minimum value on target_dim in the sub-tree.
+    /// Bounds are only updated along the path to the removed node
+    fn rec_remove_minimum(link:&mut Link<T,Elt,NB_DIM>, dim:usize, target_dim:usize) -> Option<Elt> {
+        let node = link.as_mut()?;
+        let side = Side::of_minimum(
+            node.elt().kth(target_dim),
+            node.left().as_ref().map(|n| n.bounds()[target_dim].0),
+            node.right().as_ref().map(|n| n.bounds()[target_dim].0),
+        );
+        let res = match side {
+            Side::Here => return Self::remove_link(link, dim),
+            Side::Left => Self::rec_remove_minimum(node.left_mut(), (dim+1)%NB_DIM, target_dim),
+            Side::Right => Self::rec_remove_minimum(node.right_mut(), (dim+1)%NB_DIM, target_dim),
+        };
+        node.update_bounds();
+        res
+    }
 
     /// removes a node in the tree
     fn remove_link(link: &mut Link<T,Elt,NB_DIM>, dim:usize) -> Option<Elt> {
@@ -379,10 +446,9 @@ where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
                     // if right not null, search for minimum on current dimension
                     // then use it to replace (+ remove this "minimum" node).
                     (left , mut right @ Some(_)) => {
-                        let (min_link, _, _) =
-                            Self::mut_rec_search_minimum(&mut right, dim, dim);
-                        let mut elt = Self::remove_link(min_link, dim).unwrap();
-                        swap(&mut elt, &mut node.e);
+                        let elt = node.replace_elt(
+                            Self::rec_remove_minimum(&mut right, (dim+1)%NB_DIM, dim).unwrap()
+                        );
                         node.l = left;
                         node.r = right;
                         node.update_bounds();
@@ -393,10 +459,9 @@ where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
                     // then put the left subtree to the right
                     (mut left @ Some(_), mut right @ None) => {
                         swap(&mut right, &mut left); // swap left and right subtrees
-                        let (min_link, _, _) =
-                            Self::mut_rec_search_minimum(&mut right, dim, dim);
-                        let mut elt = Self::remove_link(min_link, dim).unwrap();
-                        swap(&mut elt, &mut node.e);
+                        let elt = node.replace_elt(
+                            Self::rec_remove_minimum(&mut right, (dim+1)%NB_DIM, dim).unwrap()
+                        );
                         node.r = right;
                         node.update_bounds();
                         *link = Some(node);
@@ -408,6 +473,66 @@ where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
         }
     }
 
+    /// returns an element with the minimum value on dimension dim (None if the front is empty)
+    pub fn peek_minimum_element(&self, dim:usize) -> Option<&Elt> {
+        Self::rec_search_minimum(&self.root, dim)
+    }
+
+    /// removes and returns an element with the minimum value on dimension dim.
+    /// Bounds are only updated along the path to the removed node
+    pub fn pop_minimum_element(&mut self, dim:usize) -> Option<Elt> {
+        let res = Self::rec_remove_minimum(&mut self.root, 0, dim);
+        if res.is_some() { self.nb_elements -= 1; }
+        res
+    }
+
+    /// finds the element with the minimum value on target_dim in the sub-tree
+    fn rec_search_minimum(link:&Link<T,Elt,NB_DIM>, target_dim:usize) -> Option<&Elt> {
+        let node = link.as_ref()?;
+        let side = Side::of_minimum(
+            node.elt().kth(target_dim),
+            node.left().as_ref().map(|n| n.bounds()[target_dim].0),
+            node.right().as_ref().map(|n| n.bounds()[target_dim].0),
+        );
+        match side {
+            Side::Here => Some(node.elt()),
+            Side::Left => Self::rec_search_minimum(node.left(), target_dim),
+            Side::Right => Self::rec_search_minimum(node.right(), target_dim),
+        }
+    }
+
+    /// restores the guide bounds after the guide of the stored element having the same
+    /// coordinates as elt changed. Only the bounds along the path to this element are updated.
+    ///
+    /// returns false if no stored element has the coordinates of elt
+    pub fn update_guide(&mut self, elt:&Elt) -> bool {
+        Self::rec_update_guide(&mut self.root, elt, 0)
+    }
+
+    /// follows the insertion path of the element, and updates the bounds while unwinding
+    fn rec_update_guide(link:&mut Link<T,Elt,NB_DIM>, elt:&Elt, dim:usize) -> bool {
+        let Some(node) = link else { return false; };
+        let here = node.elt().coordinates().eq(elt.coordinates());
+        if here { node.refresh_guide(); }
+        let found = here || if elt.kth(dim) < node.elt().kth(dim) {
+            Self::rec_update_guide(node.left_mut(), elt, (dim+1)%NB_DIM)
+        } else {
+            Self::rec_update_guide(node.right_mut(), elt, (dim+1)%NB_DIM)
+        };
+        if found { node.update_bounds(); }
+        found
+    }
+
+    /// recursive update of the cached guides and bounds of all the nodes
+    fn rec_update_bounds(link:&mut Link<T,Elt,NB_DIM>) {
+        if let Some(node) = link {
+            Self::rec_update_bounds(node.left_mut());
+            Self::rec_update_bounds(node.right_mut());
+            node.refresh_guide();
+            node.update_bounds();
+        }
+    }
+
     /// recursive search for a dominating node. Returns a node dominating the element if it exists
     fn rec_exists_dominating<'a>(link: &'a Link<T,Elt,NB_DIM>, elt:&Elt) -> Option<&'a Elt> {
         match link {
@@ -430,35 +555,20 @@ where T:Ord+Copy, Elt:GuidedElement<T>+ParetoElement<T> {
             }
         }
     }
-
-    /// recursive update of bounds.
-    /// This function should eventually be removed and the update done only when needed
-    /// **parameters:**
-    ///  - guide: threshold in which the bound should be updated
-    fn rec_update_bounds(link:&mut Link<T,Elt,NB_DIM>, guide:T) {
-        let mut to_update = false;
-        if let Some(_node) = link {
-            // if node.guide_lb == guide { to_update = true; } // update bounds if needed
-            Self::rec_update_bounds(link.as_mut().unwrap().left_mut(), guide);
-            Self::rec_update_bounds(link.as_mut().unwrap().right_mut(), guide);
-            to_update = true;
-        }
-        if to_update {
-            link.as_mut().unwrap().update_bounds();
-        }
-    }
 }
 
 
 
 #[cfg(test)]
-pub mod test {
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
     use crate::priority_queue::util::CartesianParetoElement;
 
     use super::*;
 
     #[test]
-    pub fn test_insert_then_pop_min() {
+    fn test_insert_then_pop_min() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         front.insert(CartesianParetoElement::new([0,10]));
         front.insert(CartesianParetoElement::new([10,5]));
@@ -469,13 +579,155 @@ pub mod test {
     }
 
     #[test]
-    pub fn test_remove_empty() {
+    fn test_peek_k_pop_k() {
+        let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
+        // non-dominated points (i, 100-2i) of guide 100-i, inserted in a shuffled order
+        for j in 0..40 {
+            let i = (j*17) % 40;
+            assert!(front.insert(CartesianParetoElement::new([i, 100-2*i])));
+        }
+        let guides = |elts:Vec<&CartesianParetoElement<2>>| -> Vec<u32> {
+            elts.into_iter().map(|e| e.guide()).collect()
+        };
+        assert_eq!(guides(front.peek_k(3)), vec![61,62,63]);
+        for chunk in 0..8 {
+            let popped:Vec<u32> = front.pop_k(5).iter().map(|e| e.guide()).collect();
+            let expected:Vec<u32> = (0..5).map(|i| 61 + 5*chunk + i).collect();
+            assert_eq!(popped, expected);
+            assert_eq!(front.peek_min_guide(), if chunk < 7 { Some(66 + 5*chunk) } else { None });
+        }
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn test_pop_max() {
+        let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
+        for j in 0..40 {
+            let i = (j*17) % 40;
+            front.insert(CartesianParetoElement::new([i, 100-2*i]));
+        }
+        assert_eq!(front.peek_max().map(|e| e.guide()), Some(100));
+        let popped:Vec<u32> = std::iter::from_fn(|| front.pop_max()).map(|e| e.guide()).take(30).collect();
+        assert_eq!(popped, (71..=100).rev().collect::<Vec<u32>>());
+        assert_eq!((front.len(), front.peek_max_guide()), (10, Some(70)));
+        assert_eq!(front.pop_min().map(|e| e.guide()), Some(61));
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut elts:Vec<CartesianParetoElement<2>> = (0..30)
+            .map(|i| CartesianParetoElement::new([i, 100-2*i]))
+            .collect();
+        elts.push(CartesianParetoElement::new([5, 95])); // dominated by [5,90]
+        elts.push(CartesianParetoElement::new([0, 100])); // duplicate
+        let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::from_vec(elts);
+        assert_eq!(front.iter().count(), 30);
+        assert!(front.find_dominating(&CartesianParetoElement::new([5, 95])).is_some());
+        assert!(!front.insert(CartesianParetoElement::new([10, 81])));
+        assert_eq!(front.len(), 30);
+        let guides:Vec<u32> = front.into_sorted_iter().map(|e| e.guide()).collect();
+        assert_eq!(guides, (71..=100).collect::<Vec<u32>>());
+    }
+
+    /// 2D element whose guide can be changed after its insertion
+    #[derive(Debug,Clone)]
+    struct Tunable([u32;2], Rc<Cell<u32>>);
+
+    impl ParetoElement<u32> for Tunable {
+        type CoordIterator = std::array::IntoIter<u32,2>;
+        fn coordinates(&self) -> Self::CoordIterator { self.0.into_iter() }
+        fn dominates(&self, other:&Self) -> bool { self.0[0] <= other.0[0] && self.0[1] <= other.0[1] }
+        fn nb_dimensions(&self) -> usize { 2 }
+        fn kth(&self, k:usize) -> u32 { self.0[k] }
+    }
+
+    impl GuidedElement<u32> for Tunable {
+        fn guide(&self) -> u32 { self.1.get() }
+    }
+
+    #[test]
+    fn test_update_guide() {
+        let mut front:KDTreeFront<u32, Tunable, 2> = KDTreeFront::default();
+        let elts:Vec<Tunable> = (0..20)
+            .map(|i| Tunable([(i*7)%20, 40-(i*7)%20], Rc::new(Cell::new(10+i))))
+            .collect();
+        for e in &elts { assert!(front.insert(e.clone())); }
+        assert_eq!(front.peek_min_guide(), Some(10));
+        elts[13].1.set(2);
+        assert!(front.update_guide(&elts[13]));
+        assert_eq!(front.peek_min().map(|e| e.0), Some(elts[13].0));
+        assert!(!front.update_guide(&Tunable([3,3], Rc::new(Cell::new(0)))));
+        elts[4].1.set(1);
+        elts[13].1.set(50);
+        front.reprioritize();
+        assert_eq!(front.peek_min_guide(), Some(1));
+        assert_eq!(front.peek_max_guide(), Some(50));
+    }
+
+    /// 2D element counting the evaluations of its guide
+    #[derive(Debug,Clone)]
+    struct Counted([u32;2], Rc<Cell<usize>>);
+
+    impl ParetoElement<u32> for Counted {
+        type CoordIterator = std::array::IntoIter<u32,2>;
+        fn coordinates(&self) -> Self::CoordIterator { self.0.into_iter() }
+        fn dominates(&self, other:&Self) -> bool { self.0[0] <= other.0[0] && self.0[1] <= other.0[1] }
+        fn nb_dimensions(&self) -> usize { 2 }
+        fn kth(&self, k:usize) -> u32 { self.0[k] }
+    }
+
+    impl GuidedElement<u32> for Counted {
+        fn guide(&self) -> u32 {
+            self.1.set(self.1.get()+1);
+            self.0[0] + self.0[1]
+        }
+    }
+
+    #[test]
+    fn test_cached_guides() {
+        let counter = Rc::new(Cell::new(0));
+        let mut front:KDTreeFront<u32, Counted, 2> = KDTreeFront::default();
+        for i in 0..30 { front.insert(Counted([(i*7)%30, 60-2*((i*7)%30)], counter.clone())); }
+        assert_eq!(counter.get(), 30); // one evaluation per insertion
+        assert_eq!(front.peek_min().map(|e| e.0), Some([29,2]));
+        assert_eq!(front.peek_k(5).len(), 5);
+        assert_eq!(front.pop_min().map(|e| e.0), Some([29,2]));
+        assert_eq!(front.pop_min().map(|e| e.0), Some([28,4]));
+        // removals only evaluate the guides of the elements moving up in the tree
+        assert!(counter.get() < 40);
+    }
+
+    #[test]
+    fn test_minimum_element() {
+        let mut front:KDTreeFront<u32, CartesianParetoElement<3>, 3> = KDTreeFront::default();
+        for i in 0..20 {
+            let j = (i*7) % 20;
+            assert!(front.insert(CartesianParetoElement::new([j, 19-j, (j*3) % 20])));
+        }
+        assert_eq!(front.peek_minimum_element(0), Some(&CartesianParetoElement::new([0,19,0])));
+        assert_eq!(front.peek_minimum_element(1), Some(&CartesianParetoElement::new([19,0,17])));
+        assert_eq!(front.pop_minimum_element(2), Some(CartesianParetoElement::new([0,19,0])));
+        assert_eq!(front.pop_minimum_element(2), Some(CartesianParetoElement::new([7,12,1])));
+        assert_eq!(front.peek_minimum_element(0), Some(&CartesianParetoElement::new([1,18,3])));
+        // alternate with guide-driven pops
+        assert_eq!(front.pop_min().map(|e| e.guide()), Some(21));
+        assert_eq!(front.len(), 17);
+        let mut last = 0;
+        while let Some(e) = front.pop_minimum_element(1) {
+            assert!(e.kth(1) >= last);
+            last = e.kth(1);
+        }
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn test_remove_empty() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         assert!(front.pop_min().is_none());
     }
 
     #[test]
-    pub fn test_remove_1() {
+    fn test_remove_1() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         front.insert(CartesianParetoElement::new([10,10]));
         assert!(front.pop_min().is_some());
@@ -483,7 +735,7 @@ pub mod test {
     }
 
     #[test]
-    pub fn test_remove_2() { // delete center, right, left
+    fn test_remove_2() { // delete center, right, left
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         front.insert(CartesianParetoElement::new([10,10]));
         front.insert(CartesianParetoElement::new([5,5]));
@@ -492,7 +744,7 @@ pub mod test {
     }
 
     #[test]
-    pub fn test_insert_no_domination() {
+    fn test_insert_no_domination() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         front.insert(CartesianParetoElement::new([5,10]));
         front.insert(CartesianParetoElement::new([10,6]));
@@ -502,7 +754,7 @@ pub mod test {
     }
 
     #[test]
-    pub fn test_insert_domination_by_existing() {
+    fn test_insert_domination_by_existing() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         assert!(front.insert(CartesianParetoElement::new([5,5])));
         assert!(!front.insert(CartesianParetoElement::new([10,10])));
@@ -511,7 +763,7 @@ pub mod test {
     }
 
     #[test]
-    pub fn test_insert_domination_by_inserted() {
+    fn test_insert_domination_by_inserted() {
         let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
         assert!(front.insert(CartesianParetoElement::new([10,10])));
         assert!(front.insert(CartesianParetoElement::new([5,5])));
@@ -519,4 +771,20 @@ pub mod test {
         assert!(front.is_empty());
     }
 
+    #[test]
+    fn test_observed_evictions() {
+        let mut front:KDTreeFront<u32, CartesianParetoElement<2>, 2> = KDTreeFront::default();
+        front.insert(CartesianParetoElement::new([10,10]));
+        front.insert(CartesianParetoElement::new([5,12]));
+        front.insert(CartesianParetoElement::new([12,5]));
+        let mut evicted = Vec::new();
+        front.insert_observed(CartesianParetoElement::new([5,5]), |event| {
+            if let FrontEvent::EvictedBy { element, .. } = event { evicted.push(element.clone()); }
+        });
+        assert_eq!(evicted.len(), 3);
+        assert_eq!(front.len(), 1);
+        assert_eq!(front.pop_min().unwrap(), CartesianParetoElement::new([5,5]));
+        assert!(front.is_empty());
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,354 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+use crate::priority_queue::handle::{Handle, HandleTable, Handled};
+
+/// default maximum ratio of tombstones before a compaction
+const DEFAULT_GARBAGE_RATIO:f64 = 0.5;
+
+/// Lazy-deletion queue.
+///
+/// Wraps a priority queue, and implements removals and updates by marking removed elements
+/// as tombstones (their handle is invalidated). Tombstones are skipped (and discarded) when
+/// they reach the minimum or maximum of the queue. When the ratio of tombstones in the queue
+/// exceeds a threshold, the queue is compacted.
+///
+/// The minimum and maximum of the underlying queue are always alive. Elements the underlying
+/// queue drops on insertion (pareto evictions, beam overflows) are reported by its
+/// [PriorityQueue::insert_evicting], and their handles are invalidated.
+///
+/// Tombstones may make the underlying queue reject an element (e.g. a removed element still
+/// dominating it in a pareto front). In this case, the tombstones are purged and the insertion
+/// is retried, which requires cloning the inserted element while tombstones exist.
+#[derive(Debug,Clone)]
+pub struct LazyDeletionQueue<T,Elt,Q=BinaryHeapQueue<T,Handled<Elt>>> {
+    /// underlying queue (contains alive elements and tombstones)
+    queue:Q,
+    /// handles of the alive elements
+    table:HandleTable,
+    /// number of tombstones in the underlying queue
+    nb_tombstones:usize,
+    /// maximum ratio of tombstones (w.r.t. the size of the underlying queue)
+    max_garbage_ratio:f64,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> Default for LazyDeletionQueue<T,Elt,Q> {
+    fn default() -> Self {
+        Self::with_garbage_ratio(DEFAULT_GARBAGE_RATIO)
+    }
+}
+
+impl<T,Elt,Q:Default> LazyDeletionQueue<T,Elt,Q> {
+    /// creates an empty queue compacted when the ratio of tombstones exceeds max_garbage_ratio
+    pub fn with_garbage_ratio(max_garbage_ratio:f64) -> Self {
+        Self {
+            queue:Q::default(),
+            table:HandleTable::default(),
+            nb_tombstones:0,
+            max_garbage_ratio,
+            phantom:PhantomData,
+        }
+    }
+
+    /// returns the number of (alive) elements in the queue
+    pub fn len(&self) -> usize { self.table.len() }
+
+    /// returns true iff the queue contains no alive element
+    pub fn is_empty(&self) -> bool { self.table.is_empty() }
+
+    /// returns the number of tombstones in the underlying queue
+    pub fn nb_tombstones(&self) -> usize { self.nb_tombstones }
+
+    /// returns true iff the handle refers to an alive element
+    pub fn contains(&self, h:Handle) -> bool { self.table.is_valid(h) }
+}
+
+impl<T,Elt,Q> LazyDeletionQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+Clone, Q:PriorityQueue<T,Handled<Elt>>+Default {
+    /// inserts an element and returns a handle to it (None if the underlying queue rejects it).
+    /// Handles of the elements the underlying queue drops because of the insertion (e.g.
+    /// elements dominated by it in a pareto front) are invalidated
+    pub fn insert_with_handle(&mut self, elt:Elt) -> Option<Handle> {
+        self.insert_handled(elt, |_| {})
+    }
+
+    /// removes (marks as a tombstone) the element referred by the handle.
+    ///
+    /// returns true iff the element was alive
+    pub fn remove(&mut self, h:Handle) -> bool {
+        if self.table.invalidate(h).is_none() { return false; }
+        self.nb_tombstones += 1;
+        self.discard_tombstones();
+        if self.nb_tombstones as f64 > self.max_garbage_ratio * self.queue_size() as f64 {
+            self.compact();
+        }
+        true
+    }
+
+    /// replaces the element referred by the handle. Returns the handle of the new element
+    /// (None if the handle does not refer to an alive element, or if the new element is
+    /// rejected). If the new element is rejected, the old one is kept
+    pub fn update(&mut self, h:Handle, elt:Elt) -> Option<Handle> {
+        if !self.table.is_valid(h) { return None; }
+        if let Some(res) = self.try_insert(elt.clone(), |_| {}) {
+            self.remove(h); // the old element may have been evicted by the new one
+            return Some(res);
+        }
+        // the new element may be rejected because of the old one or of tombstones
+        let old = self.purge(Some(h));
+        match self.try_insert(elt, |_| {}) {
+            Some(res) => {
+                self.table.invalidate(h);
+                Some(res)
+            },
+            None => {
+                if let Some(e) = old {
+                    if !self.queue.insert(e) { self.table.invalidate(h); }
+                }
+                None
+            },
+        }
+    }
+
+    /// removes all tombstones from the underlying queue
+    pub fn compact(&mut self) {
+        self.purge(None);
+    }
+
+    /// inserts an element, and retries after purging the tombstones if the underlying queue
+    /// rejects it while containing some
+    fn insert_handled<F:FnMut(&Elt)>(&mut self, elt:Elt, mut evicted:F) -> Option<Handle> {
+        if self.nb_tombstones == 0 { return self.try_insert(elt, evicted); }
+        if let Some(h) = self.try_insert(elt.clone(), &mut evicted) { return Some(h); }
+        if self.nb_tombstones == 0 { return None; }
+        self.compact();
+        self.try_insert(elt, evicted)
+    }
+
+    /// inserts an element through [PriorityQueue::insert_evicting] of the underlying queue:
+    /// invalidates the handles of the dropped elements, and reports the alive ones to evicted
+    fn try_insert<F:FnMut(&Elt)>(&mut self, elt:Elt, mut evicted:F) -> Option<Handle> {
+        let h = self.table.create(0);
+        let (table, nb_tombstones) = (&mut self.table, &mut self.nb_tombstones);
+        let inserted = self.queue.insert_evicting(Handled::new(h, elt), |e| {
+            if table.invalidate(e.handle()).is_some() { evicted(e.elt()); } else { *nb_tombstones -= 1; }
+        });
+        if !inserted {
+            self.table.invalidate(h);
+            return None;
+        }
+        Some(h)
+    }
+
+    /// removes all tombstones from the underlying queue, and takes out the element referred by
+    /// the handle if given (its handle stays valid)
+    fn purge(&mut self, taken:Option<Handle>) -> Option<Handled<Elt>> {
+        let mut queue = Q::default();
+        let mut res = None;
+        while let Some(e) = self.queue.pop_min() {
+            if Some(e.handle()) == taken { res = Some(e); }
+            else if self.table.is_valid(e.handle()) { queue.insert(e); }
+        }
+        self.queue = queue;
+        self.nb_tombstones = 0;
+        res
+    }
+
+    /// size of the underlying queue (alive elements and tombstones)
+    fn queue_size(&self) -> usize { self.table.len() + self.nb_tombstones }
+
+    /// discards tombstones located at the minimum or maximum of the underlying queue
+    fn discard_tombstones(&mut self) {
+        while self.queue.peek_min().is_some_and(|e| !self.table.is_valid(e.handle())) {
+            self.queue.pop_min();
+            self.nb_tombstones -= 1;
+        }
+        while self.queue.peek_max().is_some_and(|e| !self.table.is_valid(e.handle())) {
+            self.queue.pop_max();
+            self.nb_tombstones -= 1;
+        }
+    }
+
+    /// invalidates the handle of a popped element
+    fn pop_alive(&mut self, e:Option<Handled<Elt>>) -> Option<Elt> {
+        let res = e?;
+        self.table.invalidate(res.handle());
+        self.discard_tombstones();
+        Some(res.into_elt())
+    }
+}
+
+impl<T,Elt,Q> PriorityQueue<T,Elt> for LazyDeletionQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+Clone, Q:PriorityQueue<T,Handled<Elt>>+Default {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.queue.peek_min().map(|e| e.elt())
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.queue.peek_max().map(|e| e.elt())
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let e = self.queue.pop_min();
+        self.pop_alive(e)
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let e = self.queue.pop_max();
+        self.pop_alive(e)
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_with_handle(elt).is_some()
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.insert_handled(elt, evicted).is_some()
+    }
+
+    /// reprioritizes the underlying queue, then discards the tombstones reaching its ends
+    fn reprioritize(&mut self) {
+        self.queue.reprioritize();
+        self.discard_tombstones();
+    }
+}
+
+impl<T,Elt,Q> ExactSizeQueue<T,Elt> for LazyDeletionQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+Clone, Q:PriorityQueue<T,Handled<Elt>>+Default {
+    fn len(&self) -> usize { self.table.len() }
+}
+
+impl<T,Elt,Q> PeekKQueue<T,Elt> for LazyDeletionQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+Clone, Q:PeekKQueue<T,Handled<Elt>>+Default {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        self.queue.peek_k(k.saturating_add(self.nb_tombstones)).into_iter()
+            .filter(|e| self.table.is_valid(e.handle()))
+            .take(k)
+            .map(|e| e.elt())
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::ParetoElement;
+    use crate::priority_queue::fixtures::Value;
+    use crate::priority_queue::kd_tree::KDTreeFront;
+    use crate::priority_queue::pareto_list::ListParetoFront;
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    #[test]
+    fn test_remove_and_update() {
+        let mut queue:LazyDeletionQueue<u32,Value> = LazyDeletionQueue::with_garbage_ratio(1.);
+        let handles:Vec<Handle> = (0..10).map(|i| queue.insert_with_handle(Value(i)).unwrap()).collect();
+        assert!(queue.remove(handles[0])); // minimum: discarded immediately
+        assert!(!queue.remove(handles[0]));
+        assert!(queue.remove(handles[5]));
+        assert_eq!(queue.nb_tombstones(), 1);
+        let h = queue.update(handles[7], Value(1)).unwrap();
+        assert!(queue.contains(h));
+        assert!(!queue.contains(handles[7]));
+        assert_eq!(queue.len(), 8);
+        let values:Vec<u32> = std::iter::from_fn(|| queue.pop_min()).map(|v| v.0).collect();
+        assert_eq!(values, vec![1,1,2,3,4,6,8,9]);
+    }
+
+    #[test]
+    fn test_peeks_skip_tombstones() {
+        let mut queue:LazyDeletionQueue<u32,Value> = LazyDeletionQueue::default();
+        let handles:Vec<Handle> = (0..5).map(|i| queue.insert_with_handle(Value(i)).unwrap()).collect();
+        queue.remove(handles[4]);
+        queue.remove(handles[0]);
+        assert_eq!(queue.peek_min(), Some(&Value(1)));
+        assert_eq!(queue.peek_max(), Some(&Value(3)));
+        queue.remove(handles[2]);
+        assert_eq!(queue.peek_k(5), vec![&Value(1), &Value(3)]);
+    }
+
+    #[test]
+    fn test_compaction() {
+        let mut queue:LazyDeletionQueue<u32,Value> = LazyDeletionQueue::with_garbage_ratio(0.25);
+        let handles:Vec<Handle> = (0..100).map(|i| queue.insert_with_handle(Value(i)).unwrap()).collect();
+        for h in &handles[10..40] { queue.remove(*h); }
+        assert!(queue.nb_tombstones() <= 25);
+        assert_eq!(queue.len(), 70);
+        assert_eq!(queue.pop_min(), Some(Value(0)));
+    }
+
+    #[test]
+    fn test_kd_tree_tombstones() {
+        let mut queue:LazyDeletionQueue<u32,CartesianParetoElement<2>,KDTreeFront<u32,Handled<CartesianParetoElement<2>>,2>> = LazyDeletionQueue::default();
+        let handles:Vec<Handle> = (0..10)
+            .map(|i| queue.insert_with_handle(CartesianParetoElement::new([i, 20-2*i])).unwrap())
+            .collect();
+        assert!(queue.remove(handles[0])); // maximum guide: discarded immediately
+        assert!(queue.remove(handles[4]));
+        assert_eq!(queue.nb_tombstones(), 1);
+        assert_eq!(queue.peek_max(), Some(&CartesianParetoElement::new([1, 18])));
+        assert_eq!(queue.pop_max(), Some(CartesianParetoElement::new([1, 18])));
+        assert_eq!(queue.len(), 7);
+    }
+
+    #[test]
+    fn test_rejected_insert() {
+        let mut queue:LazyDeletionQueue<u32,CartesianParetoElement<2>,KDTreeFront<u32,Handled<CartesianParetoElement<2>>,2>> = LazyDeletionQueue::default();
+        let h = queue.insert_with_handle(CartesianParetoElement::new([1, 1])).unwrap();
+        assert_eq!(queue.insert_with_handle(CartesianParetoElement::new([2, 2])), None);
+        assert!(!queue.insert(CartesianParetoElement::new([3, 1])));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.update(h, CartesianParetoElement::new([0, 5])).map(|h2| queue.contains(h2)), Some(true));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_front_evictions() {
+        type Point = CartesianParetoElement<2>;
+        let mut queue:LazyDeletionQueue<u32,Point,ListParetoFront<u32,Handled<Point>>> = LazyDeletionQueue::default();
+        let handles:Vec<Handle> = [[0,9], [4,6], [9,2]].into_iter()
+            .map(|c| queue.insert_with_handle(Point::new(c)).unwrap())
+            .collect();
+        assert!(queue.remove(handles[1]));
+        assert_eq!(queue.nb_tombstones(), 1);
+        // evicts the tombstone [4,6]
+        assert!(queue.insert(Point::new([3,5])));
+        assert_eq!((queue.nb_tombstones(), queue.len()), (0, 3));
+        // evicts the alive element [9,2]
+        let mut evicted = Vec::new();
+        assert!(queue.insert_evicting(Point::new([8,1]), |e| evicted.push(e.clone())));
+        assert_eq!(evicted, vec![Point::new([9,2])]);
+        assert!(!queue.contains(handles[2]) && !queue.remove(handles[2]));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.drain_sorted().count(), 3);
+        assert_eq!((queue.len(), queue.nb_tombstones()), (0, 0));
+    }
+
+    #[test]
+    fn test_front_tombstones_do_not_dominate() {
+        type Point = CartesianParetoElement<2>;
+        let mut queue:LazyDeletionQueue<u32,Point,ListParetoFront<u32,Handled<Point>>> = LazyDeletionQueue::default();
+        let handles:Vec<Handle> = [[0,10], [6,6], [20,0]].into_iter()
+            .map(|c| queue.insert_with_handle(Point::new(c)).unwrap())
+            .collect();
+        // the tombstone [6,6] dominates [7,7]
+        let h = queue.update(handles[1], Point::new([7,7])).unwrap();
+        assert!(queue.contains(h) && !queue.contains(handles[1]));
+        assert_eq!((queue.len(), queue.nb_tombstones()), (3, 0));
+        // the tombstone [7,7] dominates [8,8]
+        assert!(queue.remove(h));
+        assert!(queue.insert(Point::new([8,8])));
+        assert_eq!((queue.len(), queue.nb_tombstones()), (3, 0));
+        // [9,9] is dominated by the alive element [8,8]: the old element is kept
+        assert_eq!(queue.update(handles[0], Point::new([9,9])), None);
+        assert!(queue.contains(handles[0]));
+        assert_eq!(queue.len(), 3);
+        let mut points:Vec<Point> = queue.drain_sorted().collect();
+        points.sort_by_key(|p| p.kth(0));
+        assert_eq!(points, vec![Point::new([0,10]), Point::new([8,8]), Point::new([20,0])]);
+    }
+}
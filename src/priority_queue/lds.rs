@@ -0,0 +1,291 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
+/// Defines an element reached with some discrepancies (number of decisions deviating from
+/// the heuristic choice).
+pub trait DiscrepancyElement {
+    /// returns the number of discrepancies of the element
+    fn discrepancy(&self) -> usize;
+}
+
+/// Limited discrepancy search (LDS) queue.
+///
+/// Orders elements by (discrepancy, guide): one queue per number of discrepancies, the
+/// lowest non-empty one serving the pops. Elements with more than max_discrepancy
+/// discrepancies are rejected.
+/// Waves of increasing discrepancy are served by [LdsQueue::pop_min_in_wave], which only pops
+/// the elements with at most wave discrepancies (None once the current wave is exhausted),
+/// and [LdsQueue::next_wave] starts the next one. The PriorityQueue operations ignore waves.
+/// If *D* is the maximum discrepancy:
+/// - insertion: O(1) plus the insertion in the queue of its discrepancy
+/// - peek_min/pop_min: O(D) plus the operation on the queue of the lowest discrepancy
+#[derive(Debug,Clone)]
+pub struct LdsQueue<T,Elt,Q=BinaryHeapQueue<T,Elt>> {
+    /// queue of the elements of each number of discrepancies
+    levels:Vec<Q>,
+    /// elements with more discrepancies are rejected
+    max_discrepancy:usize,
+    /// maximum number of discrepancies of the popped elements
+    wave:usize,
+    /// number of elements in the queue
+    nb_elements:usize,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> Default for LdsQueue<T,Elt,Q> {
+    /// queue without discrepancy limit
+    fn default() -> Self { Self::new(usize::MAX) }
+}
+
+impl<T,Elt,Q:Default> LdsQueue<T,Elt,Q> {
+    /// creates a queue rejecting elements with more than max_discrepancy discrepancies. The
+    /// first wave only pops elements without discrepancy
+    pub fn new(max_discrepancy:usize) -> Self {
+        Self { levels:Vec::new(), max_discrepancy, wave:0, nb_elements:0, phantom:PhantomData }
+    }
+
+    /// returns the maximum number of discrepancies of the elements
+    pub fn max_discrepancy(&self) -> usize { self.max_discrepancy }
+
+    /// returns the maximum number of discrepancies of the elements popped in the current wave
+    pub fn wave(&self) -> usize { self.wave }
+
+    /// returns the queue of the elements with the given number of discrepancies
+    pub fn level(&self, discrepancy:usize) -> Option<&Q> { self.levels.get(discrepancy) }
+}
+
+impl<T,Elt,Q> LdsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+DiscrepancyElement, Q:ExactSizeQueue<T,Elt>+Default {
+    /// returns the number of elements that can be popped in the current wave
+    pub fn nb_in_wave(&self) -> usize {
+        self.wave_levels().iter().map(|q| q.len()).sum()
+    }
+
+    /// returns the minimum element of the current wave
+    pub fn peek_min_in_wave(&self) -> Option<&Elt> {
+        self.levels[self.wave_level()?].peek_min()
+    }
+
+    /// pops the minimum element of the current wave. Returns None once the wave is exhausted
+    pub fn pop_min_in_wave(&mut self) -> Option<Elt> {
+        let discrepancy = self.wave_level()?;
+        self.pop_level(discrepancy, true)
+    }
+
+    /// starts the next wave (up to the next number of discrepancies having elements).
+    /// Returns false if no element is left after the current wave
+    pub fn next_wave(&mut self) -> bool {
+        if self.nb_in_wave() == self.nb_elements { return false; }
+        // skips the waves without elements
+        self.wave = self.levels.iter().enumerate()
+            .skip(self.wave + 1)
+            .find(|(_,q)| !q.is_empty())
+            .map_or(self.wave + 1, |(d,_)| d);
+        true
+    }
+
+    /// lowers the maximum number of discrepancies. Returns the removed elements (having more
+    /// discrepancies)
+    pub fn set_max_discrepancy(&mut self, max_discrepancy:usize) -> Vec<Elt> {
+        let mut res = Vec::new();
+        if max_discrepancy < self.levels.len() {
+            for mut q in self.levels.drain(max_discrepancy+1..) {
+                res.extend(q.drain_sorted());
+            }
+        }
+        self.nb_elements -= res.len();
+        self.max_discrepancy = max_discrepancy;
+        self.wave = self.wave.min(max_discrepancy);
+        res
+    }
+
+    /// returns the queues that can be popped in the current wave
+    fn wave_levels(&self) -> &[Q] {
+        &self.levels[..self.levels.len().min(self.wave.saturating_add(1))]
+    }
+
+    /// returns the discrepancy of the lowest non-empty queue of the wave
+    fn wave_level(&self) -> Option<usize> {
+        self.wave_levels().iter().position(|q| !q.is_empty())
+    }
+
+    /// returns the discrepancy of the lowest (resp. highest) non-empty queue
+    fn level_bound(&self, highest:bool) -> Option<usize> {
+        let pred = |(_,q):&(usize,&Q)| !q.is_empty();
+        let mut levels = self.levels.iter().enumerate();
+        if highest { levels.rfind(pred) } else { levels.find(pred) }.map(|(d,_)| d)
+    }
+
+    /// pops the minimum (resp. maximum) element of the queue of the given discrepancy
+    fn pop_level(&mut self, discrepancy:usize, min:bool) -> Option<Elt> {
+        let q = &mut self.levels[discrepancy];
+        let res = if min { q.pop_min() } else { q.pop_max() };
+        if res.is_some() { self.nb_elements -= 1; }
+        res
+    }
+}
+
+impl<T,Elt,Q> PriorityQueue<T,Elt> for LdsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+DiscrepancyElement, Q:ExactSizeQueue<T,Elt>+Default {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.levels[self.level_bound(false)?].peek_min()
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.levels[self.level_bound(true)?].peek_max()
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let discrepancy = self.level_bound(false)?;
+        self.pop_level(discrepancy, true)
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let discrepancy = self.level_bound(true)?;
+        self.pop_level(discrepancy, false)
+    }
+
+    /// inserts the element in the queue of its discrepancy. Returns false if it has too
+    /// many discrepancies
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_evicting(elt, |_| {})
+    }
+
+    /// the elements dropped by the queue of the discrepancy are reported (and no longer counted)
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, mut evicted:F) -> bool {
+        let discrepancy = elt.discrepancy();
+        if discrepancy > self.max_discrepancy { return false; }
+        if discrepancy >= self.levels.len() { self.levels.resize_with(discrepancy+1, Q::default); }
+        let mut nb_evicted = 0;
+        let res = self.levels[discrepancy].insert_evicting(elt, |e| { nb_evicted += 1; evicted(e); });
+        if res { self.nb_elements += 1; }
+        self.nb_elements -= nb_evicted;
+        res
+    }
+
+    fn is_empty(&self) -> bool { self.nb_elements == 0 }
+
+    fn reprioritize(&mut self) {
+        for q in self.levels.iter_mut() { q.reprioritize(); }
+    }
+}
+
+impl<T,Elt,Q> ExactSizeQueue<T,Elt> for LdsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+DiscrepancyElement, Q:ExactSizeQueue<T,Elt>+Default {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T,Elt,Q> PeekKQueue<T,Elt> for LdsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>+DiscrepancyElement, Q:PeekKQueue<T,Elt>+ExactSizeQueue<T,Elt>+Default {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        let mut res = Vec::with_capacity(k);
+        for q in self.levels.iter() {
+            if res.len() >= k { break; }
+            res.extend(q.peek_k(k - res.len()));
+        }
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// node with a number of discrepancies, guided by its second field
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    struct Node(usize, u32);
+
+    impl GuidedElement<u32> for Node {
+        fn guide(&self) -> u32 { self.1 }
+    }
+
+    impl DiscrepancyElement for Node {
+        fn discrepancy(&self) -> usize { self.0 }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut queue:LdsQueue<u32,Node> = LdsQueue::new(2);
+        for (d,g) in [(1,1), (0,9), (2,0), (0,3), (3,0), (1,5)] { queue.insert(Node(d, g)); }
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.peek_k(3), vec![&Node(0,3), &Node(0,9), &Node(1,1)]);
+        assert_eq!(queue.pop_max(), Some(Node(2,0)));
+        let order:Vec<Node> = queue.drain_sorted().collect();
+        assert_eq!(order, vec![Node(0,3), Node(0,9), Node(1,1), Node(1,5)]);
+    }
+
+    #[test]
+    fn test_waves() {
+        let mut queue:LdsQueue<u32,Node> = LdsQueue::new(3);
+        for (d,g) in [(1,1), (0,9), (3,0), (0,3)] { queue.insert(Node(d, g)); }
+        assert_eq!((queue.wave(), queue.nb_in_wave()), (0, 2));
+        assert_eq!(queue.pop_min_in_wave(), Some(Node(0,3)));
+        queue.insert(Node(0, 4)); // child without discrepancy: same wave
+        assert_eq!(queue.pop_min_in_wave(), Some(Node(0,4)));
+        assert_eq!(queue.pop_min_in_wave(), Some(Node(0,9)));
+        assert_eq!((queue.pop_min_in_wave(), queue.peek_min_in_wave()), (None, None));
+        assert!(!queue.is_empty() && queue.len() == 2);
+        assert_eq!(queue.peek_min(), Some(&Node(1,1))); // pop_min ignores the wave
+        assert!(queue.next_wave());
+        assert_eq!((queue.wave(), queue.pop_min_in_wave()), (1, Some(Node(1,1))));
+        assert!(queue.next_wave()); // wave 2 is empty: skipped
+        assert_eq!(queue.wave(), 3);
+        assert_eq!(queue.set_max_discrepancy(2), vec![Node(3,0)]);
+        assert_eq!((queue.len(), queue.wave()), (0, 2));
+        assert!(!queue.next_wave());
+        assert!(!queue.insert(Node(3, 1)));
+    }
+
+    #[test]
+    fn test_evicting_levels() {
+        use crate::priority_queue::{ParetoElement, pareto_list::ListParetoFront};
+        use crate::priority_queue::util::CartesianParetoElement;
+
+        /// label with a number of discrepancies and two costs (guided by their sum)
+        #[derive(Debug,Clone,PartialEq,Eq)]
+        struct Label(usize, CartesianParetoElement<2>);
+
+        impl GuidedElement<u32> for Label {
+            fn guide(&self) -> u32 { self.1.guide() }
+        }
+
+        impl DiscrepancyElement for Label {
+            fn discrepancy(&self) -> usize { self.0 }
+        }
+
+        impl ParetoElement<u32> for Label {
+            type CoordIterator = <CartesianParetoElement<2> as ParetoElement<u32>>::CoordIterator;
+            fn coordinates(&self) -> Self::CoordIterator { self.1.coordinates() }
+            fn dominates(&self, other:&Self) -> bool { self.1.dominates(&other.1) }
+            fn nb_dimensions(&self) -> usize { 2 }
+            fn kth(&self, k:usize) -> u32 { self.1.kth(k) }
+        }
+
+        let mut queue:LdsQueue<u32,Label,ListParetoFront<u32,Label>> = LdsQueue::new(1);
+        for (d,i) in [(0,1), (0,2), (0,3), (1,2)] {
+            assert!(queue.insert(Label(d, CartesianParetoElement::new([i,5-i]))));
+        }
+        let mut evicted = Vec::new();
+        // dominates (1,4) and (2,3) in the level 0 only
+        assert!(queue.insert_evicting(Label(0, CartesianParetoElement::new([1,3])), |e| evicted.push(e.clone())));
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.drain_sorted().count(), 3);
+    }
+
+    #[test]
+    fn test_meld() {
+        let mut queue:LdsQueue<u32,Node> = LdsQueue::new(3);
+        queue.insert(Node(0, 5));
+        let mut other:LdsQueue<u32,Node> = LdsQueue::new(3);
+        for (d,g) in [(2,1), (0,2)] { other.insert(Node(d, g)); }
+        queue.meld(other);
+        assert_eq!(queue.len(), 3);
+        let order:Vec<Node> = queue.drain_sorted().collect();
+        assert_eq!(order, vec![Node(0,2), Node(0,5), Node(2,1)]);
+    }
+}
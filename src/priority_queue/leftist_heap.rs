@@ -0,0 +1,337 @@
+use std::fmt;
+
+use crate::priority_queue::{best_first_k, ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+
+type Link<T,Elt> = Option<Box<Node<T,Elt>>>;
+
+/// parent of a node copied by clone (index of the parent, true if left child)
+type ParentSlot = Option<(usize,bool)>;
+
+/// node of the leftist heap
+struct Node<T,Elt> {
+    /// cached guide of the element
+    guide:T,
+    /// element of the node
+    elt:Elt,
+    /// length of the right spine (s-value)
+    rank:usize,
+    /// left child (its rank is greater or equal than the right child rank)
+    l:Link<T,Elt>,
+    /// right child
+    r:Link<T,Elt>,
+}
+
+/// returns the rank of the link (0 if empty)
+fn rank<T,Elt>(link:&Link<T,Elt>) -> usize {
+    link.as_ref().map(|n| n.rank).unwrap_or(0)
+}
+
+/// Leftist heap priority queue.
+///
+/// Meldable heap: two heaps can be merged in O(log n) (see [PriorityQueue::meld]), which
+/// allows merging open lists without draining and reinserting them.
+/// Guides are computed once at insertion and cached.
+/// - insertion: O(log n)
+/// - meld: O(log n)
+/// - peek_min: O(1)
+/// - pop_min: O(log n)
+/// - peek_max/pop_max: O(n)
+pub struct LeftistHeap<T,Elt> {
+    /// root node
+    root:Link<T,Elt>,
+    /// number of elements in the heap
+    nb_elements:usize,
+}
+
+impl<T,Elt> Default for LeftistHeap<T,Elt> {
+    fn default() -> Self {
+        Self { root:None, nb_elements:0 }
+    }
+}
+
+impl<T:Clone,Elt:Clone> Clone for LeftistHeap<T,Elt> {
+    /// iterative clone (the left spine may be long)
+    fn clone(&self) -> Self {
+        // detached copies of the nodes (parents before children), with (parent, is left child)
+        let mut nodes:Vec<Link<T,Elt>> = Vec::with_capacity(self.nb_elements);
+        let mut parents:Vec<ParentSlot> = Vec::with_capacity(self.nb_elements);
+        let mut stack:Vec<(&Node<T,Elt>,ParentSlot)> = self.root.iter()
+            .map(|n| (&**n, None))
+            .collect();
+        while let Some((node,parent)) = stack.pop() {
+            let i = nodes.len();
+            nodes.push(Some(Box::new(Node {
+                guide:node.guide.clone(), elt:node.elt.clone(), rank:node.rank, l:None, r:None
+            })));
+            parents.push(parent);
+            if let Some(n) = &node.l { stack.push((n, Some((i,true)))); }
+            if let Some(n) = &node.r { stack.push((n, Some((i,false)))); }
+        }
+        // attaches the nodes to their parents, children first
+        for i in (1..nodes.len()).rev() {
+            let (p,is_left) = parents[i].expect("clone: the root is the only node without parent");
+            let child = nodes[i].take();
+            let copy = nodes[p].as_mut().expect("clone: parents are attached after their children");
+            if is_left { copy.l = child; } else { copy.r = child; }
+        }
+        Self { root:nodes.into_iter().next().flatten(), nb_elements:self.nb_elements }
+    }
+}
+
+/// prints the elements iteratively (the left spine may be long)
+impl<T,Elt:fmt::Debug> fmt::Debug for LeftistHeap<T,Elt> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeftistHeap")
+            .field("nb_elements", &self.nb_elements)
+            .field("elements", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T,Elt> Drop for LeftistHeap<T,Elt> {
+    /// iterative drop (the left spine may be long)
+    fn drop(&mut self) {
+        let mut stack:Vec<Box<Node<T,Elt>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.l.take());
+            stack.extend(node.r.take());
+        }
+    }
+}
+
+impl<T,Elt> LeftistHeap<T,Elt> {
+    /// returns the number of elements in the heap
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the heap is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+
+    /// returns an iterator over the elements of the heap (in no particular order)
+    pub fn iter(&self) -> LeftistHeapIterator<'_,T,Elt> {
+        LeftistHeapIterator { stack:self.root.iter().map(|n| &**n).collect() }
+    }
+}
+
+impl<T,Elt> LeftistHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// merges two heaps (recursion only follows right spines of length O(log n))
+    fn merge(a:Link<T,Elt>, b:Link<T,Elt>) -> Link<T,Elt> {
+        match (a,b) {
+            (None, x) | (x, None) => x,
+            (Some(x), Some(y)) => {
+                let (mut top, other) = if y.guide < x.guide { (y,x) } else { (x,y) };
+                top.r = Self::merge(top.r.take(), Some(other));
+                if rank(&top.l) < rank(&top.r) { std::mem::swap(&mut top.l, &mut top.r); }
+                top.rank = rank(&top.r) + 1;
+                Some(top)
+            }
+        }
+    }
+
+    /// builds a heap from a vector of elements (melding singleton heaps pairwise). O(n)
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let nb_elements = elts.len();
+        let nodes = elts.into_iter()
+            .map(|elt| Box::new(Node { guide:elt.guide(), elt, rank:1, l:None, r:None }))
+            .collect();
+        Self { root:Self::build(nodes), nb_elements }
+    }
+
+    /// removes the maximum element by rebuilding the heap. O(n)
+    fn remove_max(&mut self) -> Option<Elt> {
+        let mut nodes = self.take_nodes();
+        let max_pos = (0..nodes.len()).max_by_key(|i| nodes[*i].guide)?;
+        let res = nodes.swap_remove(max_pos).elt;
+        self.root = Self::build(nodes);
+        self.nb_elements -= 1;
+        Some(res)
+    }
+
+    /// removes all the nodes of the heap, detached (singleton heaps)
+    fn take_nodes(&mut self) -> Vec<Box<Node<T,Elt>>> {
+        let mut nodes:Vec<Box<Node<T,Elt>>> = Vec::with_capacity(self.nb_elements);
+        let mut stack:Vec<Box<Node<T,Elt>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.l.take());
+            stack.extend(node.r.take());
+            node.rank = 1;
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// builds a heap by melding detached nodes pairwise. O(n)
+    fn build(nodes:Vec<Box<Node<T,Elt>>>) -> Link<T,Elt> {
+        let mut links:Vec<Link<T,Elt>> = nodes.into_iter().map(Some).collect();
+        while links.len() > 1 {
+            let mut next = Vec::with_capacity(links.len().div_ceil(2));
+            while let Some(a) = links.pop() {
+                let b = links.pop().flatten();
+                next.push(Self::merge(a, b));
+            }
+            links = next;
+        }
+        links.pop().flatten()
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for LeftistHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.root.as_ref().map(|n| &n.elt)
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.iter().max_by_key(|e| e.guide())
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let mut root = self.root.take()?;
+        self.root = Self::merge(root.l.take(), root.r.take());
+        self.nb_elements -= 1;
+        Some(root.elt)
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        self.remove_max()
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        let node = Box::new(Node { guide:elt.guide(), elt, rank:1, l:None, r:None });
+        self.root = Self::merge(self.root.take(), Some(node));
+        self.nb_elements += 1;
+        true
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.root.as_ref().map(|n| n.guide)
+    }
+
+    /// recomputes the cached guides, then rebuilds the heap. O(n)
+    fn reprioritize(&mut self) {
+        let mut nodes = self.take_nodes();
+        for node in nodes.iter_mut() { node.guide = node.elt.guide(); }
+        self.root = Self::build(nodes);
+    }
+
+    fn meld(&mut self, mut other:Self) {
+        self.root = Self::merge(self.root.take(), other.root.take());
+        self.nb_elements += other.nb_elements;
+        other.nb_elements = 0;
+    }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for LeftistHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for LeftistHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// best-first search over the heap-ordered tree. O(k.log(k))
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        best_first_k(self.root.as_deref(), k, |n| n.guide, |n, res| {
+            res.extend(n.l.as_deref());
+            res.extend(n.r.as_deref());
+        }, |n| Some(&n.elt))
+    }
+}
+
+/// Iterator over the elements of a leftist heap.
+pub struct LeftistHeapIterator<'a,T,Elt> {
+    /// nodes to visit
+    stack:Vec<&'a Node<T,Elt>>,
+}
+
+impl<T,Elt> fmt::Debug for LeftistHeapIterator<'_,T,Elt> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeftistHeapIterator").field("nb_pending_nodes", &self.stack.len()).finish()
+    }
+}
+
+impl<'a,T,Elt> Iterator for LeftistHeapIterator<'a,T,Elt> {
+    type Item = &'a Elt;
+    fn next(&mut self) -> Option<&'a Elt> {
+        let node = self.stack.pop()?;
+        if let Some(n) = &node.l { self.stack.push(n); }
+        if let Some(n) = &node.r { self.stack.push(n); }
+        Some(&node.elt)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::binary_heap::BinaryHeapQueue;
+    use crate::priority_queue::fixtures::Value;
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut heap = LeftistHeap::default();
+        for i in 0..40 { heap.insert(Value((i*13+5) % 40)); }
+        assert_eq!(heap.pop_max(), Some(Value(39)));
+        for i in 0..39 { assert_eq!(heap.pop_min(), Some(Value(i))); }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_peek_k() {
+        let mut heap = LeftistHeap::default();
+        for i in 0..40 { heap.insert(Value((i*13+5) % 40)); }
+        assert_eq!(heap.peek_k(4), vec![&Value(0), &Value(1), &Value(2), &Value(3)]);
+        assert_eq!(heap.pop_k(2), vec![Value(0), Value(1)]);
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut heap = LeftistHeap::from_vec((0..25).map(|i| Value((i*7+3) % 25)).collect());
+        assert_eq!(heap.len(), 25);
+        for i in 0..25 { assert_eq!(heap.pop_min(), Some(Value(i))); }
+    }
+
+    #[test]
+    fn test_meld() {
+        let mut a = LeftistHeap::default();
+        let mut b = LeftistHeap::default();
+        for i in 0..20 {
+            if i % 3 == 0 { a.insert(Value(i)); } else { b.insert(Value(i)); }
+        }
+        a.meld(b);
+        assert_eq!(a.len(), 20);
+        for i in 0..20 { assert_eq!(a.pop_min(), Some(Value(i))); }
+    }
+
+    #[test]
+    fn test_default_meld() {
+        let mut a = BinaryHeapQueue::default();
+        let mut b = BinaryHeapQueue::default();
+        a.insert(Value(3));
+        b.insert(Value(1));
+        b.insert(Value(2));
+        a.meld(b);
+        assert_eq!(a.pop_min(), Some(Value(1)));
+        assert_eq!(a.len(), 2);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut heap = LeftistHeap::default();
+        heap.insert(Value(2));
+        heap.insert(Value(1));
+        assert_eq!(format!("{:?}", heap), "LeftistHeap { nb_elements: 2, elements: [Value(1), Value(2)] }");
+    }
+
+    #[test]
+    fn test_long_spine() {
+        // decreasing insertions build a left spine of n nodes (cloned and dropped iteratively)
+        let n = 100_000;
+        let mut heap = LeftistHeap::default();
+        for i in (0..n).rev() { heap.insert(Value(i)); }
+        let mut copy = heap.clone();
+        drop(heap);
+        assert_eq!((copy.pop_min(), copy.pop_min()), (Some(Value(0)), Some(Value(1))));
+        assert_eq!(copy.len(), n as usize - 2);
+    }
+}
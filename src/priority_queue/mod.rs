@@ -1,3 +1,7 @@
+use std::{cmp::Reverse, collections::BinaryHeap, marker::PhantomData};
+
+use rand::Rng;
+
 /// Pareto element trait. Defines an element that is present on the pareto front.
 pub trait ParetoElement<T:Ord> {
     /// Iterator trait over the coordinates of the element
@@ -23,6 +27,11 @@ pub trait GuidedElement<T:Ord> {
     fn guide(&self) -> T;
 }
 
+/// A (guide, payload) pair is guided by its first component
+impl<T:Ord+Clone,P> GuidedElement<T> for (T,P) {
+    fn guide(&self) -> T { self.0.clone() }
+}
+
 
 /// Defines the behavior of a priority queue.
 /// 
@@ -48,6 +57,18 @@ pub trait PriorityQueue<T,Elt> where Elt:GuidedElement<T>, T:Ord {
     /// leads to a successful insertion)
     fn insert(&mut self, elt:Elt) -> bool;
 
+    /// inserts an element in the queue, and calls evicted on each element the queue drops
+    /// because of the insertion (for instance elements dominated by the inserted one in a
+    /// pareto front, or the maximum of a full beam).
+    ///
+    /// returns true iff the element was successfully inserted.
+    /// By default, inserts the element and reports no eviction: queues dropping elements on
+    /// insertion override it.
+    fn insert_evicting<F>(&mut self, elt:Elt, _evicted:F) -> bool
+    where F:FnMut(&Elt), Self:Sized {
+        self.insert(elt)
+    }
+
     /// returns the minimum guide of the priority queue
     fn peek_min_guide(&self) -> Option<T> {
         self.peek_min().map(|e| e.guide())
@@ -60,6 +81,323 @@ pub trait PriorityQueue<T,Elt> where Elt:GuidedElement<T>, T:Ord {
 
     /// returns true iff the queue is empty
     fn is_empty(&self) -> bool { self.peek_min().is_none() }
+
+    /// pops the k elements with the lowest guides (less if the queue contains less than k
+    /// elements), sorted by guide.
+    ///
+    /// By default, pops the minimum k times.
+    fn pop_k(&mut self, k:usize) -> Vec<Elt> {
+        let mut res = Vec::with_capacity(k);
+        while res.len() < k {
+            match self.pop_min() {
+                Some(elt) => res.push(elt),
+                None => break,
+            }
+        }
+        res
+    }
+
+    /// pops an element uniformly at random among the elements with the minimum guide.
+    ///
+    /// Useful for randomized greedy algorithms (GRASP-like). By default, pops all the minimum
+    /// elements and re-inserts the ones that are not chosen.
+    fn pop_random_minimum<R:Rng+?Sized>(&mut self, rng:&mut R) -> Option<Elt> where Self:Sized {
+        let min_guide = self.peek_min_guide()?;
+        self.pop_random_within(rng, min_guide)
+    }
+
+    /// pops an element uniformly at random among the elements with a guide lower or equal
+    /// than max_guide (for instance the minimum guide plus some epsilon).
+    ///
+    /// By default, pops all these elements and re-inserts the ones that are not chosen (in
+    /// their pop order).
+    fn pop_random_within<R:Rng+?Sized>(&mut self, rng:&mut R, max_guide:T) -> Option<Elt> where Self:Sized {
+        let mut candidates = Vec::new();
+        while self.peek_min_guide().is_some_and(|g| g <= max_guide) {
+            candidates.extend(self.pop_min());
+        }
+        if candidates.is_empty() { return None; }
+        let res = candidates.remove(rng.gen_range(0..candidates.len()));
+        for elt in candidates { self.insert(elt); }
+        Some(res)
+    }
+
+    /// restores the queue invariants after the guides of the stored elements changed
+    /// (for instance after a bound tightening).
+    ///
+    /// By default, pops all the elements and re-inserts them.
+    /// Queues caching guides or guide bounds override it.
+    fn reprioritize(&mut self) where Self:Sized {
+        let elts:Vec<Elt> = std::iter::from_fn(|| self.pop_min()).collect();
+        for elt in elts { self.insert(elt); }
+    }
+
+    /// merges the other queue into this one.
+    ///
+    /// By default, pops all the elements of the other queue and inserts them.
+    /// Meldable queues (for instance the leftist heap) override it.
+    fn meld(&mut self, mut other:Self) where Self:Sized {
+        while let Some(elt) = other.pop_min() {
+            self.insert(elt);
+        }
+    }
+}
+
+
+/// Defines a priority queue knowing its number of elements.
+///
+/// Its elements can be drained in guide order by iterators with exact size hints.
+pub trait ExactSizeQueue<T,Elt>:PriorityQueue<T,Elt> where Elt:GuidedElement<T>, T:Ord {
+    /// returns the number of elements in the queue
+    fn len(&self) -> usize;
+
+    /// returns an iterator popping the elements of the queue in guide order.
+    /// Elements that are not consumed by the iterator stay in the queue
+    fn drain_sorted(&mut self) -> DrainSorted<'_,T,Elt,Self> where Self:Sized {
+        DrainSorted { queue:self, phantom:PhantomData }
+    }
+
+    /// consumes the queue and returns an iterator over its elements in guide order
+    fn into_sorted_iter(self) -> IntoSortedIter<T,Elt,Self> where Self:Sized {
+        IntoSortedIter { queue:self, phantom:PhantomData }
+    }
+}
+
+
+/// Defines a priority queue able to peek its best elements without popping them.
+///
+/// Heaps and trees implement it by a best-first search over their structure.
+pub trait PeekKQueue<T,Elt>:PriorityQueue<T,Elt> where Elt:GuidedElement<T>, T:Ord {
+    /// peeks the k elements with the lowest guides (less if the queue contains less than k
+    /// elements), sorted by guide
+    fn peek_k(&self, k:usize) -> Vec<&Elt>;
+}
+
+
+/// Iterator popping the elements of a queue in guide order (see [ExactSizeQueue::drain_sorted]).
+#[derive(Debug)]
+pub struct DrainSorted<'a,T,Elt,Q> {
+    /// drained queue
+    queue:&'a mut Q,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q> Iterator for DrainSorted<'_,T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    type Item = Elt;
+
+    fn next(&mut self) -> Option<Elt> { self.queue.pop_min() }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len(), Some(self.queue.len()))
+    }
+}
+
+impl<T,Elt,Q> ExactSizeIterator for DrainSorted<'_,T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {}
+
+/// Consuming iterator over the elements of a queue in guide order
+/// (see [ExactSizeQueue::into_sorted_iter]).
+#[derive(Debug)]
+pub struct IntoSortedIter<T,Elt,Q> {
+    /// consumed queue
+    queue:Q,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q> Iterator for IntoSortedIter<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    type Item = Elt;
+
+    fn next(&mut self) -> Option<Elt> { self.queue.pop_min() }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len(), Some(self.queue.len()))
+    }
+}
+
+impl<T,Elt,Q> ExactSizeIterator for IntoSortedIter<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {}
+
+
+/// removes the dominated elements (and duplicates) of a vector of elements.
+/// Used by the bulk constructors of the pareto fronts.
+///
+/// Once sorted lexicographically, an element may only be dominated by a previous one (the
+/// first of equal elements is kept). In 2 dimensions, a sweep over the second coordinate
+/// decides it in O(n.log(n)). In higher dimensions, the dominating elements are searched
+/// in a static kd-tree over the elements.
+pub(crate) fn filter_dominated<T,Elt>(mut elts:Vec<Elt>) -> Vec<Elt>
+where T:Ord, Elt:ParetoElement<T> {
+    elts.sort_by(|a,b| a.coordinates().cmp(b.coordinates()));
+    let dominated = match elts.first().map(|e| e.nb_dimensions()) {
+        None => return elts,
+        Some(2) => dominated_2d(&elts),
+        Some(nb_dim) => DominanceTree::new(&elts, nb_dim).dominated(),
+    };
+    elts.into_iter().zip(dominated).filter(|(_,d)| !d).map(|(e,_)| e).collect()
+}
+
+/// marks the 2-dimensional elements (sorted lexicographically) dominated by a previous one.
+/// The previous element of minimum second coordinate is the only candidate
+fn dominated_2d<T,Elt>(elts:&[Elt]) -> Vec<bool>
+where T:Ord, Elt:ParetoElement<T> {
+    let mut best:Option<&Elt> = None;
+    elts.iter().map(|e| {
+        if best.is_some_and(|b| b.dominates(e)) { return true; }
+        if best.is_none_or(|b| e.kth(1) < b.kth(1)) { best = Some(e); }
+        false
+    }).collect()
+}
+
+/// Static kd-tree over elements sorted lexicographically, finds the elements dominated by a
+/// previous one. Each node is the middle of a range of the implicit tree, and stores the
+/// minimum coordinates (and the minimum index) of its sub-tree to prune the search.
+struct DominanceTree<'a,T,Elt> {
+    /// elements (sorted lexicographically)
+    elts:&'a [Elt],
+    /// number of dimensions
+    nb_dim:usize,
+    /// indices of the elements, arranged as an implicit kd-tree
+    order:Vec<usize>,
+    /// for each node and dimension, the index of the element of minimum coordinate in the sub-tree
+    mins:Vec<usize>,
+    /// for each node, the minimum index of the elements of the sub-tree
+    first:Vec<usize>,
+    /// phantom for type T
+    phantom:PhantomData<T>,
+}
+
+impl<'a,T,Elt> DominanceTree<'a,T,Elt>
+where T:Ord, Elt:ParetoElement<T> {
+    /// builds the tree. O(n.log(n)) comparisons
+    fn new(elts:&'a [Elt], nb_dim:usize) -> Self {
+        let mut res = Self {
+            elts, nb_dim,
+            order: (0..elts.len()).collect(),
+            mins: vec![0; elts.len()*nb_dim],
+            first: vec![0; elts.len()],
+            phantom: PhantomData,
+        };
+        res.build(0, elts.len(), 0);
+        res
+    }
+
+    /// builds the sub-tree of the range lo..hi (non-empty), split along dimension depth % nb_dim
+    fn build(&mut self, lo:usize, hi:usize, depth:usize) {
+        let mid = (lo+hi)/2;
+        let dim = depth % self.nb_dim;
+        let elts = self.elts;
+        self.order[lo..hi].select_nth_unstable_by_key(mid-lo, |&i| elts[i].kth(dim));
+        let node = self.order[mid];
+        for k in 0..self.nb_dim { self.mins[mid*self.nb_dim+k] = node; }
+        self.first[mid] = node;
+        for (child_lo,child_hi) in [(lo,mid),(mid+1,hi)] {
+            if child_lo == child_hi { continue; }
+            self.build(child_lo, child_hi, depth+1);
+            let child = (child_lo+child_hi)/2;
+            for k in 0..self.nb_dim {
+                let child_min = self.mins[child*self.nb_dim+k];
+                if elts[child_min].kth(k) < elts[self.mins[mid*self.nb_dim+k]].kth(k) {
+                    self.mins[mid*self.nb_dim+k] = child_min;
+                }
+            }
+            self.first[mid] = self.first[mid].min(self.first[child]);
+        }
+    }
+
+    /// returns true if an element of index lower than i, in the sub-tree of the range lo..hi,
+    /// dominates the i-th element
+    fn has_dominating(&self, lo:usize, hi:usize, i:usize) -> bool {
+        if lo == hi { return false; }
+        let mid = (lo+hi)/2;
+        let elt = &self.elts[i];
+        if self.first[mid] >= i { return false; }
+        if (0..self.nb_dim).any(|k| self.elts[self.mins[mid*self.nb_dim+k]].kth(k) > elt.kth(k)) {
+            return false;
+        }
+        let node = self.order[mid];
+        (node < i && self.elts[node].dominates(elt))
+            || self.has_dominating(lo, mid, i)
+            || self.has_dominating(mid+1, hi, i)
+    }
+
+    /// marks the elements dominated by a previous one
+    fn dominated(&self) -> Vec<bool> {
+        (0..self.elts.len()).map(|i| self.has_dominating(0, self.elts.len(), i)).collect()
+    }
+}
+
+/// returns the k elements with the lowest guides, sorted by guide.
+/// Selects them in linear time, then sorts them. O(n + k.log(k))
+pub(crate) fn k_best_by_guide<'a,T,Elt,I>(elts:I, k:usize) -> Vec<&'a Elt>
+where T:Ord, Elt:GuidedElement<T>+'a, I:Iterator<Item=&'a Elt> {
+    if k == 0 { return Vec::new(); }
+    let mut res:Vec<(T,&Elt)> = elts.map(|e| (e.guide(), e)).collect();
+    if k < res.len() {
+        res.select_nth_unstable_by(k-1, |a,b| a.0.cmp(&b.0));
+        res.truncate(k);
+    }
+    res.sort_by(|a,b| a.0.cmp(&b.0));
+    res.into_iter().map(|(_,e)| e).collect()
+}
+
+/// returns the k elements with the lowest keys of a heap-ordered structure (best-first search).
+///
+/// The key of an item has to be a lower bound of the keys of the items it expands into.
+/// Items yielding no element (for instance sub-tree bounds) are expanded but not returned.
+/// O(k.log(k)) expansions if each item expands into a bounded number of items.
+pub(crate) fn best_first_k<'a,T,N,Elt,K,X,E>(roots:impl IntoIterator<Item=N>, k:usize, key:K, mut expand:X, elt:E) -> Vec<&'a Elt>
+where T:Ord, K:Fn(&N)->T, X:FnMut(&N, &mut Vec<N>), E:Fn(&N)->Option<&'a Elt> {
+    let mut res = Vec::with_capacity(k);
+    let mut items:Vec<N> = Vec::new();
+    let mut open:BinaryHeap<Reverse<(T,usize)>> = BinaryHeap::new();
+    let mut children = Vec::new();
+    for item in roots {
+        open.push(Reverse((key(&item), items.len())));
+        items.push(item);
+    }
+    while res.len() < k {
+        let Some(Reverse((_,i))) = open.pop() else { break; };
+        if let Some(e) = elt(&items[i]) { res.push(e); }
+        expand(&items[i], &mut children);
+        for item in children.drain(..) {
+            open.push(Reverse((key(&item), items.len())));
+            items.push(item);
+        }
+    }
+    res
+}
+
+
+/// Event reported by a pareto front during an insertion.
+#[derive(Debug)]
+pub enum FrontEvent<'a,Elt> {
+    /// the element was inserted in the front
+    Inserted(&'a Elt),
+    /// the element was not inserted as it is dominated by an element of the front
+    RejectedDominated {
+        /// rejected element
+        element:&'a Elt,
+        /// element of the front dominating it
+        by:&'a Elt
+    },
+    /// an element of the front was removed as it is dominated by the inserted element
+    EvictedBy {
+        /// evicted element
+        element:&'a Elt,
+        /// inserted element dominating it
+        by:&'a Elt
+    },
+}
+
+
+/// returns an observer forwarding the evicted elements reported by a pareto front to evicted.
+/// Used by the fronts to implement [PriorityQueue::insert_evicting]
+pub(crate) fn on_evictions<Elt,F:FnMut(&Elt)>(mut evicted:F) -> impl FnMut(FrontEvent<'_,Elt>) {
+    move |event| if let FrontEvent::EvictedBy { element, .. } = event { evicted(element) }
 }
 
 
@@ -69,6 +407,24 @@ pub trait ParetoFront<T,Elt>:Default where T:Ord, Elt:ParetoElement<T> {
     /// returns an element dominating the element if it exists
     fn find_dominating(&self, elt:&Elt) -> Option<&Elt>;
 
+    /// inserts an element in the front, and reports to the observer what happens:
+    /// rejection if the element is dominated, otherwise evictions of the elements it dominates
+    /// then its insertion.
+    ///
+    /// returns true iff the element was inserted.
+    /// By default, reports the rejection of the elements dominated according to
+    /// find_dominating, otherwise reports the insertion then inserts the element as a priority
+    /// queue (evictions are not reported). Fronts override it.
+    fn insert_observed<O>(&mut self, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>), Self:PriorityQueue<T,Elt>, Elt:GuidedElement<T> {
+        if let Some(by) = self.find_dominating(&elt) {
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return false;
+        }
+        observer(FrontEvent::Inserted(&elt));
+        self.insert(elt)
+    }
+
     /// creates a new instance of the pareto front with discretization hints
     fn new_with_discretization(_hint:&[Option<(T,T,T)>]) -> Self {
         Self::default()
@@ -77,9 +433,28 @@ pub trait ParetoFront<T,Elt>:Default where T:Ord, Elt:ParetoElement<T> {
 }
 
 
+/// Defines the behavior of a priority queue shared between threads.
+///
+/// Operations take a shared reference, thus can be called from many threads (workers of a
+/// parallel search). Implementations may relax the order of the pops.
+pub trait ConcurrentPriorityQueue<T,Elt>:Sync where Elt:GuidedElement<T>, T:Ord {
+    /// inserts an element. Returns true iff it was inserted
+    fn insert(&self, elt:Elt) -> bool;
+
+    /// removes and returns a minimum element (or an element close to the minimum)
+    fn pop_min(&self) -> Option<Elt>;
+
+    /// returns the number of elements in the queue
+    fn len(&self) -> usize;
+
+    /// returns true iff the queue is empty
+    fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+
 /// Pareto Priority-queue list.
 /// 
-/// Implements a Pareto priority queue. Each element is stored in a simple vector.
+/// Implements a Pareto priority queue. Each element is stored in a vector sorted by guide.
 /// When inserting, check all other elements for dominations.
 /// It assumes that if an element e1 dominates an element e2, guide(e1) <= guide(e2)
 pub mod pareto_list;
@@ -89,5 +464,119 @@ pub mod pareto_list;
 /// Implements a kd-tree as a pareto priority queue.
 pub mod kd_tree;
 
+/// Z-order (Morton) pareto priority queue.
+///
+/// Implements a pareto priority queue stored in a flat vector sorted by Morton code.
+pub mod morton;
+
+/// R-tree pareto priority queue.
+///
+/// Implements an R-tree as a pareto priority queue: elements are grouped into pages
+/// described by their minimum bounding rectangle.
+pub mod rtree;
+
+/// Range tree pareto priority queue.
+///
+/// Implements a layered range tree as a (semi-dynamic) pareto priority queue: orthogonal
+/// range and dominance queries in polylogarithmic time.
+pub mod range_tree;
+
+/// Binary heap priority queue.
+///
+/// Implements a (min-max) binary heap over guided elements, without pareto logic.
+pub mod binary_heap;
+
+/// Pairing heap priority queue.
+///
+/// Implements a pairing heap returning handles on insertion, and supporting decrease-key.
+pub mod pairing_heap;
+
+/// Leftist heap priority queue.
+///
+/// Implements a meldable heap: two queues can be merged in logarithmic time.
+pub mod leftist_heap;
+
+/// Persistent heap.
+///
+/// Implements an immutable leftist heap whose versions share structure (forkable open lists).
+pub mod persistent_heap;
+
+/// Indexed priority queue.
+///
+/// Implements an addressable priority queue mapping user-provided identifiers to elements.
+pub mod indexed;
+
+/// Lazy-deletion queue.
+///
+/// Wraps a priority queue and implements removals by marking tombstones.
+pub mod lazy;
+
+/// Bounded beam queue.
+///
+/// Keeps only the best elements, evicting the maximum when full.
+pub mod beam;
+
+/// k-best container.
+///
+/// Retains the k best elements seen so far, the k-th one being an admission threshold.
+pub mod k_best;
+
+/// Monotonic deque.
+///
+/// Maintains the minimum (or maximum) of a sliding window of a stream.
+pub mod monotonic;
+
+/// Stable queue.
+///
+/// Wraps a priority queue so that elements with equal guides pop in insertion order.
+pub mod stable;
+
+/// Limited discrepancy search queue.
+///
+/// Orders elements by (discrepancy, guide), with a maximum discrepancy and waves of
+/// increasing discrepancy.
+pub mod lds;
+
+/// Randomized priority queue.
+///
+/// Pops the minimum, or with some probability a random element (epsilon-greedy or softmax).
+pub mod randomized;
+
+/// Concurrent priority queues.
+///
+/// Thread-safe queues (a locked queue, and a sharded queue with relaxed pops).
+pub mod concurrent;
+
+/// Work-stealing pool.
+///
+/// Each worker owns a local queue, and idle workers steal batches of the best elements.
+pub mod work_stealing;
+
+/// Disk-spilling queue.
+///
+/// Keeps a bounded number of elements in memory, and spills the worst ones to files.
+#[cfg(feature = "spill")]
+pub mod spill;
+
+/// Multi-queue container.
+///
+/// Holds several priority queues with different guides over the same elements, and a
+/// selection policy deciding which queue serves the next pop.
+pub mod multi;
+
+/// Statistics facade.
+///
+/// Wraps a queue and collects insertion, rejection, eviction and pop counts.
+pub mod stats;
+
 /// Utility class
-pub mod util;
\ No newline at end of file
+pub mod util;
+
+/// Stable handles.
+///
+/// Handles allow referencing stored elements without keeping clones of them.
+pub mod handle;
+
+/// Elements shared by the tests of the queues.
+#[cfg(test)]
+mod fixtures;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+/// Monotonic deque (sliding-window minimum).
+///
+/// Maintains the minimum of a window of a stream: elements are pushed at the back of the
+/// window, and removed from its front (one by one, or all the elements pushed before a
+/// position). Only the elements that may become the minimum are stored (increasing
+/// sequence), which speeds up dynamic programming recurrences over sliding windows.
+/// Use std::cmp::Reverse to maintain the window maximum instead.
+/// If *w* is the size of the window:
+/// - memory: O(w)
+/// - push, pop_front: O(1) amortized
+/// - min: O(1)
+#[derive(Debug,Clone)]
+pub struct MonotonicDeque<T> {
+    /// (position, element) of the candidate minima, increasing in both components
+    candidates:VecDeque<(usize,T)>,
+    /// number of elements pushed (position of the next element)
+    nb_pushed:usize,
+    /// position of the first element of the window
+    start:usize,
+}
+
+impl<T> Default for MonotonicDeque<T> {
+    fn default() -> Self { Self { candidates:VecDeque::new(), nb_pushed:0, start:0 } }
+}
+
+impl<T:Ord> MonotonicDeque<T> {
+    /// creates an empty window
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of elements in the window
+    pub fn len(&self) -> usize { self.nb_pushed - self.start }
+
+    /// returns true iff the window is empty
+    pub fn is_empty(&self) -> bool { self.nb_pushed == self.start }
+
+    /// returns the position of the first element of the window
+    pub fn start(&self) -> usize { self.start }
+
+    /// returns the number of elements pushed (the position of the next element)
+    pub fn nb_pushed(&self) -> usize { self.nb_pushed }
+
+    /// pushes an element at the back of the window. Returns its position
+    pub fn push(&mut self, elt:T) -> usize {
+        while self.candidates.back().is_some_and(|(_,e)| *e >= elt) {
+            self.candidates.pop_back();
+        }
+        let position = self.nb_pushed;
+        self.candidates.push_back((position, elt));
+        self.nb_pushed += 1;
+        position
+    }
+
+    /// removes the first element of the window. Returns false if the window is empty
+    pub fn pop_front(&mut self) -> bool {
+        if self.is_empty() { return false; }
+        self.pop_before(self.start + 1);
+        true
+    }
+
+    /// removes the elements pushed before the given position
+    pub fn pop_before(&mut self, position:usize) {
+        self.start = self.start.max(position.min(self.nb_pushed));
+        while self.candidates.front().is_some_and(|(p,_)| *p < self.start) {
+            self.candidates.pop_front();
+        }
+    }
+
+    /// returns the minimum of the window
+    pub fn min(&self) -> Option<&T> { self.candidates.front().map(|(_,e)| e) }
+
+    /// returns the position of the minimum of the window (the last one on ties)
+    pub fn min_position(&self) -> Option<usize> { self.candidates.front().map(|(p,_)| *p) }
+
+    /// empties the window (the positions keep increasing)
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.start = self.nb_pushed;
+    }
+}
+
+impl<T:Ord> Extend<T> for MonotonicDeque<T> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for elt in iter { self.push(elt); }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cmp::Reverse;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_window() {
+        let mut deque = MonotonicDeque::new();
+        deque.extend([4, 2, 5, 2, 7]);
+        assert_eq!((deque.min(), deque.min_position(), deque.len()), (Some(&2), Some(3), 5));
+        assert!(deque.pop_front());
+        deque.pop_before(4);
+        assert_eq!((deque.min(), deque.start(), deque.len()), (Some(&7), 4, 1));
+        assert_eq!(deque.push(9), 5);
+        deque.clear();
+        assert!(deque.is_empty() && !deque.pop_front());
+        assert_eq!(deque.min(), None);
+        let mut maxima = MonotonicDeque::new();
+        maxima.extend([3, 8, 1].map(Reverse));
+        assert_eq!(maxima.min(), Some(&Reverse(8)));
+    }
+
+    #[test]
+    fn test_sliding_minimum() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let values:Vec<i32> = (0..500).map(|_| rng.gen_range(0..100)).collect();
+        let width = 7;
+        let mut deque = MonotonicDeque::new();
+        for (i,v) in values.iter().enumerate() {
+            deque.push(*v);
+            deque.pop_before((i + 1).saturating_sub(width));
+            let start = deque.start();
+            assert_eq!(deque.min(), values[start..=i].iter().min());
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{filter_dominated, k_best_by_guide, on_evictions, FrontEvent, ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue, ParetoFront};
+
+/// Z-order (Morton) sorted pareto front.
+///
+/// Elements are stored in a flat vector sorted by the Morton code of their coordinates.
+/// Coordinates are saturated to fit in the code (128/NB_DIM bits per dimension), which keeps
+/// the code monotone: if e1 dominates e2, then code(e1) <= code(e2).
+/// Thus, dominance queries only scan an interval of the vector:
+/// - elements dominating e have a code lower or equal than code(e)
+/// - elements dominated by e have a code greater or equal than code(e)
+///
+/// The storage is cache-friendly and trivially serializable.
+/// - insertion: O(log n) search + O(n) shift and dominance checks on the relevant interval
+/// - find_dominating: O(n) on the prefix interval
+/// - peek_min/peek_max: O(n)
+#[derive(Debug)]
+pub struct MortonFront<T, Elt, const NB_DIM:usize> {
+    /// elements sorted by Morton code
+    elements:Vec<(u128,Elt)>,
+    /// phantom for type T
+    phantom_t:PhantomData<T>,
+}
+
+impl<T, Elt, const NB_DIM:usize> Default for MortonFront<T, Elt, NB_DIM> {
+    fn default() -> Self {
+        Self { elements: Vec::new(), phantom_t: PhantomData }
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> MortonFront<T, Elt, NB_DIM> {
+    /// returns an iterator over the elements of the front (sorted by Morton code)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> { self.elements.iter().map(|(_,e)| e) }
+
+    /// returns the number of elements in the front
+    pub fn len(&self) -> usize { self.elements.len() }
+
+    /// returns true iff the front is empty
+    pub fn is_empty(&self) -> bool { self.elements.is_empty() }
+}
+
+impl<T, Elt, const NB_DIM:usize> MortonFront<T, Elt, NB_DIM>
+where T:Ord+Copy+Into<u64>, Elt:ParetoElement<T> {
+    /// number of bits used per dimension in the Morton code
+    const BITS:usize = if 128/NB_DIM > 64 { 64 } else { 128/NB_DIM };
+
+    /// computes the Morton code of an element (interleaving the bits of its coordinates)
+    pub fn morton_code(elt:&Elt) -> u128 {
+        let max_value:u64 = if Self::BITS == 64 { u64::MAX } else { (1<<Self::BITS)-1 };
+        let mut res:u128 = 0;
+        for (d,c) in elt.coordinates().enumerate() {
+            let mut v = std::cmp::min(c.into(), max_value); // saturates the coordinate
+            let mut b = 0;
+            while v != 0 {
+                if v & 1 == 1 {
+                    res |= 1 << (b*NB_DIM + (NB_DIM-1-d));
+                }
+                v >>= 1;
+                b += 1;
+            }
+        }
+        res
+    }
+
+    /// builds a front from a vector of elements (sorting them by Morton code).
+    ///
+    /// Dominated elements are filtered out before building the front.
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let mut elements:Vec<(u128,Elt)> = filter_dominated(elts).into_iter()
+            .map(|e| (Self::morton_code(&e), e))
+            .collect();
+        elements.sort_by_key(|(c,_)| *c);
+        Self { elements, phantom_t:PhantomData }
+    }
+
+    /// returns the position of the first element having a code strictly greater than code
+    fn upper_bound(&self, code:u128) -> usize {
+        self.elements.partition_point(|(c,_)| *c <= code)
+    }
+
+    /// returns the position of the first element having a code greater or equal than code
+    fn lower_bound(&self, code:u128) -> usize {
+        self.elements.partition_point(|(c,_)| *c < code)
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ParetoFront<T, Elt> for MortonFront<T, Elt, NB_DIM>
+where T:Ord+Copy+Into<u64>, Elt:ParetoElement<T> {
+    fn find_dominating(&self, elt:&Elt) -> Option<&Elt> {
+        let end = self.upper_bound(Self::morton_code(elt));
+        self.elements[..end].iter().map(|(_,e)| e).find(|e| e.dominates(elt))
+    }
+
+    fn insert_observed<O>(&mut self, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        let code = Self::morton_code(&elt);
+        let end = self.upper_bound(code);
+        if let Some((_,by)) = self.elements[..end].iter().find(|(_,e)| e.dominates(&elt)) {
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return false;
+        }
+        // remove dominated elements (only in the suffix starting at code)
+        let start = self.lower_bound(code);
+        let mut kept = start;
+        for i in start..self.elements.len() {
+            if elt.dominates(&self.elements[i].1) {
+                observer(FrontEvent::EvictedBy { element:&self.elements[i].1, by:&elt });
+            } else {
+                self.elements.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.elements.truncate(kept);
+        observer(FrontEvent::Inserted(&elt));
+        let pos = self.upper_bound(code);
+        self.elements.insert(pos, (code,elt));
+        true
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> PriorityQueue<T, Elt> for MortonFront<T, Elt, NB_DIM>
+where T:Ord+Copy+Into<u64>, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.iter().min_by_key(|e| e.guide())
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.iter().max_by_key(|e| e.guide())
+    }
+
+    /// guides are not cached (elements are sorted by Morton code), nothing to do
+    fn reprioritize(&mut self) {}
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let pos = self.elements.iter().enumerate()
+            .min_by_key(|(_,(_,e))| e.guide())
+            .map(|(pos,_)| pos)?;
+        Some(self.elements.remove(pos).1)
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let pos = self.elements.iter().enumerate()
+            .max_by_key(|(_,(_,e))| e.guide())
+            .map(|(pos,_)| pos)?;
+        Some(self.elements.remove(pos).1)
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_observed(elt, |_| {})
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.insert_observed(elt, on_evictions(evicted))
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ExactSizeQueue<T, Elt> for MortonFront<T, Elt, NB_DIM>
+where T:Ord+Copy+Into<u64>, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn len(&self) -> usize { self.elements.len() }
+}
+
+impl<T, Elt, const NB_DIM:usize> PeekKQueue<T, Elt> for MortonFront<T, Elt, NB_DIM>
+where T:Ord+Copy+Into<u64>, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        k_best_by_guide(self.iter(), k)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    #[test]
+    fn test_morton_code() {
+        type Front = MortonFront<u32, CartesianParetoElement<2>, 2>;
+        assert_eq!(Front::morton_code(&CartesianParetoElement::new([0,0])), 0);
+        assert_eq!(Front::morton_code(&CartesianParetoElement::new([0,1])), 1);
+        assert_eq!(Front::morton_code(&CartesianParetoElement::new([1,0])), 2);
+        assert_eq!(Front::morton_code(&CartesianParetoElement::new([3,3])), 15);
+    }
+
+    #[test]
+    fn test_inserts() {
+        let mut front = MortonFront::<u32, CartesianParetoElement<2>, 2>::default();
+        assert!(front.insert(CartesianParetoElement::new([5,10])));
+        assert!(front.insert(CartesianParetoElement::new([10,5])));
+        assert!(!front.insert(CartesianParetoElement::new([10,10])));
+        assert!(front.insert(CartesianParetoElement::new([4,4])));
+        assert_eq!(front.len(), 1);
+        assert_eq!(front.pop_min(), Some(CartesianParetoElement::new([4,4])));
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let front = MortonFront::<u32, CartesianParetoElement<2>, 2>::from_vec(vec![
+            CartesianParetoElement::new([10,10]),
+            CartesianParetoElement::new([5,10]),
+            CartesianParetoElement::new([10,5]),
+            CartesianParetoElement::new([2,30]),
+        ]);
+        assert_eq!(front.len(), 3);
+        assert!(front.find_dominating(&CartesianParetoElement::new([10,10])).is_some());
+        assert!(front.find_dominating(&CartesianParetoElement::new([3,9])).is_none());
+    }
+
+    #[test]
+    fn test_many_dimensions() {
+        let mut front = MortonFront::<u32, CartesianParetoElement<5>, 5>::default();
+        assert!(front.insert(CartesianParetoElement::new([1,2,3,4,u32::MAX])));
+        assert!(front.insert(CartesianParetoElement::new([2,1,3,4,u32::MAX])));
+        assert!(!front.insert(CartesianParetoElement::new([2,2,3,4,u32::MAX])));
+        assert!(front.insert(CartesianParetoElement::new([1,1,3,4,5])));
+        assert_eq!(front.len(), 1);
+    }
+}
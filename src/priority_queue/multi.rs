@@ -0,0 +1,420 @@
+use std::fmt;
+
+use crate::priority_queue::{GuidedElement, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+use crate::priority_queue::handle::{Handle, HandleTable};
+
+/// Decides which queue of a [MultiQueue] serves the next pop.
+///
+/// Queues may be added to the multi-queue after the creation of the policy: policies keeping
+/// statistics per queue extend them when they are given more queues.
+pub trait SelectionPolicy {
+    /// returns the index of the queue serving the next pop, among the non-empty ones
+    /// (non_empty\[i\] is true iff the queue i is not empty). Returns None if all are empty
+    fn select(&mut self, non_empty:&[bool]) -> Option<usize>;
+
+    /// reports the reward obtained by popping from the given queue (used by learning policies)
+    fn reward(&mut self, _queue:usize, _reward:f64) {}
+}
+
+/// Selects the queues in turn.
+#[derive(Debug,Clone,Default)]
+pub struct RoundRobin {
+    /// next queue to select
+    next:usize,
+}
+
+impl SelectionPolicy for RoundRobin {
+    fn select(&mut self, non_empty:&[bool]) -> Option<usize> {
+        let n = non_empty.len();
+        let res = (0..n).map(|i| (self.next+i) % n).find(|i| non_empty[*i])?;
+        self.next = (res+1) % n;
+        Some(res)
+    }
+}
+
+/// Selects the queues proportionally to their weights (smooth weighted round-robin).
+///
+/// For instance, weights \[3,1\] select the first queue 3 times out of 4. Queues beyond the
+/// given weights have a weight of 1.
+#[derive(Debug,Clone)]
+pub struct WeightedRoundRobin {
+    /// weight of each queue
+    weights:Vec<f64>,
+    /// current credit of each queue
+    credits:Vec<f64>,
+}
+
+impl WeightedRoundRobin {
+    /// creates the policy given the (non-negative) weight of each queue
+    pub fn new(weights:Vec<f64>) -> Self {
+        let credits = vec![0.;weights.len()];
+        Self { weights, credits }
+    }
+
+    /// gives a weight of 1 to the queues beyond the known ones
+    fn extend(&mut self, nb_queues:usize) {
+        if nb_queues <= self.weights.len() { return; }
+        self.weights.resize(nb_queues, 1.);
+        self.credits.resize(nb_queues, 0.);
+    }
+}
+
+impl SelectionPolicy for WeightedRoundRobin {
+    fn select(&mut self, non_empty:&[bool]) -> Option<usize> {
+        self.extend(non_empty.len());
+        let mut total = 0.;
+        for i in (0..non_empty.len()).filter(|i| non_empty[*i]) {
+            self.credits[i] += self.weights[i];
+            total += self.weights[i];
+        }
+        let res = (0..non_empty.len()).filter(|i| non_empty[*i])
+            .max_by(|a,b| self.credits[*a].total_cmp(&self.credits[*b]).then(b.cmp(a)))?;
+        self.credits[res] -= total;
+        Some(res)
+    }
+}
+
+/// Bandit policy (UCB1): selects the queue maximizing its mean reward plus an exploration
+/// bonus. Rewards are reported with [SelectionPolicy::reward] (for instance 1 if the popped
+/// node improved the incumbent solution, 0 otherwise). Queues beyond the given number of queues
+/// start as never selected.
+#[derive(Debug,Clone)]
+pub struct Ucb1 {
+    /// number of selections of each queue
+    counts:Vec<u64>,
+    /// sum of the rewards of each queue
+    sums:Vec<f64>,
+    /// exploration coefficient (sqrt(2) in the standard UCB1)
+    exploration:f64,
+}
+
+impl Ucb1 {
+    /// creates the policy for nb_queues queues with the standard exploration coefficient
+    pub fn new(nb_queues:usize) -> Self {
+        Self::with_exploration(nb_queues, std::f64::consts::SQRT_2)
+    }
+
+    /// creates the policy for nb_queues queues with the given exploration coefficient
+    pub fn with_exploration(nb_queues:usize, exploration:f64) -> Self {
+        Self { counts:vec![0;nb_queues], sums:vec![0.;nb_queues], exploration }
+    }
+
+    /// returns the mean reward of the queue (0 if it was never selected)
+    pub fn mean_reward(&self, queue:usize) -> f64 {
+        match self.counts.get(queue) {
+            None | Some(0) => 0.,
+            Some(count) => self.sums[queue] / *count as f64,
+        }
+    }
+
+    /// adds the queues beyond the known ones (never selected)
+    fn extend(&mut self, nb_queues:usize) {
+        if nb_queues <= self.counts.len() { return; }
+        self.counts.resize(nb_queues, 0);
+        self.sums.resize(nb_queues, 0.);
+    }
+}
+
+impl SelectionPolicy for Ucb1 {
+    fn select(&mut self, non_empty:&[bool]) -> Option<usize> {
+        self.extend(non_empty.len());
+        let available = (0..non_empty.len()).filter(|i| non_empty[*i]);
+        let total = self.counts.iter().sum::<u64>().max(1) as f64;
+        let score = |i:usize| {
+            if self.counts[i] == 0 { return f64::INFINITY; }
+            self.mean_reward(i) + self.exploration * (total.ln() / self.counts[i] as f64).sqrt()
+        };
+        let res = available.max_by(|a,b| score(*a).total_cmp(&score(*b)).then(b.cmp(a)))?;
+        self.counts[res] += 1;
+        Some(res)
+    }
+
+    fn reward(&mut self, queue:usize, reward:f64) {
+        self.extend(queue+1);
+        self.sums[queue] += reward;
+    }
+}
+
+
+/// Entry of a queue of the multi-queue: a handle to the element with its guide in this queue.
+#[derive(Debug,Clone)]
+pub struct Entry<T> {
+    /// guide of the element in this queue
+    guide:T,
+    /// handle of the element
+    handle:Handle,
+}
+
+impl<T:Ord+Copy> GuidedElement<T> for Entry<T> {
+    fn guide(&self) -> T { self.guide }
+}
+
+/// guide function of a queue
+type GuideFn<T,Elt> = Box<dyn Fn(&Elt) -> T>;
+
+/// Multi-queue container.
+///
+/// Holds several priority queues over the same elements, each one ordering them with its own
+/// guide (for instance best-bound and most-promising). A selection policy decides which queue
+/// serves the next pop. An element popped from a queue is removed from all the queues.
+/// Elements are stored once; queues store (guide, handle) entries and entries of removed
+/// elements are discarded lazily (and compacted when they become too numerous). The top
+/// entry of each queue always refers to a stored element.
+///
+/// The queues of entries are binary heaps by default, any queue keeping all its elements
+/// may be used instead.
+/// - insert: O(q.log(n)) where q is the number of queues
+/// - pop: O(log n) amortized
+pub struct MultiQueue<T,Elt,P,Q=BinaryHeapQueue<T,Entry<T>>> {
+    /// elements with their handle (None if the slot is free)
+    elements:Vec<Option<(Handle,Elt)>>,
+    /// free slots
+    free:Vec<usize>,
+    /// maps handles to slots
+    table:HandleTable,
+    /// guide function of each queue
+    guides:Vec<GuideFn<T,Elt>>,
+    /// queues of entries
+    queues:Vec<Q>,
+    /// number of entries referring to removed elements
+    nb_tombstones:usize,
+    /// selection policy
+    policy:P,
+    /// queue that served the last pop
+    last_queue:Option<usize>,
+}
+
+impl<T,Elt,P:fmt::Debug,Q> fmt::Debug for MultiQueue<T,Elt,P,Q> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiQueue")
+            .field("nb_elements", &self.table.len())
+            .field("nb_queues", &self.guides.len())
+            .field("nb_tombstones", &self.nb_tombstones)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<T,Elt,P,Q> MultiQueue<T,Elt,P,Q>
+where T:Ord+Copy, P:SelectionPolicy, Q:PriorityQueue<T,Entry<T>>+Default {
+    /// creates a multi-queue with the given selection policy (and no queue)
+    pub fn new(policy:P) -> Self {
+        Self {
+            elements:Vec::new(), free:Vec::new(), table:HandleTable::default(),
+            guides:Vec::new(), queues:Vec::new(), nb_tombstones:0, policy, last_queue:None,
+        }
+    }
+
+    /// adds a queue ordered by the given guide function (the stored elements are added to
+    /// it). Returns the index of the queue
+    pub fn add_queue<G:Fn(&Elt)->T+'static>(&mut self, guide:G) -> usize {
+        let mut queue = Q::default();
+        for (handle,e) in self.elements.iter().flatten() {
+            queue.insert(Entry { guide:guide(e), handle:*handle });
+        }
+        self.queues.push(queue);
+        self.guides.push(Box::new(guide));
+        self.queues.len()-1
+    }
+
+    /// returns the number of queues
+    pub fn nb_queues(&self) -> usize { self.queues.len() }
+
+    /// returns the number of elements
+    pub fn len(&self) -> usize { self.table.len() }
+
+    /// returns true iff the multi-queue contains no element
+    pub fn is_empty(&self) -> bool { self.table.is_empty() }
+
+    /// returns the selection policy
+    pub fn policy(&self) -> &P { &self.policy }
+
+    /// returns the queue that served the last pop
+    pub fn last_queue(&self) -> Option<usize> { self.last_queue }
+
+    /// returns true iff the handle refers to a stored element
+    pub fn contains(&self, h:Handle) -> bool { self.table.is_valid(h) }
+
+    /// returns the element referred by the handle
+    pub fn get(&self, h:Handle) -> Option<&Elt> {
+        self.table.position(h).and_then(|pos| self.elements[pos].as_ref()).map(|(_,e)| e)
+    }
+
+    /// inserts an element in all the queues. Returns a handle to it
+    pub fn insert(&mut self, elt:Elt) -> Handle {
+        let pos = self.free.pop().unwrap_or(self.elements.len());
+        let handle = self.table.create(pos);
+        for (queue,guide) in self.queues.iter_mut().zip(self.guides.iter()) {
+            queue.insert(Entry { guide:guide(&elt), handle });
+        }
+        if pos == self.elements.len() { self.elements.push(Some((handle,elt))); }
+        else { self.elements[pos] = Some((handle,elt)); }
+        handle
+    }
+
+    /// removes the element from all the queues. Returns it if it was stored
+    pub fn remove(&mut self, h:Handle) -> Option<Elt> {
+        let pos = self.table.invalidate(h)?;
+        self.nb_tombstones += self.queues.len();
+        self.free.push(pos);
+        let res = self.elements[pos].take().map(|(_,e)| e);
+        self.discard_tombstones();
+        res
+    }
+
+    /// returns the minimum element of the given queue
+    pub fn peek_from(&self, queue:usize) -> Option<&Elt> {
+        self.queues[queue].peek_min().and_then(|e| self.get(e.handle))
+    }
+
+    /// pops the minimum element of the given queue (it is removed from all the queues)
+    pub fn pop_from(&mut self, queue:usize) -> Option<Elt> {
+        let handle = self.queues[queue].peek_min()?.handle;
+        self.last_queue = Some(queue);
+        self.remove(handle)
+    }
+
+    /// pops an element from the queue chosen by the selection policy
+    pub fn pop(&mut self) -> Option<Elt> {
+        let non_empty:Vec<bool> = self.queues.iter().map(|q| !q.is_empty()).collect();
+        let queue = self.policy.select(&non_empty)?;
+        self.pop_from(queue)
+    }
+
+    /// reports a reward for the queue that served the last pop (see [SelectionPolicy::reward])
+    pub fn reward(&mut self, reward:f64) {
+        if let Some(queue) = self.last_queue {
+            self.policy.reward(queue, reward);
+        }
+    }
+
+    /// discards the entries of removed elements at the top of the queues, and compacts the
+    /// queues if these entries represent more than half the entries
+    fn discard_tombstones(&mut self) {
+        for queue in self.queues.iter_mut() {
+            while queue.peek_min().is_some_and(|e| !self.table.is_valid(e.handle)) {
+                queue.pop_min();
+                self.nb_tombstones -= 1;
+            }
+        }
+        // each queue holds an entry per stored element, plus its tombstones
+        let nb_entries = self.queues.len()*self.table.len() + self.nb_tombstones;
+        if 2*self.nb_tombstones <= nb_entries { return; }
+        for queue in self.queues.iter_mut() {
+            let entries:Vec<Entry<T>> = std::iter::from_fn(|| queue.pop_min())
+                .filter(|e| self.table.is_valid(e.handle))
+                .collect();
+            for e in entries { queue.insert(e); }
+        }
+        self.nb_tombstones = 0;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// element with two criteria
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    struct Node { bound:u32, depth:u32 }
+
+    /// builds a multi-queue with a best-bound queue (0) and a depth-first queue (1)
+    fn build<P:SelectionPolicy>(policy:P) -> MultiQueue<u32,Node,P> {
+        let mut multi = MultiQueue::new(policy);
+        multi.add_queue(|n:&Node| n.bound);
+        for i in 0..10 { multi.insert(Node { bound:i, depth:i%4 }); }
+        multi.add_queue(|n:&Node| 100-n.depth);
+        multi
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut multi = build(RoundRobin::default());
+        assert_eq!(multi.pop(), Some(Node { bound:0, depth:0 }));
+        assert_eq!(multi.pop().map(|n| n.depth), Some(3));
+        assert_eq!(multi.pop(), Some(Node { bound:1, depth:1 }));
+        assert_eq!(multi.last_queue(), Some(0));
+        assert_eq!(multi.pop().map(|n| n.depth), Some(3));
+        assert_eq!(multi.len(), 6);
+        let mut bounds:Vec<u32> = std::iter::from_fn(|| multi.pop()).map(|n| n.bound).collect();
+        bounds.sort();
+        assert_eq!(bounds, vec![2,4,5,6,8,9]);
+        assert!(multi.pop().is_none());
+    }
+
+    #[test]
+    fn test_membership() {
+        let mut multi = build(RoundRobin::default());
+        let h = multi.insert(Node { bound:20, depth:50 });
+        assert_eq!(multi.peek_from(1), Some(&Node { bound:20, depth:50 }));
+        assert_eq!(multi.remove(h), Some(Node { bound:20, depth:50 }));
+        assert!(!multi.contains(h));
+        assert_eq!(multi.peek_from(1).map(|n| n.depth), Some(3));
+        for _ in 0..10 { assert!(multi.pop_from(1).is_some()); }
+        assert!(multi.pop_from(0).is_none());
+        assert!(multi.is_empty());
+    }
+
+    #[test]
+    fn test_other_queues() {
+        use crate::priority_queue::pairing_heap::PairingHeap;
+        let mut multi:MultiQueue<u32,Node,RoundRobin,PairingHeap<u32,Entry<u32>>> =
+            MultiQueue::new(RoundRobin::default());
+        multi.add_queue(|n:&Node| n.bound);
+        multi.add_queue(|n:&Node| 100-n.depth);
+        let handles:Vec<Handle> = (0..20).map(|i| multi.insert(Node { bound:i, depth:i%4 })).collect();
+        // removes enough elements to compact the queues
+        for h in handles.iter().skip(1).step_by(2) { assert!(multi.remove(*h).is_some()); }
+        assert_eq!(multi.len(), 10);
+        assert_eq!(multi.pop_from(0), Some(Node { bound:0, depth:0 }));
+        let popped = multi.pop_from(1).unwrap();
+        assert_eq!(popped.depth, 2);
+        let mut bounds:Vec<u32> = std::iter::from_fn(|| multi.pop()).map(|n| n.bound).collect();
+        bounds.push(popped.bound);
+        bounds.sort();
+        assert_eq!(bounds, (1..10).map(|i| 2*i).collect::<Vec<u32>>());
+        assert!(multi.is_empty());
+    }
+
+    #[test]
+    fn test_weighted_round_robin() {
+        let mut policy = WeightedRoundRobin::new(vec![3.,1.]);
+        let selected:Vec<usize> = (0..8).map(|_| policy.select(&[true,true]).unwrap()).collect();
+        assert_eq!(selected.iter().filter(|q| **q == 0).count(), 6);
+        assert_eq!(policy.select(&[false,true]), Some(1));
+        assert_eq!(policy.select(&[false,false]), None);
+    }
+
+    #[test]
+    fn test_ucb1() {
+        let mut policy = Ucb1::new(2);
+        let mut counts = [0;2];
+        for _ in 0..100 {
+            let q = policy.select(&[true,true]).unwrap();
+            counts[q] += 1;
+            policy.reward(q, if q == 1 { 1. } else { 0. });
+        }
+        assert!(counts[1] > counts[0]);
+        assert!(counts[0] > 0);
+    }
+
+    #[test]
+    fn test_policies_extended() {
+        let mut weighted = build(WeightedRoundRobin::new(vec![2.]));
+        weighted.add_queue(|n:&Node| n.depth);
+        assert_eq!(weighted.nb_queues(), 3);
+        assert_eq!(std::iter::from_fn(|| weighted.pop()).count(), 10);
+        let mut bandit = build(Ucb1::new(1));
+        let mut selected = [false;2];
+        for _ in 0..4 {
+            assert!(bandit.pop().is_some());
+            selected[bandit.last_queue().unwrap()] = true;
+            bandit.reward(1.);
+        }
+        assert_eq!(selected, [true,true]);
+        assert!(bandit.policy().mean_reward(1) > 0.);
+        assert_eq!(bandit.policy().mean_reward(5), 0.);
+    }
+}
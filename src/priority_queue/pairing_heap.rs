@@ -0,0 +1,370 @@
+use crate::priority_queue::{best_first_k, ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::handle::{Handle, HandleTable};
+
+/// node of the pairing heap (stored in an arena)
+#[derive(Debug,Clone)]
+struct Node<T,Elt> {
+    /// cached guide of the element
+    guide:T,
+    /// element of the node (None if the node is free)
+    elt:Option<Elt>,
+    /// first child
+    child:Option<usize>,
+    /// next sibling
+    next:Option<usize>,
+    /// previous sibling (or parent if the node is the first child)
+    prev:Option<usize>,
+    /// handle referring to the node
+    handle:Handle,
+}
+
+/// Pairing heap priority queue.
+///
+/// Each insertion returns a stable handle that can be used to access, update (decrease-key)
+/// or remove the element. Nodes are stored in an arena to avoid an allocation per element.
+/// Guides are computed once at insertion and cached.
+/// - insertion: O(1)
+/// - peek_min: O(1)
+/// - pop_min: O(log n) amortized
+/// - decrease_key: o(log n) amortized
+/// - peek_max/pop_max: O(n)
+#[derive(Debug,Clone)]
+pub struct PairingHeap<T,Elt> {
+    /// arena of nodes
+    nodes:Vec<Node<T,Elt>>,
+    /// free positions in the arena
+    free:Vec<usize>,
+    /// root of the heap
+    root:Option<usize>,
+    /// maps handles to positions in the arena
+    table:HandleTable,
+}
+
+impl<T,Elt> Default for PairingHeap<T,Elt> {
+    fn default() -> Self {
+        Self { nodes:Vec::new(), free:Vec::new(), root:None, table:HandleTable::default() }
+    }
+}
+
+impl<T,Elt> PairingHeap<T,Elt> {
+    /// returns the number of elements in the heap
+    pub fn len(&self) -> usize { self.nodes.len() - self.free.len() }
+
+    /// returns true iff the heap is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+
+    /// returns the element referred by the handle (None if it is no longer stored)
+    pub fn get(&self, h:Handle) -> Option<&Elt> {
+        self.table.position(h).and_then(|pos| self.nodes[pos].elt.as_ref())
+    }
+
+    /// returns true iff the handle refers to a stored element
+    pub fn contains_handle(&self, h:Handle) -> bool { self.table.is_valid(h) }
+
+    /// returns an iterator over the elements of the heap (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> {
+        self.nodes.iter().filter_map(|n| n.elt.as_ref())
+    }
+}
+
+impl<T,Elt> PairingHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// inserts an element and returns a stable handle to it
+    pub fn insert_with_handle(&mut self, elt:Elt) -> Handle {
+        let pos = self.free.pop().unwrap_or(self.nodes.len());
+        let node = Node {
+            guide:elt.guide(), elt:Some(elt), child:None, next:None, prev:None,
+            handle:self.table.create(pos),
+        };
+        if pos == self.nodes.len() { self.nodes.push(node); }
+        else { self.nodes[pos] = node; }
+        self.root = self.meld_roots(self.root, Some(pos));
+        self.nodes[pos].handle
+    }
+
+    /// replaces the element referred by the handle, and restores the heap order.
+    ///
+    /// The new element is expected to have a lower or equal guide (decrease-key),
+    /// but greater guides are also supported.
+    /// Returns false (and drops the element) if the handle is no longer valid.
+    pub fn decrease_key(&mut self, h:Handle, elt:Elt) -> bool {
+        let pos = match self.table.position(h) {
+            None => return false,
+            Some(pos) => pos,
+        };
+        let previous_guide = self.nodes[pos].guide;
+        self.nodes[pos].guide = elt.guide();
+        self.nodes[pos].elt = Some(elt);
+        if self.nodes[pos].guide <= previous_guide {
+            if self.root != Some(pos) { // cut the sub-tree and merge it with the root
+                self.cut(pos);
+                self.root = self.meld_roots(self.root, Some(pos));
+            }
+        } else { // key increase: detach the node from its children and reinsert it
+            let children = self.detach(pos);
+            let rest = self.meld_roots(self.root, children);
+            self.root = self.meld_roots(rest, Some(pos));
+        }
+        true
+    }
+
+    /// restores the heap order after the guide of the element referred by the handle changed.
+    /// Returns false if the handle is no longer valid
+    pub fn update(&mut self, h:Handle) -> bool {
+        match self.table.position(h).and_then(|pos| self.nodes[pos].elt.take()) {
+            None => false,
+            Some(elt) => self.decrease_key(h, elt),
+        }
+    }
+
+    /// removes the element referred by the handle. Returns it if it was still stored
+    pub fn remove_by_handle(&mut self, h:Handle) -> Option<Elt> {
+        let pos = self.table.position(h)?;
+        let children = self.detach(pos);
+        self.root = self.meld_roots(self.root, children);
+        Some(self.free_node(pos))
+    }
+
+    /// detaches the node from its parent (if any) and from its children.
+    /// Returns the merged children
+    fn detach(&mut self, pos:usize) -> Option<usize> {
+        if self.root == Some(pos) { self.root = None; }
+        else { self.cut(pos); }
+        let first_child = self.nodes[pos].child.take();
+        self.merge_pairs(first_child)
+    }
+
+    /// removes the node from its siblings and its parent
+    fn cut(&mut self, pos:usize) {
+        let (prev, next) = (self.nodes[pos].prev.take(), self.nodes[pos].next.take());
+        if let Some(p) = prev {
+            if self.nodes[p].child == Some(pos) { self.nodes[p].child = next; }
+            else { self.nodes[p].next = next; }
+        }
+        if let Some(n) = next { self.nodes[n].prev = prev; }
+    }
+
+    /// links two detached roots. Returns the new root
+    fn link(&mut self, a:usize, b:usize) -> usize {
+        let (parent, child) = if self.nodes[b].guide < self.nodes[a].guide { (b,a) } else { (a,b) };
+        let first_child = self.nodes[parent].child;
+        self.nodes[child].next = first_child;
+        if let Some(c) = first_child { self.nodes[c].prev = Some(child); }
+        self.nodes[child].prev = Some(parent);
+        self.nodes[parent].child = Some(child);
+        parent
+    }
+
+    /// melds two (possibly empty) detached roots
+    fn meld_roots(&mut self, a:Option<usize>, b:Option<usize>) -> Option<usize> {
+        match (a,b) {
+            (Some(x), Some(y)) => Some(self.link(x,y)),
+            (x, None) | (None, x) => x,
+        }
+    }
+
+    /// two-pass pairing of a list of siblings. Returns the resulting root
+    fn merge_pairs(&mut self, first:Option<usize>) -> Option<usize> {
+        let mut siblings = Vec::new();
+        let mut current = first;
+        while let Some(c) = current {
+            current = self.nodes[c].next.take();
+            self.nodes[c].prev = None;
+            siblings.push(c);
+        }
+        // first pass: link pairs from left to right
+        let mut pairs = Vec::with_capacity(siblings.len().div_ceil(2));
+        for chunk in siblings.chunks(2) {
+            pairs.push(if chunk.len() == 2 { self.link(chunk[0], chunk[1]) } else { chunk[0] });
+        }
+        // second pass: link the pairs from right to left
+        let mut res = pairs.pop();
+        while let Some(p) = pairs.pop() {
+            res = Some(self.link(p, res.unwrap()));
+        }
+        res
+    }
+
+    /// frees the node and returns its element
+    fn free_node(&mut self, pos:usize) -> Elt {
+        self.table.invalidate(self.nodes[pos].handle);
+        self.free.push(pos);
+        self.nodes[pos].child = None;
+        self.nodes[pos].elt.take().unwrap()
+    }
+
+    /// returns the position of the maximum element
+    fn max_position(&self) -> Option<usize> {
+        (0..self.nodes.len())
+            .filter(|i| self.nodes[*i].elt.is_some())
+            .max_by_key(|i| self.nodes[*i].guide)
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for PairingHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.root.and_then(|r| self.nodes[r].elt.as_ref())
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.max_position().and_then(|i| self.nodes[i].elt.as_ref())
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let root = self.root?;
+        let first_child = self.nodes[root].child.take();
+        self.root = self.merge_pairs(first_child);
+        Some(self.free_node(root))
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let pos = self.max_position()?;
+        self.remove_by_handle(self.nodes[pos].handle)
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_with_handle(elt);
+        true
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.root.map(|r| self.nodes[r].guide)
+    }
+
+    /// recomputes the cached guides, then links all the nodes. O(n)
+    fn reprioritize(&mut self) {
+        self.root = None;
+        for pos in 0..self.nodes.len() {
+            let node = &mut self.nodes[pos];
+            let Some(elt) = &node.elt else { continue; };
+            node.guide = elt.guide();
+            node.child = None;
+            node.next = None;
+            node.prev = None;
+            self.root = self.meld_roots(self.root, Some(pos));
+        }
+    }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for PairingHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    fn len(&self) -> usize { self.nodes.len() - self.free.len() }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for PairingHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T> {
+    /// best-first search over the heap-ordered tree. O(k.log(k)) + children scans
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        best_first_k(self.root, k, |i| self.nodes[*i].guide, |i, res| {
+            let mut child = self.nodes[*i].child;
+            while let Some(c) = child {
+                res.push(c);
+                child = self.nodes[c].next;
+            }
+        }, |i| self.nodes[*i].elt.as_ref())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::priority_queue::fixtures::Tunable;
+
+    /// element (id, guide)
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    struct Label(usize,u32);
+
+    impl GuidedElement<u32> for Label {
+        fn guide(&self) -> u32 { self.1 }
+    }
+
+    #[test]
+    fn test_pop_min() {
+        let mut heap = PairingHeap::default();
+        for i in 0..50 { heap.insert(Label(i, ((i*37+11) % 50) as u32)); }
+        for g in 0..50 { assert_eq!(heap.pop_min().map(|l| l.1), Some(g)); }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_peek_k() {
+        let mut heap = PairingHeap::default();
+        for i in 0..50 { heap.insert(Label(i, ((i*37+11) % 50) as u32)); }
+        heap.pop_min(); // builds a deeper tree
+        let guides:Vec<u32> = heap.peek_k(6).into_iter().map(|l| l.1).collect();
+        assert_eq!(guides, vec![1,2,3,4,5,6]);
+    }
+
+    #[test]
+    fn test_pop_random_minimum() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut seen = [false;3];
+        for _ in 0..30 {
+            let mut heap = PairingHeap::default();
+            for i in 0..6 { heap.insert(Label(i, if i < 3 { 1 } else { i as u32 })); }
+            let label = heap.pop_random_minimum(&mut rng).unwrap();
+            assert_eq!(label.1, 1);
+            seen[label.0] = true;
+            assert_eq!(heap.len(), 5);
+            assert_eq!(heap.peek_min_guide(), Some(1));
+        }
+        assert_eq!(seen, [true;3]);
+        let mut heap = PairingHeap::default();
+        for i in 0..6 { heap.insert(Label(i, i as u32)); }
+        assert!(heap.pop_random_within(&mut rng, 2).is_some_and(|l| l.1 <= 2));
+        assert_eq!(heap.len(), 5);
+    }
+
+    #[test]
+    fn test_update_and_reprioritize() {
+        let cells:Vec<Rc<Cell<u32>>> = (0..20).map(|i| Rc::new(Cell::new(10+i))).collect();
+        let mut heap = PairingHeap::default();
+        let handles:Vec<Handle> = cells.iter().map(|c| heap.insert_with_handle(Tunable(c.clone()))).collect();
+        heap.pop_min();
+        cells[7].set(3);
+        assert!(heap.update(handles[7]));
+        assert_eq!(heap.peek_min_guide(), Some(3));
+        cells[7].set(100);
+        assert!(heap.update(handles[7]));
+        assert!(!heap.update(handles[0]));
+        for (i,c) in cells.iter().enumerate() { c.set(50 - i as u32); }
+        heap.reprioritize();
+        let guides:Vec<u32> = heap.drain_sorted().map(|e| e.guide()).collect();
+        assert_eq!(guides, (31..=49).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_decrease_key() {
+        let mut heap = PairingHeap::default();
+        let handles:Vec<Handle> = (0..10).map(|i| heap.insert_with_handle(Label(i, 100+i as u32))).collect();
+        assert!(heap.decrease_key(handles[7], Label(7, 5)));
+        assert!(heap.decrease_key(handles[3], Label(3, 50)));
+        assert!(heap.decrease_key(handles[0], Label(0, 200))); // key increase
+        assert_eq!(heap.get(handles[3]), Some(&Label(3,50)));
+        assert_eq!(heap.pop_min(), Some(Label(7,5)));
+        assert!(!heap.contains_handle(handles[7]));
+        assert!(!heap.decrease_key(handles[7], Label(7, 1)));
+        assert_eq!(heap.pop_min(), Some(Label(3,50)));
+        assert_eq!(heap.pop_max(), Some(Label(0,200)));
+        assert_eq!(heap.len(), 7);
+    }
+
+    #[test]
+    fn test_remove_by_handle() {
+        let mut heap = PairingHeap::default();
+        let handles:Vec<Handle> = (0..10).map(|i| heap.insert_with_handle(Label(i, i as u32))).collect();
+        heap.pop_min(); // restructures the heap
+        assert_eq!(heap.remove_by_handle(handles[4]), Some(Label(4,4)));
+        assert_eq!(heap.remove_by_handle(handles[4]), None);
+        assert_eq!(heap.remove_by_handle(handles[1]), Some(Label(1,1)));
+        let remaining:Vec<u32> = std::iter::from_fn(|| heap.pop_min()).map(|l| l.1).collect();
+        assert_eq!(remaining, vec![2,3,5,6,7,8,9]);
+    }
+}
@@ -1,51 +1,112 @@
+use std::cmp::Reverse;
 use std::marker::PhantomData;
 
-use crate::priority_queue::{GuidedElement, ParetoElement, PriorityQueue};
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue};
 
-use super::ParetoFront;
+use super::{filter_dominated, on_evictions, FrontEvent, ParetoFront};
+use super::handle::{Handle, HandleTable};
 
 /// Simple pareto front that stores element in a list
+///
+/// Elements are sorted by decreasing guide (ties are popped in insertion order):
+/// - peek_min, peek_max, pop_min: O(1)
+/// - pop_max: O(n)
+/// - insertion: O(n) (dominance checks), the position is found by binary search
+///
+/// Elements inserted through [ListParetoFront::insert_observed] (which does not require guided
+/// elements) are appended to the list, and sorted by the next pop or insertion of the
+/// priority queue (peeks scan the list until then).
+///
+/// By default, each insertion checks dominance against the whole list (O(n)). In lazy mode
+/// (see [ListParetoFront::with_lazy_dominance]), elements are appended unchecked, and dominated
+/// elements are removed by a compaction when the list grows by some factor, or before a pop.
+///
+/// Elements may be inserted with a stable handle ([ListParetoFront::insert_with_handle]), to
+/// be accessed ([ListParetoFront::get]) or removed ([ListParetoFront::remove_by_handle]) later.
+/// Handles of the elements evicted by dominance are invalidated. Only this front provides
+/// handles: the tree-based fronts (kd-tree, r-tree, Morton, range tree) move their elements
+/// when they are rebalanced or rebuilt. They may be wrapped in a
+/// [LazyDeletionQueue](crate::priority_queue::lazy::LazyDeletionQueue) to remove elements by
+/// handle.
 #[derive(Debug)]
 pub struct ListParetoFront<T,Elt> {
+    /// elements sorted by decreasing guide (up to sorted_len)
     elements:Vec<Elt>,
+    /// handles\[i\] is the handle of elements\[i\]
+    handles:Vec<Handle>,
+    /// maps handles to positions in the list
+    table:HandleTable,
+    /// number of elements (prefix of the list) sorted by decreasing guide. The next ones are
+    /// in insertion order
+    sorted_len:usize,
+    /// growth factor triggering a compaction (None if dominance is checked at insertion)
+    growth_factor:Option<f64>,
+    /// number of elements after the last compaction
+    compacted_len:usize,
+    /// number of elements inserted unchecked since the last compaction
+    nb_unchecked:usize,
     phantom_t:PhantomData<T>,
 }
 
 impl<T,Elt> PriorityQueue<T,Elt> for ListParetoFront<T,Elt>
 where T:Ord, Elt:ParetoElement<T>+GuidedElement<T> {
     fn peek_min(&self) -> Option<&Elt> {
-        self.elements.iter().min_by_key(|e| e.guide())
+        if self.is_sorted() { self.elements.last() } else { self.by_age().min_by_key(|e| e.guide()) }
     }
 
     fn peek_max(&self) -> Option<&Elt> {
-        self.elements.iter().max_by_key(|e| e.guide())
+        if self.is_sorted() { self.elements.first() } else { self.by_age().max_by_key(|e| e.guide()) }
+    }
+
+    /// sorts the list again by guide
+    fn reprioritize(&mut self) {
+        let mut entries:Vec<(Handle,Elt)> = self.handles.drain(..)
+            .zip(self.elements.drain(..))
+            .collect();
+        entries.sort_by_cached_key(|e| Reverse(e.1.guide()));
+        self.set_entries(entries);
+        self.sorted_len = self.elements.len();
     }
 
     fn pop_min(&mut self) -> Option<Elt> {
-        if self.elements.is_empty() { return None; }
-        let min_pos = self.elements.iter().enumerate()
-            .min_by_key(|(_,elt)| elt.guide())
-            .map(|(pos,_)| pos).unwrap();
-        Some(self.elements.swap_remove(min_pos))
+        self.compact();
+        self.sort_appended();
+        let h = self.handles.pop()?;
+        self.table.invalidate(h);
+        self.sorted_len -= 1;
+        self.elements.pop()
     }
 
     fn pop_max(&mut self) -> Option<Elt> {
+        self.compact();
+        self.sort_appended();
         if self.elements.is_empty() { return None; }
-        let min_pos = self.elements.iter().enumerate()
-            .max_by_key(|(_,elt)| elt.guide())
-            .map(|(pos,_)| pos).unwrap();
-        Some(self.elements.swap_remove(min_pos))
+        Some(self.remove_at(0))
     }
 
     fn insert(&mut self, elt:Elt) -> bool {
-        match self.find_dominating(&elt) {
-            None => { // if the current element is not dominated, remove the ones dominated by it
-                self.elements.retain(|e| !elt.dominates(e) );
-                self.elements.push(elt);
-                true
-            },
-            Some(_) => false // do not insert as we found some dominating element
-        }
+        self.insert_with_handle(elt).is_some()
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.sort_appended();
+        self.insert_placed(elt, on_evictions(evicted), Self::insert_sorted).is_some()
+    }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for ListParetoFront<T,Elt>
+where T:Ord, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn len(&self) -> usize { self.elements.len() }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for ListParetoFront<T,Elt>
+where T:Ord, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        if self.is_sorted() { return self.elements.iter().rev().take(k).collect(); }
+        let mut res:Vec<&Elt> = self.by_age().collect();
+        res.sort_by_cached_key(|e| e.guide());
+        res.truncate(k);
+        res
     }
 }
 
@@ -54,28 +115,445 @@ where T:Ord, Elt:ParetoElement<T> {
     fn find_dominating(&self, elt:&Elt) -> Option<&Elt> {
         self.elements.iter().find(|e| e.dominates(elt))
     }
+
+    fn insert_observed<O>(&mut self, elt:Elt, observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        self.insert_observed_with_handle(elt, observer).is_some()
+    }
+}
+
+
+impl<T,Elt> ListParetoFront<T,Elt> {
+    /// creates an empty front in lazy mode: inserted elements are not checked for dominance,
+    /// and the front is compacted when its size exceeds growth_factor times its size after the
+    /// last compaction, or before a pop.
+    ///
+    /// Until the next compaction, peeks, iterations and len may include dominated elements, and
+    /// handles of inserted elements may be invalidated by the compaction.
+    pub fn with_lazy_dominance(growth_factor:f64) -> Self {
+        Self { growth_factor:Some(growth_factor), ..Self::default() }
+    }
+
+    /// returns true iff the front contains no element inserted unchecked (lazy mode)
+    pub fn is_compact(&self) -> bool { self.nb_unchecked == 0 }
+
+    /// returns an iterator over the elements of the front (by decreasing guide, followed by
+    /// the elements appended by [ListParetoFront::insert_observed] since the last sort)
+    pub fn iter(&self) -> std::slice::Iter<'_, Elt> { self.elements.iter() }
+
+    /// returns the number of elements in the front
+    pub fn len(&self) -> usize { self.elements.len() }
+
+    /// returns true iff the front is empty
+    pub fn is_empty(&self) -> bool { self.elements.is_empty() }
+
+    /// returns the element referred by the handle (None if it is no longer stored)
+    pub fn get(&self, h:Handle) -> Option<&Elt> {
+        self.table.position(h).map(|pos| &self.elements[pos])
+    }
+
+    /// returns true iff the handle refers to a stored element
+    pub fn contains_handle(&self, h:Handle) -> bool { self.table.is_valid(h) }
+
+    /// removes the element referred by the handle. Returns it if it was still stored
+    pub fn remove_by_handle(&mut self, h:Handle) -> Option<Elt> {
+        let pos = self.table.position(h)?;
+        Some(self.remove_at(pos))
+    }
+
+    /// true iff all the elements are sorted by guide
+    fn is_sorted(&self) -> bool { self.sorted_len == self.elements.len() }
+
+    /// iterates over the elements from the oldest to the newest among equal guides of the
+    /// sorted part (then the appended elements, in insertion order)
+    fn by_age(&self) -> impl Iterator<Item=&Elt> {
+        let (sorted, appended) = self.elements.split_at(self.sorted_len);
+        sorted.iter().rev().chain(appended.iter())
+    }
+
+    /// updates the positions of the handles of the elements located after from
+    fn update_positions(&mut self, from:usize) {
+        for (pos,h) in self.handles.iter().enumerate().skip(from) {
+            self.table.set_position(*h, pos);
+        }
+    }
+
+    /// replaces the content of the list by the entries (their order is kept)
+    fn set_entries(&mut self, entries:Vec<(Handle,Elt)>) {
+        for (h,e) in entries {
+            self.handles.push(h);
+            self.elements.push(e);
+        }
+        self.update_positions(0);
+    }
+
+    /// removes the element at the given position, and keeps the handle table up to date
+    fn remove_at(&mut self, pos:usize) -> Elt {
+        self.table.invalidate(self.handles.remove(pos));
+        if pos < self.sorted_len { self.sorted_len -= 1; }
+        self.update_positions(pos);
+        self.elements.remove(pos)
+    }
+
+    /// appends the element to the list, and returns its handle and position
+    fn append(&mut self, elt:Elt) -> (Handle,usize) {
+        let pos = self.elements.len();
+        let h = self.table.create(pos);
+        self.elements.push(elt);
+        self.handles.push(h);
+        (h,pos)
+    }
+}
+
+impl<T,Elt> ListParetoFront<T,Elt>
+where T:Ord, Elt:ParetoElement<T> {
+    /// removes the dominated elements (and duplicates) inserted unchecked in lazy mode, and
+    /// invalidates their handles. The order of the remaining elements is kept.
+    /// O(n.log(n) + n.f) where f is the size of the front.
+    pub fn compact(&mut self) { self.compact_observed(|_| {}); }
+
+    /// compacts the front (see [ListParetoFront::compact]), and reports the evictions of the
+    /// dominated elements to the observer
+    pub fn compact_observed<O>(&mut self, mut observer:O)
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        if self.nb_unchecked == 0 { return; }
+        // in lexicographic order, an element is usually dominated by previous ones
+        let mut order:Vec<usize> = (0..self.elements.len()).collect();
+        order.sort_by(|a,b| self.elements[*a].coordinates().cmp(self.elements[*b].coordinates()));
+        // dominated_by[i] is an element dominating i (possibly dominated itself)
+        let mut dominated_by:Vec<Option<usize>> = vec![None; self.elements.len()];
+        let mut front:Vec<usize> = Vec::with_capacity(order.len());
+        for i in order {
+            let e = &self.elements[i];
+            if let Some(f) = front.iter().find(|f| self.elements[**f].dominates(e)) {
+                dominated_by[i] = Some(*f);
+                continue;
+            }
+            front.retain(|f| {
+                let dominated = e.dominates(&self.elements[*f]);
+                if dominated { dominated_by[*f] = Some(i); }
+                !dominated
+            });
+            front.push(i);
+        }
+        let kept:Vec<bool> = dominated_by.iter().map(|d| d.is_none()).collect();
+        for (i,d) in dominated_by.iter().enumerate() {
+            let Some(mut by) = *d else { continue; };
+            while let Some(next) = dominated_by[by] { by = next; }
+            observer(FrontEvent::EvictedBy { element:&self.elements[i], by:&self.elements[by] });
+        }
+        let entries = std::mem::take(&mut self.handles).into_iter()
+            .zip(std::mem::take(&mut self.elements))
+            .enumerate();
+        let mut remaining = Vec::with_capacity(kept.len());
+        let mut nb_sorted = 0;
+        for (i,(h,e)) in entries {
+            if !kept[i] {
+                self.table.invalidate(h);
+                continue;
+            }
+            if i < self.sorted_len { nb_sorted += 1; }
+            remaining.push((h,e));
+        }
+        self.set_entries(remaining);
+        self.sorted_len = nb_sorted;
+        self.compacted_len = self.elements.len();
+        self.nb_unchecked = 0;
+    }
+
+    /// inserts an element in the front and reports events to the observer (see
+    /// [ParetoFront::insert_observed], also available for elements that are not guided).
+    /// The element is appended to the list.
+    ///
+    /// returns true iff the element was inserted
+    pub fn insert_observed<O>(&mut self, elt:Elt, observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        self.insert_observed_with_handle(elt, observer).is_some()
+    }
+
+    /// inserts an element in the front, reports events to the observer and returns a
+    /// stable handle to the element if it was inserted. The element is appended to the list
+    /// (it is sorted by guide by the next pop or insertion of the priority queue).
+    pub fn insert_observed_with_handle<O>(&mut self, elt:Elt, observer:O) -> Option<Handle>
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        self.insert_placed(elt, observer, Self::append)
+    }
+
+    /// inserts an element in the front using place to add it to the list (returns its handle
+    /// and position), and reports events to the observer
+    fn insert_placed<O,P>(&mut self, elt:Elt, mut observer:O, place:P) -> Option<Handle>
+    where O:FnMut(FrontEvent<'_,Elt>), P:FnOnce(&mut Self, Elt) -> (Handle,usize) {
+        if let Some(factor) = self.growth_factor {
+            return Some(self.insert_unchecked(elt, factor, observer, place));
+        }
+        if let Some(by) = self.find_dominating(&elt) {
+            // do not insert as we found some dominating element
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return None;
+        }
+        // if the current element is not dominated, remove the ones dominated by it
+        if self.elements.iter().any(|e| elt.dominates(e)) {
+            let entries = std::mem::take(&mut self.handles).into_iter()
+                .zip(std::mem::take(&mut self.elements))
+                .enumerate();
+            let mut nb_sorted = 0;
+            for (i,(h,e)) in entries {
+                if elt.dominates(&e) {
+                    self.table.invalidate(h);
+                    observer(FrontEvent::EvictedBy { element:&e, by:&elt });
+                } else {
+                    if i < self.sorted_len { nb_sorted += 1; }
+                    self.handles.push(h);
+                    self.elements.push(e);
+                }
+            }
+            self.sorted_len = nb_sorted;
+            self.update_positions(0);
+        }
+        let (h,pos) = place(self, elt);
+        observer(FrontEvent::Inserted(&self.elements[pos]));
+        Some(h)
+    }
+
+    /// adds the element without dominance checks (lazy mode), and compacts the front if it
+    /// grew by the given factor since the last compaction
+    fn insert_unchecked<O,P>(&mut self, elt:Elt, factor:f64, mut observer:O, place:P) -> Handle
+    where O:FnMut(FrontEvent<'_,Elt>), P:FnOnce(&mut Self, Elt) -> (Handle,usize) {
+        let (h,pos) = place(self, elt);
+        self.nb_unchecked += 1;
+        observer(FrontEvent::Inserted(&self.elements[pos]));
+        if self.elements.len() as f64 > factor * self.compacted_len.max(1) as f64 {
+            self.compact_observed(observer);
+        }
+        h
+    }
+}
+
+impl<T,Elt> ListParetoFront<T,Elt>
+where T:Ord, Elt:ParetoElement<T>+GuidedElement<T> {
+    /// builds a front from a vector of elements.
+    ///
+    /// Dominated elements are filtered out before building the front.
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let mut elements = filter_dominated(elts);
+        elements.sort_by_cached_key(|e| Reverse(e.guide()));
+        let mut table = HandleTable::default();
+        let handles = (0..elements.len()).map(|pos| table.create(pos)).collect();
+        Self {
+            sorted_len:elements.len(), elements, handles, table, growth_factor:None,
+            compacted_len:0, nb_unchecked:0, phantom_t:PhantomData
+        }
+    }
+
+    /// inserts an element in the front (at its position by guide) and returns a stable
+    /// handle to it.
+    ///
+    /// returns None if the element is dominated (and thus not inserted).
+    /// Handles of elements dominated by the new element are invalidated.
+    pub fn insert_with_handle(&mut self, elt:Elt) -> Option<Handle> {
+        self.sort_appended();
+        self.insert_placed(elt, |_| {}, Self::insert_sorted)
+    }
+
+    /// inserts the element at its position (by binary search on the guides, before the
+    /// elements with equal guides, thus popped after them), and returns its handle and position
+    fn insert_sorted(&mut self, elt:Elt) -> (Handle,usize) {
+        let pos = self.sorted_position(&elt);
+        let h = self.table.create(pos);
+        self.elements.insert(pos, elt);
+        self.handles.insert(pos, h);
+        self.sorted_len += 1;
+        self.update_positions(pos+1);
+        (h,pos)
+    }
+
+    /// returns the position of the element in the (sorted) list, by binary search on the guides
+    fn sorted_position(&self, elt:&Elt) -> usize {
+        let guide = elt.guide();
+        self.elements.partition_point(|e| e.guide() > guide)
+    }
+
+    /// moves the elements appended by [ListParetoFront::insert_observed] to their position by
+    /// guide
+    fn sort_appended(&mut self) {
+        if self.is_sorted() { return; }
+        let appended:Vec<(Handle,Elt)> = self.handles.drain(self.sorted_len..)
+            .zip(self.elements.drain(self.sorted_len..))
+            .collect();
+        for (h,elt) in appended {
+            let pos = self.sorted_position(&elt);
+            self.elements.insert(pos, elt);
+            self.handles.insert(pos, h);
+        }
+        self.sorted_len = self.elements.len();
+        self.update_positions(0);
+    }
 }
 
 
 impl<Elt,T> Default for ListParetoFront<T,Elt> {
     fn default() -> Self {
-        Self { elements: Default::default(), phantom_t:PhantomData::default() }
+        Self {
+            elements: Default::default(),
+            handles: Default::default(),
+            table: Default::default(),
+            sorted_len: 0,
+            growth_factor: None,
+            compacted_len: 0,
+            nb_unchecked: 0,
+            phantom_t:PhantomData
+        }
     }
 }
 
 
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
 
     use crate::priority_queue::util::CartesianParetoElement;
 
     #[test]
-    pub fn test_some_inserts() {
+    fn test_some_inserts() {
         let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::default();
         assert!(front.insert(CartesianParetoElement::<2>::new([1,0])));
         assert!(front.insert(CartesianParetoElement::<2>::new([0,1])));
         assert!(!front.insert(CartesianParetoElement::<2>::new([1,1])));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_handles() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::default();
+        let h1 = front.insert_with_handle(CartesianParetoElement::<2>::new([5,5])).unwrap();
+        let h2 = front.insert_with_handle(CartesianParetoElement::<2>::new([1,8])).unwrap();
+        let h3 = front.insert_with_handle(CartesianParetoElement::<2>::new([8,1])).unwrap();
+        assert!(front.insert_with_handle(CartesianParetoElement::<2>::new([6,6])).is_none());
+        assert_eq!(front.remove_by_handle(h2), Some(CartesianParetoElement::<2>::new([1,8])));
+        assert!(front.get(h2).is_none());
+        assert_eq!(front.get(h3), Some(&CartesianParetoElement::<2>::new([8,1])));
+        // evicts h1 by dominance
+        front.insert(CartesianParetoElement::<2>::new([4,4]));
+        assert!(!front.contains_handle(h1));
+        assert_eq!(front.get(h3), Some(&CartesianParetoElement::<2>::new([8,1])));
+    }
+
+    #[test]
+    fn test_observed_insert() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::default();
+        let (mut inserted, mut rejected, mut evicted) = (0,0,0);
+        let mut observer = |event:FrontEvent<'_,CartesianParetoElement<2>>| match event {
+            FrontEvent::Inserted(_) => inserted += 1,
+            FrontEvent::RejectedDominated { .. } => rejected += 1,
+            FrontEvent::EvictedBy { .. } => evicted += 1,
+        };
+        front.insert_observed(CartesianParetoElement::<2>::new([5,5]), &mut observer);
+        front.insert_observed(CartesianParetoElement::<2>::new([3,7]), &mut observer);
+        front.insert_observed(CartesianParetoElement::<2>::new([6,6]), &mut observer);
+        front.insert_observed(CartesianParetoElement::<2>::new([1,1]), &mut observer);
+        assert_eq!((inserted, rejected, evicted), (3,1,2));
+    }
+
+    #[test]
+    fn test_lazy_dominance() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::with_lazy_dominance(4.);
+        let h1 = front.insert_with_handle(CartesianParetoElement::<2>::new([5,5])).unwrap();
+        assert!(front.insert(CartesianParetoElement::<2>::new([6,6])));
+        assert!(front.insert(CartesianParetoElement::<2>::new([2,8])));
+        assert!(front.insert(CartesianParetoElement::<2>::new([4,4])));
+        assert!(!front.is_compact());
+        assert_eq!(front.len(), 4);
+        assert!(front.find_dominating(&CartesianParetoElement::<2>::new([7,7])).is_some());
+        assert_eq!(front.pop_max(), Some(CartesianParetoElement::<2>::new([2,8])));
+        assert!(front.is_compact());
+        assert!(!front.contains_handle(h1));
+        assert_eq!(front.len(), 1);
+        // growth by a factor 4 since the last compaction (2 elements) triggers a compaction
+        for i in 0..8 { front.insert(CartesianParetoElement::<2>::new([5+i,5+i])); }
+        assert!(front.is_compact());
+        assert_eq!(front.iter().collect::<Vec<_>>(), vec![&CartesianParetoElement::<2>::new([4,4])]);
+    }
+
+    #[test]
+    fn test_sorted_by_guide() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::default();
+        let h = front.insert_with_handle(CartesianParetoElement::<2>::new([4,4])).unwrap();
+        front.insert(CartesianParetoElement::<2>::new([1,9]));
+        front.insert(CartesianParetoElement::<2>::new([9,0]));
+        front.insert(CartesianParetoElement::<2>::new([0,8]));
+        front.insert(CartesianParetoElement::<2>::new([3,5]));
+        assert_eq!(front.peek_min(), Some(&CartesianParetoElement::<2>::new([4,4])));
+        assert_eq!(front.peek_max(), Some(&CartesianParetoElement::<2>::new([9,0])));
+        assert_eq!(front.peek_k(2), vec![
+            &CartesianParetoElement::<2>::new([4,4]), &CartesianParetoElement::<2>::new([0,8])
+        ]);
+        assert_eq!(front.get(h), Some(&CartesianParetoElement::<2>::new([4,4])));
+        assert_eq!(front.pop_max(), Some(CartesianParetoElement::<2>::new([9,0])));
+        assert_eq!(front.get(h), Some(&CartesianParetoElement::<2>::new([4,4])));
+        let guides:Vec<u32> = std::iter::from_fn(|| front.pop_min()).map(|e| e.guide()).collect();
+        assert_eq!(guides, vec![8,8,8]);
+        assert!(!front.contains_handle(h));
+    }
+
+    /// 2D point without guide
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    struct Point([u32;2]);
+
+    impl ParetoElement<u32> for Point {
+        type CoordIterator = std::array::IntoIter<u32,2>;
+        fn coordinates(&self) -> Self::CoordIterator { self.0.into_iter() }
+        fn dominates(&self, other:&Self) -> bool { self.0[0] <= other.0[0] && self.0[1] <= other.0[1] }
+        fn nb_dimensions(&self) -> usize { 2 }
+        fn kth(&self, k:usize) -> u32 { self.0[k] }
+    }
+
+    #[test]
+    fn test_unguided_elements() {
+        let mut front = ListParetoFront::<u32, Point>::default();
+        assert!(front.insert_observed(Point([3,3]), |_| {}));
+        assert!(front.insert_observed(Point([1,5]), |_| {}));
+        assert!(!front.insert_observed(Point([4,4]), |_| {}));
+        assert!(front.insert_observed(Point([2,2]), |_| {}));
+        assert_eq!(front.iter().collect::<Vec<_>>(), vec![&Point([1,5]), &Point([2,2])]);
+        assert!(front.find_dominating(&Point([5,5])).is_some());
+    }
+
+    #[test]
+    fn test_sorted_after_observed_inserts() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::default();
+        front.insert_observed(CartesianParetoElement::<2>::new([4,4]), |_| {});
+        front.insert(CartesianParetoElement::<2>::new([1,9]));
+        front.insert_observed(CartesianParetoElement::<2>::new([9,0]), |_| {});
+        front.insert_observed(CartesianParetoElement::<2>::new([2,6]), |_| {});
+        assert_eq!(front.peek_min(), Some(&CartesianParetoElement::<2>::new([4,4])));
+        assert_eq!(front.peek_max(), Some(&CartesianParetoElement::<2>::new([1,9])));
+        assert_eq!(front.peek_k(2), vec![
+            &CartesianParetoElement::<2>::new([4,4]), &CartesianParetoElement::<2>::new([2,6])
+        ]);
+        let popped:Vec<CartesianParetoElement<2>> = std::iter::from_fn(|| front.pop_min()).collect();
+        assert_eq!(popped, vec![
+            CartesianParetoElement::<2>::new([4,4]), CartesianParetoElement::<2>::new([2,6]),
+            CartesianParetoElement::<2>::new([9,0]), CartesianParetoElement::<2>::new([1,9]),
+        ]);
+    }
+
+    #[test]
+    fn test_lazy_evictions_observed() {
+        let mut front = ListParetoFront::<u32, CartesianParetoElement<2>>::with_lazy_dominance(2.);
+        let mut evictions = Vec::new();
+        let mut observer = |event:FrontEvent<'_,CartesianParetoElement<2>>| {
+            if let FrontEvent::EvictedBy { element, by } = event { evictions.push((element.clone(), by.clone())); }
+        };
+        // the third insertion triggers a compaction
+        for coords in [[5,5], [6,6], [2,8], [4,4]] {
+            front.insert_observed(CartesianParetoElement::<2>::new(coords), &mut observer);
+        }
+        assert!(!front.is_compact());
+        front.compact_observed(&mut observer);
+        assert_eq!(evictions, vec![
+            (CartesianParetoElement::<2>::new([6,6]), CartesianParetoElement::<2>::new([5,5])),
+            (CartesianParetoElement::<2>::new([5,5]), CartesianParetoElement::<2>::new([4,4])),
+        ]);
+        assert_eq!(front.len(), 2);
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::priority_queue::{best_first_k, ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+
+/// sub-heap of a persistent heap (shared between versions)
+type Link<T,Elt> = Option<Rc<Node<T,Elt>>>;
+
+/// node of a persistent leftist heap
+struct Node<T,Elt> {
+    /// cached guide of the element
+    guide:T,
+    /// element of the node
+    elt:Elt,
+    /// length of the right spine (s-value)
+    rank:usize,
+    /// left child (its rank is greater or equal than the right child rank)
+    l:Link<T,Elt>,
+    /// right child
+    r:Link<T,Elt>,
+}
+
+/// Persistent (immutable) leftist heap.
+///
+/// insert, meld and pop_min return new versions of the heap sharing all the unchanged nodes
+/// with the previous ones (path copying along the right spines). Thus, a branch and bound
+/// can fork its open list at each node: cloning a version is O(1), and modifying it does not
+/// change the other versions. Elements on the copied paths are cloned (use cheap-to-clone
+/// elements, for instance Rc).
+/// The PriorityQueue implementation replaces the version held by the queue.
+/// - memory: O(log n) new nodes per modification
+/// - clone, peek_min: O(1)
+/// - insertion, meld, pop_min: O(log n)
+/// - peek_max/pop_max: O(n)
+/// - construction from an iterator: O(n)
+pub struct PersistentHeap<T,Elt> {
+    /// root node
+    root:Link<T,Elt>,
+    /// number of elements in the heap
+    nb_elements:usize,
+}
+
+/// prints the node without its children (the left spine may be long)
+impl<T:fmt::Debug,Elt:fmt::Debug> fmt::Debug for Node<T,Elt> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("guide", &self.guide)
+            .field("elt", &self.elt)
+            .field("rank", &self.rank)
+            .finish_non_exhaustive()
+    }
+}
+
+/// prints the elements iteratively (the left spine may be long)
+impl<T,Elt:fmt::Debug> fmt::Debug for PersistentHeap<T,Elt> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentHeap")
+            .field("nb_elements", &self.nb_elements)
+            .field("elements", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T,Elt> Drop for PersistentHeap<T,Elt> {
+    /// iterative drop (the left spine may be long). Nodes shared with other versions are
+    /// only released
+    fn drop(&mut self) {
+        let mut stack:Vec<Rc<Node<T,Elt>>> = self.root.take().into_iter().collect();
+        while let Some(shared) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(shared) {
+                stack.extend(node.l.take());
+                stack.extend(node.r.take());
+            }
+        }
+    }
+}
+
+impl<T,Elt> Clone for PersistentHeap<T,Elt> {
+    fn clone(&self) -> Self { Self { root:self.root.clone(), nb_elements:self.nb_elements } }
+}
+
+impl<T,Elt> Default for PersistentHeap<T,Elt> {
+    fn default() -> Self { Self { root:None, nb_elements:0 } }
+}
+
+impl<T,Elt> PersistentHeap<T,Elt> {
+    /// creates an empty heap
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of elements in the heap
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the heap is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+
+    /// returns true iff both versions share the same root (thus are equal)
+    pub fn ptr_eq(&self, other:&Self) -> bool {
+        match (&self.root, &other.root) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// returns an iterator over the elements of the heap (in no particular order)
+    pub fn iter(&self) -> PersistentHeapIterator<'_,T,Elt> {
+        PersistentHeapIterator { stack:self.root.iter().map(|n| &**n).collect() }
+    }
+}
+
+impl<T,Elt> PersistentHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Clone {
+    /// returns the heap with elt inserted
+    pub fn inserted(&self, elt:Elt) -> Self {
+        let single = Some(Rc::new(Node { guide:elt.guide(), elt, rank:1, l:None, r:None }));
+        Self { root:merge(&self.root, &single), nb_elements:self.nb_elements + 1 }
+    }
+
+    /// returns the union of both heaps
+    pub fn melded(&self, other:&Self) -> Self {
+        Self { root:merge(&self.root, &other.root), nb_elements:self.nb_elements + other.nb_elements }
+    }
+
+    /// returns the heap without its minimum element (the same version if it is empty)
+    pub fn without_min(&self) -> Self {
+        match &self.root {
+            None => self.clone(),
+            Some(root) => Self { root:merge(&root.l, &root.r), nb_elements:self.nb_elements - 1 },
+        }
+    }
+}
+
+impl<T,Elt> FromIterator<Elt> for PersistentHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Clone {
+    /// melds the elements pairwise. O(n)
+    fn from_iter<I:IntoIterator<Item=Elt>>(iter:I) -> Self {
+        let mut heaps:VecDeque<(Link<T,Elt>,usize)> = iter.into_iter()
+            .map(|elt| (Some(Rc::new(Node { guide:elt.guide(), elt, rank:1, l:None, r:None })), 1))
+            .collect();
+        while heaps.len() > 1 {
+            let (a, size_a) = heaps.pop_front().unwrap();
+            let (b, size_b) = heaps.pop_front().unwrap();
+            heaps.push_back((merge(&a, &b), size_a + size_b));
+        }
+        let (root, nb_elements) = heaps.pop_front().unwrap_or((None, 0));
+        Self { root, nb_elements }
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for PersistentHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Clone {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.root.as_ref().map(|n| &n.elt)
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.iter().max_by_key(|e| e.guide())
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let res = self.peek_min()?.clone();
+        *self = self.without_min();
+        Some(res)
+    }
+
+    /// rebuilds the heap without its maximum element (melding the other elements pairwise, no
+    /// node is shared with the previous version). O(n)
+    fn pop_max(&mut self) -> Option<Elt> {
+        let mut elts:Vec<Elt> = self.iter().cloned().collect();
+        let max_pos = (0..elts.len()).max_by_key(|i| elts[*i].guide())?;
+        let res = elts.swap_remove(max_pos);
+        *self = elts.into_iter().collect();
+        Some(res)
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        *self = self.inserted(elt);
+        true
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.root.as_ref().map(|n| n.guide)
+    }
+
+    /// recomputes the cached guides (rebuilds the heap). O(n)
+    fn reprioritize(&mut self) {
+        *self = self.iter().cloned().collect();
+    }
+
+    fn meld(&mut self, other:Self) {
+        *self = self.melded(&other);
+    }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for PersistentHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Clone {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for PersistentHeap<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Clone {
+    /// best-first search over the heap-ordered tree. O(k.log(k))
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        best_first_k(self.root.as_deref(), k, |n| n.guide, |n, res| {
+            res.extend(n.l.as_deref());
+            res.extend(n.r.as_deref());
+        }, |n| Some(&n.elt))
+    }
+}
+
+/// Iterator over the elements of a persistent heap.
+pub struct PersistentHeapIterator<'a,T,Elt> {
+    /// nodes to visit
+    stack:Vec<&'a Node<T,Elt>>,
+}
+
+impl<T,Elt> fmt::Debug for PersistentHeapIterator<'_,T,Elt> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentHeapIterator").field("nb_pending_nodes", &self.stack.len()).finish()
+    }
+}
+
+impl<'a,T,Elt> Iterator for PersistentHeapIterator<'a,T,Elt> {
+    type Item = &'a Elt;
+    fn next(&mut self) -> Option<&'a Elt> {
+        let node = self.stack.pop()?;
+        if let Some(n) = &node.l { self.stack.push(n); }
+        if let Some(n) = &node.r { self.stack.push(n); }
+        Some(&node.elt)
+    }
+}
+
+/// returns the rank of the link (0 if empty)
+fn rank<T,Elt>(link:&Link<T,Elt>) -> usize {
+    link.as_ref().map_or(0, |n| n.rank)
+}
+
+/// merges two heaps. Only the nodes on the right spines are copied (O(log n))
+fn merge<T:Ord+Copy,Elt:Clone>(a:&Link<T,Elt>, b:&Link<T,Elt>) -> Link<T,Elt> {
+    match (a, b) {
+        (None, _) => b.clone(),
+        (_, None) => a.clone(),
+        (Some(x), Some(y)) => {
+            let (top, other) = if y.guide < x.guide { (y, a) } else { (x, b) };
+            let merged = merge(&top.r, other);
+            let (l, r) = if rank(&top.l) < rank(&merged) { (merged, top.l.clone()) } else { (top.l.clone(), merged) };
+            Some(Rc::new(Node { guide:top.guide, elt:top.elt.clone(), rank:rank(&r) + 1, l, r }))
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::priority_queue::fixtures::Value;
+
+    /// returns the elements of a version, sorted
+    fn sorted(heap:&PersistentHeap<u32,Value>) -> Vec<u32> {
+        let mut res:Vec<u32> = heap.iter().map(|v| v.0).collect();
+        res.sort_unstable();
+        res
+    }
+
+    #[test]
+    fn test_versions() {
+        let empty = PersistentHeap::new();
+        let a = empty.inserted(Value(5)).inserted(Value(1)).inserted(Value(3));
+        let b = a.without_min();
+        let c = a.melded(&b.inserted(Value(0)));
+        assert!(empty.is_empty() && empty.without_min().ptr_eq(&empty));
+        assert_eq!((a.peek_min(), a.len()), (Some(&Value(1)), 3));
+        assert_eq!((b.peek_min(), b.len()), (Some(&Value(3)), 2));
+        assert_eq!(sorted(&c), vec![0, 1, 3, 3, 5, 5]);
+        assert!(a.clone().ptr_eq(&a));
+        let mut queue = c.clone();
+        assert_eq!(queue.pop_max(), Some(Value(5)));
+        assert_eq!(queue.pop_k(3), vec![Value(0), Value(1), Value(3)]);
+        let mut built:PersistentHeap<u32,Value> = [4, 2, 7, 1, 9].into_iter().map(Value).collect();
+        assert_eq!((built.len(), built.pop_max()), (5, Some(Value(9))));
+        assert_eq!(built.pop_k(5), vec![Value(1), Value(2), Value(4), Value(7)]);
+        assert_eq!(queue.peek_k(5), vec![&Value(3), &Value(5)]);
+        assert_eq!(c.len(), 6);
+    }
+
+    #[test]
+    fn test_debug() {
+        let heap = PersistentHeap::new().inserted(Value(2)).inserted(Value(1));
+        assert_eq!(format!("{:?}", heap), "PersistentHeap { nb_elements: 2, elements: [Value(1), Value(2)] }");
+    }
+
+    #[test]
+    fn test_long_spine() {
+        // decreasing insertions build a left spine of n nodes, shared by the forked version
+        let n = 100_000;
+        let mut heap = PersistentHeap::new();
+        for i in (0..n).rev() { heap = heap.inserted(Value(i)); }
+        let forked = heap.without_min().inserted(Value(n));
+        drop(heap); // only releases the shared spine
+        assert_eq!((forked.len(), forked.peek_min()), (n as usize, Some(&Value(1))));
+        drop(forked); // drops the spine iteratively
+    }
+
+    #[test]
+    fn test_random_forks() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut versions = vec![(PersistentHeap::new(), Vec::new())];
+        for _ in 0..500 {
+            let (heap, expected) = versions[rng.gen_range(0..versions.len())].clone();
+            let mut next_expected:Vec<u32> = expected;
+            let next = match rng.gen_range(0..4) {
+                0 | 1 => {
+                    let v = rng.gen_range(0..100);
+                    next_expected.push(v);
+                    heap.inserted(Value(v))
+                },
+                2 => {
+                    if let Some(min) = next_expected.iter().min().copied() {
+                        assert_eq!(heap.peek_min_guide(), Some(min));
+                        let pos = next_expected.iter().position(|v| *v == min).unwrap();
+                        next_expected.swap_remove(pos);
+                    }
+                    heap.without_min()
+                },
+                _ => {
+                    let (other, other_expected) = &versions[rng.gen_range(0..versions.len())];
+                    if next_expected.len() + other_expected.len() > 200 { continue; }
+                    next_expected.extend(other_expected);
+                    heap.melded(other)
+                },
+            };
+            next_expected.sort_unstable();
+            versions.push((next, next_expected));
+        }
+        for (heap, expected) in &versions {
+            assert_eq!(heap.len(), expected.len());
+            assert_eq!(&sorted(heap), expected);
+        }
+    }
+}
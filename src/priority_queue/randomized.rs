@@ -0,0 +1,256 @@
+use rand::Rng;
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+use crate::priority_queue::handle::{Handle, HandleTable};
+
+/// Policy used by a [RandomizedQueue] to choose the popped element.
+#[derive(Debug,Clone,Copy)]
+pub enum PopPolicy<T> {
+    /// always pops the minimum
+    Greedy,
+    /// pops the minimum with probability 1-epsilon, otherwise a uniformly random element
+    EpsilonGreedy(f64),
+    /// pops an element with probability proportional to exp(-(guide-min_guide)/temperature)
+    Softmax {
+        /// temperature (the higher, the more uniform). A temperature of 0 (or lower) always
+        /// pops the minimum
+        temperature:f64,
+        /// converts a guide to a float
+        to_f64:fn(T) -> f64,
+    },
+}
+
+impl<T:Into<f64>> PopPolicy<T> {
+    /// softmax policy for guides convertible to floats
+    pub fn softmax(temperature:f64) -> Self {
+        PopPolicy::Softmax { temperature, to_f64:|g| g.into() }
+    }
+}
+
+/// entry of the heap: a handle to the element with its guide
+#[derive(Debug,Clone)]
+struct Entry<T> {
+    /// guide of the element
+    guide:T,
+    /// handle of the element
+    handle:Handle,
+}
+
+impl<T:Ord+Copy> GuidedElement<T> for Entry<T> {
+    fn guide(&self) -> T { self.guide }
+}
+
+/// Randomized priority queue.
+///
+/// pop_min follows a pop policy: it returns the minimum, or with some probability a random
+/// element (epsilon-greedy or softmax over guides), diversifying anytime searches.
+/// pop_max and the peeks are not randomized.
+/// Elements are stored in a vector (allowing uniform sampling), and ordered by a min-max heap
+/// of (guide, handle) entries. Entries of removed elements are discarded lazily.
+/// - insertion: O(log n)
+/// - greedy pop: O(log n) amortized
+/// - epsilon-greedy random pop: O(1) amortized
+/// - softmax pop: O(n)
+#[derive(Debug,Clone)]
+pub struct RandomizedQueue<T,Elt,R> {
+    /// elements with their handle
+    elements:Vec<(Handle,Elt)>,
+    /// maps handles to positions in the elements
+    table:HandleTable,
+    /// heap of entries (top entries always refer to stored elements)
+    heap:BinaryHeapQueue<T,Entry<T>>,
+    /// number of entries referring to removed elements
+    nb_tombstones:usize,
+    /// pop policy
+    policy:PopPolicy<T>,
+    /// random number generator
+    rng:R,
+}
+
+impl<T,Elt,R> RandomizedQueue<T,Elt,R> {
+    /// creates an empty queue with the given pop policy and random number generator
+    pub fn new(policy:PopPolicy<T>, rng:R) -> Self {
+        Self {
+            elements:Vec::new(), table:HandleTable::default(), heap:BinaryHeapQueue::default(),
+            nb_tombstones:0, policy, rng,
+        }
+    }
+
+    /// returns the pop policy
+    pub fn policy(&self) -> &PopPolicy<T> { &self.policy }
+
+    /// changes the pop policy (for instance to reduce epsilon over time)
+    pub fn set_policy(&mut self, policy:PopPolicy<T>) { self.policy = policy; }
+
+    /// returns an iterator over the elements of the queue (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> { self.elements.iter().map(|(_,e)| e) }
+}
+
+impl<T,Elt,R> RandomizedQueue<T,Elt,R>
+where T:Ord+Copy, Elt:GuidedElement<T>, R:Rng {
+    /// pops a uniformly random element
+    pub fn pop_uniform(&mut self) -> Option<Elt> {
+        if self.elements.is_empty() { return None; }
+        let pos = self.rng.gen_range(0..self.elements.len());
+        Some(self.remove_at(pos))
+    }
+
+    /// pops an element sampled by softmax over the guides (the temperature is positive)
+    fn pop_softmax(&mut self, temperature:f64, to_f64:fn(T)->f64) -> Option<Elt> {
+        let min_guide = to_f64(self.heap.peek_min_guide()?);
+        let weights:Vec<f64> = self.elements.iter()
+            .map(|(_,e)| (-(to_f64(e.guide()) - min_guide) / temperature).exp())
+            .collect();
+        let mut r = self.rng.gen::<f64>() * weights.iter().sum::<f64>();
+        let mut pos = weights.len()-1;
+        for (i,w) in weights.iter().enumerate() {
+            if r < *w { pos = i; break; }
+            r -= w;
+        }
+        Some(self.remove_at(pos))
+    }
+
+    /// removes the element referred by the heap entry at the minimum or maximum
+    fn pop_entry(&mut self, entry:Option<&Entry<T>>) -> Option<Elt> {
+        let pos = self.table.position(entry?.handle)?;
+        Some(self.remove_at(pos))
+    }
+
+    /// removes the element at the given position
+    fn remove_at(&mut self, pos:usize) -> Elt {
+        let (h, res) = self.elements.swap_remove(pos);
+        self.table.invalidate(h);
+        if pos < self.elements.len() { // an element was moved at position pos
+            self.table.set_position(self.elements[pos].0, pos);
+        }
+        self.nb_tombstones += 1;
+        self.discard_tombstones();
+        res
+    }
+
+    /// discards the entries of removed elements at the minimum and maximum of the heap,
+    /// and compacts the heap if these entries represent more than half the entries
+    fn discard_tombstones(&mut self) {
+        while self.heap.peek_min().is_some_and(|e| !self.table.is_valid(e.handle)) {
+            self.heap.pop_min();
+            self.nb_tombstones -= 1;
+        }
+        while self.heap.peek_max().is_some_and(|e| !self.table.is_valid(e.handle)) {
+            self.heap.pop_max();
+            self.nb_tombstones -= 1;
+        }
+        if 2*self.nb_tombstones > self.heap.len() {
+            self.heap.retain(|e| self.table.is_valid(e.handle));
+            self.nb_tombstones = 0;
+        }
+    }
+
+    /// returns the element referred by the entry
+    fn entry_elt(&self, entry:Option<&Entry<T>>) -> Option<&Elt> {
+        let pos = self.table.position(entry?.handle)?;
+        Some(&self.elements[pos].1)
+    }
+}
+
+impl<T,Elt,R> PriorityQueue<T,Elt> for RandomizedQueue<T,Elt,R>
+where T:Ord+Copy, Elt:GuidedElement<T>, R:Rng {
+    fn peek_min(&self) -> Option<&Elt> { self.entry_elt(self.heap.peek_min()) }
+
+    fn peek_max(&self) -> Option<&Elt> { self.entry_elt(self.heap.peek_max()) }
+
+    /// pops an element following the pop policy
+    fn pop_min(&mut self) -> Option<Elt> {
+        match self.policy {
+            PopPolicy::EpsilonGreedy(epsilon) if self.rng.gen::<f64>() < epsilon => {
+                self.pop_uniform()
+            },
+            // the weights are not defined for a temperature of 0 (greedy limit)
+            PopPolicy::Softmax { temperature, to_f64 } if temperature > 0. => self.pop_softmax(temperature, to_f64),
+            _ => {
+                let entry = self.heap.peek_min().cloned();
+                self.pop_entry(entry.as_ref())
+            }
+        }
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let entry = self.heap.peek_max().cloned();
+        self.pop_entry(entry.as_ref())
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        let handle = self.table.create(self.elements.len());
+        self.heap.insert(Entry { guide:elt.guide(), handle });
+        self.elements.push((handle, elt));
+        true
+    }
+
+    fn is_empty(&self) -> bool { self.elements.is_empty() }
+
+    fn peek_min_guide(&self) -> Option<T> { self.heap.peek_min_guide() }
+
+    fn peek_max_guide(&self) -> Option<T> { self.heap.peek_max_guide() }
+}
+
+impl<T,Elt,R> ExactSizeQueue<T,Elt> for RandomizedQueue<T,Elt,R>
+where T:Ord+Copy, Elt:GuidedElement<T>, R:Rng {
+    fn len(&self) -> usize { self.elements.len() }
+}
+
+impl<T,Elt,R> PeekKQueue<T,Elt> for RandomizedQueue<T,Elt,R>
+where T:Ord+Copy, Elt:GuidedElement<T>, R:Rng {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        self.heap.peek_k(k.saturating_add(self.nb_tombstones)).into_iter()
+            .filter_map(|e| self.entry_elt(Some(e)))
+            .take(k)
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::priority_queue::fixtures::Value;
+
+    /// pops all the elements of a queue containing 0..n
+    fn pop_all(policy:PopPolicy<u32>, n:u32) -> Vec<u32> {
+        let mut queue = RandomizedQueue::new(policy, StdRng::seed_from_u64(0));
+        for i in 0..n { queue.insert(Value((i*7) % n)); }
+        queue.drain_sorted().map(|v| v.0).collect()
+    }
+
+    #[test]
+    fn test_greedy() {
+        assert_eq!(pop_all(PopPolicy::Greedy, 50), (0..50).collect::<Vec<u32>>());
+        assert_eq!(pop_all(PopPolicy::EpsilonGreedy(0.), 50), (0..50).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_epsilon_greedy() {
+        let popped = pop_all(PopPolicy::EpsilonGreedy(0.3), 200);
+        assert_eq!(popped.len(), 200);
+        let nb_sorted = popped.windows(2).filter(|w| w[0] < w[1]).count();
+        assert!(nb_sorted < 199);
+        let mut sorted = popped.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..200).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_softmax() {
+        // very low temperature: greedy
+        assert_eq!(pop_all(PopPolicy::softmax(1e-3), 20), (0..20).collect::<Vec<u32>>());
+        assert_eq!(pop_all(PopPolicy::softmax(0.), 20), (0..20).collect::<Vec<u32>>());
+        let mut queue = RandomizedQueue::new(PopPolicy::softmax(1.), StdRng::seed_from_u64(3));
+        queue.insert(Value(0));
+        queue.insert(Value(1000));
+        assert_eq!(queue.pop_min(), Some(Value(0)));
+        assert_eq!(queue.peek_max(), Some(&Value(1000)));
+        assert_eq!(queue.len(), 1);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::priority_queue::{filter_dominated, k_best_by_guide, on_evictions, FrontEvent, ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue, ParetoFront};
+
+/// node of a layered range tree on a dimension
+#[derive(Debug,Clone)]
+struct Layer<T> {
+    /// coordinates (on the dimension of the layer) of the elements, sorted
+    keys:Vec<T>,
+    /// identifiers of the elements, sorted as the keys
+    ids:Vec<usize>,
+    /// lower and upper halves of the elements (None for a single element or the last dimension)
+    halves:Option<Box<(Layer<T>,Layer<T>)>>,
+    /// layer on the next dimension over the same elements (None for the last dimension)
+    next:Option<Box<Layer<T>>>,
+}
+
+impl<T:Ord+Copy> Layer<T> {
+    /// builds the layer of the dimension dim over the elements ids
+    fn build<C:Fn(usize,usize) -> T>(mut ids:Vec<usize>, dim:usize, nb_dim:usize, coord:&C) -> Self {
+        ids.sort_by_key(|id| coord(*id, dim));
+        let keys = ids.iter().map(|id| coord(*id, dim)).collect();
+        if dim + 1 == nb_dim { return Self { keys, ids, halves:None, next:None }; }
+        let next = Some(Box::new(Self::build(ids.clone(), dim+1, nb_dim, coord)));
+        let halves = (ids.len() > 1).then(|| {
+            let upper = ids[ids.len()/2..].to_vec();
+            let lower = ids[..ids.len()/2].to_vec();
+            Box::new((Self::build(lower, dim, nb_dim, coord), Self::build(upper, dim, nb_dim, coord)))
+        });
+        Self { keys, ids, halves, next }
+    }
+
+    /// visits the elements whose coordinates are within the bounds (inclusive, None for
+    /// unbounded) until the visitor returns true. Returns true iff the visit was stopped
+    fn query<V:FnMut(usize) -> bool>(&self, dim:usize, lo:&[Option<T>], hi:&[Option<T>], visitor:&mut V) -> bool {
+        // one-sided ranges on the last dimension are scanned from the bounded end (no search)
+        if self.next.is_none() {
+            let elements = self.keys.iter().zip(&self.ids);
+            match (lo[dim], hi[dim]) {
+                (None, bound) => return elements.take_while(|(k,_)| bound.is_none_or(|h| **k <= h)).any(|(_,id)| visitor(*id)),
+                (Some(l), None) => return elements.rev().take_while(|(k,_)| **k >= l).any(|(_,id)| visitor(*id)),
+                _ => {},
+            }
+        }
+        let start = lo[dim].map_or(0, |l| self.keys.partition_point(|k| *k < l));
+        let end = hi[dim].map_or(self.keys.len(), |h| self.keys.partition_point(|k| *k <= h));
+        self.query_positions(dim, start, end, lo, hi, visitor)
+    }
+
+    /// visits the elements at the positions start..end of the layer whose coordinates on the
+    /// next dimensions are within the bounds
+    fn query_positions<V:FnMut(usize) -> bool>(&self, dim:usize, start:usize, end:usize, lo:&[Option<T>], hi:&[Option<T>], visitor:&mut V) -> bool {
+        if start >= end { return false; }
+        match &self.next {
+            None => self.ids[start..end].iter().any(|id| visitor(*id)),
+            Some(next) if start == 0 && end == self.ids.len() => next.query(dim+1, lo, hi, visitor),
+            Some(_) => {
+                let (lower, upper) = self.halves.as_deref().expect("range tree: partial range over a single element");
+                let mid = lower.ids.len();
+                lower.query_positions(dim, start, end.min(mid), lo, hi, visitor)
+                || upper.query_positions(dim, start.max(mid) - mid, end.saturating_sub(mid), lo, hi, visitor)
+            },
+        }
+    }
+}
+
+/// Layered range tree pareto front.
+///
+/// Elements are indexed by a range tree: a balanced tree on the first dimension whose nodes
+/// store a range tree of their elements on the next dimensions (sorted arrays on the last
+/// dimension). Orthogonal range queries decompose the range into O(log n) nodes per
+/// dimension. Dominance queries are range queries (elements dominating e have coordinates
+/// lower or equal than the ones of e).
+///
+/// The front is semi-dynamic: inserted elements are kept in a buffer scanned linearly, and
+/// removed elements are marked. The tree is rebuilt when the buffer size exceeds the square
+/// root of the number of indexed elements, or when half of the elements are removed. Thus,
+/// it suits static or query-heavy fronts (see [RangeTreeFront::from_vec]). Dominance is
+/// assumed to imply lower or equal coordinates (as for the kd-tree).
+/// If *n* is the number of elements, *d* the number of dimensions, and *k* the number of
+/// reported elements:
+/// - memory: O(n.log(n)^(d-1))
+/// - range query: O(log(n)^d + k + √n)
+/// - dominance query (one-sided range), find_dominating: O(log(n)^(d-1) + k + √n)
+/// - insertion: O(log(n)^d + k + √n) plus O(√n.log(n)^(d-1)) amortized rebuilds
+/// - peek_min/peek_max/pop_min/pop_max: O(n)
+#[derive(Debug,Clone)]
+pub struct RangeTreeFront<T, Elt, const NB_DIM:usize> {
+    /// elements (None if removed). The first nb_indexed ones are in the tree
+    elements:Vec<Option<Elt>>,
+    /// range tree over the first nb_indexed elements
+    root:Option<Layer<T>>,
+    /// number of elements indexed by the tree (removed or not)
+    nb_indexed:usize,
+    /// number of elements (not removed)
+    nb_elements:usize,
+}
+
+impl<T, Elt, const NB_DIM:usize> Default for RangeTreeFront<T, Elt, NB_DIM> {
+    fn default() -> Self {
+        Self { elements:Vec::new(), root:None, nb_indexed:0, nb_elements:0 }
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> RangeTreeFront<T, Elt, NB_DIM> {
+    /// returns an iterator over the elements of the front (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item=&Elt> { self.elements.iter().flatten() }
+
+    /// returns the number of elements in the front
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the front is empty
+    pub fn is_empty(&self) -> bool { self.nb_elements == 0 }
+}
+
+impl<T, Elt, const NB_DIM:usize> RangeTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T> {
+    /// minimum number of buffered elements before a rebuild
+    const MIN_BUFFER:usize = 16;
+
+    /// builds a front from a vector of elements (all of them are indexed by the tree).
+    ///
+    /// Dominated elements are filtered out before building the front.
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        let elements:Vec<Option<Elt>> = filter_dominated(elts).into_iter().map(Some).collect();
+        let mut res = Self { nb_elements:elements.len(), elements, ..Self::default() };
+        res.rebuild();
+        res
+    }
+
+    /// returns the elements whose coordinates are within lo..=hi
+    pub fn range(&self, lo:&[T;NB_DIM], hi:&[T;NB_DIM]) -> Vec<&Elt> {
+        let mut res = Vec::new();
+        self.visit(&lo.map(Some), &hi.map(Some), |e| { res.push(e); false });
+        res
+    }
+
+    /// removes the marked elements, and indexes all the elements in a new tree
+    pub fn rebuild(&mut self) {
+        self.elements.retain(|e| e.is_some());
+        self.nb_indexed = self.elements.len();
+        self.root = (!self.elements.is_empty()).then(|| {
+            let elements = &self.elements;
+            let coord = |id:usize, dim:usize| elements[id].as_ref().expect("range tree: indexing a removed element").kth(dim);
+            Layer::build((0..elements.len()).collect(), 0, NB_DIM, &coord)
+        });
+    }
+
+    /// visits the elements within the bounds (tree, then buffer) until the visitor returns
+    /// true. Returns true iff the visit was stopped
+    fn visit<'a,V:FnMut(&'a Elt) -> bool>(&'a self, lo:&[Option<T>], hi:&[Option<T>], mut visitor:V) -> bool {
+        let within = |e:&Elt| (0..NB_DIM).all(|d| {
+            lo[d].is_none_or(|l| e.kth(d) >= l) && hi[d].is_none_or(|h| e.kth(d) <= h)
+        });
+        let mut visit_id = |id:usize| self.elements[id].as_ref().is_some_and(&mut visitor);
+        if self.root.as_ref().is_some_and(|root| root.query(0, lo, hi, &mut visit_id)) {
+            return true;
+        }
+        self.elements[self.nb_indexed..].iter().flatten().any(|e| within(e) && visitor(e))
+    }
+
+    /// returns the identifiers of the elements dominated by elt
+    fn dominated_by(&self, elt:&Elt) -> Vec<usize> {
+        let lo:Vec<Option<T>> = (0..NB_DIM).map(|d| Some(elt.kth(d))).collect();
+        let mut res = Vec::new();
+        let mut collect = |id:usize| {
+            if self.elements[id].as_ref().is_some_and(|e| elt.dominates(e)) { res.push(id); }
+            false
+        };
+        if let Some(root) = &self.root { root.query(0, &lo, &[None;NB_DIM], &mut collect); }
+        for id in self.nb_indexed..self.elements.len() { collect(id); }
+        res
+    }
+
+    /// removes the element of the given identifier (rebuilds the tree if many are removed)
+    fn remove_id(&mut self, id:usize) -> Elt {
+        let res = self.elements[id].take().expect("range tree: removing a removed element");
+        self.nb_elements -= 1;
+        if self.elements.len() > Self::MIN_BUFFER && 2*self.nb_elements < self.elements.len() {
+            self.rebuild();
+        }
+        res
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ParetoFront<T, Elt> for RangeTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T> {
+    fn find_dominating(&self, elt:&Elt) -> Option<&Elt> {
+        let hi:Vec<Option<T>> = (0..NB_DIM).map(|d| Some(elt.kth(d))).collect();
+        let mut res = None;
+        self.visit(&[None;NB_DIM], &hi, |e| {
+            if e.dominates(elt) { res = Some(e); }
+            res.is_some()
+        });
+        res
+    }
+
+    fn insert_observed<O>(&mut self, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        if let Some(by) = self.find_dominating(&elt) {
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return false;
+        }
+        for id in self.dominated_by(&elt) {
+            if let Some(e) = self.elements[id].take() {
+                observer(FrontEvent::EvictedBy { element:&e, by:&elt });
+                self.nb_elements -= 1;
+            }
+        }
+        observer(FrontEvent::Inserted(&elt));
+        self.elements.push(Some(elt));
+        self.nb_elements += 1;
+        let nb_buffered = self.elements.len() - self.nb_indexed;
+        if (nb_buffered > Self::MIN_BUFFER && nb_buffered*nb_buffered > self.nb_indexed)
+        || (self.elements.len() > Self::MIN_BUFFER && 2*self.nb_elements < self.elements.len()) {
+            self.rebuild();
+        }
+        true
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> PriorityQueue<T, Elt> for RangeTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.iter().min_by_key(|e| e.guide())
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.iter().max_by_key(|e| e.guide())
+    }
+
+    /// guides are not cached (elements are indexed by coordinates), nothing to do
+    fn reprioritize(&mut self) {}
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let id = self.elements.iter().enumerate()
+            .filter_map(|(id,slot)| slot.as_ref().map(|e| (id,e.guide())))
+            .min_by(|a,b| a.1.cmp(&b.1))?.0;
+        Some(self.remove_id(id))
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let id = self.elements.iter().enumerate()
+            .filter_map(|(id,slot)| slot.as_ref().map(|e| (id,e.guide())))
+            .max_by(|a,b| a.1.cmp(&b.1))?.0;
+        Some(self.remove_id(id))
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_observed(elt, |_| {})
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.insert_observed(elt, on_evictions(evicted))
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ExactSizeQueue<T, Elt> for RangeTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T, Elt, const NB_DIM:usize> PeekKQueue<T, Elt> for RangeTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        k_best_by_guide(self.iter(), k)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::priority_queue::pareto_list::ListParetoFront;
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    type Point = CartesianParetoElement<3>;
+
+    #[test]
+    fn test_inserts() {
+        let mut front = RangeTreeFront::<u32, CartesianParetoElement<2>, 2>::default();
+        assert!(front.insert(CartesianParetoElement::new([5,10])));
+        assert!(front.insert(CartesianParetoElement::new([10,5])));
+        assert!(!front.insert(CartesianParetoElement::new([10,10])));
+        assert!(front.insert(CartesianParetoElement::new([4,4])));
+        assert_eq!(front.len(), 1);
+        assert_eq!(front.pop_min(), Some(CartesianParetoElement::new([4,4])));
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn test_range() {
+        let points:Vec<Point> = (0..10).map(|i| Point::new([i, 9-i, i % 3])).collect();
+        let front = RangeTreeFront::<u32, Point, 3>::from_vec(points.clone());
+        assert_eq!(front.len(), 10);
+        let mut found:Vec<Point> = front.range(&[2,0,1], &[7,9,2]).into_iter().cloned().collect();
+        found.sort_by_key(|p| p.kth(0));
+        let expected:Vec<Point> = points.into_iter().filter(|p| (2..=7).contains(&p.kth(0)) && p.kth(2) >= 1).collect();
+        assert_eq!(found, expected);
+        assert!(front.find_dominating(&Point::new([4,6,2])).is_some());
+        assert!(front.find_dominating(&Point::new([4,4,2])).is_none());
+    }
+
+    #[test]
+    fn test_against_list() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut front = RangeTreeFront::<u32, Point, 3>::default();
+        let mut list = ListParetoFront::<u32, Point>::default();
+        for i in 0..2000 {
+            let p = Point::new([rng.gen_range(0..100), rng.gen_range(0..100), rng.gen_range(0..100)]);
+            assert_eq!(front.find_dominating(&p).is_some(), list.find_dominating(&p).is_some());
+            let (mut evicted, mut expected_evicted) = (0, 0);
+            let inserted = front.insert_observed(p.clone(), |ev| if let FrontEvent::EvictedBy{..} = ev { evicted += 1 });
+            let expected = list.insert_observed(p, |ev| if let FrontEvent::EvictedBy{..} = ev { expected_evicted += 1 });
+            assert_eq!((inserted, evicted), (expected, expected_evicted));
+            if i % 100 == 99 { assert_eq!(front.pop_min().map(|e| e.guide()), list.pop_min().map(|e| e.guide())); }
+            assert_eq!(front.len(), list.len());
+        }
+    }
+}
@@ -0,0 +1,600 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{best_first_k, filter_dominated, on_evictions, FrontEvent, ExactSizeQueue, GuidedElement, ParetoElement, PeekKQueue, PriorityQueue, ParetoFront};
+
+/// default maximum number of entries per page
+const DEFAULT_PAGE_CAPACITY:usize = 16;
+
+/// content of a page: either elements (leaf) or sub-pages
+#[derive(Debug)]
+enum Content<T, Elt, const NB_DIM:usize> {
+    /// leaf page storing elements
+    Leaf(Vec<Elt>),
+    /// internal page storing sub-pages
+    Internal(Vec<Page<T,Elt,NB_DIM>>),
+}
+
+/// page of the R-tree. Contains the minimum bounding rectangle (MBR) of its entries
+#[derive(Debug)]
+struct Page<T, Elt, const NB_DIM:usize> {
+    /// lower and upper bounds on dimensions (MBR)
+    b:[(T,T);NB_DIM],
+    /// guide lower bound
+    guide_lb:T,
+    /// guide upper bound
+    guide_ub:T,
+    /// entries of the page
+    content:Content<T,Elt,NB_DIM>,
+}
+
+/// entry visited during a best-first search
+enum Entry<'a, T, Elt, const NB_DIM:usize> {
+    /// page (sub-tree)
+    Page(&'a Page<T,Elt,NB_DIM>),
+    /// element
+    Elt(&'a Elt),
+}
+
+impl<T, Elt, const NB_DIM:usize> Page<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    /// creates a leaf page (elements should not be empty)
+    fn leaf(elts:Vec<Elt>) -> Self {
+        Self::new(Content::Leaf(elts))
+    }
+
+    /// creates an internal page (pages should not be empty)
+    fn internal(pages:Vec<Self>) -> Self {
+        Self::new(Content::Internal(pages))
+    }
+
+    /// creates a page and computes its bounds
+    fn new(content:Content<T,Elt,NB_DIM>) -> Self {
+        let (b, lb, ub) = Self::compute_bounds(&content);
+        Self { b, guide_lb:lb, guide_ub:ub, content }
+    }
+
+    /// number of entries in the page
+    fn len(&self) -> usize {
+        match &self.content {
+            Content::Leaf(v) => v.len(),
+            Content::Internal(v) => v.len(),
+        }
+    }
+
+    /// updates the bounds of the page
+    fn update_bounds(&mut self) {
+        let (b, lb, ub) = Self::compute_bounds(&self.content);
+        self.b = b;
+        self.guide_lb = lb;
+        self.guide_ub = ub;
+    }
+
+    /// computes the bounds of the content (it should not be empty)
+    fn compute_bounds(content:&Content<T,Elt,NB_DIM>) -> ([(T,T);NB_DIM],T,T) {
+        match content {
+            Content::Leaf(elts) => {
+                let first = &elts[0];
+                let mut res:[(T,T);NB_DIM] = [(first.kth(0),first.kth(0));NB_DIM];
+                for (i,c) in first.coordinates().enumerate() { res[i] = (c,c); }
+                let (mut lb, mut ub) = (first.guide(), first.guide());
+                for e in &elts[1..] {
+                    for (i,c) in e.coordinates().enumerate() {
+                        res[i] = (std::cmp::min(res[i].0, c), std::cmp::max(res[i].1, c));
+                    }
+                    lb = std::cmp::min(lb, e.guide());
+                    ub = std::cmp::max(ub, e.guide());
+                }
+                (res, lb, ub)
+            },
+            Content::Internal(pages) => {
+                let mut res = pages[0].b;
+                let (mut lb, mut ub) = (pages[0].guide_lb, pages[0].guide_ub);
+                for p in &pages[1..] {
+                    for (i,(lower,upper)) in p.b.iter().enumerate() {
+                        res[i] = (std::cmp::min(res[i].0, *lower), std::cmp::max(res[i].1, *upper));
+                    }
+                    lb = std::cmp::min(lb, p.guide_lb);
+                    ub = std::cmp::max(ub, p.guide_ub);
+                }
+                (res, lb, ub)
+            }
+        }
+    }
+
+    /// number of dimensions of the MBR that need to be enlarged to contain the element
+    fn nb_enlarged_dimensions(&self, elt:&Elt) -> usize {
+        elt.coordinates().zip(self.b.iter())
+            .filter(|(c,(lower,upper))| c < lower || c > upper)
+            .count()
+    }
+
+    /// returns true iff no element of the page can dominate elt
+    fn cannot_dominate(&self, elt:&Elt) -> bool {
+        elt.coordinates().zip(self.b.iter()).any(|(c,(lower,_))| c < *lower)
+    }
+
+    /// returns true iff elt cannot dominate any element of the page
+    fn cannot_be_dominated_by(&self, elt:&Elt) -> bool {
+        elt.coordinates().zip(self.b.iter()).any(|(c,(_,upper))| c > *upper)
+    }
+}
+
+
+/// R-tree based pareto front.
+///
+/// Elements are grouped into pages (leaves) described by their minimum bounding rectangle
+/// (MBR). Pages are themselves grouped in higher-level pages. Dominance checks prune whole
+/// pages using their MBR, and guide bounds stored in each page allow fast minimum/maximum
+/// extraction.
+///
+/// Pages may be bulk-loaded using the Sort-Tile-Recursive (STR) algorithm ([RTreeFront::from_vec]).
+/// Insertions choose the sub-page requiring the fewest enlarged dimensions, and split
+/// overflowing pages in two halves. Pages that become empty are removed (underfull pages are
+/// not condensed).
+#[derive(Debug)]
+pub struct RTreeFront<T, Elt, const NB_DIM:usize> {
+    /// root page
+    root:Option<Page<T,Elt,NB_DIM>>,
+    /// maximum number of entries per page
+    capacity:usize,
+    /// number of elements in the front
+    nb_elements:usize,
+    /// phantom for type T
+    phantom_t:PhantomData<T>,
+}
+
+impl<T, Elt, const NB_DIM:usize> Default for RTreeFront<T, Elt, NB_DIM> {
+    fn default() -> Self {
+        Self::with_page_capacity(DEFAULT_PAGE_CAPACITY)
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> RTreeFront<T, Elt, NB_DIM> {
+    /// creates an empty front in which pages contain at most capacity entries
+    pub fn with_page_capacity(capacity:usize) -> Self {
+        assert!(capacity >= 2);
+        Self { root:None, capacity, nb_elements:0, phantom_t:PhantomData }
+    }
+
+    /// returns the number of elements in the front
+    pub fn len(&self) -> usize { self.nb_elements }
+
+    /// returns true iff the front is empty
+    pub fn is_empty(&self) -> bool { self.nb_elements == 0 }
+
+    /// returns the height of the tree (0 if empty, 1 if only one leaf page)
+    pub fn height(&self) -> usize {
+        let mut res = 0;
+        let mut page = self.root.as_ref();
+        while let Some(p) = page {
+            res += 1;
+            page = match &p.content {
+                Content::Leaf(_) => None,
+                Content::Internal(pages) => pages.first(),
+            };
+        }
+        res
+    }
+
+    /// returns an iterator over the elements of the front (in no particular order)
+    pub fn iter(&self) -> RTreeIterator<'_, T, Elt, NB_DIM> {
+        RTreeIterator { pages:self.root.iter().collect(), leaf:[].iter() }
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> RTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    /// builds a front from a vector of elements using the STR bulk-loading algorithm.
+    ///
+    /// Dominated elements are filtered out before building the tree.
+    pub fn from_vec(elts:Vec<Elt>) -> Self {
+        Self::from_vec_with_page_capacity(elts, DEFAULT_PAGE_CAPACITY)
+    }
+
+    /// builds a front from a vector of elements using the STR bulk-loading algorithm,
+    /// in which pages contain at most capacity entries.
+    ///
+    /// Dominated elements are filtered out before building the tree.
+    pub fn from_vec_with_page_capacity(elts:Vec<Elt>, capacity:usize) -> Self {
+        let mut res = Self::with_page_capacity(capacity);
+        let front = filter_dominated(elts);
+        if front.is_empty() { return res; }
+        res.nb_elements = front.len();
+        // pack elements into leaves, then pack pages until a single one remains
+        let mut pages:Vec<Page<T,Elt,NB_DIM>> = Vec::new();
+        Self::str_pack(front, 0, capacity, &mut pages);
+        while pages.len() > 1 {
+            let mut upper:Vec<Page<T,Elt,NB_DIM>> = Vec::new();
+            let mut group:Vec<Page<T,Elt,NB_DIM>> = Vec::new();
+            for p in pages {
+                group.push(p);
+                if group.len() == capacity {
+                    upper.push(Page::internal(std::mem::take(&mut group)));
+                }
+            }
+            if !group.is_empty() { upper.push(Page::internal(group)); }
+            pages = upper;
+        }
+        res.root = pages.pop();
+        res
+    }
+
+    /// recomputes the bounds of the page and its sub-pages
+    fn rec_update_bounds(page:&mut Page<T,Elt,NB_DIM>) {
+        if let Content::Internal(pages) = &mut page.content {
+            for p in pages.iter_mut() { Self::rec_update_bounds(p); }
+        }
+        page.update_bounds();
+    }
+
+    /// Sort-Tile-Recursive packing of the elements into leaf pages
+    fn str_pack(mut elts:Vec<Elt>, dim:usize, capacity:usize, res:&mut Vec<Page<T,Elt,NB_DIM>>) {
+        elts.sort_by_key(|e| e.kth(dim));
+        let nb_leaves = elts.len().div_ceil(capacity);
+        if dim+1 >= NB_DIM || nb_leaves <= 1 {
+            while !elts.is_empty() {
+                let rest = elts.split_off(std::cmp::min(capacity, elts.len()));
+                res.push(Page::leaf(elts));
+                elts = rest;
+            }
+            return;
+        }
+        // number of slices along the current dimension
+        let nb_slices = (nb_leaves as f64).powf(1./(NB_DIM-dim) as f64).ceil() as usize;
+        let slice_size = nb_leaves.div_ceil(nb_slices) * capacity;
+        while !elts.is_empty() {
+            let rest = elts.split_off(std::cmp::min(slice_size, elts.len()));
+            Self::str_pack(elts, dim+1, capacity, res);
+            elts = rest;
+        }
+    }
+
+    /// recursive search of a dominating element
+    fn rec_find_dominating<'a>(page:&'a Page<T,Elt,NB_DIM>, elt:&Elt) -> Option<&'a Elt> {
+        if page.cannot_dominate(elt) { return None; }
+        match &page.content {
+            Content::Leaf(elts) => elts.iter().find(|e| e.dominates(elt)),
+            Content::Internal(pages) => pages.iter()
+                .find_map(|p| Self::rec_find_dominating(p, elt)),
+        }
+    }
+
+    /// recursive removal of the elements dominated by elt. Returns the number of removed elements
+    fn rec_remove_dominated_by<O>(page:&mut Page<T,Elt,NB_DIM>, elt:&Elt, observer:&mut O) -> usize
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        if page.cannot_be_dominated_by(elt) { return 0; }
+        let mut res = 0;
+        match &mut page.content {
+            Content::Leaf(elts) => {
+                let mut i = 0;
+                while i < elts.len() {
+                    if elt.dominates(&elts[i]) {
+                        let evicted = elts.swap_remove(i);
+                        observer(FrontEvent::EvictedBy { element:&evicted, by:elt });
+                        res += 1;
+                    } else { i += 1; }
+                }
+            },
+            Content::Internal(pages) => {
+                for p in pages.iter_mut() {
+                    res += Self::rec_remove_dominated_by(p, elt, observer);
+                }
+                pages.retain(|p| p.len() > 0);
+            }
+        }
+        if res > 0 && page.len() > 0 { page.update_bounds(); }
+        res
+    }
+
+    /// recursive insertion. Returns a new sibling page if the page was split
+    fn rec_insert(page:&mut Page<T,Elt,NB_DIM>, elt:Elt, capacity:usize) -> Option<Page<T,Elt,NB_DIM>> {
+        match &mut page.content {
+            Content::Leaf(elts) => { elts.push(elt); },
+            Content::Internal(pages) => {
+                let best = (0..pages.len())
+                    .min_by_key(|i| pages[*i].nb_enlarged_dimensions(&elt))
+                    .unwrap();
+                if let Some(sibling) = Self::rec_insert(&mut pages[best], elt, capacity) {
+                    pages.push(sibling);
+                }
+            }
+        }
+        let res = if page.len() > capacity { Some(Self::split(page)) } else { None };
+        page.update_bounds();
+        res
+    }
+
+    /// splits the page in two halves. Returns the new sibling page.
+    ///
+    /// The split dimension is the first one in which both halves do not overlap.
+    fn split(page:&mut Page<T,Elt,NB_DIM>) -> Page<T,Elt,NB_DIM> {
+        let key = |c:&Content<T,Elt,NB_DIM>, i:usize, d:usize| -> (T,T) {
+            match c {
+                Content::Leaf(elts) => (elts[i].kth(d), elts[i].kth(d)),
+                Content::Internal(pages) => pages[i].b[d],
+            }
+        };
+        let n = page.len();
+        let mut order:Vec<usize> = (0..n).collect();
+        let mut split_dim = 0;
+        for d in 0..NB_DIM {
+            order.sort_by_key(|i| key(&page.content, *i, d));
+            let max_first = order[..n/2].iter().map(|i| key(&page.content, *i, d).1).max();
+            let min_second = order[n/2..].iter().map(|i| key(&page.content, *i, d).0).min();
+            if max_first < min_second { split_dim = d; break; }
+        }
+        match &mut page.content {
+            Content::Leaf(elts) => {
+                elts.sort_by_key(|e| e.kth(split_dim));
+                let second = elts.split_off(n/2);
+                Page::leaf(second)
+            },
+            Content::Internal(pages) => {
+                pages.sort_by_key(|p| p.b[split_dim]);
+                let second = pages.split_off(n/2);
+                Page::internal(second)
+            }
+        }
+    }
+
+    /// returns the element with the minimum (resp. maximum) guide in the page
+    fn rec_peek(page:&Page<T,Elt,NB_DIM>, minimum:bool) -> &Elt {
+        match &page.content {
+            Content::Leaf(elts) => {
+                if minimum { elts.iter().min_by_key(|e| e.guide()).unwrap() }
+                else { elts.iter().max_by_key(|e| e.guide()).unwrap() }
+            },
+            Content::Internal(pages) => {
+                let p = if minimum { pages.iter().min_by_key(|p| p.guide_lb).unwrap() }
+                    else { pages.iter().max_by_key(|p| p.guide_ub).unwrap() };
+                Self::rec_peek(p, minimum)
+            }
+        }
+    }
+
+    /// removes the element with the minimum (resp. maximum) guide in the page
+    fn rec_pop(page:&mut Page<T,Elt,NB_DIM>, minimum:bool) -> Elt {
+        let res = match &mut page.content {
+            Content::Leaf(elts) => {
+                let pos = if minimum {
+                    (0..elts.len()).min_by_key(|i| elts[*i].guide()).unwrap()
+                } else {
+                    (0..elts.len()).max_by_key(|i| elts[*i].guide()).unwrap()
+                };
+                elts.swap_remove(pos)
+            },
+            Content::Internal(pages) => {
+                let pos = if minimum {
+                    (0..pages.len()).min_by_key(|i| pages[*i].guide_lb).unwrap()
+                } else {
+                    (0..pages.len()).max_by_key(|i| pages[*i].guide_ub).unwrap()
+                };
+                let res = Self::rec_pop(&mut pages[pos], minimum);
+                if pages[pos].len() == 0 { pages.swap_remove(pos); }
+                res
+            }
+        };
+        if page.len() > 0 { page.update_bounds(); }
+        res
+    }
+
+    /// removes the element with the minimum (resp. maximum) guide in the front
+    fn pop(&mut self, minimum:bool) -> Option<Elt> {
+        let root = self.root.as_mut()?;
+        let res = Self::rec_pop(root, minimum);
+        self.nb_elements -= 1;
+        self.shrink_root();
+        Some(res)
+    }
+
+    /// removes the root if it is empty, and replaces it by its child if it has only one
+    fn shrink_root(&mut self) {
+        loop {
+            match self.root.take() {
+                Some(p) if p.len() == 0 => { return; },
+                Some(Page { content:Content::Internal(mut pages), .. }) if pages.len() == 1 => {
+                    self.root = pages.pop();
+                },
+                r => { self.root = r; return; },
+            }
+        }
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ParetoFront<T, Elt> for RTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn find_dominating(&self, elt:&Elt) -> Option<&Elt> {
+        self.root.as_ref().and_then(|p| Self::rec_find_dominating(p, elt))
+    }
+
+    fn insert_observed<O>(&mut self, elt:Elt, mut observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        if let Some(by) = self.find_dominating(&elt) {
+            observer(FrontEvent::RejectedDominated { element:&elt, by });
+            return false;
+        }
+        if let Some(root) = self.root.as_mut() {
+            self.nb_elements -= Self::rec_remove_dominated_by(root, &elt, &mut observer);
+            self.shrink_root();
+        }
+        observer(FrontEvent::Inserted(&elt));
+        self.nb_elements += 1;
+        match self.root.as_mut() {
+            None => { self.root = Some(Page::leaf(vec![elt])); },
+            Some(root) => {
+                if let Some(sibling) = Self::rec_insert(root, elt, self.capacity) {
+                    let previous_root = self.root.take().unwrap();
+                    self.root = Some(Page::internal(vec![previous_root, sibling]));
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> PriorityQueue<T, Elt> for RTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.root.as_ref().map(|p| Self::rec_peek(p, true))
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.root.as_ref().map(|p| Self::rec_peek(p, false))
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> { self.pop(true) }
+
+    fn pop_max(&mut self) -> Option<Elt> { self.pop(false) }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.insert_observed(elt, |_| {})
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        self.insert_observed(elt, on_evictions(evicted))
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.root.as_ref().map(|p| p.guide_lb)
+    }
+
+    /// recomputes the guide bounds of all the pages. O(n)
+    fn reprioritize(&mut self) {
+        if let Some(root) = self.root.as_mut() { Self::rec_update_bounds(root); }
+    }
+
+    fn peek_max_guide(&self) -> Option<T> {
+        self.root.as_ref().map(|p| p.guide_ub)
+    }
+}
+
+impl<T, Elt, const NB_DIM:usize> ExactSizeQueue<T, Elt> for RTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    fn len(&self) -> usize { self.nb_elements }
+}
+
+impl<T, Elt, const NB_DIM:usize> PeekKQueue<T, Elt> for RTreeFront<T, Elt, NB_DIM>
+where T:Ord+Copy, Elt:ParetoElement<T>+GuidedElement<T> {
+    /// best-first search over the pages (guided by their guide lower bound)
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        best_first_k(self.root.iter().map(Entry::Page), k, |entry| match entry {
+            Entry::Page(p) => p.guide_lb,
+            Entry::Elt(e) => e.guide(),
+        }, |entry, res| if let Entry::Page(p) = entry {
+            match &p.content {
+                Content::Leaf(elts) => res.extend(elts.iter().map(Entry::Elt)),
+                Content::Internal(pages) => res.extend(pages.iter().map(Entry::Page)),
+            }
+        }, |entry| match entry {
+            Entry::Page(_) => None,
+            Entry::Elt(e) => Some(*e),
+        })
+    }
+}
+
+/// Iterator over the elements of an R-tree front.
+#[derive(Debug)]
+pub struct RTreeIterator<'a, T, Elt, const NB_DIM:usize> {
+    /// pages to visit
+    pages:Vec<&'a Page<T,Elt,NB_DIM>>,
+    /// elements of the current leaf
+    leaf:std::slice::Iter<'a, Elt>,
+}
+
+impl<'a, T, Elt, const NB_DIM:usize> Iterator for RTreeIterator<'a, T, Elt, NB_DIM> {
+    type Item = &'a Elt;
+    fn next(&mut self) -> Option<&'a Elt> {
+        loop {
+            if let Some(e) = self.leaf.next() { return Some(e); }
+            match &self.pages.pop()?.content {
+                Content::Leaf(elts) => { self.leaf = elts.iter(); },
+                Content::Internal(pages) => { self.pages.extend(pages.iter()); },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    /// points on the anti-diagonal x+y = n (none dominates another)
+    fn diagonal(n:u32) -> Vec<CartesianParetoElement<2>> {
+        (0..=n).map(|i| CartesianParetoElement::new([i,n-i])).collect()
+    }
+
+    #[test]
+    fn test_inserts_and_splits() {
+        let mut front = RTreeFront::<u32, CartesianParetoElement<2>, 2>::with_page_capacity(4);
+        for e in diagonal(100) { assert!(front.insert(e)); }
+        assert_eq!(front.len(), 101);
+        assert!(front.height() > 2);
+        assert_eq!(front.iter().count(), 101);
+        assert!(!front.insert(CartesianParetoElement::new([50,51])));
+        // dominates the elements from [40,60] to [50,50]
+        assert!(front.insert(CartesianParetoElement::new([40,50])));
+        assert_eq!(front.len(), 101-11+1);
+    }
+
+    #[test]
+    fn test_pop_min_max() {
+        let mut front = RTreeFront::<u32, CartesianParetoElement<2>, 2>::with_page_capacity(3);
+        front.insert(CartesianParetoElement::new([1,8]));
+        front.insert(CartesianParetoElement::new([4,4]));
+        front.insert(CartesianParetoElement::new([9,1]));
+        front.insert(CartesianParetoElement::new([0,20]));
+        assert_eq!(front.peek_min_guide(), Some(8));
+        assert_eq!(front.peek_k(2), vec![
+            &CartesianParetoElement::new([4,4]), &CartesianParetoElement::new([1,8])
+        ]);
+        assert_eq!(front.pop_max(), Some(CartesianParetoElement::new([0,20])));
+        assert_eq!(front.pop_min(), Some(CartesianParetoElement::new([4,4])));
+        assert_eq!(front.pop_min(), Some(CartesianParetoElement::new([1,8])));
+        assert_eq!(front.pop_min(), Some(CartesianParetoElement::new([9,1])));
+        assert!(front.is_empty());
+        assert!(front.pop_min().is_none());
+    }
+
+    #[test]
+    fn test_bulk_loading() {
+        let mut elts = diagonal(200);
+        elts.push(CartesianParetoElement::new([100,101])); // dominated
+        let mut front = RTreeFront::<u32, CartesianParetoElement<2>, 2>::from_vec_with_page_capacity(elts, 8);
+        assert_eq!(front.len(), 201);
+        assert_eq!(front.height(), 3);
+        assert!(front.find_dominating(&CartesianParetoElement::new([150,51])).is_some());
+        assert!(front.find_dominating(&CartesianParetoElement::new([150,49])).is_none());
+        let mut count = 0;
+        while front.pop_min().is_some() { count += 1; }
+        assert_eq!(count, 201);
+    }
+
+    #[test]
+    fn test_bulk_loading_large() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(0);
+        let elts:Vec<CartesianParetoElement<3>> = (0..50_000)
+            .map(|_| CartesianParetoElement::new([rng.gen_range(0..1000), rng.gen_range(0..1000), rng.gen_range(0..1000)]))
+            .collect();
+        let mut inserted = RTreeFront::<u32, CartesianParetoElement<3>, 3>::default();
+        for e in elts.iter() { inserted.insert(e.clone()); }
+        let loaded = RTreeFront::<u32, CartesianParetoElement<3>, 3>::from_vec(elts);
+        let mut expected:Vec<_> = inserted.iter().cloned().collect();
+        let mut actual:Vec<_> = loaded.iter().cloned().collect();
+        expected.sort_by_key(|e| e.coordinates().collect::<Vec<_>>());
+        actual.sort_by_key(|e| e.coordinates().collect::<Vec<_>>());
+        assert_eq!(actual, expected);
+        // large front: points of equal coordinate sums (none dominates another), and duplicates
+        let mut antichain:Vec<CartesianParetoElement<3>> = (0..20_000).map(|_| {
+            let x = rng.gen_range(0..1000);
+            let y = rng.gen_range(0..1000);
+            CartesianParetoElement::new([x, y, 2000-x-y])
+        }).collect();
+        antichain.extend_from_slice(&antichain.clone());
+        let nb_distinct = antichain.iter().map(|e| e.coordinates().collect::<Vec<_>>()).collect::<std::collections::HashSet<_>>().len();
+        assert_eq!(RTreeFront::<u32, CartesianParetoElement<3>, 3>::from_vec(antichain).len(), nb_distinct);
+    }
+}
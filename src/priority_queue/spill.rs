@@ -0,0 +1,299 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
+/// counter used to give a unique identifier to each spilling queue (file names)
+static NEXT_QUEUE_ID:AtomicUsize = AtomicUsize::new(0);
+
+/// elements spilled to a file (sorted by guide). The maximum is kept in memory
+#[derive(Debug)]
+struct Run<T,Elt> {
+    /// path of the file
+    path:PathBuf,
+    /// number of elements in the file
+    len:usize,
+    /// minimum guide of the run
+    min_guide:T,
+    /// maximum element of the run (not written in the file)
+    max:Elt,
+}
+
+/// Disk-spilling priority queue (memory-bounded search).
+///
+/// Keeps at most a given number of elements in memory (in a min-max heap). When the cap is
+/// exceeded, the worst half of the in-memory elements is serialized (bincode) to a temporary
+/// file (a run). When the in-memory minimum is no longer lower than the minimum of a run,
+/// this run is reloaded (and its file removed). Thus, the in-memory minimum is always the
+/// minimum of the queue. Remaining files are removed when the queue is dropped.
+///
+/// The trait methods panic on IO errors, use try_insert and try_pop_min to handle them.
+/// No element is lost on IO errors: the elements of a failed spill stay in memory (beyond
+/// the cap), and a run is only forgotten once it is loaded back.
+/// peek_k only returns in-memory elements not worse than the spilled ones (possibly fewer
+/// than k elements).
+#[derive(Debug)]
+pub struct SpillingQueue<T,Elt> {
+    /// in-memory elements
+    memory:BinaryHeapQueue<T,Elt>,
+    /// maximum number of in-memory elements
+    cap:usize,
+    /// spilled runs
+    runs:Vec<Run<T,Elt>>,
+    /// directory of the spill files
+    dir:PathBuf,
+    /// identifier of the queue (unique in the process)
+    id:usize,
+    /// number of files created so far
+    nb_files:usize,
+}
+
+impl<T,Elt> SpillingQueue<T,Elt> {
+    /// creates an empty queue keeping at most cap elements in memory, spilling in the
+    /// temporary directory of the system
+    pub fn with_cap(cap:usize) -> Self { Self::with_cap_in(cap, std::env::temp_dir()) }
+
+    /// creates an empty queue keeping at most cap elements in memory, spilling in the given
+    /// directory
+    pub fn with_cap_in<P:AsRef<Path>>(cap:usize, dir:P) -> Self {
+        Self {
+            memory:BinaryHeapQueue::default(),
+            cap:cap.max(2),
+            runs:Vec::new(),
+            dir:dir.as_ref().to_path_buf(),
+            id:NEXT_QUEUE_ID.fetch_add(1, Ordering::Relaxed),
+            nb_files:0,
+        }
+    }
+
+    /// returns the maximum number of in-memory elements
+    pub fn cap(&self) -> usize { self.cap }
+
+    /// returns the number of in-memory elements
+    pub fn nb_in_memory(&self) -> usize { self.memory.len() }
+
+    /// returns the number of spilled runs (files)
+    pub fn nb_runs(&self) -> usize { self.runs.len() }
+}
+
+impl<T,Elt> SpillingQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Serialize+DeserializeOwned {
+    /// inserts an element, spilling the worst in-memory elements if the cap is exceeded.
+    /// The element is kept (in memory) even if the spill fails
+    pub fn try_insert(&mut self, elt:Elt) -> io::Result<bool> {
+        let res = self.memory.insert(elt);
+        self.spill_if_needed()?;
+        Ok(res)
+    }
+
+    /// pops the minimum, reloading a run if needed. The queue is unchanged on IO errors
+    pub fn try_pop_min(&mut self) -> io::Result<Option<Elt>> {
+        let res = self.memory.pop_min();
+        self.restore_on_error(res)
+    }
+
+    /// pops the maximum (reloads its run if it was spilled). The queue is unchanged on IO errors
+    pub fn try_pop_max(&mut self) -> io::Result<Option<Elt>> {
+        let mem_max = self.memory.peek_max_guide();
+        let run_max = self.runs.iter().enumerate().max_by_key(|(_,r)| r.max.guide());
+        if let Some((pos,r)) = run_max {
+            if mem_max.is_none_or(|g| r.max.guide() > g) { self.reload(pos)?; }
+        }
+        let res = self.memory.pop_max();
+        self.restore_on_error(res)
+    }
+
+    /// reloads a run and spills if needed after popping an element. Puts the popped element
+    /// back on errors
+    fn restore_on_error(&mut self, popped:Option<Elt>) -> io::Result<Option<Elt>> {
+        match self.reload_if_needed().and_then(|_| self.spill_if_needed()) {
+            Ok(()) => Ok(popped),
+            Err(e) => {
+                if let Some(elt) = popped { self.memory.insert(elt); }
+                Err(e)
+            }
+        }
+    }
+
+    /// reloads the run with the lowest minimum if it is lower than the in-memory minimum
+    fn reload_if_needed(&mut self) -> io::Result<()> {
+        let best = self.runs.iter().enumerate()
+            .min_by_key(|(_,r)| r.min_guide)
+            .map(|(i,r)| (i,r.min_guide));
+        if let Some((pos,g)) = best {
+            if self.memory.peek_min_guide().is_none_or(|m| g < m) { self.reload(pos)?; }
+        }
+        Ok(())
+    }
+
+    /// moves the elements of a run back to memory, then removes its file. The run is kept
+    /// if its file cannot be read or removed
+    fn reload(&mut self, pos:usize) -> io::Result<()> {
+        let path = &self.runs[pos].path;
+        let elts:Vec<Elt> = bincode::deserialize_from(BufReader::new(File::open(path)?))
+            .map_err(io::Error::other)?;
+        fs::remove_file(path)?;
+        let run = self.runs.swap_remove(pos);
+        for e in elts { self.memory.insert(e); }
+        self.memory.insert(run.max);
+        Ok(())
+    }
+
+    /// spills if the cap is exceeded
+    fn spill_if_needed(&mut self) -> io::Result<()> {
+        if self.memory.len() > self.cap { self.spill() } else { Ok(()) }
+    }
+
+    /// writes the worst half of the in-memory elements to a new run. The elements are put
+    /// back in memory if the write fails
+    fn spill(&mut self) -> io::Result<()> {
+        let nb_spilled = self.memory.len() / 2;
+        let Some(max) = self.memory.pop_max() else { return Ok(()); };
+        let mut elts:Vec<Elt> = (1..nb_spilled).filter_map(|_| self.memory.pop_max()).collect();
+        elts.reverse();
+        let min_guide = elts.first().map_or(max.guide(), |e| e.guide());
+        let path = self.dir.join(format!(
+            "do_util_spill_{}_{}_{}.bin", std::process::id(), self.id, self.nb_files
+        ));
+        let written = File::create(&path).and_then(|f| {
+            bincode::serialize_into(BufWriter::new(f), &elts).map_err(io::Error::other)
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&path);
+            for elt in elts { self.memory.insert(elt); }
+            self.memory.insert(max);
+            return Err(e);
+        }
+        self.nb_files += 1;
+        self.runs.push(Run { path, len:elts.len(), min_guide, max });
+        Ok(())
+    }
+}
+
+impl<T,Elt> PriorityQueue<T,Elt> for SpillingQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Serialize+DeserializeOwned {
+    fn peek_min(&self) -> Option<&Elt> { self.memory.peek_min() }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        let run_max = self.runs.iter().map(|r| &r.max).max_by_key(|e| e.guide());
+        match (self.memory.peek_max(), run_max) {
+            (Some(a), Some(b)) => Some(if b.guide() > a.guide() { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        self.try_pop_min().expect("unable to reload spilled elements")
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        self.try_pop_max().expect("unable to reload spilled elements")
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        self.try_insert(elt).expect("unable to spill elements")
+    }
+
+    fn peek_min_guide(&self) -> Option<T> { self.memory.peek_min_guide() }
+}
+
+impl<T,Elt> ExactSizeQueue<T,Elt> for SpillingQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Serialize+DeserializeOwned {
+    fn len(&self) -> usize {
+        self.memory.len() + self.runs.iter().map(|r| r.len + 1).sum::<usize>()
+    }
+}
+
+impl<T,Elt> PeekKQueue<T,Elt> for SpillingQueue<T,Elt>
+where T:Ord+Copy, Elt:GuidedElement<T>+Serialize+DeserializeOwned {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        let bound = self.runs.iter().map(|r| r.min_guide).min();
+        self.memory.peek_k(k).into_iter()
+            .take_while(|e| bound.is_none_or(|g| e.guide() <= g))
+            .collect()
+    }
+}
+
+impl<T,Elt> Drop for SpillingQueue<T,Elt> {
+    fn drop(&mut self) {
+        for r in &self.runs { let _ = fs::remove_file(&r.path); }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::fixtures::Value;
+
+    #[test]
+    fn test_spill_and_reload() {
+        let mut queue:SpillingQueue<u32,Value> = SpillingQueue::with_cap(10);
+        for i in 0..100 { queue.insert(Value((i*37) % 100)); }
+        assert!(queue.nb_in_memory() <= 10);
+        assert!(queue.nb_runs() > 0);
+        assert_eq!(queue.len(), 100);
+        assert_eq!(queue.peek_min(), Some(&Value(0)));
+        assert_eq!(queue.peek_max(), Some(&Value(99)));
+        assert_eq!(queue.pop_max(), Some(Value(99)));
+        let values:Vec<u32> = queue.drain_sorted().map(|v| v.0).collect();
+        assert_eq!(values, (0..99).collect::<Vec<u32>>());
+        assert_eq!(queue.nb_runs(), 0);
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let mut queue:SpillingQueue<u32,Value> = SpillingQueue::with_cap(4);
+        for i in 0..20 { queue.insert(Value(100 - i)); }
+        assert_eq!(queue.pop_min(), Some(Value(81)));
+        queue.insert(Value(200));
+        queue.insert(Value(50));
+        assert_eq!(queue.peek_min_guide(), Some(50));
+        assert!(queue.peek_k(3).iter().all(|e| e.0 <= 83));
+        assert_eq!(queue.pop_k(3), vec![Value(50), Value(82), Value(83)]);
+        assert_eq!(queue.pop_max(), Some(Value(200)));
+        assert_eq!(queue.len(), 17);
+    }
+
+    #[test]
+    fn test_files_removed() {
+        let dir = std::env::temp_dir().join(format!("do_util_spill_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        {
+            let mut queue:SpillingQueue<u32,Value> = SpillingQueue::with_cap_in(4, &dir);
+            for i in 0..50 { queue.insert(Value(i)); }
+            assert!(fs::read_dir(&dir).unwrap().count() > 0);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_io_errors() {
+        let dir = std::env::temp_dir().join(format!("do_util_spill_error_test_{}", std::process::id()));
+        let moved = dir.with_extension("moved");
+        let mut queue:SpillingQueue<u32,Value> = SpillingQueue::with_cap_in(4, &dir);
+        for i in 0..4 { queue.insert(Value(i)); }
+        assert!(queue.try_insert(Value(10)).is_err()); // missing directory
+        assert_eq!((queue.len(), queue.nb_runs()), (5, 0));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(queue.try_insert(Value(11)).unwrap());
+        assert_eq!((queue.len(), queue.nb_runs()), (6, 1));
+        fs::rename(&dir, &moved).unwrap(); // the run cannot be reloaded
+        assert_eq!(queue.pop_k(2), vec![Value(0), Value(1)]);
+        assert!(queue.try_pop_min().is_err());
+        assert!(queue.try_pop_max().is_err());
+        assert_eq!((queue.len(), queue.nb_runs()), (4, 1));
+        fs::rename(&moved, &dir).unwrap();
+        assert_eq!(queue.try_pop_max().unwrap(), Some(Value(11)));
+        let values:Vec<u32> = queue.drain_sorted().map(|v| v.0).collect();
+        assert_eq!(values, vec![2, 3, 10]);
+        fs::remove_dir(&dir).unwrap();
+    }
+}
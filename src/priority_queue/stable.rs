@@ -0,0 +1,189 @@
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement, PeekKQueue, PriorityQueue};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
+/// Element tagged with its insertion sequence number.
+///
+/// Its guide is the pair (guide of the element, sequence number), thus elements with equal
+/// guides are ordered by insertion.
+#[derive(Debug,Clone)]
+pub struct Sequenced<Elt> {
+    /// insertion sequence number
+    seq:u64,
+    /// element
+    elt:Elt,
+}
+
+impl<Elt> Sequenced<Elt> {
+    /// returns the insertion sequence number of the element
+    pub fn seq(&self) -> u64 { self.seq }
+
+    /// returns the element
+    pub fn elt(&self) -> &Elt { &self.elt }
+
+    /// returns the element, consuming the tagged element
+    pub fn into_elt(self) -> Elt { self.elt }
+}
+
+impl<T:Ord,Elt:GuidedElement<T>> GuidedElement<(T,u64)> for Sequenced<Elt> {
+    fn guide(&self) -> (T,u64) { (self.elt.guide(), self.seq) }
+}
+
+/// Stable (FIFO tie-breaking) queue.
+///
+/// Wraps a priority queue and guarantees that elements with equal guides are popped (by
+/// pop_min) in insertion order, whatever the underlying structure. pop_max pops the most
+/// recently inserted element among the ones with the maximum guide.
+/// This makes runs reproducible across queue implementations.
+#[derive(Debug,Clone)]
+pub struct StableQueue<T,Elt,Q=BinaryHeapQueue<(T,u64),Sequenced<Elt>>> {
+    /// underlying queue
+    queue:Q,
+    /// next sequence number
+    next_seq:u64,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> Default for StableQueue<T,Elt,Q> {
+    fn default() -> Self {
+        Self { queue:Q::default(), next_seq:0, phantom:PhantomData }
+    }
+}
+
+impl<T,Elt,Q> StableQueue<T,Elt,Q> {
+    /// returns the underlying queue
+    pub fn inner(&self) -> &Q { &self.queue }
+}
+
+impl<T,Elt,Q> PriorityQueue<T,Elt> for StableQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:PriorityQueue<(T,u64),Sequenced<Elt>> {
+    fn peek_min(&self) -> Option<&Elt> {
+        self.queue.peek_min().map(|e| e.elt())
+    }
+
+    fn peek_max(&self) -> Option<&Elt> {
+        self.queue.peek_max().map(|e| e.elt())
+    }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        self.queue.pop_min().map(|e| e.into_elt())
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        self.queue.pop_max().map(|e| e.into_elt())
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queue.insert(Sequenced { seq, elt })
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, mut evicted:F) -> bool {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queue.insert_evicting(Sequenced { seq, elt }, |e| evicted(e.elt()))
+    }
+
+    fn peek_min_guide(&self) -> Option<T> {
+        self.queue.peek_min_guide().map(|(g,_)| g)
+    }
+
+    fn peek_max_guide(&self) -> Option<T> {
+        self.queue.peek_max_guide().map(|(g,_)| g)
+    }
+
+    fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    fn reprioritize(&mut self) { self.queue.reprioritize() }
+
+    fn pop_k(&mut self, k:usize) -> Vec<Elt> {
+        self.queue.pop_k(k).into_iter().map(|e| e.into_elt()).collect()
+    }
+
+    /// re-inserts the elements that are not chosen with their sequence numbers (their
+    /// insertion order is kept)
+    fn pop_random_within<R:Rng+?Sized>(&mut self, rng:&mut R, max_guide:T) -> Option<Elt> {
+        let mut candidates = Vec::new();
+        while self.queue.peek_min_guide().is_some_and(|(g,_)| g <= max_guide) {
+            candidates.extend(self.queue.pop_min());
+        }
+        if candidates.is_empty() { return None; }
+        let res = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        for elt in candidates { self.queue.insert(elt); }
+        Some(res.into_elt())
+    }
+}
+
+impl<T,Elt,Q> ExactSizeQueue<T,Elt> for StableQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<(T,u64),Sequenced<Elt>> {
+    fn len(&self) -> usize { self.queue.len() }
+}
+
+impl<T,Elt,Q> PeekKQueue<T,Elt> for StableQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:PeekKQueue<(T,u64),Sequenced<Elt>> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> {
+        self.queue.peek_k(k).into_iter().map(|e| e.elt()).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::priority_queue::leftist_heap::LeftistHeap;
+    use crate::priority_queue::pairing_heap::PairingHeap;
+
+    /// element with an identifier, guided by its second field
+    #[derive(Debug,Clone,PartialEq,Eq)]
+    struct Label(usize, u32);
+
+    impl GuidedElement<u32> for Label {
+        fn guide(&self) -> u32 { self.1 }
+    }
+
+    /// inserts labels with many ties, then returns the identifiers in pop_min order
+    fn pop_order<Q:PriorityQueue<(u32,u64),Sequenced<Label>>+Default>() -> Vec<usize> {
+        let mut queue:StableQueue<u32,Label,Q> = StableQueue::default();
+        for i in 0..30 { queue.insert(Label(i, (i % 3) as u32)); }
+        std::iter::from_fn(|| queue.pop_min()).map(|l| l.0).collect()
+    }
+
+    #[test]
+    fn test_fifo_ties() {
+        let expected:Vec<usize> = (0..3).flat_map(|g| (0..10).map(move |j| 3*j+g)).collect();
+        assert_eq!(pop_order::<BinaryHeapQueue<(u32,u64),Sequenced<Label>>>(), expected);
+        assert_eq!(pop_order::<PairingHeap<(u32,u64),Sequenced<Label>>>(), expected);
+        assert_eq!(pop_order::<LeftistHeap<(u32,u64),Sequenced<Label>>>(), expected);
+    }
+
+    #[test]
+    fn test_pop_max() {
+        let mut queue:StableQueue<u32,Label> = StableQueue::default();
+        queue.insert(Label(0, 5));
+        queue.insert(Label(1, 5));
+        queue.insert(Label(2, 1));
+        assert_eq!(queue.peek_max_guide(), Some(5));
+        assert_eq!(queue.pop_max(), Some(Label(1, 5)));
+        assert_eq!(queue.pop_k(2), vec![Label(2, 1), Label(0, 5)]);
+    }
+
+    #[test]
+    fn test_random_pop_keeps_ties_order() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut queue:StableQueue<u32,Label> = StableQueue::default();
+        for i in 0..8 { queue.insert(Label(i, (i % 2) as u32)); }
+        let chosen = queue.pop_random_within(&mut rng, 0).unwrap();
+        queue.insert(Label(8, 0));
+        let order:Vec<usize> = std::iter::from_fn(|| queue.pop_min()).map(|l| l.0).collect();
+        let expected:Vec<usize> = [0, 2, 4, 6, 8, 1, 3, 5, 7].into_iter().filter(|i| *i != chosen.0).collect();
+        assert_eq!(order, expected);
+    }
+}
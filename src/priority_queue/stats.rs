@@ -0,0 +1,188 @@
+use std::marker::PhantomData;
+
+use crate::priority_queue::{ExactSizeQueue, FrontEvent, GuidedElement, ParetoElement, ParetoFront, PeekKQueue, PriorityQueue};
+
+/// Statistics collected by a [StatsQueue].
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct QueueStats {
+    /// number of insertion attempts
+    pub nb_inserts:usize,
+    /// number of rejected insertions (e.g. dominated elements, or elements not entering a beam)
+    pub nb_rejections:usize,
+    /// number of elements evicted by insertions (e.g. elements dominated by the inserted one)
+    pub nb_evictions:usize,
+    /// number of popped elements (minimum or maximum)
+    pub nb_pops:usize,
+    /// maximum number of elements in the queue
+    pub max_len:usize,
+}
+
+impl QueueStats {
+    /// returns the number of accepted insertions
+    pub fn nb_accepted(&self) -> usize { self.nb_inserts - self.nb_rejections }
+}
+
+/// Statistics facade.
+///
+/// Wraps a priority queue (or a Pareto front) and counts insertions, rejections, evictions,
+/// pops, and the maximum size reached. Evictions are deduced from the size of the queue
+/// after an accepted insertion, thus they are counted for any queue (Pareto fronts, beams).
+#[derive(Debug,Clone)]
+pub struct StatsQueue<T,Elt,Q> {
+    /// underlying queue
+    queue:Q,
+    /// statistics collected so far
+    stats:QueueStats,
+    /// phantom for types T and Elt
+    phantom:PhantomData<(T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> Default for StatsQueue<T,Elt,Q> {
+    fn default() -> Self { Self::new(Q::default()) }
+}
+
+impl<T,Elt,Q> StatsQueue<T,Elt,Q> {
+    /// wraps a queue
+    pub fn new(queue:Q) -> Self {
+        Self { queue, stats:QueueStats::default(), phantom:PhantomData }
+    }
+
+    /// returns the statistics collected so far
+    pub fn report(&self) -> QueueStats { self.stats }
+
+    /// resets the statistics
+    pub fn reset_stats(&mut self) { self.stats = QueueStats::default(); }
+
+    /// returns the underlying queue
+    pub fn inner(&self) -> &Q { &self.queue }
+
+    /// returns the underlying queue, consuming the wrapper
+    pub fn into_inner(self) -> Q { self.queue }
+}
+
+impl<T,Elt,Q> StatsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    /// counts an insertion given the size of the queue before it
+    fn record_insert(&mut self, inserted:bool, len_before:usize) {
+        self.stats.nb_inserts += 1;
+        let len = self.queue.len();
+        if inserted {
+            self.stats.nb_evictions += (len_before + 1).saturating_sub(len);
+        } else {
+            self.stats.nb_rejections += 1;
+        }
+        self.stats.max_len = self.stats.max_len.max(len);
+    }
+
+    /// counts a pop
+    fn record_pop(&mut self, res:Option<Elt>) -> Option<Elt> {
+        if res.is_some() { self.stats.nb_pops += 1; }
+        res
+    }
+}
+
+impl<T,Elt,Q> PriorityQueue<T,Elt> for StatsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    fn peek_min(&self) -> Option<&Elt> { self.queue.peek_min() }
+
+    fn peek_max(&self) -> Option<&Elt> { self.queue.peek_max() }
+
+    fn pop_min(&mut self) -> Option<Elt> {
+        let res = self.queue.pop_min();
+        self.record_pop(res)
+    }
+
+    fn pop_max(&mut self) -> Option<Elt> {
+        let res = self.queue.pop_max();
+        self.record_pop(res)
+    }
+
+    fn insert(&mut self, elt:Elt) -> bool {
+        let len_before = self.queue.len();
+        let res = self.queue.insert(elt);
+        self.record_insert(res, len_before);
+        res
+    }
+
+    fn insert_evicting<F:FnMut(&Elt)>(&mut self, elt:Elt, evicted:F) -> bool {
+        let len_before = self.queue.len();
+        let res = self.queue.insert_evicting(elt, evicted);
+        self.record_insert(res, len_before);
+        res
+    }
+
+    fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    fn peek_min_guide(&self) -> Option<T> { self.queue.peek_min_guide() }
+
+    fn peek_max_guide(&self) -> Option<T> { self.queue.peek_max_guide() }
+
+    fn reprioritize(&mut self) { self.queue.reprioritize() }
+}
+
+impl<T,Elt,Q> ExactSizeQueue<T,Elt> for StatsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt> {
+    fn len(&self) -> usize { self.queue.len() }
+}
+
+impl<T,Elt,Q> PeekKQueue<T,Elt> for StatsQueue<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:PeekKQueue<T,Elt>+ExactSizeQueue<T,Elt> {
+    fn peek_k(&self, k:usize) -> Vec<&Elt> { self.queue.peek_k(k) }
+}
+
+impl<T,Elt,Q> ParetoFront<T,Elt> for StatsQueue<T,Elt,Q>
+where T:Ord, Elt:ParetoElement<T>+GuidedElement<T>, Q:ParetoFront<T,Elt>+ExactSizeQueue<T,Elt> {
+    fn find_dominating(&self, elt:&Elt) -> Option<&Elt> { self.queue.find_dominating(elt) }
+
+    fn insert_observed<O>(&mut self, elt:Elt, observer:O) -> bool
+    where O:FnMut(FrontEvent<'_,Elt>) {
+        let len_before = self.queue.len();
+        let res = self.queue.insert_observed(elt, observer);
+        self.record_insert(res, len_before);
+        res
+    }
+
+    fn new_with_discretization(hint:&[Option<(T,T,T)>]) -> Self {
+        Self::new(Q::new_with_discretization(hint))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::priority_queue::beam::BeamQueue;
+    use crate::priority_queue::fixtures::Value;
+    use crate::priority_queue::kd_tree::KDTreeFront;
+    use crate::priority_queue::util::CartesianParetoElement;
+
+    #[test]
+    fn test_pareto_stats() {
+        let mut front:StatsQueue<u32,CartesianParetoElement<2>,KDTreeFront<u32,CartesianParetoElement<2>,2>> =
+            StatsQueue::default();
+        front.insert(CartesianParetoElement::new([5,5]));
+        front.insert(CartesianParetoElement::new([3,7]));
+        front.insert(CartesianParetoElement::new([6,6])); // rejected
+        front.insert(CartesianParetoElement::new([1,1])); // evicts both
+        front.pop_min();
+        front.pop_min();
+        assert_eq!(front.report(), QueueStats {
+            nb_inserts:4, nb_rejections:1, nb_evictions:2, nb_pops:1, max_len:2
+        });
+        assert_eq!(front.report().nb_accepted(), 3);
+        front.reset_stats();
+        assert_eq!(front.report(), QueueStats::default());
+    }
+
+    #[test]
+    fn test_beam_stats() {
+        let mut beam = StatsQueue::new(BeamQueue::with_width(2));
+        for v in [4, 6, 7, 2, 1] { beam.insert(Value(v)); }
+        beam.pop_max();
+        let stats = beam.report();
+        assert_eq!((stats.nb_inserts, stats.nb_rejections, stats.nb_evictions), (5, 1, 2));
+        assert_eq!((stats.nb_pops, stats.max_len), (1, 2));
+        assert_eq!(beam.into_inner().len(), 1);
+    }
+}
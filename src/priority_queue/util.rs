@@ -1,3 +1,12 @@
+use std::cmp::{Ordering, Reverse};
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
+use std::num::ParseFloatError;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+use std::sync::Arc;
+
 use crate::priority_queue::{ParetoElement};
 
 use super::GuidedElement;
@@ -39,49 +48,613 @@ impl<const NB_DIM:usize> GuidedElement<u32> for CartesianParetoElement<NB_DIM> {
 }
 
 
+/// Element guided by a weighted sum of its coordinates (scalarization).
+///
+/// Forwards the pareto element, and defines the guide as sum_i w_i.c_i. With non-negative
+/// weights, an element dominating another one has a lower or equal guide.
+/// Weights are shared between elements (cloning them is cheap).
+#[derive(Debug,Clone)]
+pub struct WeightedSumGuide<T,E> {
+    /// element
+    elt:E,
+    /// weight of each dimension
+    weights:Arc<[T]>,
+}
+
+impl<T,E> WeightedSumGuide<T,E> {
+    /// wraps the element with the given weights (one per dimension)
+    pub fn new(elt:E, weights:Arc<[T]>) -> Self { Self { elt, weights } }
+
+    /// returns the element
+    pub fn elt(&self) -> &E { &self.elt }
+
+    /// returns the element, consuming the wrapper
+    pub fn into_elt(self) -> E { self.elt }
+
+    /// returns the weights
+    pub fn weights(&self) -> &[T] { &self.weights }
+}
+
+impl<T,E> GuidedElement<T> for WeightedSumGuide<T,E>
+where T:Ord+Copy+Default+Add<Output=T>+Mul<Output=T>, E:ParetoElement<T> {
+    fn guide(&self) -> T {
+        self.elt.coordinates().zip(self.weights.iter())
+            .fold(T::default(), |acc,(c,w)| acc + *w * c)
+    }
+}
+
+impl<T,E> ParetoElement<T> for WeightedSumGuide<T,E>
+where T:Ord, E:ParetoElement<T> {
+    type CoordIterator = E::CoordIterator;
+
+    fn coordinates(&self) -> Self::CoordIterator { self.elt.coordinates() }
+
+    fn dominates(&self, other:&Self) -> bool { self.elt.dominates(&other.elt) }
+
+    fn nb_dimensions(&self) -> usize { self.elt.nb_dimensions() }
+
+    fn kth(&self, k:usize) -> T { self.elt.kth(k) }
+}
+
+/// Element guided by a weighted Chebyshev distance to a reference point (scalarization).
+///
+/// Forwards the pareto element, and defines the guide as max_i w_i.(c_i - z_i), where z is
+/// the reference (ideal) point. Coordinates are assumed to be greater or equal than the
+/// reference point. Without reference point, z is the origin (T::default()).
+#[derive(Debug,Clone)]
+pub struct ChebyshevGuide<T,E> {
+    /// element
+    elt:E,
+    /// weight of each dimension
+    weights:Arc<[T]>,
+    /// reference point (origin if None)
+    reference:Option<Arc<[T]>>,
+}
+
+impl<T,E> ChebyshevGuide<T,E> {
+    /// wraps the element with the given weights (one per dimension), using the origin as
+    /// reference point
+    pub fn new(elt:E, weights:Arc<[T]>) -> Self { Self { elt, weights, reference:None } }
+
+    /// wraps the element with the given weights and reference point
+    pub fn with_reference(elt:E, weights:Arc<[T]>, reference:Arc<[T]>) -> Self {
+        Self { elt, weights, reference:Some(reference) }
+    }
+
+    /// returns the element
+    pub fn elt(&self) -> &E { &self.elt }
+
+    /// returns the element, consuming the wrapper
+    pub fn into_elt(self) -> E { self.elt }
+
+    /// returns the weights
+    pub fn weights(&self) -> &[T] { &self.weights }
+}
+
+impl<T,E> GuidedElement<T> for ChebyshevGuide<T,E>
+where T:Ord+Copy+Default+Sub<Output=T>+Mul<Output=T>, E:ParetoElement<T> {
+    fn guide(&self) -> T {
+        self.elt.coordinates().zip(self.weights.iter()).enumerate()
+            .map(|(i,(c,w))| match &self.reference {
+                None => *w * c,
+                Some(z) => *w * (c - z[i]),
+            })
+            .max().unwrap_or_default()
+    }
+}
+
+impl<T,E> ParetoElement<T> for ChebyshevGuide<T,E>
+where T:Ord, E:ParetoElement<T> {
+    type CoordIterator = E::CoordIterator;
+
+    fn coordinates(&self) -> Self::CoordIterator { self.elt.coordinates() }
+
+    fn dominates(&self, other:&Self) -> bool { self.elt.dominates(&other.elt) }
+
+    fn nb_dimensions(&self) -> usize { self.elt.nb_dimensions() }
+
+    fn kth(&self, k:usize) -> T { self.elt.kth(k) }
+}
+
+
+/// Element whose guide order is reversed (a min-queue of reversed elements is a max-queue).
+///
+/// Its guide is the guide of the element wrapped in std::cmp::Reverse.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ReverseGuide<E>(pub E);
+
+impl<E> ReverseGuide<E> {
+    /// returns the element
+    pub fn elt(&self) -> &E { &self.0 }
+
+    /// returns the element, consuming the wrapper
+    pub fn into_elt(self) -> E { self.0 }
+}
+
+impl<T:Ord,E:GuidedElement<T>> GuidedElement<Reverse<T>> for ReverseGuide<E> {
+    fn guide(&self) -> Reverse<T> { Reverse(self.0.guide()) }
+}
+
+/// Totally ordered f64, allowing floating-point guides (e.g. LP bounds) and coordinates.
+///
+/// Values are compared with f64::total_cmp: -0.0 is lower than 0.0, and NaN values are
+/// greater than +inf (negative NaN values lower than -inf).
+#[derive(Debug,Clone,Copy,Default)]
+pub struct OrderedF64(pub f64);
+
+impl OrderedF64 {
+    /// returns the wrapped float
+    pub fn get(self) -> f64 { self.0 }
+}
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other:&Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other:&Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other:&Self) -> Ordering { self.0.total_cmp(&other.0) }
+}
+
+impl Hash for OrderedF64 {
+    fn hash<H:Hasher>(&self, state:&mut H) { self.0.to_bits().hash(state) }
+}
+
+impl From<f64> for OrderedF64 {
+    fn from(v:f64) -> Self { Self(v) }
+}
+
+impl From<OrderedF64> for f64 {
+    fn from(v:OrderedF64) -> Self { v.0 }
+}
+
+impl Add for OrderedF64 {
+    type Output = Self;
+    fn add(self, rhs:Self) -> Self { Self(self.0 + rhs.0) }
+}
+
+impl Sub for OrderedF64 {
+    type Output = Self;
+    fn sub(self, rhs:Self) -> Self { Self(self.0 - rhs.0) }
+}
+
+impl Mul for OrderedF64 {
+    type Output = Self;
+    fn mul(self, rhs:Self) -> Self { Self(self.0 * rhs.0) }
+}
+
+impl Neg for OrderedF64 {
+    type Output = Self;
+    fn neg(self) -> Self { Self(-self.0) }
+}
+
+impl Display for OrderedF64 {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+}
+
+impl FromStr for OrderedF64 {
+    type Err = ParseFloatError;
+    fn from_str(s:&str) -> Result<Self, Self::Err> { s.parse().map(Self) }
+}
+
+/// Element guided by a floating-point value.
+///
+/// Implementing it provides GuidedElement<OrderedF64>, so that the element can be stored in
+/// any queue (or Pareto front with OrderedF64 coordinates).
+pub trait FloatGuidedElement {
+    /// returns the (floating-point) guide of the element
+    fn float_guide(&self) -> f64;
+}
+
+impl<E:FloatGuidedElement> GuidedElement<OrderedF64> for E {
+    fn guide(&self) -> OrderedF64 { OrderedF64(self.float_guide()) }
+}
+
+
+/// URL of the Vega-Lite schema used by the exports
+const VEGA_LITE_SCHEMA:&str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+/// formats a coordinate as a JSON number. Coordinates that are not finite numbers (NaN or
+/// infinite floats) are not valid JSON, and are written as null
+fn json_number<T:Display>(c:&T) -> String {
+    let res = c.to_string();
+    if res.parse::<f64>().is_ok_and(f64::is_finite) { res } else { "null".to_string() }
+}
+
+/// builds the JSON fields of each data point (with the given field names)
+fn json_fields<'a,T,Elt,I>(elts:I, fields:&[&str]) -> Vec<String>
+where T:Ord+Display, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt> {
+    elts.into_iter().map(|e| {
+        let coords:Vec<String> = fields.iter().zip(e.coordinates())
+            .map(|(f,c)| format!("\"{}\":{}", f, json_number(&c)))
+            .collect();
+        coords.join(",")
+    }).collect()
+}
+
+/// exports a 2D front as a Vega-Lite specification (JSON).
+///
+/// The front is displayed as a scatter plot with its staircase. Like the other JSON exports,
+/// non-finite coordinates are written as null (Vega-Lite skips them).
+pub fn export_2d_visualization<'a,T,Elt,I>(elts:I) -> String
+where T:Ord+Display, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt> {
+    format!(
+        "{{\"$schema\":\"{}\",\"data\":{{\"values\":[{}]}},\"encoding\":{{\
+        \"x\":{{\"field\":\"x\",\"type\":\"quantitative\"}},\
+        \"y\":{{\"field\":\"y\",\"type\":\"quantitative\"}}}},\"layer\":[\
+        {{\"mark\":{{\"type\":\"line\",\"interpolate\":\"step-after\"}}}},\
+        {{\"mark\":\"point\"}}]}}",
+        VEGA_LITE_SCHEMA,
+        json_fields(elts, &["x","y"]).iter()
+            .map(|p| format!("{{{}}}", p))
+            .collect::<Vec<String>>().join(",")
+    )
+}
+
+/// exports a 3D front as a Plotly figure (JSON) displaying a 3D scatter plot.
+///
+/// Vega-Lite does not support 3D plots, the resulting figure can be displayed using
+/// `plotly.io.from_json` in Python or `Plotly.newPlot` in Javascript.
+pub fn export_3d_visualization<'a,T,Elt,I>(elts:I) -> String
+where T:Ord+Display, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt> {
+    let mut axes:[Vec<String>;3] = Default::default();
+    for e in elts {
+        for (axis,c) in axes.iter_mut().zip(e.coordinates()) {
+            axis.push(json_number(&c));
+        }
+    }
+    format!(
+        "{{\"data\":[{{\"type\":\"scatter3d\",\"mode\":\"markers\",\
+        \"x\":[{}],\"y\":[{}],\"z\":[{}]}}]}}",
+        axes[0].join(","), axes[1].join(","), axes[2].join(",")
+    )
+}
+
+/// exports a front as a Vega-Lite parallel coordinates specification (JSON).
+///
+/// Each element is displayed as a line crossing one vertical axis per objective.
+/// Intended for fronts with many (typically 4 or more) objectives.
+pub fn export_parallel_coordinates<'a,T,Elt,I>(elts:I, nb_dimensions:usize) -> String
+where T:Ord+Display, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt> {
+    let fields:Vec<String> = (0..nb_dimensions).map(|i| format!("f{}", i)).collect();
+    let field_refs:Vec<&str> = fields.iter().map(|f| f.as_str()).collect();
+    let elts_with_ids:Vec<String> = json_fields(elts, &field_refs).iter().enumerate()
+        .map(|(i,p)| format!("{{\"id\":{},{}}}", i, p))
+        .collect();
+    let fold:Vec<String> = fields.iter().map(|f| format!("\"{}\"", f)).collect();
+    format!(
+        "{{\"$schema\":\"{}\",\"data\":{{\"values\":[{}]}},\
+        \"transform\":[{{\"fold\":[{}]}}],\"mark\":\"line\",\"encoding\":{{\
+        \"x\":{{\"field\":\"key\",\"type\":\"nominal\"}},\
+        \"y\":{{\"field\":\"value\",\"type\":\"quantitative\"}},\
+        \"detail\":{{\"field\":\"id\",\"type\":\"nominal\"}}}}}}",
+        VEGA_LITE_SCHEMA, elts_with_ids.join(","), fold.join(",")
+    )
+}
+
+/// renders a 2D front as a SVG image of the given size (in pixels).
+///
+/// Elements are displayed as points, linked by their staircase (minimization on both axes).
+pub fn export_2d_svg<'a,T,Elt,I>(elts:I, width:u32, height:u32) -> String
+where T:Ord+Copy+Into<f64>, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt> {
+    let margin = 10.;
+    let mut points:Vec<(f64,f64)> = elts.into_iter()
+        .map(|e| (e.kth(0).into(), e.kth(1).into()))
+        .collect();
+    points.sort_by(|a,b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let mut res = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width, height
+    );
+    if !points.is_empty() {
+        let (min_x, max_x) = (points[0].0, points[points.len()-1].0);
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        // projects the point in the image (y axis goes downwards)
+        let project = |(x,y):(f64,f64)| -> (f64,f64) {
+            let px = if max_x > min_x { (x-min_x)/(max_x-min_x) } else { 0.5 };
+            let py = if max_y > min_y { (y-min_y)/(max_y-min_y) } else { 0.5 };
+            (
+                margin + px*(width as f64 - 2.*margin),
+                height as f64 - margin - py*(height as f64 - 2.*margin)
+            )
+        };
+        let mut path = String::new();
+        for (i,p) in points.iter().enumerate() {
+            let (x,y) = project(*p);
+            if i == 0 { path += format!("M{:.1},{:.1}", x, y).as_str(); }
+            else {
+                let (_,prev_y) = project(points[i-1]);
+                path += format!(" L{:.1},{:.1} L{:.1},{:.1}", x, prev_y, x, y).as_str();
+            }
+        }
+        res += format!(
+            "\t<path d=\"{}\" fill=\"none\" stroke=\"steelblue\"/>\n", path
+        ).as_str();
+        for p in &points {
+            let (x,y) = project(*p);
+            res += format!(
+                "\t<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"steelblue\"/>\n", x, y
+            ).as_str();
+        }
+    }
+    res += "</svg>";
+    res
+}
+
+
+/// writes the front as CSV: one row per element, one column per coordinate.
+///
+/// If a header is given, it is written as the first row.
+pub fn write_front_csv<'a,T,Elt,I,W>(writer:&mut W, elts:I, header:Option<&[&str]>) -> io::Result<()>
+where T:Ord+Display, Elt:ParetoElement<T>+'a, I:IntoIterator<Item=&'a Elt>, W:Write {
+    if let Some(h) = header {
+        writeln!(writer, "{}", h.join(","))?;
+    }
+    for e in elts {
+        let row:Vec<String> = e.coordinates().map(|c| c.to_string()).collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// reads a front from CSV rows (one element per row, one column per coordinate).
+///
+/// Each row is parsed and translated into an element by the row adapter.
+/// Empty lines are ignored. If has_header is true, the first row is skipped.
+/// Returns an `InvalidData` error if some value cannot be parsed.
+pub fn read_front_csv<T,Elt,R,F>(reader:R, has_header:bool, mut adapter:F) -> io::Result<Vec<Elt>>
+where T:FromStr, R:BufRead, F:FnMut(&[T]) -> Elt {
+    let mut res = Vec::new();
+    let mut row:Vec<T> = Vec::new();
+    for (i,line_res) in reader.lines().enumerate() {
+        let line = line_res?;
+        if (i == 0 && has_header) || line.trim().is_empty() { continue; }
+        row.clear();
+        for v in line.split(',') {
+            match v.trim().parse::<T>() {
+                Ok(x) => row.push(x),
+                Err(_) => return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: unable to parse value \"{}\"", i+1, v)
+                )),
+            }
+        }
+        res.push(adapter(&row));
+    }
+    Ok(res)
+}
+
+/// reads a front of cartesian elements from CSV rows (see [read_front_csv]).
+///
+/// Returns an `InvalidData` error if some row does not contain NB_DIM values.
+pub fn read_cartesian_front_csv<R,const NB_DIM:usize>(reader:R, has_header:bool)
+-> io::Result<Vec<CartesianParetoElement<NB_DIM>>> where R:BufRead {
+    let rows = read_front_csv(reader, has_header, |row:&[u32]| row.to_vec())?;
+    rows.into_iter().map(|row| {
+        let coords:[u32;NB_DIM] = row.try_into().map_err(|r:Vec<u32>| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} values per row, found {}", NB_DIM, r.len())
+        ))?;
+        Ok(CartesianParetoElement::new(coords))
+    }).collect()
+}
+
+
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
 
+    use crate::priority_queue::PriorityQueue;
+    use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
     #[test]
-    pub fn test_strict_dominance() {
+    fn test_strict_dominance() {
         let e1 = CartesianParetoElement::new([0]);
         let e2 = CartesianParetoElement::new([1]);
         assert!(e1.dominates(&e2));
     }
 
     #[test]
-    pub fn test_nonstrict_dominance() {
+    fn test_nonstrict_dominance() {
         let e1 = CartesianParetoElement::new([0]);
         let e2 = CartesianParetoElement::new([0]);
         assert!(e1.dominates(&e2));
     }
 
     #[test]
-    pub fn test_non_dominance() {
+    fn test_non_dominance() {
         let e1 = CartesianParetoElement::new([1]);
         let e2 = CartesianParetoElement::new([0]);
         assert!(!e1.dominates(&e2));
     }
 
     #[test]
-    pub fn test_2d_dominance() {
+    fn test_2d_dominance() {
         let e1 = CartesianParetoElement::new([1,0]);
         let e2 = CartesianParetoElement::new([2,1]);
         assert!(e1.dominates(&e2));
     }
 
     #[test]
-    pub fn test_2d_dominance2() {
+    fn test_2d_dominance2() {
         let e1 = CartesianParetoElement::new([1,0]);
         let e2 = CartesianParetoElement::new([1,1]);
         assert!(e1.dominates(&e2));
     }
 
     #[test]
-    pub fn test_2d_non_dominance() {
+    fn test_2d_non_dominance() {
         let e1 = CartesianParetoElement::new([1,0]);
         let e2 = CartesianParetoElement::new([0,1]);
         assert!(!e1.dominates(&e2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_export_2d_visualization() {
+        let front = [CartesianParetoElement::new([1,3]), CartesianParetoElement::new([2,1])];
+        let res = export_2d_visualization(front.iter());
+        assert!(res.contains("[{\"x\":1,\"y\":3},{\"x\":2,\"y\":1}]"));
+    }
+
+    #[test]
+    fn test_export_3d_visualization() {
+        let front = [CartesianParetoElement::new([1,3,4]), CartesianParetoElement::new([2,1,5])];
+        let res = export_3d_visualization(front.iter());
+        assert!(res.contains("\"x\":[1,2],\"y\":[3,1],\"z\":[4,5]"));
+    }
+
+    #[test]
+    fn test_export_parallel_coordinates() {
+        let front = [CartesianParetoElement::new([1,3,4,2]), CartesianParetoElement::new([2,1,5,0])];
+        let res = export_parallel_coordinates(front.iter(), 4);
+        assert!(res.contains("{\"id\":1,\"f0\":2,\"f1\":1,\"f2\":5,\"f3\":0}"));
+        assert!(res.contains("\"fold\":[\"f0\",\"f1\",\"f2\",\"f3\"]"));
+    }
+
+    #[test]
+    fn test_weighted_sum_guide() {
+        let weights:Arc<[u32]> = Arc::from([1,3]);
+        let a = WeightedSumGuide::new(CartesianParetoElement::new([10,2]), weights.clone());
+        let b = WeightedSumGuide::new(CartesianParetoElement::new([2,5]), weights);
+        assert_eq!(a.guide(), 16);
+        assert_eq!(b.guide(), 17);
+        assert!(!a.dominates(&b));
+        let mut queue = BinaryHeapQueue::default();
+        queue.insert(b);
+        queue.insert(a);
+        assert_eq!(queue.pop_min().map(|e| e.into_elt()), Some(CartesianParetoElement::new([10,2])));
+    }
+
+    #[test]
+    fn test_chebyshev_guide() {
+        let weights:Arc<[u32]> = Arc::from([1,2]);
+        let a = ChebyshevGuide::new(CartesianParetoElement::new([10,2]), weights.clone());
+        assert_eq!(a.guide(), 10);
+        let b = ChebyshevGuide::with_reference(
+            CartesianParetoElement::new([10,7]), weights, Arc::from([8,1])
+        );
+        assert_eq!(b.guide(), 12);
+        assert_eq!(b.kth(1), 7);
+    }
+
+    #[test]
+    fn test_export_2d_svg() {
+        let front = [CartesianParetoElement::new([1,3]), CartesianParetoElement::new([2,1])];
+        let res = export_2d_svg(front.iter(), 100, 100);
+        assert_eq!(res.matches("<circle").count(), 2);
+        assert!(res.contains("M10.0,10.0 L90.0,10.0 L90.0,90.0"));
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let front = [CartesianParetoElement::new([1,3]), CartesianParetoElement::new([2,1])];
+        let mut buffer:Vec<u8> = Vec::new();
+        write_front_csv(&mut buffer, front.iter(), Some(&["cost","time"])).unwrap();
+        assert_eq!(String::from_utf8(buffer.clone()).unwrap(), "cost,time\n1,3\n2,1\n");
+        let res:Vec<CartesianParetoElement<2>> = read_cartesian_front_csv(buffer.as_slice(), true).unwrap();
+        assert_eq!(res, front.to_vec());
+    }
+
+    #[test]
+    fn test_csv_invalid_rows() {
+        assert!(read_cartesian_front_csv::<_,2>("1,2\n3,x\n".as_bytes(), false).is_err());
+        assert!(read_cartesian_front_csv::<_,2>("1,2\n3,4,5\n".as_bytes(), false).is_err());
+    }
+
+    /// 2D point with float coordinates, guided by an (LP-like) float bound
+    #[derive(Debug,Clone,PartialEq)]
+    struct FloatPoint([f64;2], f64);
+
+    impl ParetoElement<OrderedF64> for FloatPoint {
+        type CoordIterator = std::array::IntoIter<OrderedF64,2>;
+
+        fn coordinates(&self) -> Self::CoordIterator { self.0.map(OrderedF64).into_iter() }
+
+        fn dominates(&self, other:&Self) -> bool {
+            self.0[0] <= other.0[0] && self.0[1] <= other.0[1]
+        }
+
+        fn nb_dimensions(&self) -> usize { 2 }
+
+        fn kth(&self, k:usize) -> OrderedF64 { OrderedF64(self.0[k]) }
+    }
+
+    impl FloatGuidedElement for FloatPoint {
+        fn float_guide(&self) -> f64 { self.1 }
+    }
+
+    #[test]
+    fn test_ordered_f64() {
+        let mut values:Vec<OrderedF64> = [2.5, f64::NAN, -1., 0., f64::INFINITY]
+            .into_iter().map(OrderedF64).collect();
+        values.sort();
+        assert_eq!(values[0], OrderedF64(-1.));
+        assert_eq!(values[3].get(), f64::INFINITY);
+        assert!(values[4].get().is_nan());
+        assert_eq!(OrderedF64(1.5) * OrderedF64(2.) - OrderedF64(1.), OrderedF64(2.));
+        assert_eq!("0.25".parse::<OrderedF64>().unwrap(), OrderedF64(0.25));
+    }
+
+    #[test]
+    fn test_export_non_finite() {
+        let front = [FloatPoint([1.,f64::NAN], 0.), FloatPoint([f64::INFINITY,2.5], 0.)];
+        let res = export_2d_visualization(front.iter());
+        assert!(res.contains("[{\"x\":1,\"y\":null},{\"x\":null,\"y\":2.5}]"));
+        let res_parallel = export_parallel_coordinates(front.iter(), 2);
+        assert!(res_parallel.contains("{\"id\":1,\"f0\":null,\"f1\":2.5}"));
+    }
+
+    #[test]
+    fn test_export_2d_svg_nan() {
+        let front = [FloatPoint([1.,f64::NAN], 0.), FloatPoint([f64::NAN,2.], 0.), FloatPoint([0.,3.], 0.)];
+        let res = export_2d_svg(front.iter(), 100, 100);
+        assert_eq!(res.matches("<circle").count(), 3);
+    }
+
+    #[test]
+    fn test_float_guides() {
+        let mut queue:BinaryHeapQueue<OrderedF64,FloatPoint> = BinaryHeapQueue::default();
+        queue.insert(FloatPoint([1.,2.], 3.5));
+        queue.insert(FloatPoint([0.,4.], 0.25));
+        assert_eq!(queue.pop_min().map(|e| e.1), Some(0.25));
+        let weights:Arc<[OrderedF64]> = Arc::new([OrderedF64(0.5), OrderedF64(2.)]);
+        let e = WeightedSumGuide::new(FloatPoint([1.,2.], 0.), weights);
+        assert_eq!(e.guide(), OrderedF64(4.5));
+    }
+
+    #[test]
+    fn test_float_kd_tree() {
+        use crate::priority_queue::ParetoFront;
+        use crate::priority_queue::kd_tree::KDTreeFront;
+
+        let mut front:KDTreeFront<OrderedF64,FloatPoint,2> = KDTreeFront::default();
+        assert!(front.insert(FloatPoint([1.5,2.], 2.)));
+        assert!(front.insert(FloatPoint([0.5,3.], 1.)));
+        assert!(!front.insert(FloatPoint([2.,2.5], 0.5)));
+        assert!(front.find_dominating(&FloatPoint([0.5,3.5], 0.)).is_some());
+        assert_eq!(front.peek_min_guide(), Some(OrderedF64(1.)));
+        assert_eq!(front.pop_min(), Some(FloatPoint([0.5,3.], 1.)));
+        assert_eq!(front.len(), 1);
+    }
+
+    #[test]
+    fn test_tuples_and_reverse_guide() {
+        let mut queue:BinaryHeapQueue<u32,(u32,&str)> = BinaryHeapQueue::default();
+        queue.insert((3, "c"));
+        queue.insert((1, "a"));
+        queue.insert((2, "b"));
+        assert_eq!(queue.pop_min(), Some((1, "a")));
+        let mut reversed:BinaryHeapQueue<Reverse<u32>,ReverseGuide<(u32,&str)>> =
+            BinaryHeapQueue::default();
+        for e in [(3, "c"), (1, "a"), (2, "b")] { reversed.insert(ReverseGuide(e)); }
+        assert_eq!(reversed.peek_min_guide(), Some(Reverse(3)));
+        let order:Vec<&str> = std::iter::from_fn(|| reversed.pop_min()).map(|e| e.into_elt().1).collect();
+        assert_eq!(order, vec!["c", "b", "a"]);
+    }
+}
@@ -0,0 +1,234 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::priority_queue::{ExactSizeQueue, GuidedElement};
+use crate::priority_queue::binary_heap::BinaryHeapQueue;
+
+/// default maximum number of elements stolen at once
+const DEFAULT_STEAL_BATCH:usize = 16;
+
+/// locks the mutex, ignoring poisoning (a panicking worker leaves the queues usable)
+fn lock<Q>(m:&Mutex<Q>) -> MutexGuard<'_,Q> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Work-stealing pool of priority queues.
+///
+/// Each worker owns a local queue, and pushes and pops its elements locally (the lock of a
+/// local queue is almost never contended). When its local queue is empty, a worker steals a
+/// batch of the best elements of the worker having the best minimum (at most half of them).
+/// The global best bound is the minimum guide over all the local queues.
+///
+/// The pool is shared between threads, each thread using its own [Worker].
+#[derive(Debug)]
+pub struct WorkStealingPool<T,Elt,Q=BinaryHeapQueue<T,Elt>> {
+    /// local queue of each worker
+    locals:Vec<Mutex<Q>>,
+    /// maximum number of elements stolen at once
+    steal_batch:usize,
+    /// number of elements in the pool
+    nb_elements:AtomicUsize,
+    /// number of steals performed
+    nb_steals:AtomicUsize,
+    /// phantom for types T and Elt
+    phantom:PhantomData<fn() -> (T,Elt)>,
+}
+
+impl<T,Elt,Q:Default> WorkStealingPool<T,Elt,Q> {
+    /// creates a pool of empty queues for the given number of workers (at least one)
+    pub fn new(nb_workers:usize) -> Self {
+        Self::with_steal_batch(nb_workers, DEFAULT_STEAL_BATCH)
+    }
+
+    /// creates a pool of empty queues, workers stealing at most steal_batch elements at once
+    pub fn with_steal_batch(nb_workers:usize, steal_batch:usize) -> Self {
+        Self {
+            locals:(0..nb_workers.max(1)).map(|_| Mutex::new(Q::default())).collect(),
+            steal_batch:steal_batch.max(1),
+            nb_elements:AtomicUsize::new(0),
+            nb_steals:AtomicUsize::new(0),
+            phantom:PhantomData,
+        }
+    }
+
+    /// returns the number of workers
+    pub fn nb_workers(&self) -> usize { self.locals.len() }
+
+    /// returns the number of elements in the pool
+    pub fn len(&self) -> usize { self.nb_elements.load(Ordering::Relaxed) }
+
+    /// returns true iff the pool is empty
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// returns the number of steals performed so far
+    pub fn nb_steals(&self) -> usize { self.nb_steals.load(Ordering::Relaxed) }
+
+    /// returns the handle of the worker id (panics if id >= nb_workers)
+    pub fn worker(&self, id:usize) -> Worker<'_,T,Elt,Q> {
+        assert!(id < self.locals.len(), "worker {} does not exist", id);
+        Worker { pool:self, id }
+    }
+
+    /// consumes the pool and returns the local queues
+    pub fn into_queues(self) -> Vec<Q> {
+        self.locals.into_iter()
+            .map(|m| m.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect()
+    }
+}
+
+impl<T,Elt,Q> WorkStealingPool<T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt>+Send {
+    /// returns the global best bound: the minimum guide over all the local queues.
+    /// Local queues are locked in turn, thus it may be outdated if workers are running
+    pub fn best_bound(&self) -> Option<T> {
+        self.locals.iter().filter_map(|l| lock(l).peek_min_guide()).min()
+    }
+}
+
+/// Handle of a worker of a [WorkStealingPool].
+#[derive(Debug)]
+pub struct Worker<'a,T,Elt,Q> {
+    /// pool of the worker
+    pool:&'a WorkStealingPool<T,Elt,Q>,
+    /// identifier of the worker
+    id:usize,
+}
+
+impl<'a,T,Elt,Q> Worker<'a,T,Elt,Q>
+where T:Ord, Elt:GuidedElement<T>, Q:ExactSizeQueue<T,Elt>+Send {
+    /// returns the identifier of the worker
+    pub fn id(&self) -> usize { self.id }
+
+    /// returns the number of elements of the local queue
+    pub fn local_len(&self) -> usize { lock(&self.pool.locals[self.id]).len() }
+
+    /// inserts an element in the local queue. Returns true iff it was inserted.
+    /// Elements the local queue drops (e.g. dominated elements) are removed from the count
+    pub fn push(&self, elt:Elt) -> bool {
+        let mut nb_evicted = 0;
+        let res = lock(&self.pool.locals[self.id]).insert_evicting(elt, |_| nb_evicted += 1);
+        if res { self.pool.nb_elements.fetch_add(1, Ordering::Relaxed); }
+        if nb_evicted > 0 { self.pool.nb_elements.fetch_sub(nb_evicted, Ordering::Relaxed); }
+        res
+    }
+
+    /// pops the minimum of the local queue. If it is empty, steals a batch of elements
+    /// first. Returns None if no element was found in the pool
+    pub fn pop(&self) -> Option<Elt> {
+        loop {
+            let res = lock(&self.pool.locals[self.id]).pop_min();
+            if res.is_some() {
+                self.pool.nb_elements.fetch_sub(1, Ordering::Relaxed);
+                return res;
+            }
+            if self.steal() == 0 { return None; }
+        }
+    }
+
+    /// moves a batch of the best elements of the worker having the best minimum into the
+    /// local queue. Returns the number of stolen elements
+    pub fn steal(&self) -> usize {
+        let best = self.pool.locals.iter().enumerate()
+            .filter(|(i,_)| *i != self.id)
+            .filter_map(|(i,l)| lock(l).peek_min_guide().map(|g| (g,i)))
+            .min_by(|a,b| a.0.cmp(&b.0))
+            .map(|(_,i)| i);
+        let Some(victim) = best else { return 0; };
+        // queues are locked one at a time (no deadlock)
+        let batch = {
+            let mut queue = lock(&self.pool.locals[victim]);
+            let k = self.pool.steal_batch.min(queue.len().div_ceil(2));
+            queue.pop_k(k)
+        };
+        if batch.is_empty() { return 0; }
+        self.pool.nb_steals.fetch_add(1, Ordering::Relaxed);
+        let mut local = lock(&self.pool.locals[self.id]);
+        let (mut nb_stolen, mut nb_dropped) = (0, 0);
+        for e in batch {
+            if local.insert_evicting(e, |_| nb_dropped += 1) { nb_stolen += 1; } else { nb_dropped += 1; }
+        }
+        if nb_dropped > 0 { self.pool.nb_elements.fetch_sub(nb_dropped, Ordering::Relaxed); }
+        nb_stolen
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::thread;
+
+    use crate::priority_queue::fixtures::Value;
+
+    #[test]
+    fn test_steal_best_batch() {
+        let pool:WorkStealingPool<u32,Value> = WorkStealingPool::with_steal_batch(3, 4);
+        let (w0, w1, w2) = (pool.worker(0), pool.worker(1), pool.worker(2));
+        for i in 10..20 { w0.push(Value(i)); }
+        for i in 5..8 { w1.push(Value(i)); }
+        assert_eq!(pool.best_bound(), Some(5));
+        // steals from worker 1 (best minimum), at most half of its elements
+        assert_eq!(w2.pop(), Some(Value(5)));
+        assert_eq!(w2.local_len(), 1);
+        assert_eq!(w1.local_len(), 1);
+        assert_eq!(w2.pop(), Some(Value(6)));
+        assert_eq!(w1.pop(), Some(Value(7)));
+        // steals 4 elements from worker 0
+        assert_eq!(w1.pop(), Some(Value(10)));
+        assert_eq!(w1.local_len(), 3);
+        assert_eq!(pool.nb_steals(), 2);
+        assert_eq!(pool.len(), 9);
+        assert_eq!(pool.best_bound(), Some(11));
+    }
+
+    #[test]
+    fn test_parallel_tree_search() {
+        // binary tree of depth 12: each element v < 2^12 has children 2v and 2v+1
+        let pool:WorkStealingPool<u32,Value> = WorkStealingPool::new(4);
+        pool.worker(0).push(Value(1));
+        let nb_processed:usize = thread::scope(|s| {
+            let workers:Vec<_> = (0..4).map(|id| {
+                let worker = pool.worker(id);
+                s.spawn(move || {
+                    let mut nb = 0;
+                    while let Some(Value(v)) = worker.pop() {
+                        nb += 1;
+                        if v < 1 << 12 {
+                            worker.push(Value(2*v));
+                            worker.push(Value(2*v+1));
+                        }
+                    }
+                    nb
+                })
+            }).collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum()
+        });
+        assert_eq!(nb_processed, (1 << 13) - 1);
+        assert!(pool.is_empty());
+        assert!(pool.into_queues().iter().all(|q| q.is_empty()));
+    }
+
+    #[test]
+    fn test_pareto_evictions() {
+        use crate::priority_queue::pareto_list::ListParetoFront;
+        use crate::priority_queue::util::CartesianParetoElement;
+        type Point = CartesianParetoElement<2>;
+        let pool:WorkStealingPool<u32,Point,ListParetoFront<u32,Point>> = WorkStealingPool::new(2);
+        let (w0, w1) = (pool.worker(0), pool.worker(1));
+        for i in 0..10 { assert!(w0.push(Point::new([i,10-i]))); }
+        assert_eq!(pool.len(), 10);
+        // dominates 5 elements
+        assert!(w0.push(Point::new([2,4])));
+        assert_eq!(pool.len(), 6);
+        assert!(w1.push(Point::new([5,5])));
+        // the stolen element [2,4] dominates [5,5]
+        assert_eq!(w1.steal(), 3);
+        assert_eq!(pool.len(), 6);
+        assert_eq!(w0.local_len() + w1.local_len(), 6);
+        assert_eq!(std::iter::from_fn(|| w1.pop()).count(), 6);
+        assert!(pool.is_empty());
+    }
+}
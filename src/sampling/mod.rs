@@ -0,0 +1,10 @@
+/// Reservoir sampler.
+///
+/// Keeps a uniform sample of bounded size of a stream of unknown length, and merges samples
+/// of parallel workers.
+pub mod reservoir;
+
+/// Dynamic roulette wheel.
+///
+/// Weighted sampling with weights updatable online (Fenwick tree of prefix sums).
+pub mod roulette;
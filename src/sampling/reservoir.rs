@@ -0,0 +1,118 @@
+use rand::Rng;
+
+/// Reservoir sampler (algorithm R).
+///
+/// Keeps a uniform random sample (without replacement) of at most k elements of a stream of
+/// unknown length. Reservoirs filled by parallel workers can be merged into a uniform sample of
+/// the union of their streams. The random generator is given by the caller (reproducible runs).
+/// If *k* is the capacity:
+/// - memory: O(k)
+/// - push: O(1)
+/// - merge: O(k)
+#[derive(Debug,Clone)]
+pub struct Reservoir<T> {
+    /// sampled elements (in no particular order)
+    elements:Vec<T>,
+    /// maximum number of sampled elements
+    capacity:usize,
+    /// number of elements of the stream
+    nb_seen:u64,
+}
+
+impl<T> Reservoir<T> {
+    /// creates an empty reservoir sampling at most capacity elements
+    pub fn new(capacity:usize) -> Self { Self { elements:Vec::with_capacity(capacity), capacity, nb_seen:0 } }
+
+    /// returns the maximum number of sampled elements
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// returns the number of sampled elements
+    pub fn len(&self) -> usize { self.elements.len() }
+
+    /// returns true iff no element is sampled
+    pub fn is_empty(&self) -> bool { self.elements.is_empty() }
+
+    /// returns the number of elements of the stream (pushed or merged)
+    pub fn nb_seen(&self) -> u64 { self.nb_seen }
+
+    /// returns the sampled elements (in no particular order)
+    pub fn elements(&self) -> &[T] { &self.elements }
+
+    /// consumes the reservoir and returns the sampled elements
+    pub fn into_vec(self) -> Vec<T> { self.elements }
+
+    /// offers the next element of the stream. Returns true iff it is sampled (replacing a
+    /// random sampled element if the reservoir is full)
+    pub fn push<R:Rng+?Sized>(&mut self, rng:&mut R, elt:T) -> bool {
+        self.nb_seen += 1;
+        if self.elements.len() < self.capacity {
+            self.elements.push(elt);
+            return true;
+        }
+        let i = rng.gen_range(0..self.nb_seen);
+        if i < self.capacity as u64 {
+            self.elements[i as usize] = elt;
+            true
+        } else { false }
+    }
+
+    /// merges another reservoir (sampled from another stream): the result is a uniform sample
+    /// of the concatenation of both streams (with the capacity of this reservoir)
+    pub fn merge<R:Rng+?Sized>(&mut self, rng:&mut R, other:Reservoir<T>) {
+        // each sampled element represents the remaining elements of its stream
+        let (mut remaining_a, mut remaining_b) = (self.nb_seen, other.nb_seen);
+        let mut a = std::mem::take(&mut self.elements);
+        let mut b = other.elements;
+        while self.elements.len() < self.capacity && !(a.is_empty() && b.is_empty()) {
+            let from_a = b.is_empty() || (!a.is_empty() && rng.gen_range(0..remaining_a+remaining_b) < remaining_a);
+            let (source, remaining) = if from_a { (&mut a, &mut remaining_a) } else { (&mut b, &mut remaining_b) };
+            let i = rng.gen_range(0..source.len());
+            self.elements.push(source.swap_remove(i));
+            *remaining -= 1;
+        }
+        self.nb_seen += other.nb_seen;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn uniform_sample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut counts = [0; 10];
+        for _ in 0..10_000 {
+            let mut reservoir = Reservoir::new(3);
+            for e in 0..10 { reservoir.push(&mut rng, e); }
+            assert_eq!((reservoir.len(), reservoir.nb_seen()), (3, 10));
+            for e in reservoir.elements() { counts[*e] += 1; }
+        }
+        // each element is sampled with probability 3/10
+        assert!(counts.iter().all(|c| *c > 2700 && *c < 3300));
+        let mut small = Reservoir::new(5);
+        assert!(small.push(&mut rng, 'a') && small.push(&mut rng, 'b'));
+        assert_eq!(small.into_vec(), vec!['a', 'b']);
+    }
+
+    #[test]
+    fn merges() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut counts = [0; 12];
+        for _ in 0..10_000 {
+            // streams of 3 and 9 elements
+            let mut a = Reservoir::new(4);
+            for e in 0..3 { a.push(&mut rng, e); }
+            let mut b = Reservoir::new(4);
+            for e in 3..12 { b.push(&mut rng, e); }
+            a.merge(&mut rng, b);
+            assert_eq!((a.len(), a.nb_seen()), (4, 12));
+            for e in a.elements() { counts[*e] += 1; }
+        }
+        // each element is sampled with probability 4/12
+        assert!(counts.iter().all(|c| *c > 3000 && *c < 3700));
+    }
+}
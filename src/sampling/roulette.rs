@@ -0,0 +1,168 @@
+use rand::Rng;
+
+/// Dynamic roulette wheel (weighted sampling with updatable weights).
+///
+/// Samples index i with probability weights\[i\] / sum of the weights. The weights are stored
+/// in a Fenwick tree (binary indexed tree) of prefix sums, thus they can be changed online
+/// (e.g. pheromone-like adaptive weights), unlike with a static alias table.
+/// To bound floating-point drift, the tree is rebuilt from the weights every n updates.
+/// If *n* is the number of weights:
+/// - memory: O(n)
+/// - construction: O(n)
+/// - set_weight: O(log n) amortized
+/// - sample: O(log n)
+#[derive(Debug,Clone)]
+pub struct RouletteWheel {
+    /// weight of each index
+    weights:Vec<f64>,
+    /// Fenwick tree: tree\[i\] is the sum of the weights of the indices i-lowbit(i)..i (1-based)
+    tree:Vec<f64>,
+    /// number of updates since the last rebuild
+    nb_updates:usize,
+}
+
+impl RouletteWheel {
+    /// creates a wheel with the given weights. Panics if a weight is negative or not finite
+    pub fn from_weights(weights:&[f64]) -> Self {
+        assert!(weights.iter().all(|w| w.is_finite() && *w >= 0.), "RouletteWheel: invalid weight");
+        let mut res = Self { weights:weights.to_vec(), tree:Vec::new(), nb_updates:0 };
+        res.rebuild();
+        res
+    }
+
+    /// creates a wheel of n indices of weight 0
+    pub fn new(n:usize) -> Self { Self::from_weights(&vec![0.; n]) }
+
+    /// returns the number of indices
+    pub fn len(&self) -> usize { self.weights.len() }
+
+    /// returns true iff there is no index
+    pub fn is_empty(&self) -> bool { self.weights.is_empty() }
+
+    /// returns the weight of index i
+    pub fn weight(&self, i:usize) -> f64 { self.weights[i] }
+
+    /// returns the sum of the weights
+    pub fn total(&self) -> f64 { self.prefix_sum(self.len()) }
+
+    /// returns the sum of the weights of the indices 0..i
+    pub fn prefix_sum(&self, i:usize) -> f64 {
+        let mut res = 0.;
+        let mut pos = i;
+        while pos > 0 {
+            res += self.tree[pos];
+            pos &= pos - 1;
+        }
+        res
+    }
+
+    /// changes the weight of index i. Panics if the weight is negative or not finite
+    pub fn set_weight(&mut self, i:usize, weight:f64) {
+        assert!(weight.is_finite() && weight >= 0., "RouletteWheel: invalid weight {weight}");
+        let delta = weight - self.weights[i];
+        self.weights[i] = weight;
+        self.nb_updates += 1;
+        if self.nb_updates >= self.len() {
+            self.rebuild();
+            return;
+        }
+        let mut pos = i + 1;
+        while pos < self.tree.len() {
+            self.tree[pos] += delta;
+            pos += pos & pos.wrapping_neg();
+        }
+    }
+
+    /// samples an index with a probability proportional to its weight
+    /// (None if every weight is 0). Indices of weight 0 are never sampled, even if
+    /// floating-point drift in the prefix sums makes the draw land on them
+    pub fn sample<R:Rng+?Sized>(&self, rng:&mut R) -> Option<usize> {
+        let total = self.total();
+        if total <= 0. { return None; }
+        let mut remaining = rng.gen::<f64>() * total;
+        // largest pos such that prefix_sum(pos) <= target (binary lifting)
+        let mut pos = 0;
+        let mut step = 1 << self.len().ilog2();
+        while step > 0 {
+            if pos + step <= self.len() && self.tree[pos + step] <= remaining {
+                pos += step;
+                remaining -= self.tree[pos];
+            }
+            step /= 2;
+        }
+        // drift may push the draw on a zero weight (e.g. past the last bucket): falls back to
+        // the closest positive weight before it (or after it)
+        let drawn = pos.min(self.len() - 1);
+        if self.weights[drawn] > 0. { return Some(drawn); }
+        self.weights[..drawn].iter().rposition(|w| *w > 0.)
+            .or_else(|| self.weights[drawn..].iter().position(|w| *w > 0.).map(|i| drawn + i))
+    }
+
+    /// rebuilds the Fenwick tree from the weights in O(n)
+    fn rebuild(&mut self) {
+        let n = self.weights.len();
+        self.tree = vec![0.; n+1];
+        for i in 1..=n {
+            self.tree[i] += self.weights[i-1];
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= n { self.tree[parent] += self.tree[i]; }
+        }
+        self.nb_updates = 0;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::{mock::StepRng, StdRng}, SeedableRng};
+
+    /// returns the number of times each index is sampled
+    fn histogram(wheel:&RouletteWheel, rng:&mut StdRng, nb_samples:usize) -> Vec<usize> {
+        let mut res = vec![0; wheel.len()];
+        for _ in 0..nb_samples { res[wheel.sample(rng).unwrap()] += 1; }
+        res
+    }
+
+    #[test]
+    fn prefix_sums() {
+        let mut wheel = RouletteWheel::from_weights(&[1., 2., 3., 4., 5.]);
+        assert_eq!((wheel.total(), wheel.prefix_sum(2), wheel.prefix_sum(4)), (15., 3., 10.));
+        wheel.set_weight(1, 0.5);
+        assert_eq!((wheel.total(), wheel.prefix_sum(3), wheel.weight(1)), (13.5, 4.5, 0.5));
+        for i in 0..5 { wheel.set_weight(i, 1.); }
+        assert_eq!(wheel.total(), 5.);
+    }
+
+    #[test]
+    fn sampling() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut wheel = RouletteWheel::from_weights(&[1., 0., 3., 0., 0., 4.]);
+        let counts = histogram(&wheel, &mut rng, 8000);
+        assert_eq!((counts[1], counts[3], counts[4]), (0, 0, 0));
+        assert!(counts[0] > 850 && counts[0] < 1150);
+        assert!(counts[2] > 2800 && counts[2] < 3200);
+        wheel.set_weight(5, 0.);
+        wheel.set_weight(4, 1.);
+        // weights [1, 0, 3, 0, 1, 0]
+        let updated_counts = histogram(&wheel, &mut rng, 5000);
+        assert_eq!(updated_counts[5], 0);
+        assert!(updated_counts[4] > 850 && updated_counts[4] < 1150);
+        assert_eq!(RouletteWheel::new(3).sample(&mut rng), None);
+        assert_eq!(RouletteWheel::new(0).sample(&mut rng), None);
+    }
+
+    #[test]
+    fn trailing_zero_weights() {
+        // the total drifts to 0.1 + 0.3 - 0.3 > 0.1: the largest draw (just below 1) falls past
+        // index 0, on the trailing zero weights
+        let mut rng = StepRng::new(u64::MAX, 0);
+        let mut wheel = RouletteWheel::from_weights(&[0.1, 0., 0., 0., 0.3, 0., 0., 0.]);
+        wheel.set_weight(4, 0.);
+        assert!(wheel.total() > 0.1);
+        assert_eq!(wheel.sample(&mut rng), Some(0));
+        wheel.set_weight(0, 0.);
+        assert_eq!(wheel.sample(&mut rng), None);
+    }
+}
@@ -0,0 +1,17 @@
+/// Timeline of busy intervals.
+///
+/// Maintains disjoint busy intervals within a horizon, and answers first-fit queries over the
+/// free time (constructive scheduling heuristics).
+pub mod timeline;
+
+/// Theta and Theta-Lambda trees.
+///
+/// Maintain the earliest completion time of a set of tasks under insertions and removals
+/// (disjunctive edge-finding).
+pub mod theta_tree;
+
+/// Cumulative resource profile.
+///
+/// Maintains the usage of a resource over time as a step function, with maximum-usage and
+/// first-fit queries under a capacity.
+pub mod profile;
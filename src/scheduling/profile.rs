@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::ops::{Bound, Range};
+
+/// implements a cumulative resource profile: the usage of a resource over time, as a step
+/// function (event-based: only the times at which the usage changes are stored).
+/// Tasks add their demand over an interval (and remove it when unscheduled).
+/// If *k* is the number of usage changes, this data-structure has the following complexities:
+/// - memory: O(k)
+/// - add/remove a demand over an interval: O(log k + changes in the interval)
+/// - usage at a time: O(log k)
+/// - maximum usage over a window: O(log k + changes in the window)
+/// - first fit: O(log k + changes scanned)
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct CumulativeProfile {
+    /// usage from each time until the next one (the usage is 0 before the first time, and
+    /// consecutive usages differ)
+    levels:BTreeMap<i64,i64>,
+}
+
+impl CumulativeProfile {
+    /// creates a profile with no usage
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of usage changes
+    pub fn nb_changes(&self) -> usize { self.levels.len() }
+
+    /// returns true iff the usage is 0 everywhere
+    pub fn is_empty(&self) -> bool { self.levels.is_empty() }
+
+    /// returns the usage at time t
+    pub fn usage_at(&self, t:i64) -> i64 {
+        self.levels.range(..=t).next_back().map_or(0, |(_,level)| *level)
+    }
+
+    /// adds the demand over the range
+    pub fn add(&mut self, range:Range<i64>, demand:i64) {
+        if range.is_empty() || demand == 0 { return; }
+        self.split(range.start);
+        self.split(range.end);
+        for (_,level) in self.levels.range_mut(range.start..range.end) { *level += demand; }
+        self.merge(range.start);
+        self.merge(range.end);
+    }
+
+    /// removes the demand over the range (previously added)
+    pub fn remove(&mut self, range:Range<i64>, demand:i64) { self.add(range, -demand); }
+
+    /// returns the maximum usage over the window (0 if it is empty)
+    pub fn max_usage(&self, window:Range<i64>) -> i64 {
+        if window.is_empty() { return 0; }
+        self.levels.range((Bound::Excluded(window.start), Bound::Excluded(window.end)))
+            .map(|(_,level)| *level)
+            .fold(self.usage_at(window.start), i64::max)
+    }
+
+    /// returns the earliest start s ≥ t such that the demand fits under the capacity over
+    /// s..s+length. None if the demand exceeds the capacity
+    pub fn first_fit(&self, t:i64, length:i64, demand:i64, capacity:i64) -> Option<i64> {
+        let limit = capacity - demand;
+        // start of the current run of times whose usage is under the limit
+        let mut start = (self.usage_at(t) <= limit).then_some(t);
+        for (time,level) in self.levels.range((Bound::Excluded(t), Bound::Unbounded)) {
+            if start.is_some_and(|s| s + length <= *time) { return start; }
+            if *level > limit { start = None; } else if start.is_none() { start = Some(*time); }
+        }
+        start
+    }
+
+    /// iterates over the intervals of non-zero usage (in increasing order) with their usage
+    pub fn steps(&self) -> impl Iterator<Item=(Range<i64>,i64)> + '_ {
+        self.levels.iter().zip(self.levels.keys().skip(1))
+            .filter(|((_,level),_)| **level != 0)
+            .map(|((start,level),end)| (*start..*end, *level))
+    }
+
+    /// adds a change at time t (with the current usage)
+    fn split(&mut self, t:i64) {
+        let level = self.usage_at(t);
+        self.levels.entry(t).or_insert(level);
+    }
+
+    /// removes the change at time t if the usage is the same before it
+    fn merge(&mut self, t:i64) {
+        let before = self.levels.range(..t).next_back().map_or(0, |(_,level)| *level);
+        if self.levels.get(&t) == Some(&before) { self.levels.remove(&t); }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn usage() {
+        let mut profile = CumulativeProfile::new();
+        profile.add(0..10, 2);
+        profile.add(5..15, 3);
+        profile.add(10..12, 1);
+        assert_eq!(profile.steps().collect::<Vec<_>>(), vec![(0..5, 2), (5..10, 5), (10..12, 4), (12..15, 3)]);
+        assert_eq!((profile.usage_at(-1), profile.usage_at(9), profile.usage_at(15)), (0, 5, 0));
+        assert_eq!(profile.max_usage(10..20), 4);
+        assert_eq!(profile.max_usage(0..5), 2);
+        assert_eq!(profile.first_fit(0, 3, 2, 5), Some(0));
+        assert_eq!(profile.first_fit(0, 6, 2, 5), Some(12));
+        assert_eq!(profile.first_fit(3, 2, 1, 5), Some(3));
+        assert_eq!(profile.first_fit(3, 4, 1, 5), Some(10));
+        assert_eq!(profile.first_fit(0, 1, 6, 5), None);
+        profile.remove(5..15, 3);
+        profile.remove(10..12, 1);
+        assert_eq!(profile.steps().collect::<Vec<_>>(), vec![(0..10, 2)]);
+        assert_eq!(profile.nb_changes(), 2);
+        profile.remove(0..10, 2);
+        assert!(profile.is_empty());
+    }
+
+    #[test]
+    fn random_operations() {
+        // compares with a time-indexed array over 0..50
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut profile = CumulativeProfile::new();
+        let mut naive = [0i64; 50];
+        for _ in 0..200 {
+            let start = rng.gen_range(0..45);
+            let range = start..rng.gen_range(start..50);
+            let demand = rng.gen_range(1..4);
+            profile.add(range.clone(), demand);
+            for t in range { naive[t as usize] += demand; }
+            let window = 10..rng.gen_range(10..50);
+            let expected = naive[window.start as usize..window.end as usize].iter().copied().max().unwrap_or(0);
+            assert_eq!(profile.max_usage(window), expected);
+            let (t, length, capacity) = (rng.gen_range(0..50), rng.gen_range(1..5), rng.gen_range(10..60));
+            let expected_fit = (t..).find(|s| (*s..s+length).all(|u| u >= 50 || naive[u as usize] + 2 <= capacity));
+            assert_eq!(profile.first_fit(t, length, 2, capacity), expected_fit);
+        }
+    }
+}
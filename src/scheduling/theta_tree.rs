@@ -0,0 +1,302 @@
+/// value of the earliest completion time of an empty set of tasks (far enough from i64::MIN
+/// to add durations without overflowing)
+const NEG:i64 = i64::MIN / 4;
+
+/// Node of a Theta-Lambda tree (aggregates the tasks of its leaves)
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+struct Node {
+    /// sum of the durations of the theta tasks
+    sum_p:i64,
+    /// earliest completion time of the theta tasks
+    ect:i64,
+    /// sum of the durations of the theta tasks plus at most one lambda task (maximized)
+    sum_p_bar:i64,
+    /// earliest completion time of the theta tasks plus at most one lambda task (maximized)
+    ect_bar:i64,
+    /// lambda task responsible for sum_p_bar
+    resp_p:Option<usize>,
+    /// lambda task responsible for ect_bar
+    resp_ect:Option<usize>,
+}
+
+impl Node {
+    /// node without task
+    const EMPTY:Node = Node { sum_p:0, ect:NEG, sum_p_bar:0, ect_bar:NEG, resp_p:None, resp_ect:None };
+
+    /// aggregates the left and right children (tasks of the left one have smaller est)
+    fn combine(l:&Node, r:&Node) -> Node {
+        let (sum_p_bar, resp_p) = best([
+            (l.sum_p_bar + r.sum_p, l.resp_p),
+            (l.sum_p + r.sum_p_bar, r.resp_p),
+        ]);
+        let (ect_bar, resp_ect) = best([
+            (r.ect_bar, r.resp_ect),
+            (l.ect + r.sum_p_bar, r.resp_p),
+            (l.ect_bar + r.sum_p, l.resp_ect),
+        ]);
+        Node {
+            sum_p:l.sum_p + r.sum_p,
+            ect:r.ect.max(l.ect + r.sum_p),
+            sum_p_bar,
+            ect_bar,
+            resp_p,
+            resp_ect,
+        }
+    }
+}
+
+/// returns the maximum value and its responsible lambda task (the candidates without
+/// responsible task are preferred on ties: the value is then reached by theta tasks only)
+fn best<const N:usize>(candidates:[(i64,Option<usize>);N]) -> (i64,Option<usize>) {
+    let max = candidates.iter().map(|c| c.0).max().unwrap();
+    candidates.iter().filter(|c| c.0 == max)
+        .min_by_key(|c| c.1.is_some())
+        .copied().unwrap()
+}
+
+/// Location of a task in a Theta-Lambda tree
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum TaskSet {
+    /// not in the tree
+    None,
+    /// in the theta set (white task)
+    Theta,
+    /// in the lambda set (gray task)
+    Lambda,
+}
+
+/// implements a Theta-Lambda tree (Vilím), used by disjunctive edge-finding.
+///
+/// Tasks (with an earliest start time and a duration) are the leaves of a balanced binary tree,
+/// sorted by earliest start time. Each task is either absent, in the theta set or in the lambda
+/// set. The tree maintains ECT(Θ), the earliest completion time of the theta tasks, and
+/// ECT(Θ,Λ), the maximum earliest completion time of the theta tasks plus one lambda task
+/// (together with the lambda task responsible for it).
+/// If *n* is the number of tasks, this data-structure has the following complexities:
+/// - memory: O(n)
+/// - insertion/removal/move of a task: O(log n)
+/// - ECT(Θ), ECT(Θ,Λ), responsible lambda task: O(1)
+#[derive(Debug,Clone)]
+pub struct ThetaLambdaTree {
+    /// nodes of the tree (root at 1, children of i at 2i and 2i+1, leaves at nb_leaves..)
+    nodes:Vec<Node>,
+    /// number of leaves (power of two)
+    nb_leaves:usize,
+    /// leaf position of each task
+    positions:Vec<usize>,
+    /// earliest start time of each task
+    est:Vec<i64>,
+    /// duration of each task
+    durations:Vec<i64>,
+    /// set of each task
+    sets:Vec<TaskSet>,
+    /// number of theta tasks
+    nb_theta:usize,
+    /// number of lambda tasks
+    nb_lambda:usize,
+}
+
+impl ThetaLambdaTree {
+    /// creates a tree over the tasks (earliest start times, durations), initially empty.
+    /// Panics if the slices have different lengths
+    pub fn new(est:&[i64], durations:&[i64]) -> Self {
+        assert_eq!(est.len(), durations.len(), "ThetaLambdaTree: one duration per task expected");
+        let nb_leaves = est.len().next_power_of_two();
+        let mut order:Vec<usize> = (0..est.len()).collect();
+        order.sort_by_key(|i| est[*i]);
+        let mut positions = vec![0; est.len()];
+        for (pos,task) in order.into_iter().enumerate() { positions[task] = pos; }
+        Self {
+            nodes:vec![Node::EMPTY; 2*nb_leaves],
+            nb_leaves,
+            positions,
+            est:est.to_vec(),
+            durations:durations.to_vec(),
+            sets:vec![TaskSet::None; est.len()],
+            nb_theta:0,
+            nb_lambda:0,
+        }
+    }
+
+    /// returns the number of tasks
+    pub fn nb_tasks(&self) -> usize { self.est.len() }
+
+    /// returns the set of a task
+    pub fn set_of(&self, task:usize) -> TaskSet { self.sets[task] }
+
+    /// returns the number of theta tasks
+    pub fn nb_theta(&self) -> usize { self.nb_theta }
+
+    /// returns the number of lambda tasks
+    pub fn nb_lambda(&self) -> usize { self.nb_lambda }
+
+    /// inserts a task in the theta set (moves it if it was in the lambda set)
+    pub fn insert_theta(&mut self, task:usize) {
+        let p = self.durations[task];
+        let ect = self.est[task] + p;
+        self.set_leaf(task, TaskSet::Theta, Node { sum_p:p, ect, sum_p_bar:p, ect_bar:ect, resp_p:None, resp_ect:None });
+    }
+
+    /// inserts a task in the lambda set (moves it if it was in the theta set)
+    pub fn insert_lambda(&mut self, task:usize) {
+        let p = self.durations[task];
+        let ect = self.est[task] + p;
+        self.set_leaf(task, TaskSet::Lambda, Node { sum_p:0, ect:NEG, sum_p_bar:p, ect_bar:ect, resp_p:Some(task), resp_ect:Some(task) });
+    }
+
+    /// removes a task from the tree
+    pub fn remove(&mut self, task:usize) { self.set_leaf(task, TaskSet::None, Node::EMPTY); }
+
+    /// returns the earliest completion time of the theta tasks (None if there is none)
+    pub fn ect(&self) -> Option<i64> { (self.nb_theta > 0).then_some(self.nodes[1].ect) }
+
+    /// returns the sum of the durations of the theta tasks
+    pub fn sum_durations(&self) -> i64 { self.nodes[1].sum_p }
+
+    /// returns the maximum earliest completion time of the theta tasks plus at most one lambda
+    /// task (None if both sets are empty)
+    pub fn ect_bar(&self) -> Option<i64> {
+        (self.nb_theta + self.nb_lambda > 0).then_some(self.nodes[1].ect_bar)
+    }
+
+    /// returns the lambda task responsible for ect_bar (None if ect_bar is reached by the theta
+    /// tasks alone)
+    pub fn responsible_ect_bar(&self) -> Option<usize> { self.nodes[1].resp_ect }
+
+    /// sets the leaf of a task and updates its ancestors
+    fn set_leaf(&mut self, task:usize, set:TaskSet, node:Node) {
+        match self.sets[task] {
+            TaskSet::Theta => self.nb_theta -= 1,
+            TaskSet::Lambda => self.nb_lambda -= 1,
+            TaskSet::None => {},
+        }
+        match set {
+            TaskSet::Theta => self.nb_theta += 1,
+            TaskSet::Lambda => self.nb_lambda += 1,
+            TaskSet::None => {},
+        }
+        self.sets[task] = set;
+        let mut i = self.nb_leaves + self.positions[task];
+        self.nodes[i] = node;
+        while i > 1 {
+            i /= 2;
+            self.nodes[i] = Node::combine(&self.nodes[2*i], &self.nodes[2*i+1]);
+        }
+    }
+}
+
+/// implements a Theta tree: a Theta-Lambda tree without lambda tasks.
+/// Maintains the earliest completion time of a set of tasks in O(log n) per insertion/removal
+/// (overload checking, detectable precedences, not-first/not-last).
+#[derive(Debug,Clone)]
+pub struct ThetaTree {
+    /// underlying tree (the lambda set stays empty)
+    tree:ThetaLambdaTree,
+}
+
+impl ThetaTree {
+    /// creates a tree over the tasks (earliest start times, durations), initially empty.
+    /// Panics if the slices have different lengths
+    pub fn new(est:&[i64], durations:&[i64]) -> Self { Self { tree:ThetaLambdaTree::new(est, durations) } }
+
+    /// inserts a task
+    pub fn insert(&mut self, task:usize) { self.tree.insert_theta(task); }
+
+    /// removes a task
+    pub fn remove(&mut self, task:usize) { self.tree.remove(task); }
+
+    /// returns true iff the task is in the tree
+    pub fn contains(&self, task:usize) -> bool { self.tree.set_of(task) == TaskSet::Theta }
+
+    /// returns the number of tasks in the tree
+    pub fn len(&self) -> usize { self.tree.nb_theta() }
+
+    /// returns true iff the tree contains no task
+    pub fn is_empty(&self) -> bool { self.tree.nb_theta() == 0 }
+
+    /// returns the earliest completion time of the tasks (None if empty)
+    pub fn ect(&self) -> Option<i64> { self.tree.ect() }
+
+    /// returns the sum of the durations of the tasks
+    pub fn sum_durations(&self) -> i64 { self.tree.sum_durations() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// earliest completion time of a set of tasks: max over j of est_j + sum of the durations
+    /// of the tasks starting after est_j
+    fn naive_ect(est:&[i64], durations:&[i64], tasks:&[usize]) -> Option<i64> {
+        tasks.iter().map(|j| {
+            est[*j] + tasks.iter().filter(|i| est[**i] >= est[*j]).map(|i| durations[*i]).sum::<i64>()
+        }).max()
+    }
+
+    #[test]
+    fn theta_tree() {
+        // classic example: tasks (est, p)
+        let est = [0, 25, 30, 32];
+        let durations = [11, 10, 5, 10];
+        let mut tree = ThetaTree::new(&est, &durations);
+        assert_eq!(tree.ect(), None);
+        for t in 0..4 { tree.insert(t); }
+        assert_eq!((tree.ect(), tree.sum_durations(), tree.len()), (Some(50), 36, 4));
+        tree.remove(1);
+        assert_eq!(tree.ect(), Some(45));
+        assert!(!tree.contains(1) && tree.contains(2));
+    }
+
+    #[test]
+    fn theta_lambda_tree() {
+        let est = [0, 25, 30, 32];
+        let durations = [11, 10, 5, 10];
+        let mut tree = ThetaLambdaTree::new(&est, &durations);
+        for t in [0, 2, 3] { tree.insert_theta(t); }
+        tree.insert_lambda(1);
+        assert_eq!((tree.ect(), tree.ect_bar()), (Some(45), Some(50)));
+        assert_eq!(tree.responsible_ect_bar(), Some(1));
+        tree.insert_lambda(3);
+        assert_eq!((tree.ect(), tree.ect_bar()), (Some(35), Some(45)));
+        assert_eq!((tree.nb_theta(), tree.nb_lambda()), (2, 2));
+        tree.remove(1);
+        tree.remove(3);
+        assert_eq!((tree.ect_bar(), tree.responsible_ect_bar()), (Some(35), None));
+    }
+
+    #[test]
+    fn random_sets() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let n = rng.gen_range(1..12);
+            let est:Vec<i64> = (0..n).map(|_| rng.gen_range(0..30)).collect();
+            let durations:Vec<i64> = (0..n).map(|_| rng.gen_range(0..10)).collect();
+            let mut tree = ThetaLambdaTree::new(&est, &durations);
+            for _ in 0..30 {
+                let task = rng.gen_range(0..n);
+                match rng.gen_range(0..3) {
+                    0 => tree.insert_theta(task),
+                    1 => tree.insert_lambda(task),
+                    _ => tree.remove(task),
+                }
+                let in_set = |set| (0..n).filter(|t| tree.set_of(*t) == set).collect::<Vec<_>>();
+                let (theta, lambda) = (in_set(TaskSet::Theta), in_set(TaskSet::Lambda));
+                assert_eq!(tree.ect(), naive_ect(&est, &durations, &theta));
+                let with_lambda = |l:usize| {
+                    let mut tasks = theta.clone();
+                    tasks.push(l);
+                    naive_ect(&est, &durations, &tasks)
+                };
+                let expected = lambda.iter().map(|l| with_lambda(*l)).max().flatten()
+                    .max(naive_ect(&est, &durations, &theta));
+                assert_eq!(tree.ect_bar(), expected);
+                if let Some(l) = tree.responsible_ect_bar() {
+                    assert_eq!(with_lambda(l), expected);
+                }
+            }
+        }
+    }
+}
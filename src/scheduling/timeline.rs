@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// implements a timeline of disjoint busy intervals within a horizon (the rest of the horizon
+/// is free). Busy intervals are kept distinct (adjacent ones are not merged), so that each can
+/// be removed individually.
+/// If *k* is the number of busy intervals, this data-structure has the following complexities:
+/// - memory: O(k)
+/// - insertion/removal of a busy interval, is_free: O(log k)
+/// - first free slot: O(log k + number of busy intervals skipped)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Timeline {
+    /// free time outside of the busy intervals (start included, end excluded)
+    horizon:Range<i64>,
+    /// busy intervals (start → end)
+    busy:BTreeMap<i64,i64>,
+}
+
+impl Timeline {
+    /// creates a timeline free on the whole horizon
+    pub fn new(horizon:Range<i64>) -> Self { Self { horizon, busy:BTreeMap::new() } }
+
+    /// returns the horizon
+    pub fn horizon(&self) -> Range<i64> { self.horizon.clone() }
+
+    /// returns the number of busy intervals
+    pub fn len(&self) -> usize { self.busy.len() }
+
+    /// returns true iff there is no busy interval
+    pub fn is_empty(&self) -> bool { self.busy.is_empty() }
+
+    /// returns the total busy time
+    pub fn busy_time(&self) -> i64 { self.busy.iter().map(|(s,e)| e - s).sum() }
+
+    /// returns true iff the range is in the horizon and does not intersect a busy interval
+    pub fn is_free(&self, range:Range<i64>) -> bool {
+        if range.is_empty() { return true; }
+        if range.start < self.horizon.start || range.end > self.horizon.end { return false; }
+        // the last busy interval starting before the end of the range must end before its start
+        self.busy.range(..range.end).next_back().is_none_or(|(_,e)| *e <= range.start)
+    }
+
+    /// marks the range as busy. Returns false (and does nothing) if the range is empty or not
+    /// free
+    pub fn insert_busy(&mut self, range:Range<i64>) -> bool {
+        if range.is_empty() || !self.is_free(range.clone()) { return false; }
+        self.busy.insert(range.start, range.end);
+        true
+    }
+
+    /// removes a busy interval (given exactly as inserted). Returns true iff it existed
+    pub fn remove_busy(&mut self, range:Range<i64>) -> bool {
+        if self.busy.get(&range.start) != Some(&range.end) { return false; }
+        self.busy.remove(&range.start);
+        true
+    }
+
+    /// returns the busy interval containing t if it exists
+    pub fn busy_at(&self, t:i64) -> Option<Range<i64>> {
+        self.busy.range(..=t).next_back().filter(|(_,e)| **e > t).map(|(s,e)| *s..*e)
+    }
+
+    /// returns the earliest start s ≥ t such that s..s+length is free (None if no slot of
+    /// this length remains in the horizon)
+    pub fn first_fit(&self, t:i64, length:i64) -> Option<i64> {
+        let mut start = t.max(self.horizon.start);
+        if let Some(busy) = self.busy_at(start) { start = busy.end; }
+        for (s,e) in self.busy.range(start..) {
+            if s - start >= length { break; }
+            start = *e;
+        }
+        (start + length <= self.horizon.end).then_some(start)
+    }
+
+    /// iterates over the busy intervals (in increasing order)
+    pub fn busy(&self) -> impl Iterator<Item=Range<i64>> + '_ {
+        self.busy.iter().map(|(s,e)| *s..*e)
+    }
+
+    /// iterates over the maximal free intervals of the horizon (in increasing order)
+    pub fn gaps(&self) -> impl Iterator<Item=Range<i64>> + '_ { self.gaps_from(self.horizon.start) }
+
+    /// iterates over the maximal free intervals of the horizon after t (in increasing order,
+    /// the first one is truncated to start at t)
+    pub fn gaps_from(&self, t:i64) -> impl Iterator<Item=Range<i64>> + '_ {
+        let mut start = t.max(self.horizon.start);
+        if let Some(busy) = self.busy_at(start) { start = busy.end; }
+        let end = self.horizon.end;
+        self.busy.range(start..).map(|(s,e)| (*s,*e))
+            .chain(std::iter::once((end, end)))
+            .filter_map(move |(s,e)| {
+                let gap = start..s.min(end);
+                start = start.max(e);
+                (!gap.is_empty()).then_some(gap)
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_intervals() {
+        let mut timeline = Timeline::new(0..100);
+        assert!(timeline.insert_busy(10..20));
+        assert!(timeline.insert_busy(20..25)); // adjacent intervals stay distinct
+        assert!(!timeline.insert_busy(15..30));
+        assert!(!timeline.insert_busy(95..105));
+        assert!(!timeline.insert_busy(5..5));
+        assert!(timeline.insert_busy(50..60));
+        assert_eq!((timeline.len(), timeline.busy_time()), (3, 25));
+        assert!(timeline.is_free(25..50) && !timeline.is_free(24..26));
+        assert_eq!(timeline.busy_at(24), Some(20..25));
+        assert_eq!(timeline.busy_at(25), None);
+        assert!(!timeline.remove_busy(10..25));
+        assert!(timeline.remove_busy(20..25));
+        assert_eq!(timeline.busy().collect::<Vec<_>>(), vec![10..20, 50..60]);
+    }
+
+    #[test]
+    fn first_fit_and_gaps() {
+        let mut timeline = Timeline::new(0..100);
+        for range in [10..20, 20..25, 30..50, 52..60] { timeline.insert_busy(range); }
+        assert_eq!(timeline.first_fit(0, 10), Some(0));
+        assert_eq!(timeline.first_fit(3, 10), Some(60));
+        assert_eq!(timeline.first_fit(12, 5), Some(25));
+        assert_eq!(timeline.first_fit(12, 2), Some(25));
+        assert_eq!(timeline.first_fit(40, 2), Some(50));
+        assert_eq!(timeline.first_fit(61, 39), Some(61));
+        assert_eq!(timeline.first_fit(61, 40), None);
+        assert_eq!(timeline.gaps().collect::<Vec<_>>(), vec![0..10, 25..30, 50..52, 60..100]);
+        assert_eq!(timeline.gaps_from(27).collect::<Vec<_>>(), vec![27..30, 50..52, 60..100]);
+        assert_eq!(timeline.gaps_from(15).next(), Some(25..30));
+        let full = Timeline { horizon:0..10, busy:BTreeMap::from([(0, 10)]) };
+        assert_eq!(full.gaps().count(), 0);
+    }
+}
@@ -0,0 +1,137 @@
+use super::bitset::BitSet;
+
+/// implements an n×n bit-matrix (e.g. adjacency of a conflict graph, precedence matrix) with
+/// n <= 64*WORDS. Each row is a BitSet, thus rows compose with the bit-set operations.
+/// - memory: n*WORDS words
+/// - get/set: O(1)
+/// - row and/or: O(WORDS)
+/// - transpose: O(n²)
+/// - transitive closure: O(n²*WORDS) (Warshall on rows)
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct BitMatrix<const WORDS:usize> {
+    /// rows of the matrix (bit j of row i is the cell (i,j))
+    rows:Vec<BitSet<WORDS>>,
+}
+
+impl<const WORDS:usize> BitMatrix<WORDS> {
+    /// creates an empty n×n matrix
+    pub fn new(n:usize) -> Self {
+        assert!(n <= BitSet::<WORDS>::CAPACITY, "the rows cannot store n bits");
+        Self { rows:vec![BitSet::new(); n] }
+    }
+
+    /// returns the number of rows (and columns)
+    pub fn size(&self) -> usize { self.rows.len() }
+
+    /// returns true iff the cell (i,j) is set
+    pub fn contains(&self, i:usize, j:usize) -> bool { self.rows[i].contains(j) }
+
+    /// sets the cell (i,j). Returns true iff it was not set
+    pub fn insert(&mut self, i:usize, j:usize) -> bool { self.rows[i].insert(j) }
+
+    /// unsets the cell (i,j). Returns true iff it was set
+    pub fn remove(&mut self, i:usize, j:usize) -> bool { self.rows[i].remove(j) }
+
+    /// returns the row i (columns j such that (i,j) is set)
+    pub fn row(&self, i:usize) -> &BitSet<WORDS> { &self.rows[i] }
+
+    /// returns the row i (mutable)
+    pub fn row_mut(&mut self, i:usize) -> &mut BitSet<WORDS> { &mut self.rows[i] }
+
+    /// returns the column j (rows i such that (i,j) is set). O(n)
+    pub fn column(&self, j:usize) -> BitSet<WORDS> {
+        (0..self.size()).filter(|i| self.rows[*i].contains(j)).collect()
+    }
+
+    /// returns row i AND row j
+    pub fn and_rows(&self, i:usize, j:usize) -> BitSet<WORDS> {
+        let mut res = self.rows[i];
+        res.intersect_with(&self.rows[j]);
+        res
+    }
+
+    /// returns row i OR row j
+    pub fn or_rows(&self, i:usize, j:usize) -> BitSet<WORDS> {
+        let mut res = self.rows[i];
+        res.union_with(&self.rows[j]);
+        res
+    }
+
+    /// row dst |= row src
+    pub fn or_row_into(&mut self, dst:usize, src:usize) {
+        let row = self.rows[src];
+        self.rows[dst].union_with(&row);
+    }
+
+    /// row dst &= row src
+    pub fn and_row_into(&mut self, dst:usize, src:usize) {
+        let row = self.rows[src];
+        self.rows[dst].intersect_with(&row);
+    }
+
+    /// returns the transposed matrix
+    pub fn transpose(&self) -> Self {
+        let mut res = Self::new(self.size());
+        for (i,row) in self.rows.iter().enumerate() {
+            for j in row { res.rows[j].insert(i); }
+        }
+        res
+    }
+
+    /// computes the transitive closure of the matrix (seen as a relation): (i,j) is set iff
+    /// there is a path from i to j
+    pub fn transitive_closure(&mut self) {
+        for k in 0..self.size() {
+            let row_k = self.rows[k];
+            for row in &mut self.rows {
+                if row.contains(k) { row.union_with(&row_k); }
+            }
+        }
+    }
+
+    /// iterates over the set cells (i,j) (row by row)
+    pub fn iter(&self) -> impl Iterator<Item=(usize,usize)> + '_ {
+        self.rows.iter().enumerate().flat_map(|(i,row)| row.iter().map(move |j| (i,j)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_and_transpose() {
+        let mut m:BitMatrix<2> = BitMatrix::new(100);
+        assert!(m.insert(0, 1));
+        assert!(!m.insert(0, 1));
+        m.insert(0, 99);
+        m.insert(2, 1);
+        m.insert(2, 5);
+        assert!(m.contains(2, 5) && !m.contains(5, 2));
+        assert_eq!(m.and_rows(0, 2).iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(m.or_rows(0, 2).iter().collect::<Vec<_>>(), vec![1, 5, 99]);
+        assert_eq!(m.column(1).iter().collect::<Vec<_>>(), vec![0, 2]);
+        let t = m.transpose();
+        assert_eq!(t.row(1).iter().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(t.transpose(), m);
+        m.or_row_into(3, 2);
+        assert_eq!(m.row(3).iter().collect::<Vec<_>>(), vec![1, 5]);
+        m.and_row_into(3, 0);
+        assert_eq!(m.iter().filter(|(i,_)| *i == 3).collect::<Vec<_>>(), vec![(3, 1)]);
+        assert!(m.remove(3, 1) && m.row(3).is_empty());
+        m.row_mut(4).insert(7);
+        assert!(m.contains(4, 7));
+    }
+
+    #[test]
+    fn transitive_closure() {
+        // precedences 0 -> 1 -> 2 -> 3, 4 -> 0
+        let mut m:BitMatrix<1> = BitMatrix::new(5);
+        for (i,j) in [(0, 1), (1, 2), (2, 3), (4, 0)] { m.insert(i, j); }
+        m.transitive_closure();
+        assert_eq!(m.row(4).iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(m.row(1).iter().collect::<Vec<_>>(), vec![2, 3]);
+        assert!(m.row(3).is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use super::set_ops::{hash_sorted, ElementSet};
+use super::universe::Universe;
+
+/// implements a fixed-size bit-set over 0..64*WORDS (stack allocated).
+/// Each element is a bit of an array of WORDS 64-bit words. It is efficient for small dense
+/// universes (e.g. WORDS=8 for 512 elements), and can be copied and hashed.
+/// - memory: WORDS words
+/// - insertion: O(1)
+/// - remove: O(1)
+/// - contains: O(1)
+/// - union/intersection/difference/subset check: O(WORDS)
+/// - count: O(WORDS) (popcount)
+/// - lazy union/intersection/difference iterators: O(WORDS + size of the result)
+/// - successor/predecessor: O(WORDS) (word scans)
+///
+/// Bit-sets hash in the canonical form of the crate (see set_ops::hash_sorted) and are
+/// ordered lexicographically by their sorted elements.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct BitSet<const WORDS:usize> {
+    /// bits of the elements (element e is the bit e%64 of the word e/64)
+    words:[u64;WORDS],
+}
+
+impl<const WORDS:usize> Default for BitSet<WORDS> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const WORDS:usize> BitSet<WORDS> {
+    /// maximum number of elements (elements are in 0..CAPACITY)
+    pub const CAPACITY:usize = 64*WORDS;
+
+    /// creates an empty bit-set
+    pub const fn new() -> Self { Self { words:[0;WORDS] } }
+
+    /// creates the bit-set containing 0..n
+    pub fn full(n:usize) -> Self {
+        debug_assert!(n <= Self::CAPACITY);
+        let mut res = Self::new();
+        for (i,w) in res.words.iter_mut().enumerate() {
+            let nb_bits = n.saturating_sub(64*i).min(64);
+            *w = if nb_bits == 64 { u64::MAX } else { (1 << nb_bits) - 1 };
+        }
+        res
+    }
+
+    /// returns the words of the bit-set
+    pub fn words(&self) -> &[u64;WORDS] { &self.words }
+
+    /// true iff e ∈ Set
+    pub fn contains(&self, e:usize) -> bool {
+        debug_assert!(e < Self::CAPACITY);
+        self.words[e / 64] & (1 << (e % 64)) != 0
+    }
+
+    /// inserts e into the set. Returns true iff the element was missing
+    pub fn insert(&mut self, e:usize) -> bool {
+        debug_assert!(e < Self::CAPACITY);
+        let res = !self.contains(e);
+        self.words[e / 64] |= 1 << (e % 64);
+        res
+    }
+
+    /// removes e from the set. Returns true iff the element was present
+    pub fn remove(&mut self, e:usize) -> bool {
+        debug_assert!(e < Self::CAPACITY);
+        let res = self.contains(e);
+        self.words[e / 64] &= !(1 << (e % 64));
+        res
+    }
+
+    /// removes all the elements
+    pub fn clear(&mut self) { self.words = [0;WORDS]; }
+
+    /// returns the number of elements in the set
+    pub fn len(&self) -> usize { self.words.iter().map(|w| w.count_ones() as usize).sum() }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.words.iter().all(|w| *w == 0) }
+
+    /// adds the elements of other to the set
+    pub fn union_with(&mut self, other:&Self) {
+        for (a,b) in self.words.iter_mut().zip(&other.words) { *a |= b; }
+    }
+
+    /// keeps only the elements that are also in other
+    pub fn intersect_with(&mut self, other:&Self) {
+        for (a,b) in self.words.iter_mut().zip(&other.words) { *a &= b; }
+    }
+
+    /// removes the elements of other from the set
+    pub fn difference_with(&mut self, other:&Self) {
+        for (a,b) in self.words.iter_mut().zip(&other.words) { *a &= !b; }
+    }
+
+    /// returns true iff every element of the set is in other
+    pub fn is_subset_of(&self, other:&Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(a,b)| a & !b == 0)
+    }
+
+    /// returns true iff every element of other is in the set
+    pub fn is_superset_of(&self, other:&Self) -> bool { other.is_subset_of(self) }
+
+    /// returns true iff no element of the set is in other
+    pub fn is_disjoint_from(&self, other:&Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(a,b)| a & b == 0)
+    }
+
+    /// returns the elements of the universe that are not in the set (word-wise for a range).
+    /// Elements of the universe greater than or equal to the capacity are ignored
+    pub fn complement(&self, universe:&Universe) -> Self {
+        match universe {
+            Universe::Range(r) if r.is_empty() => Self::new(),
+            Universe::Range(r) => {
+                let mut res = Self::full(r.end.min(Self::CAPACITY));
+                res.difference_with(&Self::full(r.start.min(Self::CAPACITY)));
+                res.difference_with(self);
+                res
+            },
+            Universe::Elements(v) => v.iter().copied()
+                .filter(|e| *e < Self::CAPACITY && !self.contains(*e))
+                .collect(),
+        }
+    }
+
+    /// returns the smallest element greater than or equal to v
+    pub fn next_at_or_after(&self, v:usize) -> Option<usize> {
+        if v >= Self::CAPACITY { return None; }
+        let mut i = v / 64;
+        // ignores the bits lower than v in its word
+        let mut w = self.words[i] & (u64::MAX << (v % 64));
+        loop {
+            if w != 0 { return Some(64*i + w.trailing_zeros() as usize); }
+            i += 1;
+            if i >= WORDS { return None; }
+            w = self.words[i];
+        }
+    }
+
+    /// returns the largest element lower than or equal to v
+    pub fn prev_at_or_before(&self, v:usize) -> Option<usize> {
+        if Self::CAPACITY == 0 { return None; }
+        let start = v.min(Self::CAPACITY-1);
+        let mut i = start / 64;
+        // ignores the bits greater than start in its word
+        let mut w = self.words[i] & (u64::MAX >> (63 - start % 64));
+        loop {
+            if w != 0 { return Some(64*i + 63 - w.leading_zeros() as usize); }
+            if i == 0 { return None; }
+            i -= 1;
+            w = self.words[i];
+        }
+    }
+
+    /// returns the minimum element (takes self by value so that it is not shadowed by Ord::min)
+    pub fn min(self) -> Option<usize> { self.next_at_or_after(0) }
+
+    /// returns the maximum element (takes self by value so that it is not shadowed by Ord::max)
+    pub fn max(self) -> Option<usize> { self.prev_at_or_before(usize::MAX) }
+
+    /// iterates over the elements in increasing order
+    pub fn iter(&self) -> BitSetIterator<'_,WORDS> {
+        BitSetIterator { words:&self.words, index:0, current:self.words.first().copied().unwrap_or(0) }
+    }
+
+    /// iterates over the elements of self ∪ other (without computing the union)
+    pub fn union_iter<'a>(&'a self, other:&'a Self) -> BitSetOpIterator<'a,WORDS> {
+        BitSetOpIterator::new(&self.words, &other.words, |a,b| a | b)
+    }
+
+    /// iterates over the elements of self ∩ other (without computing the intersection)
+    pub fn intersection_iter<'a>(&'a self, other:&'a Self) -> BitSetOpIterator<'a,WORDS> {
+        BitSetOpIterator::new(&self.words, &other.words, |a,b| a & b)
+    }
+
+    /// iterates over the elements of self \ other (without computing the difference)
+    pub fn difference_iter<'a>(&'a self, other:&'a Self) -> BitSetOpIterator<'a,WORDS> {
+        BitSetOpIterator::new(&self.words, &other.words, |a,b| a & !b)
+    }
+
+    /// iterates over the elements of self Δ other (without computing the symmetric difference)
+    pub fn symmetric_difference_iter<'a>(&'a self, other:&'a Self) -> BitSetOpIterator<'a,WORDS> {
+        BitSetOpIterator::new(&self.words, &other.words, |a,b| a ^ b)
+    }
+}
+
+impl<const WORDS:usize> ElementSet for BitSet<WORDS> {
+    fn contains_element(&self, e:usize) -> bool { e < Self::CAPACITY && self.contains(e) }
+
+    fn nb_elements(&self) -> usize { self.len() }
+
+    fn elements(&self) -> impl Iterator<Item=usize> + '_ { self.iter() }
+}
+
+impl<const WORDS:usize> Hash for BitSet<WORDS> {
+    fn hash<H:Hasher>(&self, state:&mut H) { hash_sorted(self.len(), self.iter(), state); }
+}
+
+impl<const WORDS:usize> PartialOrd for BitSet<WORDS> {
+    fn partial_cmp(&self, other:&Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<const WORDS:usize> Ord for BitSet<WORDS> {
+    fn cmp(&self, other:&Self) -> Ordering { self.iter().cmp(other.iter()) }
+}
+
+impl<const WORDS:usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<I:IntoIterator<Item=usize>>(iter:I) -> Self {
+        let mut res = Self::new();
+        for e in iter { res.insert(e); }
+        res
+    }
+}
+
+impl<const WORDS:usize> Extend<usize> for BitSet<WORDS> {
+    fn extend<I:IntoIterator<Item=usize>>(&mut self, iter:I) {
+        for e in iter { self.insert(e); }
+    }
+}
+
+/// iterates over the elements in increasing order
+impl<const WORDS:usize> IntoIterator for BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = BitSetIntoIterator<WORDS>;
+
+    fn into_iter(self) -> Self::IntoIter { BitSetIntoIterator { words:self.words, index:0 } }
+}
+
+/// iterates over the elements in increasing order
+impl<'a,const WORDS:usize> IntoIterator for &'a BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = BitSetIterator<'a,WORDS>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the elements of a bit-set (increasing order).
+#[derive(Debug)]
+pub struct BitSetIterator<'a,const WORDS:usize> {
+    /// words of the bit-set
+    words:&'a [u64;WORDS],
+    /// index of the current word
+    index:usize,
+    /// bits of the current word not visited yet
+    current:u64,
+}
+
+impl<'a,const WORDS:usize> Iterator for BitSetIterator<'a,WORDS> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            if self.index >= WORDS { return None; }
+            self.current = self.words[self.index];
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1; // clears the lowest bit
+        Some(64*self.index + bit)
+    }
+}
+
+/// Iterator over the elements of a bit-set, consuming it (increasing order).
+#[derive(Debug)]
+pub struct BitSetIntoIterator<const WORDS:usize> {
+    /// words not visited yet (visited bits are cleared)
+    words:[u64;WORDS],
+    /// index of the current word
+    index:usize,
+}
+
+impl<const WORDS:usize> Iterator for BitSetIntoIterator<WORDS> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        while self.index < WORDS && self.words[self.index] == 0 { self.index += 1; }
+        let w = self.words.get_mut(self.index)?;
+        let bit = w.trailing_zeros() as usize;
+        *w &= *w - 1; // clears the lowest bit
+        Some(64*self.index + bit)
+    }
+}
+
+/// Iterator over the elements of a word-wise operation between two bit-sets (increasing
+/// order). The words of the result are computed one at a time.
+#[derive(Debug)]
+pub struct BitSetOpIterator<'a,const WORDS:usize> {
+    /// words of the first bit-set
+    a:&'a [u64;WORDS],
+    /// words of the second bit-set
+    b:&'a [u64;WORDS],
+    /// operation applied on each pair of words
+    op:fn(u64,u64) -> u64,
+    /// index of the current word
+    index:usize,
+    /// bits of the current word not visited yet
+    current:u64,
+}
+
+impl<'a,const WORDS:usize> BitSetOpIterator<'a,WORDS> {
+    /// creates the iterator over the words op(a\[i\], b\[i\])
+    fn new(a:&'a [u64;WORDS], b:&'a [u64;WORDS], op:fn(u64,u64) -> u64) -> Self {
+        let current = if WORDS == 0 { 0 } else { op(a[0], b[0]) };
+        Self { a, b, op, index:0, current }
+    }
+}
+
+impl<'a,const WORDS:usize> Iterator for BitSetOpIterator<'a,WORDS> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            if self.index >= WORDS { return None; }
+            self.current = (self.op)(self.a[self.index], self.b[self.index]);
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1; // clears the lowest bit
+        Some(64*self.index + bit)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove() {
+        let mut set:BitSet<2> = BitSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(3));
+        assert!(set.insert(64));
+        assert!(set.insert(127));
+        assert!(!set.insert(3));
+        assert!(set.contains(64));
+        assert!(!set.contains(63));
+        assert_eq!(set.len(), 3);
+        assert!(set.remove(64));
+        assert!(!set.remove(64));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 127]);
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(BitSet::<2>::CAPACITY, 128);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a:BitSet<8> = [1, 70, 300, 511].into_iter().collect();
+        let b:BitSet<8> = [70, 100, 511].into_iter().collect();
+        let mut union = a;
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 70, 100, 300, 511]);
+        let mut intersection = a;
+        intersection.intersect_with(&b);
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), vec![70, 511]);
+        let mut difference = a;
+        difference.difference_with(&b);
+        assert_eq!((&difference).into_iter().collect::<Vec<_>>(), vec![1, 300]);
+        assert!(intersection.is_subset_of(&a));
+        assert!(!a.is_subset_of(&b));
+        assert!(BitSet::<8>::new().is_subset_of(&b));
+        assert!(a.is_superset_of(&intersection));
+        assert!(difference.is_disjoint_from(&b) && !a.is_disjoint_from(&b));
+    }
+
+    #[test]
+    fn mixed_predicates() {
+        let bits:BitSet<1> = [2, 5, 7].into_iter().collect();
+        let sorted:&[usize] = &[2, 7, 100];
+        assert!(!sorted.is_subset_of_set(&bits));
+        assert!(bits.is_superset_of_set(&sorted[..2]));
+        assert!(bits.is_disjoint_from_set(&[3usize, 100][..]));
+    }
+
+    #[test]
+    fn lazy_operations() {
+        let a:BitSet<3> = [1, 70, 130, 191].into_iter().collect();
+        let b:BitSet<3> = [70, 100, 191].into_iter().collect();
+        assert_eq!(a.union_iter(&b).collect::<Vec<_>>(), vec![1, 70, 100, 130, 191]);
+        assert_eq!(a.intersection_iter(&b).collect::<Vec<_>>(), vec![70, 191]);
+        assert_eq!(a.difference_iter(&b).collect::<Vec<_>>(), vec![1, 130]);
+        assert_eq!(a.symmetric_difference_iter(&b).collect::<Vec<_>>(), vec![1, 100, 130]);
+        assert_eq!(BitSet::<0>::new().union_iter(&BitSet::new()).count(), 0);
+    }
+
+    #[test]
+    fn successor_predecessor() {
+        let set:BitSet<4> = [5, 63, 64, 200].into_iter().collect();
+        assert_eq!(set.next_at_or_after(0), Some(5));
+        assert_eq!(set.next_at_or_after(5), Some(5));
+        assert_eq!(set.next_at_or_after(6), Some(63));
+        assert_eq!(set.next_at_or_after(65), Some(200));
+        assert_eq!(set.next_at_or_after(201), None);
+        assert_eq!(set.next_at_or_after(1000), None);
+        assert_eq!(set.prev_at_or_before(1000), Some(200));
+        assert_eq!(set.prev_at_or_before(199), Some(64));
+        assert_eq!(set.prev_at_or_before(63), Some(63));
+        assert_eq!(set.prev_at_or_before(4), None);
+        assert_eq!((set.min(), set.max()), (Some(5), Some(200)));
+        assert_eq!(BitSet::<2>::new().max(), None);
+        assert_eq!(BitSet::<0>::new().max(), None);
+    }
+
+    #[test]
+    fn extend_into_iter() {
+        let mut set:BitSet<3> = BitSet::new();
+        set.extend([130, 2, 64]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![2, 64, 130]);
+        assert_eq!(BitSet::<0>::new().into_iter().next(), None);
+    }
+
+    #[test]
+    fn full() {
+        assert_eq!(BitSet::<2>::full(70).len(), 70);
+        assert_eq!(BitSet::<2>::full(128).len(), 128);
+        assert_eq!(BitSet::<2>::full(0), BitSet::new());
+        assert_eq!(BitSet::<1>::full(3).iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,189 @@
+use super::bitset::BitSet;
+
+/// Iterator over the k-element subsets of a slice, in lexicographic order of the positions
+/// (thus in lexicographic order of the elements if the slice is sorted).
+/// Each step takes O(k) (plus the copy of the subset).
+#[derive(Debug,Clone)]
+pub struct Combinations<'a,T> {
+    /// elements of the set
+    elements:&'a [T],
+    /// positions of the elements of the next subset (None if the enumeration is over)
+    positions:Option<Vec<usize>>,
+}
+
+impl<'a,T:Copy> Combinations<'a,T> {
+    /// creates the iterator over the k-subsets of elements (no subset if k > elements.len())
+    pub fn new(elements:&'a [T], k:usize) -> Self {
+        let positions = if k <= elements.len() { Some((0..k).collect()) } else { None };
+        Self { elements, positions }
+    }
+}
+
+impl<'a,T:Copy> Iterator for Combinations<'a,T> {
+    type Item = Vec<T>;
+    fn next(&mut self) -> Option<Vec<T>> {
+        let positions = self.positions.as_mut()?;
+        let res = positions.iter().map(|i| self.elements[*i]).collect();
+        if !advance(positions, self.elements.len()) { self.positions = None; }
+        Some(res)
+    }
+}
+
+/// moves the positions (of a k-subset of 0..n) to the next k-subset in lexicographic order.
+/// Returns false if they were the last one
+fn advance(positions:&mut [usize], n:usize) -> bool {
+    let k = positions.len();
+    // rightmost position that can be increased
+    let Some(i) = (0..k).rev().find(|i| positions[*i] < n - k + i) else { return false; };
+    positions[i] += 1;
+    for j in i+1..k { positions[j] = positions[j-1] + 1; }
+    true
+}
+
+/// returns the next word (in increasing order) having the same number of bits set than x
+/// (Gosper's hack). Returns None if x is 0 or if there is no such word
+pub fn next_combination(x:u64) -> Option<u64> {
+    if x == 0 { return None; }
+    let lowest = x & x.wrapping_neg();
+    let ripple = x.checked_add(lowest)?;
+    Some(ripple | (((x ^ ripple) >> 2) / lowest))
+}
+
+impl<const WORDS:usize> BitSet<WORDS> {
+    /// iterates over the k-element subsets of the set (lexicographic order of the elements)
+    pub fn combinations(&self, k:usize) -> BitSetCombinations<WORDS> {
+        let elements:Vec<usize> = self.iter().collect();
+        let positions = if k <= elements.len() { Some((0..k).collect()) } else { None };
+        BitSetCombinations { elements, positions }
+    }
+}
+
+/// Iterator over the k-element subsets of a bit-set (lexicographic order of the elements).
+#[derive(Debug,Clone)]
+pub struct BitSetCombinations<const WORDS:usize> {
+    /// elements of the bit-set (increasing order)
+    elements:Vec<usize>,
+    /// positions of the elements of the next subset (None if the enumeration is over)
+    positions:Option<Vec<usize>>,
+}
+
+impl<const WORDS:usize> Iterator for BitSetCombinations<WORDS> {
+    type Item = BitSet<WORDS>;
+    fn next(&mut self) -> Option<BitSet<WORDS>> {
+        let positions = self.positions.as_mut()?;
+        let res = positions.iter().map(|i| self.elements[*i]).collect();
+        if !advance(positions, self.elements.len()) { self.positions = None; }
+        Some(res)
+    }
+}
+
+/// Iterator over all the subsets of a slice (at most 63 elements) in Gray-code order:
+/// two consecutive subsets differ by a single element. The enumeration starts from the
+/// empty set (not yielded), and each step yields the toggled element and true iff it was
+/// inserted (false if it was removed). The current subset is given by mask (bit i set iff
+/// elements\[i\] is in it). The 2^n - 1 steps take O(1) each.
+#[derive(Debug,Clone)]
+pub struct GrayCodeSubsets<'a,T> {
+    /// elements of the set
+    elements:&'a [T],
+    /// number of steps done
+    step:u64,
+    /// current subset (Gray code of step)
+    mask:u64,
+}
+
+impl<'a,T:Copy> GrayCodeSubsets<'a,T> {
+    /// creates the iterator over the subsets of elements (starting from the empty set)
+    pub fn new(elements:&'a [T]) -> Self {
+        assert!(elements.len() < 64, "too many elements to enumerate the subsets");
+        Self { elements, step:0, mask:0 }
+    }
+
+    /// returns the current subset (bit i set iff elements\[i\] is in it)
+    pub fn mask(&self) -> u64 { self.mask }
+
+    /// returns the elements of the current subset
+    pub fn subset(&self) -> Vec<T> {
+        self.elements.iter().enumerate()
+            .filter(|(i,_)| self.mask & (1 << i) != 0)
+            .map(|(_,e)| *e)
+            .collect()
+    }
+}
+
+impl<'a,T:Copy> Iterator for GrayCodeSubsets<'a,T> {
+    type Item = (T,bool);
+    fn next(&mut self) -> Option<(T,bool)> {
+        if self.step + 1 >= 1 << self.elements.len() { return None; }
+        self.step += 1;
+        // the bit toggled between the Gray codes of step-1 and step
+        let bit = self.step.trailing_zeros() as usize;
+        self.mask ^= 1 << bit;
+        Some((self.elements[bit], self.mask & (1 << bit) != 0))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = ((1u64 << self.elements.len()) - 1 - self.step) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_combinations() {
+        let subsets:Vec<Vec<char>> = Combinations::new(&['a', 'b', 'c', 'd'], 2).collect();
+        assert_eq!(subsets.len(), 6);
+        assert_eq!(subsets[0], vec!['a', 'b']);
+        assert_eq!(subsets[1], vec!['a', 'c']);
+        assert_eq!(subsets[5], vec!['c', 'd']);
+        assert_eq!(Combinations::new(&[1, 2, 3], 0).collect::<Vec<_>>(), vec![vec![]]);
+        assert_eq!(Combinations::new(&[1, 2, 3], 3).count(), 1);
+        assert_eq!(Combinations::new(&[1, 2, 3], 4).count(), 0);
+        assert_eq!(Combinations::new(&(0..10).collect::<Vec<_>>(), 4).count(), 210);
+    }
+
+    #[test]
+    fn bitset_combinations() {
+        let set:BitSet<2> = [3, 64, 100].into_iter().collect();
+        let subsets:Vec<Vec<usize>> = set.combinations(2).map(|s| s.iter().collect()).collect();
+        assert_eq!(subsets, vec![vec![3, 64], vec![3, 100], vec![64, 100]]);
+        assert_eq!(set.combinations(4).count(), 0);
+    }
+
+    #[test]
+    fn gosper() {
+        // 3-subsets of 0..5 (5 choose 3 = 10)
+        let words:Vec<u64> = std::iter::successors(Some(0b111), |x| next_combination(*x).filter(|y| *y < 1 << 5)).collect();
+        assert_eq!(words.len(), 10);
+        assert!(words.iter().all(|w| w.count_ones() == 3));
+        assert_eq!(words[1], 0b1011);
+        assert_eq!(next_combination(0), None);
+        assert_eq!(next_combination(u64::MAX), None);
+        assert_eq!(next_combination(1 << 63), None);
+    }
+
+    #[test]
+    fn gray_code() {
+        let mut subsets = GrayCodeSubsets::new(&[10, 20, 30]);
+        assert_eq!(subsets.next(), Some((10, true)));
+        assert_eq!(subsets.next(), Some((20, true)));
+        assert_eq!(subsets.next(), Some((10, false)));
+        assert_eq!(subsets.subset(), vec![20]);
+        // incremental sum of the subsets
+        let mut sum = 20;
+        let mut sums = vec![sum];
+        for (e,inserted) in subsets.by_ref() {
+            if inserted { sum += e; } else { sum -= e; }
+            sums.push(sum);
+        }
+        assert_eq!(subsets.mask(), 0b100);
+        sums.sort_unstable();
+        assert_eq!(sums, vec![20, 30, 40, 50, 60]);
+        assert_eq!(GrayCodeSubsets::<u8>::new(&[]).count(), 0);
+        assert_eq!(GrayCodeSubsets::new(&[0; 10]).count(), 1023);
+    }
+}
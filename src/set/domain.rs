@@ -0,0 +1,327 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use crate::trail::Backtrackable;
+
+/// Events describing how a domain changed (bit mask), e.g. to decide which propagators to
+/// wake up.
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq,Hash)]
+pub struct DomainEvents(u8);
+
+impl DomainEvents {
+    /// no change
+    pub const NONE:Self = Self(0);
+    /// at least one value was removed
+    pub const REMOVED:Self = Self(1);
+    /// the minimum increased
+    pub const MIN:Self = Self(1 << 1);
+    /// the maximum decreased
+    pub const MAX:Self = Self(1 << 2);
+    /// the domain became a single value
+    pub const ASSIGNED:Self = Self(1 << 3);
+    /// the domain became empty (failure)
+    pub const EMPTY:Self = Self(1 << 4);
+
+    /// returns true iff no event happened
+    pub fn is_empty(&self) -> bool { self.0 == 0 }
+
+    /// returns true iff all the events of other happened
+    pub fn contains(&self, other:Self) -> bool { self.0 & other.0 == other.0 }
+
+    /// returns true iff a bound changed (min or max)
+    pub fn is_bound_change(&self) -> bool { self.0 & (Self::MIN.0 | Self::MAX.0) != 0 }
+}
+
+impl BitOr for DomainEvents {
+    type Output = Self;
+    fn bitor(self, rhs:Self) -> Self { Self(self.0 | rhs.0) }
+}
+
+impl BitOrAssign for DomainEvents {
+    fn bitor_assign(&mut self, rhs:Self) { self.0 |= rhs.0; }
+}
+
+/// implements a bounded integer domain (values of a CP variable over min..=max) as a bit-set
+/// with a cached minimum, maximum and size. Each modification returns the events it caused
+/// (see DomainEvents), also accumulated until take_events is called.
+/// If *n* is the size of the initial range, this data-structure has the following complexities:
+/// - memory: n/64 words
+/// - contains/min/max/size: O(1)
+/// - remove_value: O(1) (plus a word scan if it removes a bound)
+/// - remove_below/remove_above: O(number of words between the old and the new bound)
+/// - save_level: O(1), restore_level: O(number of words modified since then)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Domain {
+    /// bits of the values (minus the offset)
+    words:Vec<u64>,
+    /// smallest value of the initial range
+    offset:i32,
+    /// number of values of the initial range
+    capacity:usize,
+    /// minimum value (minus the offset, meaningless if the domain is empty)
+    min:usize,
+    /// maximum value (minus the offset, meaningless if the domain is empty)
+    max:usize,
+    /// number of values in the domain
+    size:usize,
+    /// events since the last call to take_events
+    events:DomainEvents,
+    /// modified words since the first saved level (index, previous word)
+    trail:Vec<(usize,u64)>,
+    /// saved levels (trail size, min, max, size)
+    levels:Vec<(usize,usize,usize,usize)>,
+}
+
+impl Domain {
+    /// creates the domain min..=max (empty if max < min)
+    pub fn new(min:i32, max:i32) -> Self {
+        let n = if max < min { 0 } else { (i64::from(max) - i64::from(min) + 1) as usize };
+        let mut words = vec![u64::MAX; n.div_ceil(64)];
+        if n % 64 != 0 { *words.last_mut().unwrap() = (1 << (n % 64)) - 1; }
+        Self {
+            words,
+            offset:min,
+            capacity:n,
+            min:0,
+            max:n.saturating_sub(1),
+            size:n,
+            events:DomainEvents::NONE,
+            trail:Vec::new(),
+            levels:Vec::new(),
+        }
+    }
+
+    /// returns the number of values in the domain
+    pub fn size(&self) -> usize { self.size }
+
+    /// returns true iff the domain is empty
+    pub fn is_empty(&self) -> bool { self.size == 0 }
+
+    /// returns true iff the domain contains a single value
+    pub fn is_assigned(&self) -> bool { self.size == 1 }
+
+    /// returns the minimum value (None if the domain is empty)
+    pub fn min(&self) -> Option<i32> {
+        if self.is_empty() { None } else { Some(self.value_of(self.min)) }
+    }
+
+    /// returns the maximum value (None if the domain is empty)
+    pub fn max(&self) -> Option<i32> {
+        if self.is_empty() { None } else { Some(self.value_of(self.max)) }
+    }
+
+    /// true iff v ∈ Domain
+    pub fn contains(&self, v:i32) -> bool { self.relative(v).is_some_and(|r| self.has(r)) }
+
+    /// removes v from the domain. Returns the events caused
+    pub fn remove_value(&mut self, v:i32) -> DomainEvents {
+        let Some(r) = self.relative(v).filter(|r| self.has(*r)) else { return DomainEvents::NONE; };
+        self.clear_word(r / 64, !(1 << (r % 64)));
+        let mut res = DomainEvents::REMOVED;
+        if self.size > 0 {
+            if r == self.min {
+                self.min = self.next_at_or_after(r);
+                res |= DomainEvents::MIN;
+            }
+            if r == self.max {
+                self.max = self.prev_at_or_before(r);
+                res |= DomainEvents::MAX;
+            }
+        }
+        self.record(res)
+    }
+
+    /// removes the values lower than v. Returns the events caused
+    pub fn remove_below(&mut self, v:i32) -> DomainEvents {
+        let Some(min) = self.min() else { return DomainEvents::NONE; };
+        if v <= min { return DomainEvents::NONE; }
+        if self.max().is_some_and(|max| v > max) { return self.remove_all(); }
+        let r = (i64::from(v) - i64::from(self.offset)) as usize;
+        for i in self.min / 64..r / 64 { self.clear_word(i, 0); }
+        self.clear_word(r / 64, u64::MAX << (r % 64));
+        self.min = self.next_at_or_after(r);
+        self.record(DomainEvents::REMOVED | DomainEvents::MIN)
+    }
+
+    /// removes the values greater than v. Returns the events caused
+    pub fn remove_above(&mut self, v:i32) -> DomainEvents {
+        let Some(max) = self.max() else { return DomainEvents::NONE; };
+        if v >= max { return DomainEvents::NONE; }
+        if self.min().is_some_and(|min| v < min) { return self.remove_all(); }
+        let r = (i64::from(v) - i64::from(self.offset)) as usize;
+        for i in r / 64 + 1..=self.max / 64 { self.clear_word(i, 0); }
+        self.clear_word(r / 64, u64::MAX >> (63 - r % 64));
+        self.max = self.prev_at_or_before(r);
+        self.record(DomainEvents::REMOVED | DomainEvents::MAX)
+    }
+
+    /// removes every value except v (empties the domain if v is missing). Returns the events
+    /// caused
+    pub fn assign(&mut self, v:i32) -> DomainEvents {
+        let events = self.remove_below(v) | self.remove_above(v);
+        if self.contains(v) { events } else { events | self.remove_all() }
+    }
+
+    /// saves the current state (restored by restore_level)
+    pub fn save_level(&mut self) {
+        self.levels.push((self.trail.len(), self.min, self.max, self.size));
+    }
+
+    /// restores the last saved state (the values removed since then are back).
+    /// Returns false if no level was saved
+    pub fn restore_level(&mut self) -> bool {
+        let Some((len,min,max,size)) = self.levels.pop() else { return false; };
+        for (i,w) in self.trail.drain(len..).rev() { self.words[i] = w; }
+        self.min = min;
+        self.max = max;
+        self.size = size;
+        true
+    }
+
+    /// returns the number of saved levels
+    pub fn level(&self) -> usize { self.levels.len() }
+
+    /// returns the events since the last call (and resets them)
+    pub fn take_events(&mut self) -> DomainEvents { std::mem::take(&mut self.events) }
+
+    /// iterates over the values in increasing order
+    pub fn iter(&self) -> impl Iterator<Item=i32> + '_ {
+        let first = if self.is_empty() { None } else { Some(self.min) };
+        std::iter::successors(first, |r| {
+            if *r >= self.max { None } else { Some(self.next_at_or_after(r+1)) }
+        }).map(|r| self.value_of(r))
+    }
+
+    /// empties the domain. Returns the events caused
+    fn remove_all(&mut self) -> DomainEvents {
+        if self.is_empty() { return DomainEvents::NONE; }
+        for i in self.min / 64..=self.max / 64 { self.clear_word(i, 0); }
+        self.record(DomainEvents::REMOVED | DomainEvents::MIN | DomainEvents::MAX)
+    }
+
+    /// completes the events (assigned, empty), accumulates them and returns them
+    fn record(&mut self, mut events:DomainEvents) -> DomainEvents {
+        if self.size == 1 { events |= DomainEvents::ASSIGNED; }
+        if self.size == 0 { events |= DomainEvents::EMPTY; }
+        self.events |= events;
+        events
+    }
+
+    /// true iff the relative value r is in the domain
+    fn has(&self, r:usize) -> bool { self.words[r / 64] & (1 << (r % 64)) != 0 }
+
+    /// keeps only the bits of the mask in the word i (and updates the size)
+    fn clear_word(&mut self, i:usize, mask:u64) {
+        let removed = self.words[i] & !mask;
+        if removed == 0 { return; }
+        if !self.levels.is_empty() { self.trail.push((i, self.words[i])); }
+        self.size -= removed.count_ones() as usize;
+        self.words[i] &= mask;
+    }
+
+    /// returns the smallest relative value greater than or equal to r (the domain must
+    /// contain such a value)
+    fn next_at_or_after(&self, r:usize) -> usize {
+        let mut i = r / 64;
+        let mut w = self.words[i] & (u64::MAX << (r % 64));
+        while w == 0 {
+            i += 1;
+            w = self.words[i];
+        }
+        64*i + w.trailing_zeros() as usize
+    }
+
+    /// returns the largest relative value lower than or equal to r (the domain must contain
+    /// such a value)
+    fn prev_at_or_before(&self, r:usize) -> usize {
+        let mut i = r / 64;
+        let mut w = self.words[i] & (u64::MAX >> (63 - r % 64));
+        while w == 0 {
+            i -= 1;
+            w = self.words[i];
+        }
+        64*i + 63 - w.leading_zeros() as usize
+    }
+
+    /// returns the value of a relative value
+    fn value_of(&self, r:usize) -> i32 { (i64::from(self.offset) + r as i64) as i32 }
+
+    /// returns the relative value of v (None if it is out of the initial range)
+    fn relative(&self, v:i32) -> Option<usize> {
+        let r = i64::from(v) - i64::from(self.offset);
+        if r < 0 || r as usize >= self.capacity { None } else { Some(r as usize) }
+    }
+}
+
+impl Backtrackable for Domain {
+    fn save_level(&mut self) { self.save_level(); }
+
+    fn restore_level(&mut self) -> bool { self.restore_level() }
+
+    fn level(&self) -> usize { self.level() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_and_events() {
+        let mut d = Domain::new(-5, 200);
+        assert_eq!(d.size(), 206);
+        assert_eq!(d.remove_value(10), DomainEvents::REMOVED);
+        assert!(d.remove_value(10).is_empty());
+        assert!(d.remove_value(500).is_empty());
+        let events = d.remove_value(-5);
+        assert!(events.contains(DomainEvents::MIN) && !events.contains(DomainEvents::MAX));
+        assert_eq!(d.min(), Some(-4));
+        assert_eq!(d.remove_below(10), DomainEvents::REMOVED | DomainEvents::MIN);
+        assert_eq!(d.min(), Some(11));
+        assert!(d.remove_above(150).is_bound_change());
+        assert_eq!((d.max(), d.size()), (Some(150), 140));
+        assert!(d.remove_above(150).is_empty());
+        assert!(d.assign(70).contains(DomainEvents::ASSIGNED));
+        assert!(d.is_assigned() && d.contains(70));
+        let all = d.take_events();
+        assert!(all.contains(DomainEvents::REMOVED | DomainEvents::MIN | DomainEvents::MAX | DomainEvents::ASSIGNED));
+        assert!(d.take_events().is_empty());
+        assert!(d.remove_value(70).contains(DomainEvents::EMPTY));
+        assert_eq!((d.min(), d.max()), (None, None));
+    }
+
+    #[test]
+    fn bounds_and_iteration() {
+        let mut d = Domain::new(0, 129);
+        for v in [0, 1, 64, 128] { d.remove_value(v); }
+        assert_eq!((d.min(), d.max()), (Some(2), Some(129)));
+        d.remove_below(63);
+        assert_eq!(d.min(), Some(63));
+        d.remove_above(66);
+        assert_eq!(d.iter().collect::<Vec<_>>(), vec![63, 65, 66]);
+        assert_eq!(d.size(), 3);
+        assert!(d.remove_below(100).contains(DomainEvents::EMPTY));
+        assert!(d.is_empty());
+        assert!(d.assign(5).is_empty());
+        let mut e = Domain::new(0, 9);
+        assert!(e.assign(20).contains(DomainEvents::EMPTY));
+        assert!(Domain::new(1, 0).is_empty());
+    }
+
+    #[test]
+    fn save_restore() {
+        let mut d = Domain::new(0, 199);
+        d.remove_value(7);
+        d.save_level();
+        d.remove_below(100);
+        d.remove_value(150);
+        d.save_level();
+        d.assign(120);
+        assert!(d.restore_level());
+        assert_eq!((d.min(), d.max(), d.size()), (Some(100), Some(199), 99));
+        assert!(!d.contains(150) && d.contains(120) && d.contains(199));
+        assert!(d.restore_level());
+        assert_eq!((d.min(), d.max(), d.size()), (Some(0), Some(199), 199));
+        assert!(!d.contains(7) && d.contains(150));
+        assert!(!d.restore_level());
+    }
+}
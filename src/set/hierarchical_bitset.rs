@@ -0,0 +1,291 @@
+use std::borrow::Borrow;
+use std::hash::{Hash, Hasher};
+
+use super::set_ops::{hash_sorted, ElementSet};
+use super::universe::Universe;
+
+/// implements a hierarchical bit-set over 0..n (van Emde Boas-like layout with 64-ary levels).
+/// The first level contains the elements (one bit per element). Each next level contains one
+/// bit per word of the previous one, set iff this word is not empty. The last level is a
+/// single word. Thus, searching the next or previous element skips empty blocks of 64^k
+/// elements at once.
+/// If *U* is the size of the universe, this data-structure has the following complexities:
+/// - memory: U/64 words (plus U/64² + ... for the summaries)
+/// - insertion/remove: O(log_64 U)
+/// - contains: O(1)
+/// - min/max/successor/predecessor: O(log_64 U)
+/// - empty check (of the set or of a block of 64 elements): O(1)
+///
+/// Two sets are equal if they have the same elements (whatever their capacities). They hash
+/// in the canonical form of the crate (see set_ops::hash_sorted). They do not implement Ord
+/// (Ord::min and Ord::max would shadow min and max): use to_canonical_bytes to key ordered maps.
+#[derive(Debug,Clone)]
+pub struct HierarchicalBitSet {
+    /// levels of words (levels\[0\] contains the elements, the last level is a single word)
+    levels:Vec<Vec<u64>>,
+    /// maximum number of elements (elements are in 0..capacity)
+    capacity:usize,
+    /// number of elements in the set
+    len:usize,
+}
+
+impl HierarchicalBitSet {
+    /// creates an empty set over 0..n
+    pub fn new(n:usize) -> Self {
+        let mut levels = vec![vec![0; n.div_ceil(64).max(1)]];
+        while levels.last().unwrap().len() > 1 {
+            let nb_words = levels.last().unwrap().len().div_ceil(64);
+            levels.push(vec![0; nb_words]);
+        }
+        Self { levels, capacity:n, len:0 }
+    }
+
+    /// returns the maximum number of elements (elements are in 0..capacity)
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    /// returns the number of levels
+    pub fn nb_levels(&self) -> usize { self.levels.len() }
+
+    /// returns the number of elements in the set
+    pub fn len(&self) -> usize { self.len }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// returns true iff the block of elements 64*b..64*(b+1) is empty
+    pub fn is_block_empty(&self, b:usize) -> bool { self.levels[0][b] == 0 }
+
+    /// true iff v ∈ Set
+    pub fn contains(&self, v:usize) -> bool {
+        debug_assert!(v < self.capacity);
+        self.levels[0][v / 64] & (1 << (v % 64)) != 0
+    }
+
+    /// inserts v into the set. Returns true iff v was missing
+    pub fn insert(&mut self, v:usize) -> bool {
+        debug_assert!(v < self.capacity);
+        if self.contains(v) { return false; }
+        let mut p = v;
+        for words in &mut self.levels {
+            let was_empty = words[p / 64] == 0;
+            words[p / 64] |= 1 << (p % 64);
+            // the summaries already mark this word as non-empty
+            if !was_empty { break; }
+            p /= 64;
+        }
+        self.len += 1;
+        true
+    }
+
+    /// removes v from the set. Returns true iff v was present
+    pub fn remove(&mut self, v:usize) -> bool {
+        debug_assert!(v < self.capacity);
+        if !self.contains(v) { return false; }
+        let mut p = v;
+        for words in &mut self.levels {
+            words[p / 64] &= !(1 << (p % 64));
+            // the word is still non-empty: the summaries are unchanged
+            if words[p / 64] != 0 { break; }
+            p /= 64;
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// removes all the elements
+    pub fn clear(&mut self) {
+        for words in &mut self.levels { words.fill(0); }
+        self.len = 0;
+    }
+
+    /// returns the elements of the universe that are not in the set (with the same capacity).
+    /// Elements of the universe greater than or equal to the capacity are ignored
+    pub fn complement(&self, universe:&Universe) -> Self {
+        let mut res = Self::new(self.capacity);
+        for e in universe.iter().filter(|e| *e < self.capacity && !self.contains_element(*e)) { res.insert(e); }
+        res
+    }
+
+    /// returns the smallest element greater than or equal to v
+    pub fn next_at_or_after(&self, v:usize) -> Option<usize> {
+        if v >= self.capacity { return None; }
+        self.next_in_level(0, v)
+    }
+
+    /// returns the largest element lower than or equal to v
+    pub fn prev_at_or_before(&self, v:usize) -> Option<usize> {
+        if self.capacity == 0 { return None; }
+        self.prev_in_level(0, v.min(self.capacity-1))
+    }
+
+    /// returns the minimum element
+    pub fn min(&self) -> Option<usize> { self.next_at_or_after(0) }
+
+    /// returns the maximum element
+    pub fn max(&self) -> Option<usize> { self.prev_at_or_before(usize::MAX) }
+
+    /// iterates over the elements in increasing order
+    pub fn iter(&self) -> HierarchicalBitSetIterator<&Self> {
+        HierarchicalBitSetIterator { next:self.min(), set:self }
+    }
+
+    /// returns the smallest position greater than or equal to p having its bit set in the
+    /// level k
+    fn next_in_level(&self, k:usize, p:usize) -> Option<usize> {
+        let words = &self.levels[k];
+        let i = p / 64;
+        if i >= words.len() { return None; }
+        let w = words[i] & (u64::MAX << (p % 64));
+        if w != 0 { return Some(64*i + w.trailing_zeros() as usize); }
+        if k+1 == self.levels.len() { return None; }
+        // next non-empty word of the level
+        let j = self.next_in_level(k+1, i+1)?;
+        Some(64*j + words[j].trailing_zeros() as usize)
+    }
+
+    /// returns the largest position lower than or equal to p having its bit set in the
+    /// level k
+    fn prev_in_level(&self, k:usize, p:usize) -> Option<usize> {
+        let words = &self.levels[k];
+        let i = p / 64;
+        let w = words[i] & (u64::MAX >> (63 - p % 64));
+        if w != 0 { return Some(64*i + 63 - w.leading_zeros() as usize); }
+        if i == 0 || k+1 == self.levels.len() { return None; }
+        // previous non-empty word of the level
+        let j = self.prev_in_level(k+1, i-1)?;
+        Some(64*j + 63 - words[j].leading_zeros() as usize)
+    }
+}
+
+impl PartialEq for HierarchicalBitSet {
+    fn eq(&self, other:&Self) -> bool { self.len == other.len && self.iter().eq(other.iter()) }
+}
+
+impl Eq for HierarchicalBitSet {}
+
+impl Hash for HierarchicalBitSet {
+    fn hash<H:Hasher>(&self, state:&mut H) { hash_sorted(self.len, self.iter(), state); }
+}
+
+/// creates the set of the elements (its capacity is the maximum element plus one)
+impl FromIterator<usize> for HierarchicalBitSet {
+    fn from_iter<I:IntoIterator<Item=usize>>(iter:I) -> Self {
+        let elements:Vec<usize> = iter.into_iter().collect();
+        let mut res = Self::new(elements.iter().max().map_or(0, |e| e+1));
+        res.extend(elements);
+        res
+    }
+}
+
+/// inserts the elements (they must be lower than the capacity)
+impl Extend<usize> for HierarchicalBitSet {
+    fn extend<I:IntoIterator<Item=usize>>(&mut self, iter:I) {
+        for e in iter { self.insert(e); }
+    }
+}
+
+/// iterates over the elements in increasing order
+impl IntoIterator for HierarchicalBitSet {
+    type Item = usize;
+    type IntoIter = HierarchicalBitSetIterator<Self>;
+
+    fn into_iter(self) -> Self::IntoIter { HierarchicalBitSetIterator { next:self.min(), set:self } }
+}
+
+/// iterates over the elements in increasing order
+impl IntoIterator for &HierarchicalBitSet {
+    type Item = usize;
+    type IntoIter = HierarchicalBitSetIterator<Self>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the elements of a hierarchical bit-set (increasing order), owning or
+/// borrowing the set.
+#[derive(Debug)]
+pub struct HierarchicalBitSetIterator<S> {
+    /// set iterated
+    set:S,
+    /// next element to return
+    next:Option<usize>,
+}
+
+impl<S:Borrow<HierarchicalBitSet>> Iterator for HierarchicalBitSetIterator<S> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let res = self.next?;
+        self.next = self.set.borrow().next_at_or_after(res+1);
+        Some(res)
+    }
+}
+
+impl ElementSet for HierarchicalBitSet {
+    fn contains_element(&self, e:usize) -> bool { e < self.capacity && self.contains(e) }
+
+    fn nb_elements(&self) -> usize { self.len }
+
+    fn elements(&self) -> impl Iterator<Item=usize> + '_ { self.iter() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn insert_remove() {
+        let mut set = HierarchicalBitSet::new(1_000_000);
+        assert_eq!(set.nb_levels(), 4);
+        assert_eq!(set.min(), None);
+        assert!(set.insert(999_999));
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(4096));
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 4096, 999_999]);
+        assert!(set.is_block_empty(1));
+        assert!(!set.is_block_empty(64));
+        assert!(set.remove(4096));
+        assert!(!set.remove(4096));
+        assert_eq!(set.next_at_or_after(4), Some(999_999));
+        assert_eq!(set.prev_at_or_before(999_998), Some(3));
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.max(), None);
+        assert_eq!(HierarchicalBitSet::new(0).min(), None);
+    }
+
+    #[test]
+    fn collect_extend() {
+        let mut set:HierarchicalBitSet = [500, 3, 64].into_iter().collect();
+        assert_eq!(set.capacity(), 501);
+        set.extend([0, 3]);
+        assert_eq!((&set).into_iter().collect::<Vec<_>>(), vec![0, 3, 64, 500]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![0, 3, 64, 500]);
+    }
+
+    #[test]
+    fn random_against_btree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let n = 300_000;
+        let mut set = HierarchicalBitSet::new(n);
+        let mut expected = BTreeSet::new();
+        for _ in 0..2000 {
+            let v = rng.gen_range(0..n);
+            if rng.gen_bool(0.7) {
+                assert_eq!(set.insert(v), expected.insert(v));
+            } else {
+                assert_eq!(set.remove(v), expected.remove(&v));
+            }
+            let q = rng.gen_range(0..n);
+            assert_eq!(set.next_at_or_after(q), expected.range(q..).next().copied());
+            assert_eq!(set.prev_at_or_before(q), expected.range(..=q).next_back().copied());
+        }
+        assert_eq!(set.len(), expected.len());
+        assert!(set.iter().eq(expected.iter().copied()));
+    }
+}
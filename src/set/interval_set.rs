@@ -0,0 +1,262 @@
+use std::ops::Range;
+
+/// implements a set of integers as a sorted list of disjoint intervals (run-length encoding).
+/// Adjacent intervals are merged, thus the representation is canonical. Large contiguous
+/// sets (e.g. scheduling horizons) only take a few intervals.
+/// If *k* is the number of intervals, this data-structure has the following complexities:
+/// - memory: O(k)
+/// - contains: O(log k)
+/// - range insertion/removal: O(log k + number of intervals merged or shifted)
+/// - complement: O(k)
+///
+/// As the representation is canonical, interval sets hash by their intervals in O(k) (not in
+/// the canonical form of the other set types, that would enumerate every integer).
+///
+/// Intervals exclude their end, thus the integers of the set are in i64::MIN..i64::MAX
+/// (i64::MAX cannot be inserted).
+#[derive(Debug,Clone,Default,PartialEq,Eq,Hash)]
+pub struct IntervalSet {
+    /// sorted disjoint non-adjacent intervals (start, end) (end excluded)
+    intervals:Vec<(i64,i64)>,
+}
+
+impl IntervalSet {
+    /// creates an empty set
+    pub fn new() -> Self { Self::default() }
+
+    /// creates the set containing the range
+    pub fn from_range(range:Range<i64>) -> Self {
+        let mut res = Self::new();
+        res.insert_range(range);
+        res
+    }
+
+    /// returns the number of integers in the set
+    pub fn len(&self) -> u64 { self.intervals.iter().map(|(s,e)| e.abs_diff(*s)).sum() }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.intervals.is_empty() }
+
+    /// returns the number of (maximal) intervals
+    pub fn nb_intervals(&self) -> usize { self.intervals.len() }
+
+    /// returns the minimum of the set
+    pub fn min(&self) -> Option<i64> { self.intervals.first().map(|(s,_)| *s) }
+
+    /// returns the maximum of the set
+    pub fn max(&self) -> Option<i64> { self.intervals.last().map(|(_,e)| e - 1) }
+
+    /// true iff v ∈ Set
+    pub fn contains(&self, v:i64) -> bool {
+        let i = self.intervals.partition_point(|(_,e)| *e <= v);
+        self.intervals.get(i).is_some_and(|(s,_)| *s <= v)
+    }
+
+    /// inserts v into the set. Returns true iff v was missing
+    ///
+    /// Panics if v is i64::MAX (the end of its interval would overflow)
+    pub fn insert(&mut self, v:i64) -> bool {
+        let end = v.checked_add(1).expect("an IntervalSet cannot contain i64::MAX");
+        let res = !self.contains(v);
+        self.insert_range(v..end);
+        res
+    }
+
+    /// removes v from the set. Returns true iff v was present
+    pub fn remove(&mut self, v:i64) -> bool {
+        // i64::MAX is never in the set
+        let Some(end) = v.checked_add(1) else { return false; };
+        let res = self.contains(v);
+        self.remove_range(v..end);
+        res
+    }
+
+    /// inserts the integers of the range (merging the overlapping or adjacent intervals)
+    pub fn insert_range(&mut self, range:Range<i64>) {
+        if range.is_empty() { return; }
+        let lo = self.intervals.partition_point(|(_,e)| *e < range.start);
+        let hi = self.intervals.partition_point(|(s,_)| *s <= range.end);
+        let mut merged = (range.start, range.end);
+        if lo < hi {
+            merged.0 = merged.0.min(self.intervals[lo].0);
+            merged.1 = merged.1.max(self.intervals[hi-1].1);
+        }
+        self.intervals.splice(lo..hi, std::iter::once(merged));
+    }
+
+    /// removes the integers of the range (splitting the intervals if needed)
+    pub fn remove_range(&mut self, range:Range<i64>) {
+        if range.is_empty() { return; }
+        let lo = self.intervals.partition_point(|(_,e)| *e <= range.start);
+        let hi = self.intervals.partition_point(|(s,_)| *s < range.end);
+        if lo == hi { return; }
+        let mut kept = Vec::with_capacity(2);
+        let (first_start,_) = self.intervals[lo];
+        let (_,last_end) = self.intervals[hi-1];
+        if first_start < range.start { kept.push((first_start, range.start)); }
+        if last_end > range.end { kept.push((range.end, last_end)); }
+        self.intervals.splice(lo..hi, kept);
+    }
+
+    /// returns the integers of the universe that are not in the set
+    pub fn complement(&self, universe:Range<i64>) -> Self {
+        let mut res = Self::from_range(universe);
+        for (s,e) in &self.intervals { res.remove_range(*s..*e); }
+        res
+    }
+
+    /// iterates over the intervals of the set (in increasing order)
+    pub fn intervals(&self) -> impl Iterator<Item=Range<i64>> + '_ {
+        self.intervals.iter().map(|(s,e)| *s..*e)
+    }
+
+    /// iterates over the holes of the set: the ranges between two consecutive intervals
+    pub fn holes(&self) -> impl Iterator<Item=Range<i64>> + '_ {
+        self.intervals.windows(2).map(|w| w[0].1..w[1].0)
+    }
+
+    /// iterates over the integers of the set (in increasing order)
+    pub fn iter(&self) -> IntervalSetIterator<std::iter::Copied<std::slice::Iter<'_,(i64,i64)>>> {
+        IntervalSetIterator { intervals:self.intervals.iter().copied(), current:0..0 }
+    }
+}
+
+impl FromIterator<i64> for IntervalSet {
+    fn from_iter<I:IntoIterator<Item=i64>>(iter:I) -> Self {
+        let mut res = Self::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I:IntoIterator<Item=Range<i64>>>(iter:I) -> Self {
+        let mut res = Self::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl Extend<i64> for IntervalSet {
+    fn extend<I:IntoIterator<Item=i64>>(&mut self, iter:I) {
+        for v in iter { self.insert(v); }
+    }
+}
+
+impl Extend<Range<i64>> for IntervalSet {
+    fn extend<I:IntoIterator<Item=Range<i64>>>(&mut self, iter:I) {
+        for range in iter { self.insert_range(range); }
+    }
+}
+
+/// iterates over the integers in increasing order
+impl IntoIterator for IntervalSet {
+    type Item = i64;
+    type IntoIter = IntervalSetIterator<std::vec::IntoIter<(i64,i64)>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntervalSetIterator { intervals:self.intervals.into_iter(), current:0..0 }
+    }
+}
+
+/// iterates over the integers in increasing order
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = i64;
+    type IntoIter = IntervalSetIterator<std::iter::Copied<std::slice::Iter<'a,(i64,i64)>>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the integers of an interval set (increasing order).
+#[derive(Debug)]
+pub struct IntervalSetIterator<I> {
+    /// intervals not visited yet
+    intervals:I,
+    /// integers of the current interval not visited yet
+    current:Range<i64>,
+}
+
+impl<I:Iterator<Item=(i64,i64)>> Iterator for IntervalSetIterator<I> {
+    type Item = i64;
+    fn next(&mut self) -> Option<i64> {
+        if self.current.is_empty() {
+            let (start, end) = self.intervals.next()?;
+            self.current = start..end;
+        }
+        self.current.next()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_merges() {
+        let mut set = IntervalSet::new();
+        set.insert_range(10..20);
+        set.insert_range(30..40);
+        set.insert_range(20..25); // adjacent
+        assert_eq!(set.intervals().collect::<Vec<_>>(), vec![10..25, 30..40]);
+        set.insert_range(0..5);
+        set.insert_range(24..31); // bridges two intervals
+        assert_eq!(set.intervals().collect::<Vec<_>>(), vec![0..5, 10..40]);
+        assert!(set.insert(5));
+        assert!(!set.insert(5));
+        assert_eq!(set.nb_intervals(), 2);
+        assert_eq!(set.len(), 36);
+        assert!(set.contains(0) && set.contains(39) && !set.contains(40) && !set.contains(7));
+        assert_eq!((set.min(), set.max()), (Some(0), Some(39)));
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut set = IntervalSet::from_range(0..1_000_000);
+        set.remove_range(100..200);
+        assert!(set.remove(500));
+        assert!(!set.remove(500));
+        assert_eq!(set.intervals().collect::<Vec<_>>(), vec![0..100, 200..500, 501..1_000_000]);
+        assert_eq!(set.holes().collect::<Vec<_>>(), vec![100..200, 500..501]);
+        set.remove_range(50..600);
+        assert_eq!(set.intervals().collect::<Vec<_>>(), vec![0..50, 600..1_000_000]);
+        set.remove_range(-10..2_000_000);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn extreme_values() {
+        let mut set = IntervalSet::from_range(i64::MIN..i64::MAX);
+        assert_eq!(set.len(), u64::MAX);
+        assert!(!set.remove(i64::MAX));
+        assert!(set.remove(i64::MAX-1));
+        assert!(set.insert(i64::MAX-1));
+        assert_eq!((set.min(), set.max()), (Some(i64::MIN), Some(i64::MAX-1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "an IntervalSet cannot contain i64::MAX")]
+    fn insert_max() {
+        IntervalSet::new().insert(i64::MAX);
+    }
+
+    #[test]
+    fn complement() {
+        let mut set = IntervalSet::new();
+        set.insert_range(3..5);
+        set.insert_range(8..9);
+        let complement = set.complement(0..10);
+        assert_eq!(complement.iter().collect::<Vec<_>>(), vec![0, 1, 2, 5, 6, 7, 9]);
+        assert_eq!(complement.complement(0..10), set);
+    }
+
+    #[test]
+    fn collect_extend() {
+        let mut set:IntervalSet = [3, 1, 2, 7].into_iter().collect();
+        assert_eq!(set.intervals().collect::<Vec<_>>(), vec![1..4, 7..8]);
+        set.extend([8..10, 20..21]);
+        assert_eq!((&set).into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 7, 8, 9, 20]);
+        let ranges:IntervalSet = [0..2, 2..4].into_iter().collect();
+        assert_eq!(ranges.into_iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+}
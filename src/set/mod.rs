@@ -1,2 +1,43 @@
 /// Sparse-set implementation. Allows fast operations but consumes more memory than a standard bit-set.
-pub mod sparse_set;
\ No newline at end of file
+pub mod sparse_set;
+/// Fixed-size bit-set (const-generic number of words). Stack allocated, fast set operations on small dense universes.
+pub mod bitset;
+
+/// Reversible sparse-set (CP domain). Removals are undone in O(1) by restoring a saved level.
+pub mod reversible_sparse_set;
+
+/// Interval set: sorted disjoint ranges (run-length encoding). Compact for large contiguous domains.
+pub mod interval_set;
+
+/// Hierarchical bit-set (64-ary summaries). Fast min/max/successor/predecessor over large universes.
+pub mod hierarchical_bitset;
+
+/// Small sorted set storing up to N elements inline, spilling to the heap beyond that.
+pub mod small_set;
+
+/// Lazy set operations, subset/superset/disjointness predicates and canonical hashing/encoding of sets.
+pub mod set_ops;
+
+/// Enumeration of subsets: k-element subsets (slices, bit-sets and words) and power sets in Gray-code order.
+pub mod combinations;
+
+/// Persistent sorted set (treap with path copying). Versions share their unchanged subtrees.
+pub mod persistent_set;
+
+/// n×n bit-matrix whose rows are bit-sets (conflict graphs, precedence matrices).
+pub mod bit_matrix;
+
+/// Random subsets of a universe (uniform, k-subsets, biased) using a caller-provided generator.
+pub mod random;
+
+/// Universe of elements (range or explicit list) and complement of sets w.r.t. it.
+pub mod universe;
+
+/// Bounded integer domain (bit-set) with cached min/max/size and change events (propagators).
+pub mod domain;
+
+/// Union-find (disjoint set union): path compression, or reversible unions for backtracking.
+pub mod union_find;
+
+/// Order-statistic tree: sorted multiset with k-th smallest element and rank queries in O(log n).
+pub mod order_statistic;
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+
+/// subtree of an order-statistic tree
+type Link<T> = Option<Box<Node<T>>>;
+
+/// node of an order-statistic tree (treap node)
+#[derive(Debug,Clone)]
+struct Node<T> {
+    /// element of the node
+    element:T,
+    /// priority of the node (heap ordered: greater than the ones of the children)
+    priority:u64,
+    /// elements lower or equal than the element
+    left:Link<T>,
+    /// elements greater or equal than the element
+    right:Link<T>,
+    /// number of elements in the subtree
+    size:usize,
+}
+
+/// implements an order-statistic tree: a sorted multiset (duplicates are kept) whose nodes
+/// store the size of their subtree, thus the k-th smallest element and the rank of an element
+/// are found in logarithmic time (position-based crossovers, median maintenance).
+/// It is a treap whose priorities come from an internal deterministic generator (runs are
+/// reproducible).
+/// If *n* is the number of elements, this data-structure has the following complexities
+/// (expected):
+/// - memory: O(n)
+/// - insert/remove/remove_kth: O(log n)
+/// - kth/rank_of/count/contains: O(log n)
+#[derive(Debug,Clone)]
+pub struct OrderStatisticTree<T> {
+    /// root of the treap
+    root:Link<T>,
+    /// state of the priority generator (splitmix64)
+    seed:u64,
+}
+
+impl<T> Default for OrderStatisticTree<T> {
+    fn default() -> Self { Self { root:None, seed:0 } }
+}
+
+impl<T:Ord> OrderStatisticTree<T> {
+    /// creates an empty tree
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of elements (with multiplicity)
+    pub fn len(&self) -> usize { size(&self.root) }
+
+    /// returns true iff the tree is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+
+    /// inserts an element (after the elements equal to it)
+    pub fn insert(&mut self, e:T) {
+        let priority = self.next_priority();
+        insert(&mut self.root, Box::new(Node { element:e, priority, left:None, right:None, size:1 }));
+    }
+
+    /// removes an occurrence of e. Returns false if e is missing
+    pub fn remove(&mut self, e:&T) -> bool { remove(&mut self.root, e) }
+
+    /// removes and returns the k-th smallest element (starting from 0)
+    pub fn remove_kth(&mut self, k:usize) -> Option<T> {
+        if k >= self.len() { return None; }
+        Some(remove_kth(&mut self.root, k))
+    }
+
+    /// returns the k-th smallest element (starting from 0)
+    pub fn kth(&self, k:usize) -> Option<&T> {
+        let mut current = &self.root;
+        let mut remaining = k;
+        while let Some(node) = current {
+            let nb_left = size(&node.left);
+            current = match remaining.cmp(&nb_left) {
+                Ordering::Less => &node.left,
+                Ordering::Equal => return Some(&node.element),
+                Ordering::Greater => {
+                    remaining -= nb_left + 1;
+                    &node.right
+                },
+            };
+        }
+        None
+    }
+
+    /// returns the number of elements strictly lower than e (the position of its first
+    /// occurrence if e is in the tree)
+    pub fn rank_of(&self, e:&T) -> usize { self.nb_before(|x| x < e) }
+
+    /// returns the number of occurrences of e
+    pub fn count(&self, e:&T) -> usize { self.nb_before(|x| x <= e) - self.rank_of(e) }
+
+    /// true iff e is in the tree
+    pub fn contains(&self, e:&T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match e.cmp(&node.element) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// returns the median element (the lower one if the number of elements is even)
+    pub fn median(&self) -> Option<&T> {
+        if self.is_empty() { None } else { self.kth((self.len() - 1) / 2) }
+    }
+
+    /// iterates over the elements in increasing order
+    pub fn iter(&self) -> OrderStatisticTreeIterator<'_,T> {
+        let mut res = OrderStatisticTreeIterator { stack:Vec::new() };
+        res.push_left(&self.root);
+        res
+    }
+
+    /// returns the number of elements satisfying the predicate (the predicate must hold for a
+    /// prefix of the sorted elements)
+    fn nb_before<P:Fn(&T) -> bool>(&self, is_before:P) -> usize {
+        let mut res = 0;
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = if is_before(&node.element) {
+                res += size(&node.left) + 1;
+                &node.right
+            } else { &node.left };
+        }
+        res
+    }
+
+    /// returns the next priority (splitmix64)
+    fn next_priority(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl<T:Ord> FromIterator<T> for OrderStatisticTree<T> {
+    fn from_iter<I:IntoIterator<Item=T>>(iter:I) -> Self {
+        let mut res = Self::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl<T:Ord> Extend<T> for OrderStatisticTree<T> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for e in iter { self.insert(e); }
+    }
+}
+
+/// iterates over the elements in increasing order
+impl<'a,T:Ord> IntoIterator for &'a OrderStatisticTree<T> {
+    type Item = &'a T;
+    type IntoIter = OrderStatisticTreeIterator<'a,T>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the elements of an order-statistic tree (increasing order).
+#[derive(Debug)]
+pub struct OrderStatisticTreeIterator<'a,T> {
+    /// nodes whose element and right subtree are not visited yet
+    stack:Vec<&'a Node<T>>,
+}
+
+impl<'a,T> OrderStatisticTreeIterator<'a,T> {
+    /// pushes the leftmost path of the subtree
+    fn push_left(&mut self, link:&'a Link<T>) {
+        let mut current = link;
+        while let Some(node) = current {
+            self.stack.push(node);
+            current = &node.left;
+        }
+    }
+}
+
+impl<'a,T> Iterator for OrderStatisticTreeIterator<'a,T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some(&node.element)
+    }
+}
+
+/// returns the number of elements of a subtree
+fn size<T>(link:&Link<T>) -> usize { link.as_ref().map_or(0, |n| n.size) }
+
+/// recomputes the size of a node from its children
+fn update<T>(node:&mut Node<T>) { node.size = 1 + size(&node.left) + size(&node.right); }
+
+/// splits a subtree into the elements lower or equal than e and the elements greater than e
+fn split<T:Ord>(link:Link<T>, e:&T) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else { return (None, None); };
+    if node.element <= *e {
+        let (lower, greater) = split(node.right.take(), e);
+        node.right = lower;
+        update(&mut node);
+        (Some(node), greater)
+    } else {
+        let (lower, greater) = split(node.left.take(), e);
+        node.left = greater;
+        update(&mut node);
+        (lower, Some(node))
+    }
+}
+
+/// merges two subtrees (the elements of a are lower or equal than the ones of b)
+fn merge<T>(a:Link<T>, b:Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, y) => y,
+        (x, None) => x,
+        (Some(mut x), Some(mut y)) => {
+            if x.priority >= y.priority {
+                x.right = merge(x.right.take(), Some(y));
+                update(&mut x);
+                Some(x)
+            } else {
+                y.left = merge(Some(x), y.left.take());
+                update(&mut y);
+                Some(y)
+            }
+        },
+    }
+}
+
+/// inserts a single node in a subtree
+fn insert<T:Ord>(link:&mut Link<T>, mut single:Box<Node<T>>) {
+    match link {
+        Some(node) if node.priority >= single.priority => {
+            node.size += 1;
+            if single.element < node.element { insert(&mut node.left, single); } else { insert(&mut node.right, single); }
+        },
+        _ => {
+            let (lower, greater) = split(link.take(), &single.element);
+            single.left = lower;
+            single.right = greater;
+            update(&mut single);
+            *link = Some(single);
+        },
+    }
+}
+
+/// removes an occurrence of e from a subtree. Returns false if e is missing
+fn remove<T:Ord>(link:&mut Link<T>, e:&T) -> bool {
+    let Some(node) = link else { return false; };
+    let removed = match e.cmp(&node.element) {
+        Ordering::Less => remove(&mut node.left, e),
+        Ordering::Greater => remove(&mut node.right, e),
+        Ordering::Equal => {
+            *link = merge(node.left.take(), node.right.take());
+            return true;
+        },
+    };
+    if removed { node.size -= 1; }
+    removed
+}
+
+/// removes the k-th element of a subtree (k is lower than its size)
+fn remove_kth<T>(link:&mut Link<T>, k:usize) -> T {
+    let node = link.as_mut().expect("remove_kth: index out of bounds");
+    let nb_left = size(&node.left);
+    match k.cmp(&nb_left) {
+        Ordering::Less => {
+            node.size -= 1;
+            remove_kth(&mut node.left, k)
+        },
+        Ordering::Greater => {
+            node.size -= 1;
+            remove_kth(&mut node.right, k - nb_left - 1)
+        },
+        Ordering::Equal => {
+            let mut removed = link.take().expect("remove_kth: index out of bounds");
+            *link = merge(removed.left.take(), removed.right.take());
+            removed.element
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn ranks() {
+        let mut tree:OrderStatisticTree<i32> = [5, 1, 8, 5, 3].into_iter().collect();
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 5, 8]);
+        assert_eq!((tree.kth(0), tree.kth(3), tree.kth(5)), (Some(&1), Some(&5), None));
+        assert_eq!((tree.rank_of(&5), tree.rank_of(&6), tree.rank_of(&0)), (2, 4, 0));
+        assert_eq!((tree.count(&5), tree.count(&4)), (2, 0));
+        assert_eq!(tree.median(), Some(&5));
+        assert!(tree.remove(&5) && !tree.remove(&4));
+        assert_eq!((tree.len(), tree.median()), (4, Some(&3)));
+        assert_eq!(tree.remove_kth(1), Some(3));
+        assert_eq!(tree.remove_kth(3), None);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 5, 8]);
+        assert!(tree.contains(&8) && !tree.contains(&3));
+    }
+
+    #[test]
+    fn random_against_sorted_vec() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut tree = OrderStatisticTree::new();
+        let mut expected:Vec<u16> = Vec::new();
+        for _ in 0..2000 {
+            let e:u16 = rng.gen_range(0..50);
+            match rng.gen_range(0..3) {
+                0 | 1 => {
+                    tree.insert(e);
+                    let p = expected.partition_point(|x| *x <= e);
+                    expected.insert(p, e);
+                },
+                _ => if !expected.is_empty() && rng.gen_bool(0.5) {
+                    let k = rng.gen_range(0..expected.len());
+                    assert_eq!(tree.remove_kth(k), Some(expected.remove(k)));
+                } else {
+                    let position = expected.iter().position(|x| *x == e);
+                    assert_eq!(tree.remove(&e), position.is_some());
+                    if let Some(p) = position { expected.remove(p); }
+                },
+            }
+            assert_eq!(tree.len(), expected.len());
+            assert_eq!(tree.rank_of(&e), expected.partition_point(|x| *x < e));
+            if !expected.is_empty() {
+                let k = rng.gen_range(0..expected.len());
+                assert_eq!(tree.kth(k), Some(&expected[k]));
+            }
+        }
+        assert!(tree.iter().eq(expected.iter()));
+    }
+}
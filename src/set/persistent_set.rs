@@ -0,0 +1,338 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use super::set_ops::hash_sorted;
+
+/// subtree of a persistent set (shared between versions)
+type Link<T> = Option<Rc<Node<T>>>;
+
+/// node of a persistent set (treap node)
+#[derive(Debug)]
+struct Node<T> {
+    /// element of the node
+    element:T,
+    /// priority of the element (heap ordered: greater than the ones of the children)
+    priority:u64,
+    /// elements lower than the element
+    left:Link<T>,
+    /// elements greater than the element
+    right:Link<T>,
+    /// number of elements in the subtree
+    size:usize,
+}
+
+/// implements a persistent (immutable) sorted set with structural sharing.
+/// insert and remove return a new version of the set sharing all the unchanged subtrees
+/// with the previous one (path copying). Thus, keeping a version per search node only costs
+/// O(log n) new nodes per modification, and cloning a version is O(1).
+/// It is a treap whose priorities are hashes of the elements: the shape of the tree only
+/// depends on the elements (not on the insertion order).
+/// If *n* is the number of elements, this data-structure has the following complexities
+/// (expected):
+/// - memory: O(n) for the first version, O(log n) per modification
+/// - insertion/remove: O(log n)
+/// - contains: O(log n)
+/// - clone: O(1)
+///
+/// Persistent sets hash in the canonical form of the crate (see set_ops::hash_sorted). They
+/// do not implement Ord (Ord::min and Ord::max would shadow min and max).
+#[derive(Debug)]
+pub struct PersistentSet<T> {
+    /// root of the treap
+    root:Link<T>,
+}
+
+impl<T> Clone for PersistentSet<T> {
+    fn clone(&self) -> Self { Self { root:self.root.clone() } }
+}
+
+impl<T> Default for PersistentSet<T> {
+    fn default() -> Self { Self { root:None } }
+}
+
+impl<T:Ord+Clone+Hash> PersistentSet<T> {
+    /// creates an empty set
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of elements in the set
+    pub fn len(&self) -> usize { size(&self.root) }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.root.is_none() }
+
+    /// true iff e ∈ Set
+    pub fn contains(&self, e:&T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match e.cmp(&node.element) {
+                std::cmp::Ordering::Less => &node.left,
+                std::cmp::Ordering::Greater => &node.right,
+                std::cmp::Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// returns the set with e inserted (the same version if e was already present)
+    pub fn insert(&self, e:T) -> Self {
+        if self.contains(&e) { return self.clone(); }
+        let (less, greater) = split(&self.root, &e);
+        let priority = priority_of(&e);
+        let single = new_node(e, priority, None, None);
+        Self { root:merge(&merge(&less, &single), &greater) }
+    }
+
+    /// returns the set with e removed (the same version if e was missing)
+    pub fn remove(&self, e:&T) -> Self {
+        if !self.contains(e) { return self.clone(); }
+        let (less, greater) = split(&self.root, e);
+        Self { root:merge(&less, &greater) }
+    }
+
+    /// returns true iff both versions share the same root (thus are equal)
+    pub fn ptr_eq(&self, other:&Self) -> bool {
+        match (&self.root, &other.root) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// returns the minimum element
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left { node = left; }
+        Some(&node.element)
+    }
+
+    /// returns the maximum element
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right { node = right; }
+        Some(&node.element)
+    }
+
+    /// iterates over the elements in increasing order
+    pub fn iter(&self) -> PersistentSetIterator<'_,T> {
+        let mut res = PersistentSetIterator { stack:Vec::new() };
+        res.push_left(&self.root);
+        res
+    }
+}
+
+impl<T:Ord+Clone+Hash> FromIterator<T> for PersistentSet<T> {
+    fn from_iter<I:IntoIterator<Item=T>>(iter:I) -> Self {
+        let mut res = Self::new();
+        for e in iter { res = res.insert(e); }
+        res
+    }
+}
+
+impl<T:Ord+Clone+Hash> PartialEq for PersistentSet<T> {
+    fn eq(&self, other:&Self) -> bool {
+        self.ptr_eq(other) || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl<T:Ord+Clone+Hash> Eq for PersistentSet<T> {}
+
+impl<T:Ord+Clone+Hash> Hash for PersistentSet<T> {
+    fn hash<H:Hasher>(&self, state:&mut H) { hash_sorted(self.len(), self.iter(), state); }
+}
+
+/// inserts the elements (the set becomes the new version)
+impl<T:Ord+Clone+Hash> Extend<T> for PersistentSet<T> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for e in iter { *self = self.insert(e); }
+    }
+}
+
+/// iterates over the elements in increasing order (cloning them, as the nodes may be shared
+/// with other versions)
+impl<T:Ord+Clone+Hash> IntoIterator for PersistentSet<T> {
+    type Item = T;
+    type IntoIter = PersistentSetIntoIterator<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut res = PersistentSetIntoIterator { stack:Vec::new() };
+        res.push_left(self.root);
+        res
+    }
+}
+
+/// iterates over the elements in increasing order
+impl<'a,T:Ord+Clone+Hash> IntoIterator for &'a PersistentSet<T> {
+    type Item = &'a T;
+    type IntoIter = PersistentSetIterator<'a,T>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the elements of a persistent set (increasing order).
+#[derive(Debug)]
+pub struct PersistentSetIterator<'a,T> {
+    /// nodes whose element and right subtree are not visited yet
+    stack:Vec<&'a Node<T>>,
+}
+
+impl<'a,T> PersistentSetIterator<'a,T> {
+    /// pushes the leftmost path of the subtree
+    fn push_left(&mut self, link:&'a Link<T>) {
+        let mut current = link;
+        while let Some(node) = current {
+            self.stack.push(node);
+            current = &node.left;
+        }
+    }
+}
+
+impl<'a,T> Iterator for PersistentSetIterator<'a,T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some(&node.element)
+    }
+}
+
+/// Iterator over the elements of a persistent set, consuming it (increasing order).
+#[derive(Debug)]
+pub struct PersistentSetIntoIterator<T> {
+    /// nodes whose element and right subtree are not visited yet
+    stack:Vec<Rc<Node<T>>>,
+}
+
+impl<T> PersistentSetIntoIterator<T> {
+    /// pushes the leftmost path of the subtree
+    fn push_left(&mut self, link:Link<T>) {
+        let mut current = link;
+        while let Some(node) = current {
+            current = node.left.clone();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<T:Clone> Iterator for PersistentSetIntoIterator<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.clone());
+        Some(node.element.clone())
+    }
+}
+
+/// returns the priority of an element (deterministic hash)
+fn priority_of<T:Hash>(e:&T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    e.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// returns the number of elements of a subtree
+fn size<T>(link:&Link<T>) -> usize { link.as_ref().map_or(0, |n| n.size) }
+
+/// creates a node (its size is computed from the children)
+fn new_node<T>(element:T, priority:u64, left:Link<T>, right:Link<T>) -> Link<T> {
+    let size = 1 + size(&left) + size(&right);
+    Some(Rc::new(Node { element, priority, left, right, size }))
+}
+
+/// splits a subtree into the elements lower than e and the elements greater than e (e is
+/// dropped). Only the nodes on the search path are copied
+fn split<T:Ord+Clone>(link:&Link<T>, e:&T) -> (Link<T>, Link<T>) {
+    let Some(node) = link else { return (None, None); };
+    match e.cmp(&node.element) {
+        std::cmp::Ordering::Less => {
+            let (less, greater) = split(&node.left, e);
+            let right = new_node(node.element.clone(), node.priority, greater, node.right.clone());
+            (less, right)
+        },
+        std::cmp::Ordering::Greater => {
+            let (less, greater) = split(&node.right, e);
+            let left = new_node(node.element.clone(), node.priority, node.left.clone(), less);
+            (left, greater)
+        },
+        std::cmp::Ordering::Equal => (node.left.clone(), node.right.clone()),
+    }
+}
+
+/// merges two subtrees (the elements of a are lower than the ones of b)
+fn merge<T:Clone>(a:&Link<T>, b:&Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, _) => b.clone(),
+        (_, None) => a.clone(),
+        (Some(x), Some(y)) => {
+            if x.priority >= y.priority {
+                let right = merge(&x.right, b);
+                new_node(x.element.clone(), x.priority, x.left.clone(), right)
+            } else {
+                let left = merge(a, &y.left);
+                new_node(y.element.clone(), y.priority, left, y.right.clone())
+            }
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn versions() {
+        let empty = PersistentSet::new();
+        let a = empty.insert(5).insert(1).insert(3);
+        let b = a.insert(4);
+        let c = b.remove(&1);
+        assert!(empty.is_empty());
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+        assert_eq!(c.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert!(a.contains(&1) && !a.contains(&4) && !c.contains(&1));
+        assert_eq!((c.min(), c.max()), (Some(&3), Some(&5)));
+        assert!(a.insert(3).ptr_eq(&a));
+        assert!(a.remove(&2).ptr_eq(&a));
+        // the shape does not depend on the insertion order
+        let d:PersistentSet<i32> = [3, 4, 5].into_iter().rev().collect();
+        assert_eq!(c, d);
+        assert_eq!(d.len(), 3);
+    }
+
+    #[test]
+    fn extend_into_iter() {
+        let mut set:PersistentSet<i32> = [2, 8].into_iter().collect();
+        let previous = set.clone();
+        set.extend([5, 2]);
+        assert_eq!(previous.into_iter().collect::<Vec<_>>(), vec![2, 8]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![2, 5, 8]);
+    }
+
+    #[test]
+    fn random_against_btree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut versions = vec![(PersistentSet::new(), BTreeSet::new())];
+        for _ in 0..500 {
+            let (set, expected) = versions[rng.gen_range(0..versions.len())].clone();
+            let e:u16 = rng.gen_range(0..100);
+            let mut next_expected = expected;
+            let next = if rng.gen_bool(0.6) {
+                next_expected.insert(e);
+                set.insert(e)
+            } else {
+                next_expected.remove(&e);
+                set.remove(&e)
+            };
+            versions.push((next, next_expected));
+        }
+        for (set, expected) in &versions {
+            assert_eq!(set.len(), expected.len());
+            assert!(set.iter().eq(expected.iter()));
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use rand::Rng;
+
+/// returns a uniformly random subset of 0..n (each element is kept with probability 1/2).
+/// The elements are generated in increasing order, thus the result can be collected into any
+/// set representation (e.g. a Vec, a BitSet or a SmallSet)
+pub fn random_subset<S:FromIterator<usize>,R:Rng+?Sized>(rng:&mut R, n:usize) -> S {
+    (0..n).filter(|_| rng.gen::<bool>()).collect()
+}
+
+/// returns a uniformly random k-element subset of 0..n (elements in increasing order).
+/// Panics if k > n
+pub fn random_k_subset<S:FromIterator<usize>,R:Rng+?Sized>(rng:&mut R, n:usize, k:usize) -> S {
+    let mut res = rand::seq::index::sample(rng, n, k).into_vec();
+    res.sort_unstable();
+    res.into_iter().collect()
+}
+
+/// returns a random subset of 0..probabilities.len(), containing each element i with
+/// probability probabilities\[i\] (independently). Elements in increasing order.
+/// Probabilities outside 0..=1 are clamped. Panics if a probability is NaN
+pub fn biased_subset<S:FromIterator<usize>,R:Rng+?Sized>(rng:&mut R, probabilities:&[f64]) -> S {
+    assert!(!probabilities.iter().any(|p| p.is_nan()), "biased_subset: NaN probability");
+    probabilities.iter().enumerate()
+        .filter(|(_,p)| rng.gen_bool(p.clamp(0., 1.)))
+        .map(|(i,_)| i)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::set::bitset::BitSet;
+
+    #[test]
+    fn reproducible() {
+        let a:Vec<usize> = random_subset(&mut StdRng::seed_from_u64(42), 100);
+        let b:BitSet<2> = random_subset(&mut StdRng::seed_from_u64(42), 100);
+        assert_eq!(b.iter().collect::<Vec<_>>(), a);
+        assert!(a.windows(2).all(|w| w[0] < w[1]));
+        assert!(a.len() > 20 && a.len() < 80);
+    }
+
+    #[test]
+    fn k_subsets() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for k in [0, 3, 10] {
+            let s:Vec<usize> = random_k_subset(&mut rng, 10, k);
+            assert_eq!(s.len(), k);
+            assert!(s.windows(2).all(|w| w[0] < w[1]) && s.iter().all(|e| *e < 10));
+        }
+        // every element is selected about k/n of the time
+        let mut counts = [0; 5];
+        for _ in 0..5000 {
+            for e in random_k_subset::<Vec<usize>,_>(&mut rng, 5, 2) { counts[e] += 1; }
+        }
+        assert!(counts.iter().all(|c| *c > 1800 && *c < 2200));
+    }
+
+    #[test]
+    fn biased() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let s:Vec<usize> = biased_subset(&mut rng, &[1., 0., 1., -1., 2.]);
+        assert_eq!(s, vec![0, 2, 4]);
+        let count:usize = (0..1000).map(|_| biased_subset::<Vec<usize>,_>(&mut rng, &[0.1]).len()).sum();
+        assert!(count > 50 && count < 150);
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN probability")]
+    fn biased_nan() {
+        let _:Vec<usize> = biased_subset(&mut StdRng::seed_from_u64(0), &[0.5, f64::NAN]);
+    }
+}
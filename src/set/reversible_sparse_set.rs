@@ -0,0 +1,283 @@
+use crate::trail::Backtrackable;
+
+/// implements a reversible sparse set (domain of a CP variable over min..=max).
+/// Removed values are swapped after the present ones, thus restoring the size restores the
+/// set. The minimum and maximum are maintained on removals. save_level stores the size, the
+/// minimum and the maximum, restore_level restores them.
+/// If *n* is the number of values, this data-structure has the following complexities:
+/// - memory: O(n) (plus 3 words per saved level)
+/// - remove: O(1), plus O(d) if it removes the minimum or the maximum, where d is the distance
+///   to the new bound (the values in between are scanned). This is not amortized: restoring a
+///   level brings these values back, and they may be scanned again
+/// - contains: O(1)
+/// - assign (remove all but one): O(1)
+/// - min/max: O(1)
+/// - save_level/restore_level: O(1) (whatever the number of removals undone)
+///
+/// The set can be collected from values (over their min..=max range), but not extended:
+/// values can only be removed.
+#[derive(Debug,Clone)]
+pub struct ReversibleSparseSet {
+    /// values (minus the offset). The first size ones are present
+    values:Vec<usize>,
+    /// indices\[v\] = i <=> values\[i\] = v
+    indices:Vec<usize>,
+    /// number of values in the set
+    size:usize,
+    /// minimum value (minus the offset, meaningless if the set is empty)
+    min:usize,
+    /// maximum value (minus the offset, meaningless if the set is empty)
+    max:usize,
+    /// smallest value of the universe
+    offset:i32,
+    /// saved levels (size, min, max)
+    trail:Vec<(usize,usize,usize)>,
+}
+
+impl ReversibleSparseSet {
+    /// creates the set containing min..=max (empty if max < min)
+    pub fn new(min:i32, max:i32) -> Self {
+        let n = if max < min { 0 } else { (i64::from(max) - i64::from(min) + 1) as usize };
+        Self {
+            values:(0..n).collect(),
+            indices:(0..n).collect(),
+            size:n,
+            min:0,
+            max:n.saturating_sub(1),
+            offset:min,
+            trail:Vec::new(),
+        }
+    }
+
+    /// returns the number of values in the set
+    pub fn len(&self) -> usize { self.size }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.size == 0 }
+
+    /// returns true iff the set contains a single value
+    pub fn is_assigned(&self) -> bool { self.size == 1 }
+
+    /// returns the minimum value (None if the set is empty)
+    pub fn min(&self) -> Option<i32> {
+        if self.is_empty() { None } else { Some(self.value_of(self.min)) }
+    }
+
+    /// returns the maximum value (None if the set is empty)
+    pub fn max(&self) -> Option<i32> {
+        if self.is_empty() { None } else { Some(self.value_of(self.max)) }
+    }
+
+    /// true iff v ∈ Set
+    pub fn contains(&self, v:i32) -> bool {
+        self.relative(v).is_some_and(|r| self.indices[r] < self.size)
+    }
+
+    /// removes v from the set. Returns true iff v was present
+    pub fn remove(&mut self, v:i32) -> bool {
+        let Some(r) = self.relative(v).filter(|r| self.indices[*r] < self.size) else { return false; };
+        self.swap(r, self.size-1);
+        self.size -= 1;
+        if self.size > 0 {
+            if r == self.min {
+                while self.indices[self.min] >= self.size { self.min += 1; }
+            }
+            if r == self.max {
+                while self.indices[self.max] >= self.size { self.max -= 1; }
+            }
+        }
+        true
+    }
+
+    /// removes every value except v. Returns false (and leaves the set unchanged) if v is
+    /// not in the set
+    pub fn assign(&mut self, v:i32) -> bool {
+        let Some(r) = self.relative(v).filter(|r| self.indices[*r] < self.size) else { return false; };
+        self.swap(r, 0);
+        self.size = 1;
+        self.min = r;
+        self.max = r;
+        true
+    }
+
+    /// removes the values lower than v. Returns the number of removed values
+    pub fn remove_below(&mut self, v:i32) -> usize {
+        let mut nb_removed = 0;
+        while self.min().is_some_and(|m| m < v) {
+            self.remove(self.value_of(self.min));
+            nb_removed += 1;
+        }
+        nb_removed
+    }
+
+    /// removes the values greater than v. Returns the number of removed values
+    pub fn remove_above(&mut self, v:i32) -> usize {
+        let mut nb_removed = 0;
+        while self.max().is_some_and(|m| m > v) {
+            self.remove(self.value_of(self.max));
+            nb_removed += 1;
+        }
+        nb_removed
+    }
+
+    /// saves the current state (restored by restore_level)
+    pub fn save_level(&mut self) { self.trail.push((self.size, self.min, self.max)); }
+
+    /// restores the last saved state (the values removed since then are back).
+    /// Returns false if no level was saved
+    pub fn restore_level(&mut self) -> bool {
+        let Some((size,min,max)) = self.trail.pop() else { return false; };
+        self.size = size;
+        self.min = min;
+        self.max = max;
+        true
+    }
+
+    /// returns the number of saved levels
+    pub fn level(&self) -> usize { self.trail.len() }
+
+    /// iterates over the values of the set (in arbitrary order)
+    pub fn iter(&self) -> ReversibleSparseSetIterator<std::iter::Copied<std::slice::Iter<'_,usize>>> {
+        ReversibleSparseSetIterator { values:self.values[..self.size].iter().copied(), offset:self.offset }
+    }
+
+    /// returns the value of a relative value
+    fn value_of(&self, r:usize) -> i32 { (i64::from(self.offset) + r as i64) as i32 }
+
+    /// returns the relative value of v (None if it is out of the universe)
+    fn relative(&self, v:i32) -> Option<usize> {
+        let r = i64::from(v) - i64::from(self.offset);
+        if r < 0 || r as usize >= self.values.len() { None } else { Some(r as usize) }
+    }
+
+    /// swaps the relative value r with the value at position i
+    fn swap(&mut self, r:usize, i:usize) {
+        let j = self.indices[r];
+        let other = self.values[i];
+        self.values.swap(i, j);
+        self.indices[other] = j;
+        self.indices[r] = i;
+    }
+}
+
+impl Backtrackable for ReversibleSparseSet {
+    fn save_level(&mut self) { self.save_level(); }
+
+    fn restore_level(&mut self) -> bool { self.restore_level() }
+
+    fn level(&self) -> usize { self.level() }
+}
+
+/// creates the set of the values (over the range min..=max of the values)
+impl FromIterator<i32> for ReversibleSparseSet {
+    fn from_iter<I:IntoIterator<Item=i32>>(iter:I) -> Self {
+        let values:Vec<i32> = iter.into_iter().collect();
+        let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else { return Self::new(0, -1); };
+        let mut present = vec![false; (i64::from(*max) - i64::from(*min) + 1) as usize];
+        for v in &values { present[(i64::from(*v) - i64::from(*min)) as usize] = true; }
+        let mut res = Self::new(*min, *max);
+        for (r,p) in present.iter().enumerate() {
+            if !p { res.remove(res.value_of(r)); }
+        }
+        res
+    }
+}
+
+/// iterates over the values in arbitrary order
+impl IntoIterator for ReversibleSparseSet {
+    type Item = i32;
+    type IntoIter = ReversibleSparseSetIterator<std::iter::Take<std::vec::IntoIter<usize>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ReversibleSparseSetIterator { values:self.values.into_iter().take(self.size), offset:self.offset }
+    }
+}
+
+/// iterates over the values in arbitrary order
+impl<'a> IntoIterator for &'a ReversibleSparseSet {
+    type Item = i32;
+    type IntoIter = ReversibleSparseSetIterator<std::iter::Copied<std::slice::Iter<'a,usize>>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over the values of a reversible sparse set (arbitrary order).
+#[derive(Debug)]
+pub struct ReversibleSparseSetIterator<I> {
+    /// relative values not visited yet
+    values:I,
+    /// smallest value of the universe
+    offset:i32,
+}
+
+impl<I:Iterator<Item=usize>> Iterator for ReversibleSparseSetIterator<I> {
+    type Item = i32;
+    fn next(&mut self) -> Option<i32> {
+        self.values.next().map(|r| (i64::from(self.offset) + r as i64) as i32)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_and_bounds() {
+        let mut set = ReversibleSparseSet::new(-2, 5);
+        assert_eq!(set.len(), 8);
+        assert!(set.remove(-2));
+        assert!(!set.remove(-2));
+        assert!(!set.remove(10));
+        assert!(set.remove(-1));
+        assert_eq!(set.min(), Some(0));
+        assert!(set.remove(5));
+        assert_eq!(set.max(), Some(4));
+        assert_eq!(set.remove_below(2), 2);
+        assert_eq!(set.remove_above(3), 1);
+        let mut values:Vec<i32> = set.iter().collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![2, 3]);
+        assert!(set.assign(3));
+        assert!(set.is_assigned());
+        assert!(!set.assign(2));
+        assert_eq!((set.min(), set.max()), (Some(3), Some(3)));
+        assert!(set.remove(3));
+        assert_eq!(set.min(), None);
+    }
+
+    #[test]
+    fn save_restore() {
+        let mut set = ReversibleSparseSet::new(0, 9);
+        set.remove(4);
+        set.save_level();
+        set.remove(0);
+        set.remove(9);
+        set.save_level();
+        assert!(set.assign(5));
+        assert_eq!(set.level(), 2);
+        assert!(set.restore_level());
+        assert_eq!(set.len(), 7);
+        assert_eq!((set.min(), set.max()), (Some(1), Some(8)));
+        assert!(!set.contains(0));
+        assert!(set.restore_level());
+        assert_eq!(set.len(), 9);
+        assert!(set.contains(0) && set.contains(9) && !set.contains(4));
+        assert_eq!((set.min(), set.max()), (Some(0), Some(9)));
+        assert!(!set.restore_level());
+        assert!(ReversibleSparseSet::new(1, 0).is_empty());
+    }
+
+    #[test]
+    fn collect() {
+        let set:ReversibleSparseSet = [-3, 2, 0, 2].into_iter().collect();
+        assert_eq!(set.len(), 3);
+        assert_eq!((set.min(), set.max()), (Some(-3), Some(2)));
+        assert!(!set.contains(-1) && set.contains(0));
+        let mut values:Vec<i32> = (&set).into_iter().collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![-3, 0, 2]);
+        assert_eq!(set.into_iter().count(), 3);
+        assert!(ReversibleSparseSet::from_iter([]).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+use std::hash::{Hash, Hasher};
+use std::io;
+
+use crate::set_store::binary::{read_varint, write_varint};
+
+/// operation computed by a SortedSetOpIterator
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum SetOp {
+    /// elements in a or b
+    Union,
+    /// elements in a and b
+    Intersection,
+    /// elements in a but not in b
+    Difference,
+    /// elements in exactly one of a and b
+    SymmetricDifference,
+}
+
+/// Iterator over the result of a set operation between two sorted slices (increasing order).
+#[derive(Debug,Clone)]
+pub struct SortedSetOpIterator<'a,T> {
+    /// first sorted slice
+    a:&'a [T],
+    /// second sorted slice
+    b:&'a [T],
+    /// operation computed
+    op:SetOp,
+}
+
+impl<'a,T:Copy+Ord> Iterator for SortedSetOpIterator<'a,T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let (in_a, in_b, e) = match (self.a.first(), self.b.first()) {
+                (None, None) => return None,
+                (Some(x), Some(y)) if x == y => (true, true, *x),
+                (Some(x), Some(y)) if x < y => (true, false, *x),
+                (Some(x), None) => (true, false, *x),
+                (_, Some(y)) => (false, true, *y),
+            };
+            if in_a { self.a = &self.a[1..]; }
+            if in_b { self.b = &self.b[1..]; }
+            let kept = match self.op {
+                SetOp::Union => true,
+                SetOp::Intersection => in_a && in_b,
+                SetOp::Difference => in_a && !in_b,
+                SetOp::SymmetricDifference => in_a != in_b,
+            };
+            if kept { return Some(e); }
+            // the remaining elements cannot be in the result
+            match self.op {
+                SetOp::Intersection if self.a.is_empty() || self.b.is_empty() => return None,
+                SetOp::Difference if self.a.is_empty() => return None,
+                _ => {},
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (n, m) = (self.a.len(), self.b.len());
+        match self.op {
+            SetOp::Union => (n.max(m), Some(n + m)),
+            SetOp::Intersection => (0, Some(n.min(m))),
+            SetOp::Difference => (n.saturating_sub(m), Some(n)),
+            SetOp::SymmetricDifference => (n.abs_diff(m), Some(n + m)),
+        }
+    }
+}
+
+/// iterates over the elements of a ∪ b (a and b are sorted)
+pub fn union_iter<'a,T:Copy+Ord>(a:&'a [T], b:&'a [T]) -> SortedSetOpIterator<'a,T> {
+    SortedSetOpIterator { a, b, op:SetOp::Union }
+}
+
+/// iterates over the elements of a ∩ b (a and b are sorted)
+pub fn intersection_iter<'a,T:Copy+Ord>(a:&'a [T], b:&'a [T]) -> SortedSetOpIterator<'a,T> {
+    SortedSetOpIterator { a, b, op:SetOp::Intersection }
+}
+
+/// iterates over the elements of a \ b (a and b are sorted)
+pub fn difference_iter<'a,T:Copy+Ord>(a:&'a [T], b:&'a [T]) -> SortedSetOpIterator<'a,T> {
+    SortedSetOpIterator { a, b, op:SetOp::Difference }
+}
+
+/// iterates over the elements of a Δ b (a and b are sorted)
+pub fn symmetric_difference_iter<'a,T:Copy+Ord>(a:&'a [T], b:&'a [T]) -> SortedSetOpIterator<'a,T> {
+    SortedSetOpIterator { a, b, op:SetOp::SymmetricDifference }
+}
+
+/// returns true iff a ⊆ b (a and b are sorted)
+pub fn is_subset_of<T:Ord>(a:&[T], b:&[T]) -> bool {
+    if a.len() > b.len() { return false; }
+    let mut index_b:usize = 0;
+    for e in a {
+        while index_b < b.len() && &b[index_b] < e { index_b += 1 }
+        if index_b == b.len() || &b[index_b] > e { return false; }
+    }
+    true
+}
+
+/// returns true iff a ⊇ b (a and b are sorted)
+pub fn is_superset_of<T:Ord>(a:&[T], b:&[T]) -> bool { is_subset_of(b, a) }
+
+/// returns true iff a ∩ b = ∅ (a and b are sorted)
+pub fn is_disjoint_from<T:Copy+Ord>(a:&[T], b:&[T]) -> bool {
+    intersection_iter(a, b).next().is_none()
+}
+
+/// Set of elements in 0..n supporting (cheap) membership tests. Provides subset, superset and
+/// disjointness checks between different representations (e.g. a sparse set and a bit-set).
+/// They iterate over the elements of one set and test them in the other one, thus they take
+/// O(size of the iterated set) membership tests.
+pub trait ElementSet {
+    /// true iff e ∈ Set (false if e is out of the universe)
+    fn contains_element(&self, e:usize) -> bool;
+
+    /// returns the number of elements in the set
+    fn nb_elements(&self) -> usize;
+
+    /// iterates over the elements of the set (in an arbitrary order)
+    fn elements(&self) -> impl Iterator<Item=usize> + '_;
+
+    /// returns the elements of the set in increasing order
+    fn sorted_elements(&self) -> Vec<usize> {
+        let mut res:Vec<usize> = self.elements().collect();
+        res.sort_unstable();
+        res
+    }
+
+    /// returns the canonical byte encoding of the set (see canonical_bytes)
+    fn to_canonical_bytes(&self) -> Vec<u8> { canonical_bytes(self.sorted_elements()) }
+
+    /// returns true iff every element of the set is in other
+    fn is_subset_of_set<S:ElementSet+?Sized>(&self, other:&S) -> bool {
+        self.nb_elements() <= other.nb_elements()
+            && self.elements().all(|e| other.contains_element(e))
+    }
+
+    /// returns true iff every element of other is in the set
+    fn is_superset_of_set<S:ElementSet+?Sized>(&self, other:&S) -> bool {
+        other.is_subset_of_set(self)
+    }
+
+    /// returns true iff no element of the set is in other (iterates over the smallest set)
+    fn is_disjoint_from_set<S:ElementSet+?Sized>(&self, other:&S) -> bool {
+        if self.nb_elements() <= other.nb_elements() {
+            !self.elements().any(|e| other.contains_element(e))
+        } else {
+            !other.elements().any(|e| self.contains_element(e))
+        }
+    }
+}
+
+/// sorted slice of elements (membership tests by binary search)
+impl ElementSet for [usize] {
+    fn contains_element(&self, e:usize) -> bool { self.binary_search(&e).is_ok() }
+
+    fn nb_elements(&self) -> usize { self.len() }
+
+    fn elements(&self) -> impl Iterator<Item=usize> + '_ { self.iter().copied() }
+}
+
+/// hashes a set in its canonical form: its number of elements, then its elements in increasing
+/// order. Every set type of the crate hashes this way, thus equal sets hash equally across
+/// representations (e.g. a bit-set and a sparse set with the same elements), and a sorted
+/// slice can be hashed with the same function to look them up.
+pub fn hash_sorted<T:Hash,H:Hasher>(len:usize, sorted:impl IntoIterator<Item=T>, state:&mut H) {
+    state.write_usize(len);
+    for e in sorted { e.hash(state); }
+}
+
+/// returns the canonical byte encoding of a set given its elements in increasing order:
+/// the first element, then the gaps (minus one) between consecutive elements, as
+/// variable-length integers. Equal sets have the same encoding whatever their representation
+pub fn canonical_bytes(sorted:impl IntoIterator<Item=usize>) -> Vec<u8> {
+    let mut res = Vec::new();
+    let mut next = 0; // smallest possible next element
+    for e in sorted {
+        debug_assert!(e >= next, "elements must be increasing");
+        write_varint(&mut res, e - next).expect("writing to a vector cannot fail");
+        next = e + 1;
+    }
+    res
+}
+
+/// decodes a canonical byte encoding (returns the elements in increasing order)
+pub fn from_canonical_bytes(bytes:&[u8]) -> io::Result<Vec<usize>> {
+    let mut r = bytes;
+    let mut res = Vec::new();
+    let mut next:usize = 0;
+    while !r.is_empty() {
+        let e = next.checked_add(read_varint(&mut r)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "element overflow"))?;
+        res.push(e);
+        next = e.wrapping_add(1);
+    }
+    Ok(res)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_slices() {
+        let a = [1, 3, 5, 7, 9];
+        let b = [2, 3, 7, 10];
+        assert_eq!(union_iter(&a, &b).collect::<Vec<_>>(), vec![1, 2, 3, 5, 7, 9, 10]);
+        assert_eq!(intersection_iter(&a, &b).collect::<Vec<_>>(), vec![3, 7]);
+        assert_eq!(difference_iter(&a, &b).collect::<Vec<_>>(), vec![1, 5, 9]);
+        assert_eq!(difference_iter(&b, &a).collect::<Vec<_>>(), vec![2, 10]);
+        assert_eq!(symmetric_difference_iter(&a, &b).collect::<Vec<_>>(), vec![1, 2, 5, 9, 10]);
+        assert_eq!(intersection_iter(&a, &[]).count(), 0);
+        assert_eq!(union_iter(&[], &b).sum::<i32>(), 22);
+    }
+
+    #[test]
+    fn sorted_predicates() {
+        assert!(is_subset_of(&[3, 7], &[2, 3, 7, 10]));
+        assert!(!is_subset_of(&[3, 8], &[2, 3, 7, 10]));
+        assert!(is_superset_of(&[2, 3, 7, 10], &[]));
+        assert!(is_disjoint_from(&[1, 5], &[2, 3, 7]));
+        assert!(!is_disjoint_from(&[1, 7], &[2, 3, 7]));
+        let (a, b):(&[usize],&[usize]) = (&[1, 4], &[1, 2, 4]);
+        assert!(a.is_subset_of_set(b) && b.is_superset_of_set(a));
+        assert!(!a.is_disjoint_from_set(b));
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        let bytes = canonical_bytes([0, 1, 5, 300]);
+        assert_eq!(bytes[..3], [0, 0, 3]);
+        assert_eq!(from_canonical_bytes(&bytes).unwrap(), vec![0, 1, 5, 300]);
+        assert!(canonical_bytes([]).is_empty());
+        assert!(from_canonical_bytes(&[0x80]).is_err());
+        let sorted:&[usize] = &[2, 4];
+        assert_eq!(sorted.to_canonical_bytes(), canonical_bytes([2, 4]));
+    }
+
+    #[test]
+    fn canonical_hashing_across_representations() {
+        use std::collections::{BTreeMap, HashMap};
+        use std::hash::BuildHasher;
+        use crate::set::{bitset::BitSet, hierarchical_bitset::HierarchicalBitSet};
+        use crate::set::{persistent_set::PersistentSet, small_set::SmallSet, sparse_set::SparseSet};
+
+        let elements = [70, 3, 9];
+        let bits:BitSet<2> = elements.into_iter().collect();
+        let mut sparse = SparseSet::new(100);
+        let mut hierarchical = HierarchicalBitSet::new(1000);
+        for e in elements {
+            sparse.insert(e);
+            hierarchical.insert(e);
+        }
+        let small:SmallSet<usize,2> = elements.into_iter().collect();
+        let persistent:PersistentSet<usize> = elements.into_iter().collect();
+        let state = std::collections::hash_map::RandomState::new();
+        let expected = {
+            let mut hasher = state.build_hasher();
+            hash_sorted(3, [3usize, 9, 70], &mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(state.hash_one(bits), expected);
+        assert_eq!(state.hash_one(&sparse), expected);
+        assert_eq!(state.hash_one(&hierarchical), expected);
+        assert_eq!(state.hash_one(&small), expected);
+        assert_eq!(state.hash_one(&persistent), expected);
+        let bytes = canonical_bytes([3, 9, 70]);
+        assert_eq!(bits.to_canonical_bytes(), bytes);
+        assert_eq!(sparse.to_canonical_bytes(), bytes);
+        assert_eq!(hierarchical.to_canonical_bytes(), bytes);
+        assert_eq!(small.to_canonical_bytes(), bytes);
+        // sets as keys (e.g. DP over subsets)
+        let mut other = SparseSet::new(80);
+        for e in [9, 70, 3] { other.insert(e); }
+        let mut values = HashMap::new();
+        values.insert(sparse, 1);
+        assert_eq!(values.get(&other), Some(&1));
+        let mut ordered = BTreeMap::new();
+        for s in [[1, 5], [1, 2], [0, 9]] {
+            ordered.insert(s.into_iter().collect::<BitSet<1>>(), s);
+        }
+        assert_eq!(ordered.into_values().collect::<Vec<_>>(), vec![[0, 9], [1, 2], [1, 5]]);
+    }
+}
@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use super::set_ops::{difference_iter, hash_sorted, intersection_iter, union_iter, ElementSet};
+use super::universe::{complement, Universe};
+
+/// storage of a small set
+#[derive(Debug,Clone)]
+enum Storage<T,const N:usize> {
+    /// up to N elements stored inline (the first len ones are the elements)
+    Inline { elements:[T;N], len:usize },
+    /// elements stored in a heap-allocated vector (once more than N elements were inserted)
+    Heap(Vec<T>),
+}
+
+/// implements a small sorted set storing up to N elements inline (no heap allocation), and
+/// spilling to a vector beyond that. Once spilled, the set stays on the heap.
+/// If *n* is the number of elements, this data-structure has the following complexities:
+/// - memory: N elements (inline), or a vector of n elements
+/// - insertion/remove: O(n) (sorted array)
+/// - contains: O(log n)
+/// - union/intersection/difference/subset check: O(n+m) (merges)
+///
+/// Small sets hash in the canonical form of the crate (see set_ops::hash_sorted) and are
+/// ordered lexicographically by their sorted elements.
+#[derive(Debug,Clone)]
+pub struct SmallSet<T,const N:usize> {
+    /// elements of the set (sorted)
+    storage:Storage<T,N>,
+}
+
+impl<T:Copy+Ord+Default,const N:usize> Default for SmallSet<T,N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> SmallSet<T,N> {
+    /// creates an empty set
+    pub fn new() -> Self {
+        Self { storage:Storage::Inline { elements:[T::default();N], len:0 } }
+    }
+
+    /// returns the sorted elements of the set
+    pub fn as_slice(&self) -> &[T] {
+        match &self.storage {
+            Storage::Inline { elements, len } => &elements[..*len],
+            Storage::Heap(v) => v,
+        }
+    }
+
+    /// returns true iff the elements are stored on the heap
+    pub fn is_spilled(&self) -> bool { matches!(self.storage, Storage::Heap(_)) }
+
+    /// returns the number of elements in the set
+    pub fn len(&self) -> usize { self.as_slice().len() }
+
+    /// returns true iff the set is empty
+    pub fn is_empty(&self) -> bool { self.as_slice().is_empty() }
+
+    /// true iff e ∈ Set
+    pub fn contains(&self, e:T) -> bool { self.as_slice().binary_search(&e).is_ok() }
+
+    /// inserts e into the set. Returns true iff e was missing
+    pub fn insert(&mut self, e:T) -> bool {
+        let Err(pos) = self.as_slice().binary_search(&e) else { return false; };
+        match &mut self.storage {
+            Storage::Inline { elements, len } if *len < N => {
+                elements.copy_within(pos..*len, pos+1);
+                elements[pos] = e;
+                *len += 1;
+            },
+            Storage::Inline { elements, len } => {
+                // the inline storage is full: spills to the heap
+                let mut v = Vec::with_capacity(2*N+1);
+                v.extend_from_slice(&elements[..pos]);
+                v.push(e);
+                v.extend_from_slice(&elements[pos..*len]);
+                self.storage = Storage::Heap(v);
+            },
+            Storage::Heap(v) => { v.insert(pos, e); },
+        }
+        true
+    }
+
+    /// removes e from the set. Returns true iff e was present
+    pub fn remove(&mut self, e:T) -> bool {
+        let Ok(pos) = self.as_slice().binary_search(&e) else { return false; };
+        match &mut self.storage {
+            Storage::Inline { elements, len } => {
+                elements.copy_within(pos+1..*len, pos);
+                *len -= 1;
+            },
+            Storage::Heap(v) => { v.remove(pos); },
+        }
+        true
+    }
+
+    /// iterates over the elements in increasing order
+    pub fn iter(&self) -> std::slice::Iter<'_,T> { self.as_slice().iter() }
+
+    /// returns true iff every element of the set is in other
+    pub fn is_subset_of(&self, other:&Self) -> bool {
+        let b = other.as_slice();
+        let mut j = 0;
+        for e in self.iter() {
+            while j < b.len() && b[j] < *e { j += 1; }
+            if j == b.len() || b[j] != *e { return false; }
+            j += 1;
+        }
+        true
+    }
+
+    /// returns the union of the sets
+    pub fn union(&self, other:&Self) -> Self {
+        Self::from_sorted(union_iter(self.as_slice(), other.as_slice()))
+    }
+
+    /// returns the intersection of the sets
+    pub fn intersection(&self, other:&Self) -> Self {
+        Self::from_sorted(intersection_iter(self.as_slice(), other.as_slice()))
+    }
+
+    /// returns the elements of the set that are not in other
+    pub fn difference(&self, other:&Self) -> Self {
+        Self::from_sorted(difference_iter(self.as_slice(), other.as_slice()))
+    }
+
+    /// creates the set from increasing elements
+    fn from_sorted(elements:impl Iterator<Item=T>) -> Self {
+        let mut res = Self::new();
+        for e in elements { res.push_max(e); }
+        res
+    }
+
+    /// inserts an element greater than all the elements of the set
+    fn push_max(&mut self, e:T) {
+        match &mut self.storage {
+            Storage::Inline { elements, len } if *len < N => {
+                elements[*len] = e;
+                *len += 1;
+            },
+            _ => { self.insert(e); },
+        }
+    }
+}
+
+impl<const N:usize> SmallSet<usize,N> {
+    /// returns the elements of the universe that are not in the set
+    pub fn complement(&self, universe:&Universe) -> Self { complement(self, universe) }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> FromIterator<T> for SmallSet<T,N> {
+    fn from_iter<I:IntoIterator<Item=T>>(iter:I) -> Self {
+        let mut res = Self::new();
+        for e in iter { res.insert(e); }
+        res
+    }
+}
+
+/// iterates over the elements in increasing order
+impl<'a,T:Copy+Ord+Default,const N:usize> IntoIterator for &'a SmallSet<T,N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a,T>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> Extend<T> for SmallSet<T,N> {
+    fn extend<I:IntoIterator<Item=T>>(&mut self, iter:I) {
+        for e in iter { self.insert(e); }
+    }
+}
+
+/// iterates over the elements in increasing order
+impl<T:Copy+Ord+Default,const N:usize> IntoIterator for SmallSet<T,N> {
+    type Item = T;
+    type IntoIter = SmallSetIntoIterator<T,N>;
+
+    fn into_iter(self) -> Self::IntoIter { SmallSetIntoIterator { set:self, index:0 } }
+}
+
+/// Iterator over the elements of a small set, consuming it (increasing order).
+#[derive(Debug)]
+pub struct SmallSetIntoIterator<T,const N:usize> {
+    /// set iterated
+    set:SmallSet<T,N>,
+    /// index of the next element
+    index:usize,
+}
+
+impl<T:Copy+Ord+Default,const N:usize> Iterator for SmallSetIntoIterator<T,N> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let res = self.set.as_slice().get(self.index).copied();
+        self.index += 1;
+        res
+    }
+}
+
+/// Two sets are equal if they have the same elements (inline or spilled).
+impl<T:Copy+Ord+Default,const N:usize> PartialEq for SmallSet<T,N> {
+    fn eq(&self, other:&Self) -> bool { self.as_slice() == other.as_slice() }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> Eq for SmallSet<T,N> {}
+
+impl<T:Copy+Ord+Default+Hash,const N:usize> Hash for SmallSet<T,N> {
+    fn hash<H:Hasher>(&self, state:&mut H) { hash_sorted(self.len(), self.iter(), state); }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> PartialOrd for SmallSet<T,N> {
+    fn partial_cmp(&self, other:&Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<T:Copy+Ord+Default,const N:usize> Ord for SmallSet<T,N> {
+    fn cmp(&self, other:&Self) -> Ordering { self.as_slice().cmp(other.as_slice()) }
+}
+
+impl<const N:usize> ElementSet for SmallSet<usize,N> {
+    fn contains_element(&self, e:usize) -> bool { self.contains(e) }
+
+    fn nb_elements(&self) -> usize { self.len() }
+
+    fn elements(&self) -> impl Iterator<Item=usize> + '_ { self.iter().copied() }
+
+    fn sorted_elements(&self) -> Vec<usize> { self.as_slice().to_vec() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove_spill() {
+        let mut set:SmallSet<u32,4> = SmallSet::new();
+        for e in [5, 1, 3, 1, 4] { set.insert(e); }
+        assert!(!set.is_spilled());
+        assert_eq!(set.as_slice(), &[1, 3, 4, 5]);
+        assert!(set.insert(2));
+        assert!(set.is_spilled());
+        assert_eq!(set.as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert!(set.contains(4) && !set.contains(3));
+        assert_eq!(set.len(), 4);
+        let inline:SmallSet<u32,4> = [5, 4, 2, 1].into_iter().collect();
+        assert!(!inline.is_spilled());
+        assert_eq!(set, inline);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a:SmallSet<u8,3> = [1, 2, 3].into_iter().collect();
+        let b:SmallSet<u8,3> = [2, 3, 4].into_iter().collect();
+        assert_eq!(a.union(&b).as_slice(), &[1, 2, 3, 4]);
+        assert!(a.union(&b).is_spilled());
+        assert_eq!(a.intersection(&b).as_slice(), &[2, 3]);
+        assert_eq!(a.difference(&b).as_slice(), &[1]);
+        assert!(a.intersection(&b).is_subset_of(&b));
+        assert!(!a.is_subset_of(&b));
+        assert_eq!((&a).into_iter().copied().sum::<u8>(), 6);
+    }
+
+    #[test]
+    fn extend_into_iter() {
+        let mut set:SmallSet<u16,2> = SmallSet::new();
+        set.extend([9, 1]);
+        assert!(!set.is_spilled());
+        set.extend([5, 1]);
+        assert!(set.is_spilled());
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 5, 9]);
+    }
+}
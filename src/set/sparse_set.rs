@@ -1,4 +1,10 @@
 
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use super::set_ops::{hash_sorted, ElementSet};
+use super::universe::Universe;
+
 /// implements a sparse set data-structure.
 /// this structure is efficient to remove all but one values, but is costly in memory.
 /// if *n* is the number elements and *m* the number of subsets in the set this
@@ -8,6 +14,14 @@
 /// - remove: O(1)
 /// - contains: O(1)
 /// - remove all but one: O(1)
+/// - clear: O(1)
+///
+/// Iterating over the elements only visits the present ones (dense array).
+/// See Briggs and Torczon, "An efficient representation for sparse sets" (1993).
+///
+/// Two sparse sets are equal if they have the same elements (whatever their capacities).
+/// They hash in the canonical form of the crate (see set_ops::hash_sorted) and are ordered
+/// lexicographically by their sorted elements. Both sort the elements: O(n log n).
 #[derive(Debug)]
 pub struct SparseSet {
     /// list of (unsorted) values
@@ -46,9 +60,14 @@ impl SparseSet {
         self.dense[i]
     }
 
+    /// returns the maximum number of elements (elements are in 0..nb_max)
+    pub fn capacity(&self) -> usize { self.nb_max }
+
     /// true iff e ∈ Set
     pub fn contains(&self, e:usize) -> bool {
-        self.sparse[e] < self.n
+        // sparse[e] may be outdated after a clear: checks that it points back to e
+        let i = self.sparse[e];
+        i < self.n && self.dense[i] == e
     }
 
     /// inserts e into the set. Returns true iff the element was missing and successfully inserted
@@ -83,12 +102,98 @@ impl SparseSet {
         self.n = 1;
     }
 
+    /// removes all the elements (the sparse array is not reset)
+    pub fn clear(&mut self) { self.n = 0; }
+
+    /// returns the elements of the set (in arbitrary order)
+    pub fn as_slice(&self) -> &[usize] { &self.dense[..self.n] }
+
+    /// returns true iff every element of the set is in other. O(n)
+    pub fn is_subset_of(&self, other:&Self) -> bool { self.is_subset_of_set(other) }
+
+    /// returns true iff every element of other is in the set. O(m) (m: size of other)
+    pub fn is_superset_of(&self, other:&Self) -> bool { other.is_subset_of_set(self) }
+
+    /// returns true iff no element of the set is in other. O(min(n,m))
+    pub fn is_disjoint_from(&self, other:&Self) -> bool { self.is_disjoint_from_set(other) }
+
+    /// returns the elements of the universe that are not in the set (with the same capacity).
+    /// Elements of the universe greater than or equal to the capacity are ignored
+    pub fn complement(&self, universe:&Universe) -> Self {
+        let mut res = Self::new(self.nb_max);
+        for e in universe.iter().filter(|e| *e < self.nb_max && !self.contains(*e)) { res.insert(e); }
+        res
+    }
+
     /// returns an iterator
     pub fn iter(&'_ self) -> SparseSetIterator<'_> {
         SparseSetIterator::new(self)
     }
 }
 
+impl ElementSet for SparseSet {
+    fn contains_element(&self, e:usize) -> bool { e < self.nb_max && self.contains(e) }
+
+    fn nb_elements(&self) -> usize { self.n }
+
+    fn elements(&self) -> impl Iterator<Item=usize> + '_ { self.iter() }
+}
+
+impl PartialEq for SparseSet {
+    fn eq(&self, other:&Self) -> bool {
+        self.n == other.n && self.is_subset_of_set(other)
+    }
+}
+
+impl Eq for SparseSet {}
+
+impl Hash for SparseSet {
+    fn hash<H:Hasher>(&self, state:&mut H) { hash_sorted(self.n, self.sorted_elements(), state); }
+}
+
+impl PartialOrd for SparseSet {
+    fn partial_cmp(&self, other:&Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for SparseSet {
+    fn cmp(&self, other:&Self) -> Ordering { self.sorted_elements().cmp(&other.sorted_elements()) }
+}
+
+/// creates the set of the elements (its capacity is the maximum element plus one)
+impl FromIterator<usize> for SparseSet {
+    fn from_iter<I:IntoIterator<Item=usize>>(iter:I) -> Self {
+        let elements:Vec<usize> = iter.into_iter().collect();
+        let mut res = Self::new(elements.iter().max().map_or(0, |e| e+1));
+        res.extend(elements);
+        res
+    }
+}
+
+/// inserts the elements (they must be lower than the capacity)
+impl Extend<usize> for SparseSet {
+    fn extend<I:IntoIterator<Item=usize>>(&mut self, iter:I) {
+        for e in iter { self.insert(e); }
+    }
+}
+
+/// iterates over the elements in the order of the dense array (insertion order, until the
+/// first removal)
+impl IntoIterator for SparseSet {
+    type Item = usize;
+    type IntoIter = std::iter::Take<std::vec::IntoIter<usize>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.dense.into_iter().take(self.n) }
+}
+
+/// iterates over the elements in the order of the dense array (insertion order, until the
+/// first removal)
+impl<'a> IntoIterator for &'a SparseSet {
+    type Item = usize;
+    type IntoIter = SparseSetIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
 /// Iterator over the sparse set.
 #[derive(Debug)]
 pub struct SparseSetIterator<'a> {
@@ -169,5 +274,49 @@ mod tests {
         assert!(set.contains(3));
     }
 
+    #[test]
+    fn clear() {
+        let mut set = SparseSet::new(10);
+        set.insert(5);
+        set.insert(7);
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(5));
+        // stale sparse entries are not considered
+        set.insert(2);
+        assert!(!set.contains(5));
+        assert!(set.contains(2));
+        assert!(set.insert(5));
+        assert_eq!(set.as_slice(), &[2, 5]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(set.capacity(), 10);
+    }
+
+    #[test]
+    fn collect_extend() {
+        let mut set:SparseSet = [4, 2, 4].into_iter().collect();
+        assert_eq!((set.len(), set.capacity()), (2, 5));
+        set.extend([0, 2]);
+        assert_eq!(set.as_slice(), &[4, 2, 0]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![4, 2, 0]);
+        assert_eq!(SparseSet::from_iter([]).capacity(), 0);
+    }
+
+    #[test]
+    fn predicates() {
+        let mut a = SparseSet::new(10);
+        let mut b = SparseSet::new(20);
+        for e in [1, 4] { a.insert(e); }
+        for e in [4, 1, 15] { b.insert(e); }
+        assert!(a.is_subset_of(&b) && b.is_superset_of(&a) && !b.is_subset_of(&a));
+        assert!(!a.is_disjoint_from(&b));
+        b.remove(1);
+        b.remove(4);
+        assert!(a.is_disjoint_from(&b));
+        // mixed representations
+        let sorted:&[usize] = &[0, 1, 4, 9];
+        assert!(a.is_subset_of_set(sorted));
+        assert!(!sorted.is_subset_of_set(&a));
+    }
 }
 
@@ -0,0 +1,253 @@
+use crate::trail::Backtrackable;
+
+/// implements a union-find (disjoint set union) over 0..n with union by rank and path
+/// compression.
+/// If *n* is the number of elements, this data-structure has the following complexities
+/// (α is the inverse Ackermann function):
+/// - memory: O(n)
+/// - find/union: O(α(n)) amortized
+/// - component size, number of components: O(1) (after a find)
+/// - components: O(n α(n))
+#[derive(Debug,Clone)]
+pub struct UnionFind {
+    /// parent of each element (roots are their own parent)
+    parents:Vec<usize>,
+    /// rank of each root (upper bound of the height of its tree)
+    ranks:Vec<u8>,
+    /// size of the component of each root
+    sizes:Vec<usize>,
+    /// number of components
+    nb_components:usize,
+}
+
+impl UnionFind {
+    /// creates n singleton components
+    pub fn new(n:usize) -> Self {
+        Self { parents:(0..n).collect(), ranks:vec![0; n], sizes:vec![1; n], nb_components:n }
+    }
+
+    /// returns the number of elements
+    pub fn len(&self) -> usize { self.parents.len() }
+
+    /// returns true iff there is no element
+    pub fn is_empty(&self) -> bool { self.parents.is_empty() }
+
+    /// returns the number of components
+    pub fn nb_components(&self) -> usize { self.nb_components }
+
+    /// returns the representative of the component of x (compressing the path)
+    pub fn find(&mut self, x:usize) -> usize {
+        let root = self.root(x);
+        let mut current = x;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// returns the representative of the component of x (without compressing the path)
+    pub fn root(&self, x:usize) -> usize {
+        let mut current = x;
+        while self.parents[current] != current { current = self.parents[current]; }
+        current
+    }
+
+    /// merges the components of a and b. Returns false if they were already the same
+    pub fn union(&mut self, a:usize, b:usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb { return false; }
+        if self.ranks[ra] < self.ranks[rb] { std::mem::swap(&mut ra, &mut rb); }
+        // rb becomes a child of ra
+        self.parents[rb] = ra;
+        self.sizes[ra] += self.sizes[rb];
+        if self.ranks[ra] == self.ranks[rb] { self.ranks[ra] += 1; }
+        self.nb_components -= 1;
+        true
+    }
+
+    /// returns true iff a and b are in the same component
+    pub fn same(&mut self, a:usize, b:usize) -> bool { self.find(a) == self.find(b) }
+
+    /// returns the size of the component of x
+    pub fn component_size(&mut self, x:usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+
+    /// returns the components (elements in increasing order, components ordered by their
+    /// smallest element)
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = vec![usize::MAX; self.len()];
+        let mut res:Vec<Vec<usize>> = Vec::with_capacity(self.nb_components);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if index_of_root[root] == usize::MAX {
+                index_of_root[root] = res.len();
+                res.push(Vec::with_capacity(self.sizes[root]));
+            }
+            res[index_of_root[root]].push(x);
+        }
+        res
+    }
+}
+
+/// implements a union-find over 0..n whose unions can be undone (backtracking search).
+/// It uses union by rank without path compression (thus find takes O(log n)), and records the
+/// unions on a trail. save_level stores the trail size, restore_level undoes the unions made
+/// since then.
+/// If *n* is the number of elements, this data-structure has the following complexities:
+/// - memory: O(n) (plus one entry per union since the first saved level)
+/// - find/union: O(log n)
+/// - save_level: O(1)
+/// - restore_level: O(number of undone unions)
+#[derive(Debug,Clone)]
+pub struct ReversibleUnionFind {
+    /// parent of each element (roots are their own parent)
+    parents:Vec<usize>,
+    /// rank of each root (height of its tree)
+    ranks:Vec<u8>,
+    /// size of the component of each root
+    sizes:Vec<usize>,
+    /// number of components
+    nb_components:usize,
+    /// unions made (root attached to another one, true iff the rank of the other one increased)
+    trail:Vec<(usize,bool)>,
+    /// trail size of each saved level
+    levels:Vec<usize>,
+}
+
+impl ReversibleUnionFind {
+    /// creates n singleton components
+    pub fn new(n:usize) -> Self {
+        Self {
+            parents:(0..n).collect(),
+            ranks:vec![0; n],
+            sizes:vec![1; n],
+            nb_components:n,
+            trail:Vec::new(),
+            levels:Vec::new(),
+        }
+    }
+
+    /// returns the number of elements
+    pub fn len(&self) -> usize { self.parents.len() }
+
+    /// returns true iff there is no element
+    pub fn is_empty(&self) -> bool { self.parents.is_empty() }
+
+    /// returns the number of components
+    pub fn nb_components(&self) -> usize { self.nb_components }
+
+    /// returns the representative of the component of x
+    pub fn find(&self, x:usize) -> usize {
+        let mut current = x;
+        while self.parents[current] != current { current = self.parents[current]; }
+        current
+    }
+
+    /// merges the components of a and b. Returns false if they were already the same
+    pub fn union(&mut self, a:usize, b:usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb { return false; }
+        if self.ranks[ra] < self.ranks[rb] { std::mem::swap(&mut ra, &mut rb); }
+        // rb becomes a child of ra
+        self.parents[rb] = ra;
+        self.sizes[ra] += self.sizes[rb];
+        let rank_increased = self.ranks[ra] == self.ranks[rb];
+        if rank_increased { self.ranks[ra] += 1; }
+        self.nb_components -= 1;
+        // the unions made before the first saved level are never undone
+        if !self.levels.is_empty() { self.trail.push((rb, rank_increased)); }
+        true
+    }
+
+    /// returns true iff a and b are in the same component
+    pub fn same(&self, a:usize, b:usize) -> bool { self.find(a) == self.find(b) }
+
+    /// returns the size of the component of x
+    pub fn component_size(&self, x:usize) -> usize { self.sizes[self.find(x)] }
+
+    /// saves the current state (restored by restore_level)
+    pub fn save_level(&mut self) { self.levels.push(self.trail.len()); }
+
+    /// undoes the unions made since the last saved state.
+    /// Returns false if no level was saved
+    pub fn restore_level(&mut self) -> bool {
+        let Some(size) = self.levels.pop() else { return false; };
+        while self.trail.len() > size {
+            let (child, rank_increased) = self.trail.pop().unwrap();
+            let root = self.parents[child];
+            self.parents[child] = child;
+            self.sizes[root] -= self.sizes[child];
+            if rank_increased { self.ranks[root] -= 1; }
+            self.nb_components += 1;
+        }
+        true
+    }
+
+    /// returns the number of saved levels
+    pub fn level(&self) -> usize { self.levels.len() }
+}
+
+impl Backtrackable for ReversibleUnionFind {
+    fn save_level(&mut self) { self.save_level(); }
+
+    fn restore_level(&mut self) -> bool { self.restore_level() }
+
+    fn level(&self) -> usize { self.level() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unions() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.nb_components(), 6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(4, 5));
+        assert!(uf.union(1, 5));
+        assert!(!uf.union(0, 4));
+        assert!(uf.same(0, 5) && !uf.same(0, 2));
+        assert_eq!(uf.component_size(4), 4);
+        assert_eq!(uf.component_size(3), 1);
+        assert_eq!(uf.nb_components(), 3);
+        assert_eq!(uf.components(), vec![vec![0, 1, 4, 5], vec![2], vec![3]]);
+        assert_eq!(uf.root(5), uf.find(0));
+    }
+
+    #[test]
+    fn long_chain() {
+        let n = 100_000;
+        let mut uf = UnionFind::new(n);
+        for i in 1..n { uf.union(i-1, i); }
+        assert_eq!(uf.nb_components(), 1);
+        assert_eq!(uf.component_size(n/2), n);
+        assert!(uf.same(0, n-1));
+    }
+
+    #[test]
+    fn save_restore() {
+        let mut uf = ReversibleUnionFind::new(5);
+        uf.union(0, 1);
+        uf.save_level();
+        uf.union(2, 3);
+        uf.union(1, 3);
+        uf.save_level();
+        uf.union(4, 0);
+        assert_eq!((uf.nb_components(), uf.component_size(2), uf.level()), (1, 5, 2));
+        assert!(uf.restore_level());
+        assert_eq!(uf.component_size(4), 1);
+        assert_eq!(uf.component_size(0), 4);
+        assert!(uf.restore_level());
+        assert_eq!(uf.nb_components(), 4);
+        assert!(uf.same(0, 1) && !uf.same(1, 3) && !uf.same(2, 3));
+        assert_eq!(uf.component_size(1), 2);
+        assert!(!uf.restore_level());
+        assert!(!uf.union(1, 0));
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops::Range;
+
+use super::set_ops::ElementSet;
+
+/// Universe of elements: a range of integers or an explicit list of elements.
+/// Used to complement sets ("forbidden = universe minus allowed").
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum Universe {
+    /// every integer of the range
+    Range(Range<usize>),
+    /// explicit elements (sorted, without repetition)
+    Elements(Vec<usize>),
+}
+
+impl Universe {
+    /// creates the universe 0..n
+    pub fn range(n:usize) -> Self { Self::Range(0..n) }
+
+    /// creates the universe of the given elements (in any order, possibly repeated)
+    pub fn from_elements(elements:impl IntoIterator<Item=usize>) -> Self {
+        let mut res:Vec<usize> = elements.into_iter().collect();
+        res.sort_unstable();
+        res.dedup();
+        Self::Elements(res)
+    }
+
+    /// returns the number of elements of the universe
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Range(r) => r.len(),
+            Self::Elements(v) => v.len(),
+        }
+    }
+
+    /// returns true iff the universe is empty
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// true iff e is in the universe
+    pub fn contains(&self, e:usize) -> bool {
+        match self {
+            Self::Range(r) => r.contains(&e),
+            Self::Elements(v) => v.binary_search(&e).is_ok(),
+        }
+    }
+
+    /// returns the largest element of the universe plus one (0 if it is empty)
+    pub fn bound(&self) -> usize {
+        match self {
+            Self::Range(r) => if r.is_empty() { 0 } else { r.end },
+            Self::Elements(v) => v.last().map_or(0, |e| e+1),
+        }
+    }
+
+    /// iterates over the elements of the universe in increasing order
+    pub fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        let (range, elements) = match self {
+            Self::Range(r) => (r.clone(), &[][..]),
+            Self::Elements(v) => (0..0, v.as_slice()),
+        };
+        range.chain(elements.iter().copied())
+    }
+}
+
+/// returns the elements of the universe that are not in the set (in increasing order, thus
+/// the result can be collected into any set representation).
+/// Takes O(|universe|) membership tests
+pub fn complement<O:FromIterator<usize>,S:ElementSet+?Sized>(set:&S, universe:&Universe) -> O {
+    universe.iter().filter(|e| !set.contains_element(*e)).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::set::{bitset::BitSet, hierarchical_bitset::HierarchicalBitSet};
+    use crate::set::{small_set::SmallSet, sparse_set::SparseSet};
+
+    #[test]
+    fn universes() {
+        let range = Universe::range(5);
+        let explicit = Universe::from_elements([7, 2, 7, 4]);
+        assert_eq!((range.len(), explicit.len()), (5, 3));
+        assert!(range.contains(4) && !range.contains(5));
+        assert!(explicit.contains(4) && !explicit.contains(3));
+        assert_eq!((range.bound(), explicit.bound()), (5, 8));
+        assert_eq!(explicit.iter().collect::<Vec<_>>(), vec![2, 4, 7]);
+        assert!(Universe::Range(3..3).is_empty());
+    }
+
+    #[test]
+    fn complements() {
+        let allowed:&[usize] = &[1, 4];
+        let universe = Universe::range(6);
+        let forbidden:Vec<usize> = complement(allowed, &universe);
+        assert_eq!(forbidden, vec![0, 2, 3, 5]);
+        let forbidden_bits:BitSet<1> = complement(allowed, &Universe::from_elements([0, 1, 9]));
+        assert_eq!(forbidden_bits.iter().collect::<Vec<_>>(), vec![0, 9]);
+
+        let bits:BitSet<2> = [1, 4, 100].into_iter().collect();
+        assert_eq!(bits.complement(&universe).iter().collect::<Vec<_>>(), vec![0, 2, 3, 5]);
+        assert_eq!(bits.complement(&Universe::Range(98..102)).iter().collect::<Vec<_>>(), vec![98, 99, 101]);
+        let mut sparse = SparseSet::new(10);
+        sparse.insert(4);
+        let mut forbidden_sparse:Vec<usize> = sparse.complement(&universe).iter().collect();
+        forbidden_sparse.sort_unstable();
+        assert_eq!(forbidden_sparse, vec![0, 1, 2, 3, 5]);
+        let mut hierarchical = HierarchicalBitSet::new(10);
+        hierarchical.insert(0);
+        assert_eq!(hierarchical.complement(&universe).iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        let small:SmallSet<usize,4> = [2, 3].into_iter().collect();
+        assert_eq!(small.complement(&universe).as_slice(), &[0, 1, 4, 5]);
+    }
+
+    #[test]
+    fn complements_beyond_capacity() {
+        let range = Universe::Range(0..200);
+        let explicit = Universe::from_elements([3, 9, 63, 64, 150]);
+        let bits:BitSet<1> = [3].into_iter().collect();
+        assert_eq!(bits.complement(&range).len(), 63);
+        assert_eq!(bits.complement(&Universe::Range(100..200)), BitSet::new());
+        assert_eq!(bits.complement(&explicit).iter().collect::<Vec<_>>(), vec![9, 63]);
+        let mut sparse = SparseSet::new(10);
+        sparse.insert(3);
+        assert_eq!(sparse.complement(&range).len(), 9);
+        assert_eq!(sparse.complement(&explicit).iter().collect::<Vec<_>>(), vec![9]);
+        let mut hierarchical = HierarchicalBitSet::new(10);
+        hierarchical.insert(3);
+        assert_eq!(hierarchical.complement(&range).len(), 9);
+        assert_eq!(hierarchical.complement(&explicit).iter().collect::<Vec<_>>(), vec![9]);
+    }
+}
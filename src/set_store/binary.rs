@@ -0,0 +1,70 @@
+use std::io::{self, Read, Write};
+
+/// writes an integer as a variable-length integer (7 bits per byte, LEB128)
+pub(crate) fn write_varint<W:Write>(w:&mut W, mut v:usize) -> io::Result<()> {
+    let mut buf = [0u8;10];
+    let mut len = 0;
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    w.write_all(&buf[..len])
+}
+
+/// reads a variable-length integer written by write_varint
+pub(crate) fn read_varint<R:Read>(r:&mut R) -> io::Result<usize> {
+    let mut res:usize = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8;1];
+        r.read_exact(&mut byte)?;
+        if shift >= usize::BITS { return Err(invalid_data("varint overflow")); }
+        res |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 { return Ok(res); }
+        shift += 7;
+    }
+}
+
+/// writes the header of a binary file (magic bytes and version)
+pub(crate) fn write_header<W:Write>(w:&mut W, magic:&[u8;4], version:u8) -> io::Result<()> {
+    w.write_all(magic)?;
+    w.write_all(&[version])
+}
+
+/// reads and checks the header of a binary file
+pub(crate) fn read_header<R:Read>(r:&mut R, magic:&[u8;4], version:u8) -> io::Result<()> {
+    let mut buf = [0u8;5];
+    r.read_exact(&mut buf)?;
+    if &buf[..4] != magic { return Err(invalid_data("unexpected file type")); }
+    if buf[4] != version { return Err(invalid_data("unsupported version")); }
+    Ok(())
+}
+
+/// returns an invalid data error
+pub(crate) fn invalid_data(msg:&str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_varint_roundtrip() {
+        let values = [0, 1, 127, 128, 300, 1 << 20, usize::MAX];
+        let mut buf:Vec<u8> = Vec::new();
+        for v in values { write_varint(&mut buf, v).unwrap(); }
+        assert_eq!(buf[..4], [0, 1, 127, 0x80]);
+        let mut r = buf.as_slice();
+        for v in values { assert_eq!(read_varint(&mut r).unwrap(), v); }
+        assert!(read_varint(&mut r).is_err());
+    }
+}
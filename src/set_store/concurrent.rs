@@ -0,0 +1,188 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::set_store::SetStore;
+use crate::set_store::set_trie::TrieSetStore;
+
+/// locks the shard for reading, ignoring poisoning (a panicking thread leaves the store usable)
+fn read<S>(l:&RwLock<S>) -> RwLockReadGuard<'_,S> {
+    l.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// locks the shard for writing, ignoring poisoning
+fn write<S>(l:&RwLock<S>) -> RwLockWriteGuard<'_,S> {
+    l.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Sharded concurrent set store.
+///
+/// Sets are spread over several stores (shards) depending on their first element, each
+/// shard being protected by its own read-write lock. Thus, insertions and removals only
+/// lock one shard, and queries run concurrently (read locks).
+/// - subset queries only visit the shards of the elements of the query (a subset of s starts
+///   with an element of s)
+/// - superset queries visit all the shards (each shard is locked in turn)
+///
+/// Each operation is atomic on a shard, but not across shards: insert_if_minimal may keep a
+/// non-minimal set if a subset of it is inserted concurrently (the store stays correct for
+/// subsumption queries, it is only less compact).
+#[derive(Debug)]
+pub struct ShardedSetStore<T,S=TrieSetStore> {
+    /// shards
+    shards:Vec<RwLock<S>>,
+    /// hasher of the first elements
+    hasher:RandomState,
+    /// phantom for type T
+    phantom:PhantomData<fn() -> T>,
+}
+
+impl<T,S:Default> ShardedSetStore<T,S> {
+    /// creates an empty store with the given number of shards (at least one)
+    pub fn with_shards(nb_shards:usize) -> Self {
+        Self {
+            shards:(0..nb_shards.max(1)).map(|_| RwLock::new(S::default())).collect(),
+            hasher:RandomState::new(),
+            phantom:PhantomData,
+        }
+    }
+}
+
+impl<T,S> ShardedSetStore<T,S> {
+    /// returns the number of shards
+    pub fn nb_shards(&self) -> usize { self.shards.len() }
+
+    /// consumes the store and returns its shards
+    pub fn into_shards(self) -> Vec<S> {
+        self.shards.into_iter()
+            .map(|l| l.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect()
+    }
+}
+
+impl<T:Copy+Eq+Hash,S:SetStore<T>> ShardedSetStore<T,S> {
+    /// returns the shard of an element
+    fn shard_of(&self, e:&T) -> usize {
+        (self.hasher.hash_one(e) % self.shards.len() as u64) as usize
+    }
+
+    /// returns the shard of a set (the empty set is in the first shard)
+    fn set_shard(&self, s:&[T]) -> usize { s.first().map_or(0, |e| self.shard_of(e)) }
+
+    /// returns the sorted shards possibly containing a subset of s
+    fn subset_shards(&self, s:&[T]) -> Vec<usize> {
+        let mut res:Vec<usize> = s.iter().map(|e| self.shard_of(e)).collect();
+        res.push(0);
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+
+    /// inserts a set. Returns true if it was not present
+    pub fn insert(&self, s:&[T]) -> bool { write(&self.shards[self.set_shard(s)]).insert(s) }
+
+    /// removes a set. Returns true if it existed
+    pub fn remove(&self, s:&[T]) -> bool { write(&self.shards[self.set_shard(s)]).remove(s) }
+
+    /// returns true if the set exists in the store
+    pub fn contains(&self, s:&[T]) -> bool { read(&self.shards[self.set_shard(s)]).contains(s) }
+
+    /// returns true if the store contains a subset of s
+    pub fn contains_subset_of(&self, s:&[T]) -> bool {
+        self.subset_shards(s).into_iter().any(|i| read(&self.shards[i]).contains_subset_of(s))
+    }
+
+    /// returns true if the store contains a superset of s
+    pub fn contains_superset_of(&self, s:&[T]) -> bool {
+        self.shards.iter().any(|l| read(l).contains_superset_of(s))
+    }
+
+    /// returns the stored subsets of s
+    pub fn find_subsets(&self, s:&[T]) -> Vec<Vec<T>> {
+        self.subset_shards(s).into_iter()
+            .flat_map(|i| read(&self.shards[i]).find_subsets(s).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// returns the stored supersets of s
+    pub fn find_supersets(&self, s:&[T]) -> Vec<Vec<T>> {
+        self.shards.iter().flat_map(|l| read(l).find_supersets(s).collect::<Vec<_>>()).collect()
+    }
+
+    /// inserts a set if the store contains no subset of it. If evict is true, removes the
+    /// stored supersets of the inserted set.
+    ///
+    /// Returns true if the set was inserted
+    pub fn insert_if_minimal(&self, s:&[T], evict:bool) -> bool {
+        if self.contains_subset_of(s) { return false; }
+        if evict {
+            for l in &self.shards { write(l).remove_supersets_of(s); }
+        }
+        self.insert(s)
+    }
+
+    /// removes the stored supersets of s. Returns the number of removed sets
+    pub fn remove_supersets_of(&self, s:&[T]) -> usize {
+        self.shards.iter().map(|l| write(l).remove_supersets_of(s)).sum()
+    }
+
+    /// returns the number of stored sets (each shard is locked in turn, thus it may be
+    /// outdated if other threads modify the store)
+    pub fn len(&self) -> usize { self.shards.iter().map(|l| read(l).len()).sum() }
+
+    /// returns true iff the store contains no set
+    pub fn is_empty(&self) -> bool { self.shards.iter().all(|l| read(l).is_empty()) }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::thread;
+
+    use crate::set_store::list::ListSetStore;
+
+    #[test]
+    fn test_queries() {
+        let store:ShardedSetStore<usize> = ShardedSetStore::with_shards(4);
+        let sets:Vec<Vec<usize>> = vec![vec![], vec![1,3], vec![1,2,3], vec![2], vec![3,10]];
+        for set in &sets { assert!(store.insert(set)); }
+        assert!(!store.insert(&sets[1]));
+        assert_eq!(store.len(), 5);
+        assert!(store.contains(&[3,10]));
+        let mut subsets = store.find_subsets(&[1,2,3]);
+        subsets.sort();
+        assert_eq!(subsets, vec![vec![], vec![1,2,3], vec![1,3], vec![2]]);
+        let mut supersets = store.find_supersets(&[3]);
+        supersets.sort();
+        assert_eq!(supersets, vec![vec![1,2,3], vec![1,3], vec![3,10]]);
+        assert!(store.contains_superset_of(&[2,3]));
+        assert!(!store.contains_superset_of(&[4]));
+        assert!(store.remove(&[]));
+        assert!(!store.contains_subset_of(&[10]));
+        assert_eq!(store.remove_supersets_of(&[3]), 3);
+        assert_eq!(store.into_shards().iter().map(SetStore::<usize>::len).sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_nogoods() {
+        let store:ShardedSetStore<u32,ListSetStore<u32>> = ShardedSetStore::with_shards(8);
+        // each thread inserts the pairs {i, i+1}, and some supersets of them
+        thread::scope(|s| {
+            for t in 0..4 {
+                let shared = &store;
+                s.spawn(move || for i in (t..100).step_by(4) {
+                    shared.insert_if_minimal(&[i, i+1, i+3], true);
+                    shared.insert_if_minimal(&[i, i+1], true);
+                    assert!(shared.contains_subset_of(&[i, i+1, i+2]));
+                });
+            }
+        });
+        for i in 0..100 { assert!(store.contains(&[i, i+1])); }
+        assert!(!store.contains_subset_of(&[0, 2, 4]));
+        assert_eq!(store.len(), 100);
+        assert!(!store.is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::set_store::SetStore;
+
+/// Result of a greedy set cover.
+#[derive(Debug,Clone,PartialEq)]
+pub struct SetCover<T> {
+    /// chosen sets (in order of selection)
+    pub sets:Vec<Vec<T>>,
+    /// sum of the weights of the chosen sets
+    pub weight:f64,
+    /// elements of the universe covered by the chosen sets
+    pub nb_covered:usize,
+    /// elements of the universe that no stored set covers
+    pub uncovered:Vec<T>,
+    /// approximation ratio guaranteed by the greedy algorithm: H(d), d being the maximum
+    /// number of universe elements in a set (H the harmonic number, H(d) <= 1 + ln(d))
+    pub approximation_ratio:f64,
+}
+
+impl<T> SetCover<T> {
+    /// returns true if every element of the universe is covered
+    pub fn is_complete(&self) -> bool { self.uncovered.is_empty() }
+
+    /// returns a lower bound of the weight of an optimal cover (weight / approximation ratio)
+    pub fn lower_bound(&self) -> f64 {
+        if self.approximation_ratio > 0. { self.weight / self.approximation_ratio } else { 0. }
+    }
+}
+
+/// returns the d-th harmonic number
+fn harmonic(d:usize) -> f64 { (1..=d).map(|i| 1. / i as f64).sum() }
+
+/// Greedy weighted set cover.
+///
+/// Covers the universe with the stored sets (weighted by the weight function, assumed to be
+/// positive). At each step, chooses the set minimizing its weight divided by the number of
+/// elements it newly covers. The weight of the result is at most H(d) times the optimal one
+/// (d being the maximum number of universe elements in a set).
+///
+/// Elements of the sets that are not in the universe are ignored. If some elements cannot be
+/// covered, the result covers all the others (see [SetCover::uncovered]).
+pub fn greedy_set_cover<T,S,F>(universe:&[T], store:&S, mut weight:F) -> SetCover<T>
+where T:Copy+Eq+Hash, S:SetStore<T>, F:FnMut(&[T]) -> f64 {
+    let mut uncovered:HashSet<T> = universe.iter().copied().collect();
+    // candidate sets with their weights (sets without universe element are useless)
+    let mut candidates:Vec<(Vec<T>,f64)> = store.find_supersets(&[])
+        .filter(|s| s.iter().any(|e| uncovered.contains(e)))
+        .map(|s| { let w = weight(&s); (s,w) })
+        .collect();
+    let max_size = candidates.iter()
+        .map(|(s,_)| s.iter().filter(|e| uncovered.contains(e)).count())
+        .max().unwrap_or(0);
+    let mut res = SetCover {
+        sets:Vec::new(), weight:0., nb_covered:0, uncovered:Vec::new(), approximation_ratio:harmonic(max_size)
+    };
+    while !uncovered.is_empty() {
+        // set of minimum cost per newly covered element
+        let mut best:Option<(usize,f64)> = None;
+        for (i,(s,w)) in candidates.iter().enumerate() {
+            let nb_new = s.iter().filter(|e| uncovered.contains(e)).count();
+            if nb_new == 0 { continue; }
+            let cost = w / nb_new as f64;
+            if best.is_none_or(|(_,c)| cost < c) { best = Some((i,cost)); }
+        }
+        let Some((i,_)) = best else { break; };
+        let (s,w) = candidates.swap_remove(i);
+        for e in &s {
+            if uncovered.remove(e) { res.nb_covered += 1; }
+        }
+        res.weight += w;
+        res.sets.push(s);
+    }
+    res.uncovered = universe.iter().filter(|e| uncovered.contains(e)).copied().collect();
+    res
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::set_store::list::ListSetStore;
+    use crate::set_store::set_trie::TrieSetStore;
+
+    #[test]
+    fn test_weighted_cover() {
+        let mut store = ListSetStore::default();
+        for s in [vec![1,2,3,4,5,6], vec![1,2,3], vec![4,5,6], vec![7]] { store.insert(&s); }
+        // the large set is expensive: two small ones are cheaper
+        let cover = greedy_set_cover(&[1,2,3,4,5,6], &store, |s| if s.len() == 6 { 10. } else { 1. });
+        assert_eq!(cover.sets, vec![vec![1,2,3], vec![4,5,6]]);
+        assert_eq!(cover.weight, 2.);
+        assert_eq!(cover.nb_covered, 6);
+        assert!(cover.is_complete());
+        assert!((cover.approximation_ratio - 2.45).abs() < 1e-9);
+        // unit weights: the large set is chosen
+        let unit = greedy_set_cover(&[1,2,3,4,5,6], &store, |_| 1.);
+        assert_eq!(unit.sets, vec![vec![1,2,3,4,5,6]]);
+        assert!(unit.lower_bound() <= 1.);
+    }
+
+    #[test]
+    fn test_uncoverable_elements() {
+        let mut trie = TrieSetStore::default();
+        trie.insert(&[0,2]);
+        trie.insert(&[1,2,9]);
+        let cover = greedy_set_cover(&[0,1,2,3], &trie, |s:&[usize]| s.len() as f64);
+        assert_eq!(cover.nb_covered, 3);
+        assert_eq!(cover.uncovered, vec![3]);
+        assert!(!cover.is_complete());
+        assert_eq!(cover.sets.len(), 2);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::set_store::SetStore;
+use crate::set_store::set_trie::TrieSetStore;
+
+/// Interned set store.
+///
+/// Stores sets of arbitrary elements (strings, identifiers, ...) by mapping each element to a
+/// dense identifier (in order of first appearance). The underlying store contains the sorted
+/// identifier sets. Elements are never un-interned.
+#[derive(Debug)]
+pub struct InternedSetStore<T,S=TrieSetStore> {
+    /// underlying store of identifier sets
+    store:S,
+    /// identifier of each interned element
+    ids:HashMap<T,usize>,
+    /// element of each identifier
+    values:Vec<T>,
+}
+
+impl<T,S:Default> Default for InternedSetStore<T,S> {
+    fn default() -> Self {
+        Self { store:S::default(), ids:HashMap::new(), values:Vec::new() }
+    }
+}
+
+impl<T,S> InternedSetStore<T,S> {
+    /// returns the number of interned elements
+    pub fn nb_interned(&self) -> usize { self.values.len() }
+
+    /// returns the underlying store
+    pub fn inner(&self) -> &S { &self.store }
+}
+
+impl<T:Hash+Eq+Clone,S:SetStore<usize>> InternedSetStore<T,S> {
+    /// inserts a set (interning its new elements).
+    ///
+    /// Returns true if the set was successfully inserted (or false if it was already present)
+    pub fn insert(&mut self, s:&[T]) -> bool {
+        let ids = self.intern_all(s);
+        self.store.insert(&ids)
+    }
+
+    /// inserts a set if the store contains no subset of it (see [SetStore::insert_if_minimal])
+    pub fn insert_if_minimal(&mut self, s:&[T], evict:bool) -> bool {
+        let ids = self.intern_all(s);
+        self.store.insert_if_minimal(&ids, evict)
+    }
+
+    /// inserts a set if the store contains no superset of it (see [SetStore::insert_if_maximal])
+    pub fn insert_if_maximal(&mut self, s:&[T], evict:bool) -> bool {
+        let ids = self.intern_all(s);
+        self.store.insert_if_maximal(&ids, evict)
+    }
+
+    /// removes a set. Returns true if it existed
+    pub fn remove(&mut self, s:&[T]) -> bool {
+        match self.ids_of(s) {
+            Some(ids) => self.store.remove(&ids),
+            None => false,
+        }
+    }
+
+    /// removes all the stored supersets of s. Returns the number of removed sets
+    pub fn remove_supersets_of(&mut self, s:&[T]) -> usize {
+        match self.ids_of(s) {
+            Some(ids) => self.store.remove_supersets_of(&ids),
+            None => 0,
+        }
+    }
+
+    /// removes all the stored subsets of s. Returns the number of removed sets
+    pub fn remove_subsets_of(&mut self, s:&[T]) -> usize {
+        let ids = self.known_ids_of(s);
+        self.store.remove_subsets_of(&ids)
+    }
+
+    /// returns the number of stored sets
+    pub fn len(&self) -> usize { self.store.len() }
+
+    /// returns true iff the store contains no set
+    pub fn is_empty(&self) -> bool { self.store.is_empty() }
+
+    /// returns true if the set exists in the store
+    pub fn contains(&self, s:&[T]) -> bool {
+        self.ids_of(s).is_some_and(|ids| self.store.contains(&ids))
+    }
+
+    /// returns true if the store contains a subset of s
+    pub fn contains_subset_of(&self, s:&[T]) -> bool {
+        self.store.contains_subset_of(&self.known_ids_of(s))
+    }
+
+    /// returns true if the store contains a superset of s
+    pub fn contains_superset_of(&self, s:&[T]) -> bool {
+        self.ids_of(s).is_some_and(|ids| self.store.contains_superset_of(&ids))
+    }
+
+    /// returns the number of stored sets containing e
+    pub fn support(&self, e:&T) -> usize {
+        self.ids.get(e).map_or(0, |id| self.store.support(*id))
+    }
+
+    /// counts the stored subsets of s
+    pub fn count_subsets(&self, s:&[T]) -> usize {
+        self.store.count_subsets(&self.known_ids_of(s))
+    }
+
+    /// counts the stored supersets of s
+    pub fn count_supersets(&self, s:&[T]) -> usize {
+        self.ids_of(s).map_or(0, |ids| self.store.count_supersets(&ids))
+    }
+
+    /// enumerates all the stored subsets of s
+    pub fn find_subsets(&self, s:&[T]) -> impl Iterator<Item=Vec<T>> + '_ {
+        self.store.find_subsets(&self.known_ids_of(s)).map(|set| self.values_of(&set))
+    }
+
+    /// enumerates all the stored supersets of s
+    pub fn find_supersets(&self, s:&[T]) -> impl Iterator<Item=Vec<T>> + '_ {
+        // if an element was never interned, no stored set contains it
+        self.ids_of(s).map(|ids| self.store.find_supersets(&ids))
+            .into_iter()
+            .flatten()
+            .map(|set| self.values_of(&set))
+    }
+
+    /// enumerates the minimal stored sets
+    pub fn iter_minimal(&self) -> impl Iterator<Item=Vec<T>> + '_ {
+        self.store.iter_minimal().map(|set| self.values_of(&set))
+    }
+
+    /// enumerates the maximal stored sets
+    pub fn iter_maximal(&self) -> impl Iterator<Item=Vec<T>> + '_ {
+        self.store.iter_maximal().map(|set| self.values_of(&set))
+    }
+
+    /// returns the identifier of the element, interning it if needed
+    fn intern(&mut self, e:&T) -> usize {
+        if let Some(id) = self.ids.get(e) { return *id; }
+        let id = self.values.len();
+        self.ids.insert(e.clone(), id);
+        self.values.push(e.clone());
+        id
+    }
+
+    /// returns the sorted identifiers of the set, interning its new elements
+    fn intern_all(&mut self, s:&[T]) -> Vec<usize> {
+        let mut ids:Vec<usize> = s.iter().map(|e| self.intern(e)).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// returns the sorted identifiers of the set (None if some element was never interned)
+    fn ids_of(&self, s:&[T]) -> Option<Vec<usize>> {
+        let mut ids = s.iter().map(|e| self.ids.get(e).copied()).collect::<Option<Vec<usize>>>()?;
+        ids.sort_unstable();
+        ids.dedup();
+        Some(ids)
+    }
+
+    /// returns the sorted identifiers of the interned elements of the set (elements never
+    /// interned are not part of any stored set)
+    fn known_ids_of(&self, s:&[T]) -> Vec<usize> {
+        let mut ids:Vec<usize> = s.iter().filter_map(|e| self.ids.get(e).copied()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// returns the elements of an identifier set
+    fn values_of(&self, ids:&[usize]) -> Vec<T> {
+        ids.iter().map(|id| self.values[*id].clone()).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::set_store::list::ListSetStore;
+
+    /// converts string slices to owned strings
+    fn strings(s:&[&str]) -> Vec<String> { s.iter().map(|e| e.to_string()).collect() }
+
+    #[test]
+    fn test_string_sets() {
+        let mut store:InternedSetStore<String> = InternedSetStore::default();
+        assert!(store.insert(&strings(&["x", "a"])));
+        assert!(store.insert(&strings(&["a", "x", "b"])));
+        assert!(!store.insert(&strings(&["a", "x"])));
+        assert_eq!(store.nb_interned(), 3);
+        assert_eq!(store.len(), 2);
+        assert!(store.contains(&strings(&["x", "a"])));
+        assert!(!store.contains(&strings(&["a", "z"])));
+        assert!(store.contains_subset_of(&strings(&["a", "x", "z"])));
+        assert!(!store.contains_superset_of(&strings(&["a", "z"])));
+        assert_eq!(store.find_supersets(&strings(&["a"])).count(), 2);
+        assert_eq!(store.find_supersets(&strings(&["z"])).count(), 0);
+        assert_eq!(store.count_supersets(&strings(&["a"])), 2);
+        assert_eq!(store.count_subsets(&strings(&["a", "x", "z"])), 1);
+        assert_eq!(store.find_subsets(&strings(&["a", "b", "x", "z"])).collect::<Vec<_>>(), vec![
+            strings(&["x", "a"]), strings(&["x", "a", "b"])
+        ]);
+        assert!(store.remove(&strings(&["a", "x"])));
+        assert!(!store.remove(&strings(&["z"])));
+        assert_eq!(store.remove_supersets_of(&strings(&["z"])), 0);
+        assert_eq!(store.find_supersets(&strings(&["x"])).collect::<Vec<_>>(), vec![strings(&["x", "a", "b"])]);
+    }
+
+    #[test]
+    fn test_minimal_sets() {
+        let mut store:InternedSetStore<&str> = InternedSetStore::default();
+        assert!(store.insert_if_minimal(&["a", "b", "c"], true));
+        assert!(store.insert_if_minimal(&["b", "d"], true));
+        assert!(!store.insert_if_minimal(&["a", "b", "c", "e"], true));
+        assert!(store.insert_if_minimal(&["b"], true));
+        assert_eq!(store.count_supersets(&[]), 1);
+        assert!(store.contains(&["b"]));
+        assert_eq!(store.iter_maximal().collect::<Vec<_>>(), vec![vec!["b"]]);
+        assert_eq!(store.remove_subsets_of(&["b", "z"]), 1);
+    }
+
+    #[test]
+    fn test_list_backend() {
+        let mut store:InternedSetStore<&str,ListSetStore<usize>> = InternedSetStore::default();
+        store.insert(&["b", "a"]);
+        store.insert(&["c"]);
+        let subsets:Vec<Vec<&str>> = store.find_subsets(&["a", "b", "c"]).collect();
+        assert_eq!(subsets, vec![vec!["b", "a"], vec!["c"]]);
+    }
+}
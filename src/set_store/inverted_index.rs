@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::set_store::SetStore;
+
+/// Inverted-index set store.
+///
+/// Gives an identifier to each stored set, and maintains for each element the sorted list of
+/// identifiers of the sets containing it (posting list).
+/// - insertion/deletion: O(|s| * posting list length)
+/// - find_supersets: intersection of the posting lists of s (starting from the shortest one)
+/// - find_subsets: counts, for each set, the number of elements of s it contains
+///
+/// Trades memory for fast super-set queries on sparse sets over large universes.
+#[derive(Debug)]
+pub struct InvertedIndexSetStore<T> {
+    /// stored sets (indexed by their identifier, None if the identifier is free)
+    sets:Vec<Option<Vec<T>>>,
+    /// identifier of each stored set
+    ids:HashMap<Vec<T>,usize>,
+    /// sorted identifiers of the sets containing each element
+    postings:HashMap<T,Vec<usize>>,
+    /// free identifiers
+    free_ids:Vec<usize>,
+}
+
+impl<T> Default for InvertedIndexSetStore<T> {
+    fn default() -> Self {
+        Self { sets:Vec::new(), ids:HashMap::new(), postings:HashMap::new(), free_ids:Vec::new() }
+    }
+}
+
+impl<T:Copy+Eq+Hash> InvertedIndexSetStore<T> {
+    /// iterates over the elements of the stored sets (in arbitrary order) with their support
+    /// (number of stored sets containing them)
+    pub fn supports(&self) -> impl Iterator<Item=(T,usize)> + '_ {
+        self.postings.iter().map(|(e,ids)| (*e, ids.len()))
+    }
+
+    /// returns the sorted identifiers of the sets containing every element of s
+    fn superset_ids(&self, s:&[T]) -> Vec<usize> {
+        if s.is_empty() {
+            return self.sets.iter().enumerate()
+                .filter(|(_,set)| set.is_some())
+                .map(|(id,_)| id)
+                .collect();
+        }
+        let mut lists:Vec<&Vec<usize>> = Vec::with_capacity(s.len());
+        for e in s {
+            match self.postings.get(e) {
+                None => { return Vec::new(); },
+                Some(list) => { lists.push(list); },
+            }
+        }
+        lists.sort_by_key(|list| list.len());
+        let mut res = lists[0].clone();
+        for list in &lists[1..] {
+            res.retain(|id| list.binary_search(id).is_ok());
+            if res.is_empty() { break; }
+        }
+        res
+    }
+
+    /// returns the sorted identifiers of the sets contained in s
+    fn subset_ids(&self, s:&[T]) -> Vec<usize> {
+        let mut counts:HashMap<usize,usize> = HashMap::new();
+        for e in s {
+            for id in self.postings.get(e).into_iter().flatten() {
+                *counts.entry(*id).or_insert(0) += 1;
+            }
+        }
+        let mut res:Vec<usize> = counts.into_iter()
+            .filter(|(id,nb)| self.sets[*id].as_ref().is_some_and(|set| set.len() == *nb))
+            .map(|(id,_)| id)
+            .collect();
+        // the empty set (if stored) is not in any posting list
+        if let Some(id) = self.ids.get(&[][..]) { res.push(*id); }
+        res.sort_unstable();
+        res
+    }
+
+    /// returns the stored sets of the given identifiers
+    fn sets_of(&self, ids:&[usize]) -> Vec<Vec<T>> {
+        ids.iter().filter_map(|id| self.sets[*id].clone()).collect()
+    }
+}
+
+impl<T:Copy+Eq+Hash> SetStore<T> for InvertedIndexSetStore<T> {
+    type SubsetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
+    type SupersetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
+
+    fn insert(&mut self, s:&[T]) -> bool {
+        if self.ids.contains_key(s) { return false; }
+        let id = match self.free_ids.pop() {
+            Some(id) => { self.sets[id] = Some(s.to_vec()); id },
+            None => { self.sets.push(Some(s.to_vec())); self.sets.len()-1 },
+        };
+        self.ids.insert(s.to_vec(), id);
+        for e in s {
+            let list = self.postings.entry(*e).or_default();
+            let pos = list.partition_point(|i| *i < id);
+            list.insert(pos, id);
+        }
+        true
+    }
+
+    fn remove(&mut self, s:&[T]) -> bool {
+        let Some(id) = self.ids.remove(s) else { return false; };
+        for e in s {
+            if let Some(list) = self.postings.get_mut(e) {
+                if let Ok(pos) = list.binary_search(&id) { list.remove(pos); }
+                if list.is_empty() { self.postings.remove(e); }
+            }
+        }
+        self.sets[id] = None;
+        self.free_ids.push(id);
+        true
+    }
+
+    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator<'_> {
+        self.sets_of(&self.subset_ids(s)).into_iter()
+    }
+
+    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator<'_> {
+        self.sets_of(&self.superset_ids(s)).into_iter()
+    }
+
+    fn contains(&self, s:&[T]) -> bool { self.ids.contains_key(s) }
+
+    fn len(&self) -> usize { self.ids.len() }
+
+    fn support(&self, e:T) -> usize { self.postings.get(&e).map_or(0, Vec::len) }
+
+    fn count_subsets(&self, s:&[T]) -> usize { self.subset_ids(s).len() }
+
+    fn count_supersets(&self, s:&[T]) -> usize { self.superset_ids(s).len() }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_queries() {
+        let mut store = InvertedIndexSetStore::default();
+        let sets:Vec<Vec<u32>> = vec![vec![], vec![1,3], vec![1,2,3], vec![2], vec![3,1000]];
+        for set in &sets { assert!(store.insert(set)); }
+        assert!(!store.insert(&sets[1]));
+        assert_eq!(store.len(), 5);
+        assert_eq!(store.support(3), 3);
+        assert_eq!(store.find_supersets(&[1,3]).collect::<Vec<_>>(), vec![sets[1].clone(), sets[2].clone()]);
+        assert_eq!(store.find_supersets(&[4]).count(), 0);
+        assert_eq!(store.count_supersets(&[]), 5);
+        assert_eq!(store.find_subsets(&[1,2,3]).collect::<Vec<_>>(), vec![
+            sets[0].clone(), sets[1].clone(), sets[2].clone(), sets[3].clone()
+        ]);
+        assert!(store.contains_subset_of(&[5]));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut store = InvertedIndexSetStore::default();
+        let sets:Vec<Vec<u32>> = vec![vec![1,3], vec![1,2,3], vec![2]];
+        for set in &sets { store.insert(set); }
+        assert!(store.remove(&sets[1]));
+        assert!(!store.remove(&sets[1]));
+        assert!(!store.contains(&sets[1]));
+        assert_eq!(store.support(2), 1);
+        let mut supports:Vec<(u32,usize)> = store.supports().collect();
+        supports.sort_unstable();
+        assert_eq!(supports, vec![(1,1), (2,1), (3,1)]);
+        assert_eq!(store.find_supersets(&[1]).collect::<Vec<_>>(), vec![sets[0].clone()]);
+        // the free identifier is reused
+        assert!(store.insert(&[4]));
+        assert_eq!(store.find_supersets(&[]).collect::<Vec<_>>(), vec![sets[0].clone(), vec![4], sets[2].clone()]);
+        assert_eq!(store.remove_supersets_of(&[1]), 1);
+        assert_eq!(store.len(), 2);
+    }
+}
@@ -1,4 +1,19 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::ops::ControlFlow;
+
+use rand::Rng;
+
+use crate::set::set_ops::is_subset_of;
+
 use super::SetStore;
+use super::binary::{read_header, read_varint, write_header, write_varint};
+
+/// magic bytes of the binary format of the list
+const BINARY_MAGIC:&[u8;4] = b"DOSL";
+
+/// version of the binary format of the list
+const BINARY_VERSION:u8 = 1;
 
 
 /// ListSetStore
@@ -8,14 +23,21 @@ use super::SetStore;
 /// - deletion: O(n)
 /// - find_subsets: O(n)
 /// - find_supersets: O(n)
+///
+/// With the serde feature, the list implements Serialize and Deserialize (deserialized sets
+/// are deduplicated, and their supports recomputed).
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "SerializedList<T>"))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T:serde::Deserialize<'de>+Copy+Ord")))]
 pub struct ListSetStore<T> {
-    list:Vec<Vec<T>>
+    list:Vec<Vec<T>>,
+    /// number of stored sets containing each element
+    supports:BTreeMap<T,usize>,
 }
 
 impl<T:Copy+Eq+Ord> SetStore<T> for ListSetStore<T> {
-    type SubsetIterator = std::vec::IntoIter<Vec<T>>;
-    type SupersetIterator = std::vec::IntoIter<Vec<T>>;
+    type SubsetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
+    type SupersetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
 
     /// In this implementation, we perform a linear check if the element already exists.
     /// Another implementation would use a HashMap to store which elements were inserted or not.
@@ -23,7 +45,8 @@ impl<T:Copy+Eq+Ord> SetStore<T> for ListSetStore<T> {
         if self.list.iter().any(|e| e==s) {
             false
         } else {
-            self.list.push(s.iter().copied().collect());
+            self.list.push(s.to_vec());
+            Self::add_supports(&mut self.supports, s);
             true
         }
     }
@@ -32,44 +55,301 @@ impl<T:Copy+Eq+Ord> SetStore<T> for ListSetStore<T> {
         let previous_size = self.list.len();
         self.list = self.list.iter().filter(|e| *e != s).cloned().collect();
         let next_size = self.list.len();
+        if next_size < previous_size { Self::sub_supports(&mut self.supports, s); }
         next_size < previous_size
     }
 
-    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator {
+    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator<'_> {
         self.list.iter().filter(|e| Self::is_subset(e, s)).cloned()
             .collect::<Vec<Vec<T>>>().into_iter()
     }
 
-    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator {
+    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator<'_> {
         self.list.iter().filter(|e| Self::is_subset(s, e)).cloned()
             .collect::<Vec<Vec<T>>>().into_iter()
     }
 
     fn contains(&self, s:&[T]) -> bool { self.list.iter().any(|e| e==s) }
+
+    fn find_subsets_limit(&self, s:&[T], k:usize) -> Vec<Vec<T>> {
+        self.list.iter().filter(|e| Self::is_subset(e, s)).take(k).cloned().collect()
+    }
+
+    fn find_supersets_limit(&self, s:&[T], k:usize) -> Vec<Vec<T>> {
+        self.list.iter().filter(|e| Self::is_subset(s, e)).take(k).cloned().collect()
+    }
+
+    /// The stored sets are not copied.
+    fn for_each_subset<B,F:FnMut(&[T]) -> ControlFlow<B>>(&self, s:&[T], mut f:F) -> ControlFlow<B> {
+        self.list.iter().filter(|e| Self::is_subset(e, s)).try_for_each(|e| f(e))
+    }
+
+    /// The stored sets are not copied.
+    fn for_each_superset<B,F:FnMut(&[T]) -> ControlFlow<B>>(&self, s:&[T], mut f:F) -> ControlFlow<B> {
+        self.list.iter().filter(|e| Self::is_subset(s, e)).try_for_each(|e| f(e))
+    }
+
+    fn len(&self) -> usize { self.list.len() }
+
+    fn support(&self, e:T) -> usize { self.supports.get(&e).copied().unwrap_or(0) }
+
+    fn contains_subset_of(&self, s:&[T]) -> bool {
+        self.list.iter().any(|e| Self::is_subset(e, s))
+    }
+
+    fn contains_superset_of(&self, s:&[T]) -> bool {
+        self.list.iter().any(|e| Self::is_subset(s, e))
+    }
+
+    fn remove_supersets_of(&mut self, s:&[T]) -> usize {
+        let previous_size = self.list.len();
+        let supports = &mut self.supports;
+        self.list.retain(|e| {
+            let keep = !Self::is_subset(s, e);
+            if !keep { Self::sub_supports(supports, e); }
+            keep
+        });
+        previous_size - self.list.len()
+    }
+
+    fn remove_subsets_of(&mut self, s:&[T]) -> usize {
+        let previous_size = self.list.len();
+        let supports = &mut self.supports;
+        self.list.retain(|e| {
+            let keep = !Self::is_subset(e, s);
+            if !keep { Self::sub_supports(supports, e); }
+            keep
+        });
+        previous_size - self.list.len()
+    }
+
+    fn count_subsets(&self, s:&[T]) -> usize {
+        self.list.iter().filter(|e| Self::is_subset(e, s)).count()
+    }
+
+    fn count_supersets(&self, s:&[T]) -> usize {
+        self.list.iter().filter(|e| Self::is_subset(s, e)).count()
+    }
 }
 
 
 impl<T> ListSetStore<T> where T:Ord {
 
+    /// builds a list from sets (sorted sets of elements, duplicates are ignored).
+    ///
+    /// Sorts the sets and removes the duplicates at once, instead of a linear check per set.
+    pub fn from_sets<S:AsRef<[T]>,I:IntoIterator<Item=S>>(sets:I) -> Self where T:Copy {
+        Self::from_list(sets.into_iter().map(|s| s.as_ref().to_vec()).collect())
+    }
+
+    /// builds a list from a vector of sets (sorted sets of elements, duplicates are ignored)
+    fn from_list(mut list:Vec<Vec<T>>) -> Self where T:Copy {
+        list.sort_unstable();
+        list.dedup();
+        let mut supports = BTreeMap::new();
+        for s in &list { Self::add_supports(&mut supports, s); }
+        Self { list, supports }
+    }
+
+    /// iterates over the elements of the stored sets (in increasing order) with their support
+    /// (number of stored sets containing them)
+    pub fn supports(&self) -> impl Iterator<Item=(&T,usize)> + '_ {
+        self.supports.iter().map(|(e,n)| (e,*n))
+    }
+
+    /// iterates over the stored sets in lexicographic order (sorts the list of sets)
+    pub fn iter(&self) -> std::vec::IntoIter<&[T]> {
+        let mut sets:Vec<&[T]> = self.list.iter().map(|s| s.as_slice()).collect();
+        sets.sort_unstable();
+        sets.into_iter()
+    }
+
+    /// draws a stored set uniformly at random (None if the list is empty)
+    pub fn sample<R:Rng+?Sized>(&self, rng:&mut R) -> Option<&[T]> {
+        if self.list.is_empty() { return None; }
+        Some(&self.list[rng.gen_range(0..self.list.len())])
+    }
+
+    /// draws min(k, len) distinct stored sets uniformly at random
+    pub fn sample_k<R:Rng+?Sized>(&self, rng:&mut R, k:usize) -> Vec<&[T]> {
+        rand::seq::index::sample(rng, self.list.len(), k.min(self.list.len())).into_iter()
+            .map(|i| self.list[i].as_slice())
+            .collect()
+    }
+
+    /// counts the elements of a new set in the supports
+    fn add_supports(supports:&mut BTreeMap<T,usize>, s:&[T]) where T:Copy {
+        for e in s { *supports.entry(*e).or_insert(0) += 1; }
+    }
+
+    /// removes the elements of a removed set from the supports
+    fn sub_supports(supports:&mut BTreeMap<T,usize>, s:&[T]) where T:Copy {
+        for e in s {
+            if let Some(n) = supports.get_mut(e) {
+                *n -= 1;
+                if *n == 0 { supports.remove(e); }
+            }
+        }
+    }
+
     /// returns true if a is a subset of b
     /// 
     /// It assumes the sequences to be sorted
-    pub fn is_subset(a:&[T], b:&[T]) -> bool {
-        if a.len() > b.len() { return false; }
-        let mut index_b:usize = 0;
-        for e in a {
-            while index_b < b.len() && &b[index_b] < e { index_b += 1 }
-            if index_b == b.len() || &b[index_b] > e { return false; }
+    pub fn is_subset(a:&[T], b:&[T]) -> bool { is_subset_of(a, b) }
+}
+
+impl<T:Copy+Ord+Into<usize>+From<usize>> ListSetStore<T> {
+    /// writes the list in a compact binary format (variable-length integers)
+    pub fn write_to<W:Write>(&self, w:&mut W) -> io::Result<()> {
+        write_header(w, BINARY_MAGIC, BINARY_VERSION)?;
+        write_varint(w, self.list.len())?;
+        for s in &self.list {
+            write_varint(w, s.len())?;
+            for e in s { write_varint(w, (*e).into())?; }
         }
-        true
+        Ok(())
+    }
+
+    /// reads a list written by write_to
+    pub fn read_from<R:Read>(r:&mut R) -> io::Result<Self> {
+        read_header(r, BINARY_MAGIC, BINARY_VERSION)?;
+        let nb_sets = read_varint(r)?;
+        let list = (0..nb_sets).map(|_| {
+            let len = read_varint(r)?;
+            (0..len).map(|_| read_varint(r).map(T::from)).collect::<io::Result<Vec<T>>>()
+        }).collect::<io::Result<Vec<Vec<T>>>>()?;
+        Ok(Self::from_list(list))
+    }
+}
+
+/// serialized list, whose sets are deduplicated when converted into a [ListSetStore]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "T:serde::Deserialize<'de>+Ord"))]
+struct SerializedList<T> {
+    /// stored sets
+    list:Vec<Vec<T>>,
+    /// number of stored sets containing each element (ignored: recomputed from the sets)
+    #[serde(rename = "supports")]
+    _supports:BTreeMap<T,usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<T:Copy+Ord> From<SerializedList<T>> for ListSetStore<T> {
+    fn from(serialized:SerializedList<T>) -> Self { Self::from_list(serialized.list) }
+}
+
+impl<'a,T:Ord> IntoIterator for &'a ListSetStore<T> {
+    type Item = &'a [T];
+    type IntoIter = std::vec::IntoIter<&'a [T]>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+
+
+/// CanonicalListSetStore
+///
+/// list of sets accepting unsorted inputs with repeated elements: sets (and queries) are
+/// sorted and deduplicated before reaching the underlying [ListSetStore]. Thus, permutations
+/// of a stored set are detected as duplicates, and sub-set/super-set checks are correct.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T:serde::Deserialize<'de>+Copy+Ord")))]
+pub struct CanonicalListSetStore<T> {
+    /// underlying list of sorted sets
+    store:ListSetStore<T>,
+}
+
+impl<T:Copy+Ord> CanonicalListSetStore<T> {
+    /// returns the sorted and deduplicated set
+    pub fn canonical(s:&[T]) -> Vec<T> {
+        let mut res = s.to_vec();
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+
+    /// returns the underlying list (of sorted sets)
+    pub fn inner(&self) -> &ListSetStore<T> { &self.store }
+
+    /// iterates over the stored (sorted) sets in lexicographic order
+    pub fn iter(&self) -> std::vec::IntoIter<&[T]> { self.store.iter() }
+}
+
+impl<T:Copy+Eq+Ord> SetStore<T> for CanonicalListSetStore<T> {
+    type SubsetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
+    type SupersetIterator<'a> = std::vec::IntoIter<Vec<T>> where Self:'a;
+
+    fn insert(&mut self, s:&[T]) -> bool { self.store.insert(&Self::canonical(s)) }
+
+    fn remove(&mut self, s:&[T]) -> bool { self.store.remove(&Self::canonical(s)) }
+
+    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator<'_> {
+        self.store.find_subsets(&Self::canonical(s))
+    }
+
+    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator<'_> {
+        self.store.find_supersets(&Self::canonical(s))
+    }
+
+    fn contains(&self, s:&[T]) -> bool { self.store.contains(&Self::canonical(s)) }
+
+    fn len(&self) -> usize { self.store.len() }
+
+    fn support(&self, e:T) -> usize { self.store.support(e) }
+
+    fn contains_subset_of(&self, s:&[T]) -> bool {
+        self.store.contains_subset_of(&Self::canonical(s))
+    }
+
+    fn contains_superset_of(&self, s:&[T]) -> bool {
+        self.store.contains_superset_of(&Self::canonical(s))
+    }
+
+    fn remove_supersets_of(&mut self, s:&[T]) -> usize {
+        self.store.remove_supersets_of(&Self::canonical(s))
+    }
+
+    fn remove_subsets_of(&mut self, s:&[T]) -> usize {
+        self.store.remove_subsets_of(&Self::canonical(s))
+    }
+
+    fn count_subsets(&self, s:&[T]) -> usize { self.store.count_subsets(&Self::canonical(s)) }
+
+    fn count_supersets(&self, s:&[T]) -> usize { self.store.count_supersets(&Self::canonical(s)) }
+
+    fn find_subsets_with_at_least(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        self.store.find_subsets(&Self::canonical(s)).filter(move |set| set.len() >= k)
+    }
+
+    fn find_supersets_with_at_most(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        let query = Self::canonical(s);
+        let max_len = query.len() + k;
+        self.store.find_supersets(&query).filter(move |set| set.len() <= max_len)
+    }
+
+    fn find_matching(&self, required:&[T], forbidden:&[T]) -> impl Iterator<Item=Vec<T>> {
+        self.store.find_supersets(&Self::canonical(required))
+            .filter(move |set| !set.iter().any(|e| forbidden.contains(e)))
+    }
+
+    fn find_max_overlap(&self, s:&[T]) -> Option<(Vec<T>,usize)> {
+        self.store.find_max_overlap(&Self::canonical(s))
+    }
+
+    fn find_best_jaccard(&self, s:&[T]) -> Option<(Vec<T>,f64)> {
+        self.store.find_best_jaccard(&Self::canonical(s))
     }
 }
 
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
 
+    use rand::{rngs::StdRng, SeedableRng};
+
     #[test]
     fn test_subset() {
         assert!(ListSetStore::<usize>::is_subset(&[], &[]));
@@ -83,4 +363,134 @@ pub mod test {
         assert!(!ListSetStore::<usize>::is_subset(&[1,3], &[1,2,4]));
         assert!(!ListSetStore::<usize>::is_subset(&[1,3], &[3,4,5]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_existence_and_count_queries() {
+        let mut store = ListSetStore::default();
+        store.insert(&[1,3]);
+        store.insert(&[2,5,7]);
+        assert!(store.contains_subset_of(&[1,2,3]));
+        assert!(!store.contains_subset_of(&[1,2,5]));
+        assert!(store.contains_superset_of(&[5]));
+        assert!(!store.contains_superset_of(&[3,5]));
+        assert_eq!(store.count_subsets(&[1,2,3,5,7]), 2);
+        assert_eq!(store.count_supersets(&[7]), 1);
+    }
+
+    #[test]
+    fn test_bulk_removals() {
+        let mut store = ListSetStore::default();
+        for s in [vec![1,3], vec![1,2,3], vec![2], vec![3,4]] { store.insert(&s); }
+        assert_eq!(store.remove_supersets_of(&[3]), 3);
+        assert_eq!(store.remove_subsets_of(&[1,2,3]), 1);
+        assert!(!store.contains_superset_of(&[]));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_supports() {
+        let mut store = ListSetStore::default();
+        for s in [vec![1,3], vec![1,2,3], vec![2], vec![3,5]] { store.insert(&s); }
+        assert_eq!(store.support(3), 3);
+        assert!(store.remove(&[1,3]));
+        assert_eq!(store.remove_supersets_of(&[5]), 1);
+        assert_eq!(store.supports().collect::<Vec<_>>(), vec![(&1,1), (&2,2), (&3,1)]);
+        assert_eq!(store.support(5), 0);
+    }
+
+    #[test]
+    fn test_from_sets() {
+        let store = ListSetStore::from_sets([vec![2,3], vec![1], vec![2,3]]);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.iter().collect::<Vec<_>>(), vec![&[1][..], &[2,3][..]]);
+        assert_eq!(store.support(3), 1);
+    }
+
+    #[test]
+    fn test_sampling() {
+        let store = ListSetStore::from_sets([vec![1], vec![2,3], vec![4]]);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(store.contains(store.sample(&mut rng).unwrap()));
+        let mut drawn = store.sample_k(&mut rng, 5);
+        drawn.sort();
+        assert_eq!(drawn, vec![&[1][..], &[2,3][..], &[4][..]]);
+        assert_eq!(ListSetStore::<usize>::default().sample(&mut rng), None);
+    }
+
+    #[test]
+    fn test_limited_queries() {
+        let store = ListSetStore::from_sets([vec![1], vec![1,2], vec![1,3], vec![2]]);
+        assert_eq!(store.find_supersets_limit(&[1], 2), vec![vec![1], vec![1,2]]);
+        assert_eq!(store.find_subsets_limit(&[1,2], 10).len(), 3);
+        let mut nb_visited = 0;
+        let res = store.for_each_superset(&[1], |set| {
+            nb_visited += 1;
+            if set.len() > 1 { ControlFlow::Break(set.to_vec()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!(res, ControlFlow::Break(vec![1,2]));
+        assert_eq!(nb_visited, 2);
+        assert_eq!(store.for_each_subset(&[3], |_| ControlFlow::Break(())), ControlFlow::Continue(()));
+    }
+
+    #[test]
+    fn test_binary_format() {
+        let mut store = ListSetStore::default();
+        for s in [vec![1,3], vec![], vec![200,70000]] { store.insert(&s); }
+        let mut buf:Vec<u8> = Vec::new();
+        store.write_to(&mut buf).unwrap();
+        let read:ListSetStore<usize> = ListSetStore::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.iter().collect::<Vec<_>>(), store.iter().collect::<Vec<_>>());
+        assert_eq!(read.support(1), 1);
+        assert!(ListSetStore::<usize>::read_from(&mut &buf[..buf.len()-1]).is_err());
+        // duplicated sets are ignored
+        let duplicated = ListSetStore { list:vec![vec![2usize], vec![1,3], vec![2]], supports:BTreeMap::new() };
+        buf.clear();
+        duplicated.write_to(&mut buf).unwrap();
+        let deduplicated:ListSetStore<usize> = ListSetStore::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(deduplicated.iter().collect::<Vec<_>>(), vec![&[1,3][..], &[2][..]]);
+        assert_eq!(deduplicated.support(2), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut store = ListSetStore::default();
+        for s in [vec![1u32,3], vec![2]] { store.insert(&s); }
+        let bytes = bincode::serialize(&store).unwrap();
+        let read:ListSetStore<u32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(read.iter().collect::<Vec<_>>(), store.iter().collect::<Vec<_>>());
+        // duplicated sets and wrong supports
+        let corrupted = (vec![vec![1u32,3], vec![2], vec![1,3]], BTreeMap::from([(1u32,5usize), (7,1)]));
+        let deduplicated:ListSetStore<u32> = bincode::deserialize(&bincode::serialize(&corrupted).unwrap()).unwrap();
+        assert_eq!(deduplicated.len(), 2);
+        assert_eq!(deduplicated.supports().collect::<Vec<_>>(), vec![(&1,1), (&2,1), (&3,1)]);
+    }
+
+    #[test]
+    fn test_canonical_store() {
+        let mut store = CanonicalListSetStore::default();
+        assert!(store.insert(&[3,1,3]));
+        assert!(!store.insert(&[1,3]));
+        assert!(store.insert(&[2,1]));
+        assert!(store.contains(&[3,1]));
+        assert_eq!(store.count_supersets(&[1,1]), 2);
+        assert_eq!(store.find_subsets(&[3,2,1]).collect::<Vec<_>>(), vec![vec![1,3], vec![1,2]]);
+        assert!(store.contains_superset_of(&[3,3]));
+        assert!(!store.insert_if_minimal(&[3,2,1], true));
+        assert!(store.remove(&[3,1]));
+        assert_eq!(store.iter().collect::<Vec<_>>(), vec![&[1,2][..]]);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_canonical_queries() {
+        let mut store = CanonicalListSetStore::default();
+        for s in [vec![1], vec![2,1]] { store.insert(&s); }
+        assert_eq!(store.find_supersets_with_at_most(&[1,1], 0).collect::<Vec<_>>(), vec![vec![1]]);
+        assert_eq!(store.find_subsets_with_at_least(&[2,1,2], 2).collect::<Vec<_>>(), vec![vec![1,2]]);
+        assert_eq!(store.find_matching(&[1,1], &[2]).collect::<Vec<_>>(), vec![vec![1]]);
+        assert_eq!(store.find_max_overlap(&[2,1,2]), Some((vec![1,2], 2)));
+        assert_eq!(store.find_best_jaccard(&[1,1]), Some((vec![1], 1.)));
+        assert_eq!(store.find_best_jaccard(&[2,2,1]), Some((vec![1,2], 1.)));
+    }
+}
@@ -1,10 +1,12 @@
+use std::ops::ControlFlow;
+
 /// Stores sets. Can perform insertions, remove, find subsets/supersets.
 pub trait SetStore<T:Copy+Eq> {
     /// Iterator trait over subsets
-    type SubsetIterator: Iterator<Item=Vec<T>>;
+    type SubsetIterator<'a>: Iterator<Item=Vec<T>> where Self:'a;
 
     /// Iterator trait over supersets
-    type SupersetIterator: Iterator<Item=Vec<T>>;
+    type SupersetIterator<'a>: Iterator<Item=Vec<T>> where Self:'a;
 
     /// Inserts a subset.
     /// 
@@ -17,21 +19,204 @@ pub trait SetStore<T:Copy+Eq> {
     fn remove(&mut self, s:&[T]) -> bool;
 
     /// enumerates all subsets
-    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator;
+    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator<'_>;
 
     /// enumerates all supersets
-    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator;
+    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator<'_>;
+
+    /// returns at most k stored subsets of s (the enumeration stops after k of them)
+    fn find_subsets_limit(&self, s:&[T], k:usize) -> Vec<Vec<T>> {
+        self.find_subsets(s).take(k).collect()
+    }
+
+    /// returns at most k stored supersets of s (the enumeration stops after k of them)
+    fn find_supersets_limit(&self, s:&[T], k:usize) -> Vec<Vec<T>> {
+        self.find_supersets(s).take(k).collect()
+    }
+
+    /// calls f on the stored subsets of s, until it returns ControlFlow::Break (then returns
+    /// this break)
+    fn for_each_subset<B,F:FnMut(&[T]) -> ControlFlow<B>>(&self, s:&[T], mut f:F) -> ControlFlow<B> {
+        self.find_subsets(s).try_for_each(|set| f(&set))
+    }
+
+    /// calls f on the stored supersets of s, until it returns ControlFlow::Break (then returns
+    /// this break)
+    fn for_each_superset<B,F:FnMut(&[T]) -> ControlFlow<B>>(&self, s:&[T], mut f:F) -> ControlFlow<B> {
+        self.find_supersets(s).try_for_each(|set| f(&set))
+    }
+
+    /// enumerates the subsets of s having at least k elements
+    fn find_subsets_with_at_least(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        self.find_subsets(s).filter(move |set| set.len() >= k)
+    }
+
+    /// enumerates the supersets of s having at most k elements not in s
+    fn find_supersets_with_at_most(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        let max_len = s.len() + k;
+        self.find_supersets(s).filter(move |set| set.len() <= max_len)
+    }
+
+    /// enumerates the stored sets containing every required element, and no forbidden
+    /// element (other elements are "don't care")
+    fn find_matching(&self, required:&[T], forbidden:&[T]) -> impl Iterator<Item=Vec<T>> {
+        self.find_supersets(required).filter(move |set| !set.iter().any(|e| forbidden.contains(e)))
+    }
 
     /// returns true if the set exists in the store
     fn contains(&self, s:&[T]) -> bool;
+
+    /// returns the number of stored sets (enumerates them by default, stores knowing their
+    /// size should override it)
+    fn len(&self) -> usize { self.find_supersets(&[]).count() }
+
+    /// returns true iff the store contains no set
+    fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// returns the number of stored sets containing e
+    fn support(&self, e:T) -> usize { self.count_supersets(&[e]) }
+
+    /// removes all the stored supersets of s. Returns the number of removed sets
+    fn remove_supersets_of(&mut self, s:&[T]) -> usize {
+        let supersets:Vec<Vec<T>> = self.find_supersets(s).collect();
+        supersets.iter().filter(|set| self.remove(set)).count()
+    }
+
+    /// removes all the stored subsets of s. Returns the number of removed sets
+    fn remove_subsets_of(&mut self, s:&[T]) -> usize {
+        let subsets:Vec<Vec<T>> = self.find_subsets(s).collect();
+        subsets.iter().filter(|set| self.remove(set)).count()
+    }
+
+    /// enumerates the minimal stored sets (not containing any other stored set)
+    fn iter_minimal(&self) -> impl Iterator<Item=Vec<T>> {
+        self.find_supersets(&[]).filter(|set| self.count_subsets(set) == 1)
+    }
+
+    /// enumerates the maximal stored sets (not contained in any other stored set)
+    fn iter_maximal(&self) -> impl Iterator<Item=Vec<T>> {
+        self.find_supersets(&[]).filter(|set| self.count_supersets(set) == 1)
+    }
+
+    /// adds the sets of other to the store. Returns the number of inserted sets
+    fn union_with(&mut self, other:&Self) -> usize where Self:Sized {
+        other.find_supersets(&[]).filter(|set| self.insert(set)).count()
+    }
+
+    /// keeps only the sets that are also stored in other. Returns the number of removed sets
+    fn intersect_with(&mut self, other:&Self) -> usize where Self:Sized {
+        let removed:Vec<Vec<T>> = self.find_supersets(&[]).filter(|set| !other.contains(set)).collect();
+        removed.iter().filter(|set| self.remove(set)).count()
+    }
+
+    /// removes the sets that are stored in other. Returns the number of removed sets
+    fn difference_with(&mut self, other:&Self) -> usize where Self:Sized {
+        let removed:Vec<Vec<T>> = self.find_supersets(&[]).filter(|set| other.contains(set)).collect();
+        removed.iter().filter(|set| self.remove(set)).count()
+    }
+
+    /// inserts s if the store contains no subset of it (s included). If evict is true, the
+    /// stored supersets of s (now redundant) are removed.
+    ///
+    /// Returns true if the set was inserted (maintains a family of minimal sets, e.g. nogoods)
+    fn insert_if_minimal(&mut self, s:&[T], evict:bool) -> bool {
+        if self.contains_subset_of(s) { return false; }
+        if evict { self.remove_supersets_of(s); }
+        self.insert(s)
+    }
+
+    /// inserts s if the store contains no superset of it (s included). If evict is true, the
+    /// stored subsets of s (now redundant) are removed.
+    ///
+    /// Returns true if the set was inserted (maintains a family of maximal sets)
+    fn insert_if_maximal(&mut self, s:&[T], evict:bool) -> bool {
+        if self.contains_superset_of(s) { return false; }
+        if evict { self.remove_subsets_of(s); }
+        self.insert(s)
+    }
+
+    /// counts the stored subsets of s
+    fn count_subsets(&self, s:&[T]) -> usize { self.find_subsets(s).count() }
+
+    /// counts the stored supersets of s
+    fn count_supersets(&self, s:&[T]) -> usize { self.find_supersets(s).count() }
+
+    /// returns true if the store contains a subset of s (stops at the first one found)
+    fn contains_subset_of(&self, s:&[T]) -> bool {
+        self.find_subsets(s).next().is_some()
+    }
+
+    /// returns true if the store contains a superset of s (stops at the first one found)
+    fn contains_superset_of(&self, s:&[T]) -> bool {
+        self.find_supersets(s).next().is_some()
+    }
+
+    /// returns a stored set having the largest intersection with s, and the size of this
+    /// intersection (None if the store is empty)
+    fn find_max_overlap(&self, s:&[T]) -> Option<(Vec<T>,usize)> {
+        let mut best:Option<(Vec<T>,usize)> = None;
+        for set in self.find_supersets(&[]) {
+            let overlap = set.iter().filter(|e| s.contains(e)).count();
+            if best.as_ref().is_none_or(|(_,b)| overlap > *b) { best = Some((set, overlap)); }
+        }
+        best
+    }
+
+    /// returns a stored set having the largest Jaccard similarity with s (size of their
+    /// intersection divided by the size of their union, 1 for two empty sets), and this
+    /// similarity (None if the store is empty)
+    fn find_best_jaccard(&self, s:&[T]) -> Option<(Vec<T>,f64)> {
+        let mut best:Option<(Vec<T>,f64)> = None;
+        for set in self.find_supersets(&[]) {
+            let overlap = set.iter().filter(|e| s.contains(e)).count();
+            let union = set.len() + s.len() - overlap;
+            let similarity = if union == 0 { 1. } else { overlap as f64 / union as f64 };
+            if best.as_ref().is_none_or(|(_,b)| similarity > *b) { best = Some((set, similarity)); }
+        }
+        best
+    }
 }
 
 /// stores sets as a list.
 /// 
-/// Linear complexities, but should be fast for a small number of sets.
+/// Linear complexities, but should be fast for a small number of sets. The canonical variant
+/// accepts unsorted sets with repeated elements.
 pub mod list;
 
 /// Stores sets in a trie.
 /// 
 /// Quick sub-set/super-set enumeration
-pub mod set_trie;
\ No newline at end of file
+pub mod set_trie;
+
+/// Stores sets of arbitrary (hashable) elements.
+///
+/// Interns the elements to dense identifiers stored in an underlying set store.
+pub mod interned;
+
+/// Stores sets in an inverted index.
+///
+/// Fast super-set queries (intersection of posting lists) on sparse sets.
+pub mod inverted_index;
+
+/// Stores nogoods (sets of (variable, value) literals).
+///
+/// Minimal nogoods stored in a set trie, quick check of blocked assignments.
+pub mod nogoods;
+
+/// Stores sets shared between threads.
+///
+/// Sets are sharded by their first element, each shard behind a read-write lock.
+pub mod concurrent;
+
+/// Algorithms on stored sets.
+///
+/// Greedy weighted set cover.
+pub mod cover;
+
+/// Interns sets.
+///
+/// Deduplicates sets and identifies them by small integers (O(1) equality).
+pub mod pool;
+
+/// compact binary encoding helpers (variable-length integers)
+pub(crate) mod binary;
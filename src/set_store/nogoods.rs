@@ -0,0 +1,109 @@
+use std::hash::Hash;
+
+use crate::set_store::SetStore;
+use crate::set_store::interned::InternedSetStore;
+use crate::set_store::set_trie::TrieSetStore;
+
+/// Nogood store.
+///
+/// A nogood is a set of (variable, value) literals that cannot be extended to a solution.
+/// Literals are interned to dense identifiers, and the nogoods are stored in an underlying
+/// set store (a set trie by default).
+/// - add_nogood: only keeps the minimal nogoods (a nogood implied by a stored one is
+///   rejected, and the stored nogoods implied by a new one are removed)
+/// - is_blocked: checks if a stored nogood is included in a (partial) assignment
+#[derive(Debug)]
+pub struct NogoodStore<V,D,S=TrieSetStore> {
+    /// interned nogoods
+    store:InternedSetStore<(V,D),S>,
+}
+
+impl<V,D,S:Default> Default for NogoodStore<V,D,S> {
+    fn default() -> Self { Self { store:InternedSetStore::default() } }
+}
+
+impl<V,D,S> NogoodStore<V,D,S> {
+    /// returns the number of distinct literals seen so far
+    pub fn nb_literals(&self) -> usize { self.store.nb_interned() }
+
+    /// returns the underlying interned store
+    pub fn inner(&self) -> &InternedSetStore<(V,D),S> { &self.store }
+}
+
+impl<V:Hash+Eq+Clone,D:Hash+Eq+Clone,S:SetStore<usize>> NogoodStore<V,D,S> {
+    /// adds a nogood, removing the stored nogoods it subsumes.
+    ///
+    /// Returns false if the nogood is subsumed by a stored one (thus not added)
+    pub fn add_nogood(&mut self, literals:&[(V,D)]) -> bool {
+        self.store.insert_if_minimal(literals, true)
+    }
+
+    /// removes a nogood. Returns true if it existed
+    pub fn remove_nogood(&mut self, literals:&[(V,D)]) -> bool {
+        self.store.remove(literals)
+    }
+
+    /// returns true if the (partial) assignment contains a stored nogood
+    pub fn is_blocked(&self, assignment:&[(V,D)]) -> bool {
+        self.store.contains_subset_of(assignment)
+    }
+
+    /// enumerates the stored nogoods contained in the (partial) assignment
+    pub fn blocking_nogoods(&self, assignment:&[(V,D)]) -> impl Iterator<Item=Vec<(V,D)>> + '_ {
+        self.store.find_subsets(assignment)
+    }
+
+    /// returns the number of stored nogoods containing the literal (e.g. to branch on the
+    /// literal appearing in the most nogoods)
+    pub fn support(&self, literal:&(V,D)) -> usize { self.store.support(literal) }
+
+    /// returns true if the nogood is stored
+    pub fn contains(&self, literals:&[(V,D)]) -> bool { self.store.contains(literals) }
+
+    /// returns the number of stored nogoods
+    pub fn len(&self) -> usize { self.store.len() }
+
+    /// returns true iff no nogood is stored
+    pub fn is_empty(&self) -> bool { self.store.is_empty() }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::set_store::list::ListSetStore;
+
+    #[test]
+    fn test_blocking() {
+        let mut nogoods:NogoodStore<usize,i32> = NogoodStore::default();
+        assert!(nogoods.add_nogood(&[(0,1), (2,3)]));
+        assert!(nogoods.add_nogood(&[(1,0)]));
+        assert!(!nogoods.add_nogood(&[(1,0), (0,1)])); // subsumed by {x1=0}
+        assert_eq!(nogoods.len(), 2);
+        assert!(nogoods.is_blocked(&[(2,3), (1,1), (0,1)]));
+        assert!(!nogoods.is_blocked(&[(2,3), (1,1), (0,2)]));
+        assert!(!nogoods.is_blocked(&[(5,5)]));
+        assert_eq!(nogoods.blocking_nogoods(&[(1,0), (0,1), (2,3)]).count(), 2);
+        assert_eq!(nogoods.support(&(2,3)), 1);
+        assert_eq!(nogoods.support(&(4,4)), 0);
+        // a smaller nogood removes the subsumed ones
+        assert!(nogoods.add_nogood(&[(2,3)]));
+        assert_eq!(nogoods.len(), 2);
+        assert!(!nogoods.contains(&[(0,1), (2,3)]));
+        assert!(nogoods.remove_nogood(&[(2,3)]));
+        assert!(!nogoods.is_blocked(&[(2,3)]));
+        assert_eq!(nogoods.nb_literals(), 3);
+    }
+
+    #[test]
+    fn test_list_backend() {
+        let mut nogoods:NogoodStore<&str,bool,ListSetStore<usize>> = NogoodStore::default();
+        nogoods.add_nogood(&[("a",true), ("b",false)]);
+        assert!(nogoods.is_blocked(&[("b",false), ("c",true), ("a",true)]));
+        assert!(!nogoods.is_blocked(&[("b",true), ("a",true)]));
+        assert!(nogoods.add_nogood(&[]));
+        assert!(nogoods.is_blocked(&[]));
+        assert_eq!(nogoods.len(), 1);
+    }
+}
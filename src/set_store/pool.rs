@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// Identifier of a set interned in a SetPool (dense, in order of first interning).
+/// Two identifiers of the same pool are equal iff their sets are equal.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct SetId(u32);
+
+impl SetId {
+    /// returns the index of the identifier (in 0..nb_sets)
+    pub fn index(&self) -> usize { self.0 as usize }
+}
+
+/// Set interning pool.
+///
+/// Deduplicates sets: each distinct set is stored once (sorted, without repetition) and
+/// identified by a SetId. Comparing or hashing two interned sets is O(1) through their
+/// identifiers. Sets are never un-interned.
+#[derive(Debug)]
+pub struct SetPool<T> {
+    /// canonical set of each identifier
+    sets:Vec<Rc<[T]>>,
+    /// identifier of each canonical set (shares the sets with the vector)
+    ids:HashMap<Rc<[T]>,SetId>,
+}
+
+impl<T> Default for SetPool<T> {
+    fn default() -> Self { Self { sets:Vec::new(), ids:HashMap::new() } }
+}
+
+impl<T:Ord+Hash+Clone> SetPool<T> {
+    /// creates an empty pool
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the number of distinct sets
+    pub fn len(&self) -> usize { self.sets.len() }
+
+    /// returns true iff no set was interned
+    pub fn is_empty(&self) -> bool { self.sets.is_empty() }
+
+    /// interns a set (in any order, possibly with repeated elements) and returns its identifier
+    pub fn intern(&mut self, s:&[T]) -> SetId {
+        let canonical = Self::canonical(s);
+        if let Some(id) = self.ids.get(canonical.as_slice()) { return *id; }
+        let id = SetId(u32::try_from(self.sets.len()).expect("too many interned sets"));
+        let shared:Rc<[T]> = canonical.into();
+        self.sets.push(shared.clone());
+        self.ids.insert(shared, id);
+        id
+    }
+
+    /// returns the identifier of a set if it was interned
+    pub fn find(&self, s:&[T]) -> Option<SetId> {
+        self.ids.get(Self::canonical(s).as_slice()).copied()
+    }
+
+    /// returns the (sorted) set of an identifier
+    pub fn get(&self, id:SetId) -> &[T] { &self.sets[id.index()] }
+
+    /// iterates over the identifiers and their sets (in order of first interning)
+    pub fn iter(&self) -> impl Iterator<Item=(SetId,&[T])> + '_ {
+        self.sets.iter().enumerate().map(|(i,s)| (SetId(i as u32), s.as_ref()))
+    }
+
+    /// returns the sorted set without repetition
+    fn canonical(s:&[T]) -> Vec<T> {
+        let mut res = s.to_vec();
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut pool = SetPool::new();
+        let a = pool.intern(&[3, 1, 2]);
+        let b = pool.intern(&[1, 2, 3, 3]);
+        let c = pool.intern(&[]);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.get(a), &[1, 2, 3]);
+        assert!(pool.get(c).is_empty());
+        assert_eq!(pool.find(&[2, 3, 1]), Some(a));
+        assert_eq!(pool.find(&[2]), None);
+        assert_eq!((a.index(), c.index()), (0, 1));
+        assert_eq!(pool.iter().map(|(id,s)| (id, s.len())).collect::<Vec<_>>(), vec![(a, 3), (c, 0)]);
+    }
+}
@@ -1,72 +1,236 @@
+use std::io::{self, Read, Write};
+
+use rand::Rng;
+
 use crate::set_store::SetStore;
+use crate::trail::Backtrackable;
+use crate::set_store::binary::{invalid_data, read_header, read_varint, write_header, write_varint};
 
-/// Trie node
-#[derive(Debug)]
-struct Node {
-    /// true iff the node contains a set
-    contains_set:bool,
-    /// list of children in the node
-    children:Vec<Option<Box<Node>>>,
-    /// number of "active" children
-    nb_children:usize,
-    /// offset of the children
-    offset:usize,
+/// magic bytes of the binary format of the trie
+const BINARY_MAGIC:&[u8;4] = b"DOST";
+
+/// version of the binary format of the trie
+const BINARY_VERSION:u8 = 1;
+
+/// minimum number of children of a dense node
+const DENSE_MIN_CHILDREN:usize = 8;
+
+/// Children of a trie node, indexed by their label.
+///
+/// Small or sparse fanouts use a sorted list of (label, child), dense fanouts use a vector of
+/// slots (one per label from an offset). The representation is selected adaptively after each
+/// modification: a sparse node becomes dense when its labels span at most twice its number
+/// of children, and a dense node becomes sparse when they span more than four times.
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Children {
+    /// sorted (label, child) pairs
+    Sparse(Vec<(usize,Box<Node>)>),
+    /// child of each label from offset (None if it does not exist)
+    Dense {
+        /// label of the first slot
+        offset:usize,
+        /// children
+        slots:Vec<Option<Box<Node>>>,
+        /// number of children
+        len:usize,
+    },
 }
 
-impl Node {
-    /// creates a new empty node
-    pub fn new() -> Self {
-        Node {
-            contains_set:false,
-            children:vec![],
-            nb_children:0,
-            offset: 0
+impl Children {
+    /// returns an empty (sparse) list of children
+    fn new() -> Self { Children::Sparse(Vec::new()) }
+
+    /// returns a list containing a single child
+    fn single(v:usize, child:Node) -> Self { Children::Sparse(vec![(v, Box::new(child))]) }
+
+    /// returns the number of children
+    fn len(&self) -> usize {
+        match self {
+            Children::Sparse(list) => list.len(),
+            Children::Dense { len, .. } => *len,
+        }
+    }
+
+    /// returns the child labelled v
+    fn get(&self, v:usize) -> Option<&Node> {
+        match self {
+            Children::Sparse(list) => list.binary_search_by_key(&v, |(l,_)| *l).ok().map(|i| &*list[i].1),
+            Children::Dense { offset, slots, .. } => {
+                if v < *offset { return None; }
+                slots.get(v - offset).and_then(|c| c.as_deref())
+            },
+        }
+    }
+
+    /// returns the (mutable) child labelled v
+    fn get_mut(&mut self, v:usize) -> Option<&mut Node> {
+        match self {
+            Children::Sparse(list) => match list.binary_search_by_key(&v, |(l,_)| *l) {
+                Ok(i) => Some(&mut *list[i].1),
+                Err(_) => None,
+            },
+            Children::Dense { offset, slots, .. } => {
+                if v < *offset { return None; }
+                slots.get_mut(v - *offset).and_then(|c| c.as_deref_mut())
+            },
+        }
+    }
+
+    /// adds a child labelled v (that does not exist yet)
+    fn insert(&mut self, v:usize, child:Node) {
+        match self {
+            Children::Sparse(list) => {
+                let pos = list.partition_point(|(l,_)| *l < v);
+                list.insert(pos, (v, Box::new(child)));
+            },
+            Children::Dense { offset, slots, len } => {
+                if v < *offset {
+                    slots.splice(0..0, (v..*offset).map(|_| None));
+                    *offset = v;
+                }
+                while *offset + slots.len() <= v { slots.push(None); }
+                slots[v - *offset] = Some(Box::new(child));
+                *len += 1;
+            },
         }
+        self.adapt();
     }
 
-    /// returns true iff the given value is out of bounds
-    pub fn is_out_of_bounds(&self, v:usize) -> bool {
-        v < self.offset || v >= self.offset+self.children.len()
+    /// removes the child labelled v
+    fn remove(&mut self, v:usize) {
+        self.retain(|l,_| l != v);
     }
 
-    /// returns the child of the node using 
-    pub fn child(&self, v:usize) -> &Option<Box<Node>> {
-        &self.children[v-self.offset]
+    /// keeps only the children satisfying the predicate
+    fn retain<F:FnMut(usize,&Node) -> bool>(&mut self, mut f:F) {
+        match self {
+            Children::Sparse(list) => list.retain(|(l,c)| f(*l, c)),
+            Children::Dense { offset, slots, len } => {
+                for (i,c) in slots.iter_mut().enumerate() {
+                    if c.as_ref().is_some_and(|child| !f(i + *offset, child)) {
+                        *c = None;
+                        *len -= 1;
+                    }
+                }
+            },
+        }
+        self.adapt();
     }
 
-    /// returns the (mutable) child of the node using 
-    pub fn child_mut(&mut self, v:usize) -> &mut Option<Box<Node>> {
-        &mut self.children[v-self.offset]
+    /// removes all the children
+    fn clear(&mut self) { *self = Children::new(); }
+
+    /// removes and returns the only child (None if there is not exactly one child)
+    fn take_single(&mut self) -> Option<(usize,Box<Node>)> {
+        if self.len() != 1 { return None; }
+        match std::mem::replace(self, Children::new()) {
+            Children::Sparse(mut list) => list.pop(),
+            Children::Dense { offset, slots, .. } => slots.into_iter().enumerate()
+                .find_map(|(i,c)| c.map(|child| (i + offset, child))),
+        }
     }
 
-    /// adds a child to the node if it does not exist.
-    /// returns it
-    pub fn add_child(&mut self, v:usize) -> &mut Node{
-        assert!(v >= self.offset);
-        // add new children
-        while self.offset+self.children.len() <= v {
-            self.children.push(None);
+    /// iterates over the (label, child) pairs by increasing label
+    fn iter(&self) -> ChildrenIter<'_> {
+        match self {
+            Children::Sparse(list) => ChildrenIter::Sparse(list.iter()),
+            Children::Dense { offset, slots, .. } => ChildrenIter::Dense(*offset, slots.iter().enumerate()),
         }
-        // update current node and index
-        let children_index = v-self.offset;
-        if self.children[children_index].is_none() {
-            self.nb_children += 1;
-            self.children[children_index] = Some(Box::new(Node {
-                contains_set:false,
-                children:vec![],
-                nb_children:0,
-                offset: v+1
-            }));
+    }
+
+    /// selects the representation according to the density of the labels
+    fn adapt(&mut self) {
+        match self {
+            Children::Sparse(list) => {
+                let (Some(min), Some(max)) = (list.first(), list.last()) else { return; };
+                let span = max.0 - min.0 + 1;
+                if list.len() >= DENSE_MIN_CHILDREN && span <= 2 * list.len() {
+                    let offset = min.0;
+                    let len = list.len();
+                    let mut slots:Vec<Option<Box<Node>>> = (0..span).map(|_| None).collect();
+                    for (l,c) in list.drain(..) { slots[l - offset] = Some(c); }
+                    *self = Children::Dense { offset, slots, len };
+                }
+            },
+            Children::Dense { offset, slots, len } => {
+                if *len < DENSE_MIN_CHILDREN / 2 || slots.len() > 4 * *len {
+                    let list = std::mem::take(slots).into_iter().enumerate()
+                        .filter_map(|(i,c)| c.map(|child| (i + *offset, child)))
+                        .collect();
+                    *self = Children::Sparse(list);
+                }
+            },
         }
-        &mut (*self.children[children_index].as_mut().unwrap())
     }
+}
 
-    /// removes a child indexed by v
-    fn remove_child(&mut self, v:usize) -> bool {
-        if self.is_out_of_bounds(v) { return false; }
-        if v-self.offset >= self.children.len() { return false; }
-        self.children[v-self.offset] = None;
-        true
+/// Iterator over the children of a node (by increasing label).
+#[derive(Debug)]
+enum ChildrenIter<'a> {
+    /// iterator over the sorted (label, child) pairs
+    Sparse(std::slice::Iter<'a,(usize,Box<Node>)>),
+    /// offset and iterator over the slots
+    Dense(usize, std::iter::Enumerate<std::slice::Iter<'a,Option<Box<Node>>>>),
+}
+
+impl<'a> Iterator for ChildrenIter<'a> {
+    type Item = (usize,&'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ChildrenIter::Sparse(it) => it.next().map(|(l,c)| (*l, &**c)),
+            ChildrenIter::Dense(offset, it) => it.find_map(|(i,c)| c.as_deref().map(|child| (i + *offset, child))),
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for ChildrenIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            ChildrenIter::Sparse(it) => it.next_back().map(|(l,c)| (*l, &**c)),
+            ChildrenIter::Dense(offset, it) => loop {
+                match it.next_back() {
+                    None => { return None; },
+                    Some((i, Some(child))) => { return Some((i + *offset, child)); },
+                    Some(_) => {},
+                }
+            },
+        }
+    }
+}
+
+/// Trie node
+///
+/// Chains of nodes having no set and a single child are compressed: the node reached by an
+/// edge labelled v represents the path v, run[0], ..., run[k-1].
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node {
+    /// true iff the node contains a set
+    contains_set:bool,
+    /// children of the node
+    children:Children,
+    /// number of sets in the subtree of the node
+    nb_sets:usize,
+    /// elements following the edge label (compressed chain of single-child nodes)
+    run:Vec<usize>,
+}
+
+impl Node {
+    /// creates a new empty node
+    pub fn new() -> Self {
+        Node {
+            contains_set:false,
+            children:Children::new(),
+            nb_sets:0,
+            run:vec![],
+        }
+    }
+
+    /// creates a leaf containing a set, the edge label being followed by the run
+    fn leaf(run:Vec<usize>) -> Self {
+        Node { contains_set:true, children:Children::new(), nb_sets:1, run }
     }
 
     /// adds a set to the node. Returns true if the set was successfully added
@@ -87,134 +251,894 @@ impl Node {
         self.contains_set = false;
     }
 
-    /// returns the number of children
-    fn nb_children(&self) -> usize { self.children.len() }
+    /// removes all the sets of the subtree. Returns the number of removed sets
+    fn clear(&mut self) -> usize {
+        let nb_removed = self.nb_sets;
+        self.remove_set();
+        self.children.clear();
+        self.nb_sets = 0;
+        nb_removed
+    }
+
+    /// splits the run of the node before its p-th element.
+    /// The node keeps the beginning of the run, and its content moves to a new child
+    fn split(&mut self, p:usize) {
+        let mut run = std::mem::take(&mut self.run);
+        let tail = run.split_off(p+1);
+        let v = run.pop().unwrap();
+        let lower = Node {
+            contains_set:self.contains_set,
+            children:std::mem::replace(&mut self.children, Children::new()),
+            nb_sets:self.nb_sets,
+            run:tail,
+        };
+        self.run = run;
+        self.contains_set = false;
+        self.children = Children::single(v, lower);
+    }
+
+    /// merges the node with its child while it has no set and a single child
+    fn compress(&mut self) {
+        while !self.contains_set && self.children.len() == 1 {
+            let (v, child) = self.children.take_single().unwrap();
+            let Node { contains_set, children, run, .. } = *child;
+            self.run.push(v);
+            self.run.extend(run);
+            self.children = children;
+            self.contains_set = contains_set;
+        }
+    }
+}
+
+/// matches the run as a subsequence of the (sorted) query, starting from index.
+/// Returns the index following the last matched element (None if an element is missing)
+fn match_subset_run<T:Copy+Into<usize>>(run:&[usize], query:&[T], index:usize) -> Option<usize> {
+    let mut j = index;
+    for r in run {
+        while j < query.len() && query[j].into() < *r { j += 1; }
+        if j == query.len() || query[j].into() != *r { return None; }
+        j += 1;
+    }
+    Some(j)
+}
+
+/// covers the (sorted) query elements from index with the run. Returns the index of the
+/// next element to cover (None if an element can no longer be covered)
+fn match_superset_run<T:Copy+Into<usize>>(run:&[usize], query:&[T], index:usize) -> Option<usize> {
+    let mut j = index;
+    for r in run {
+        match query.get(j).map(|q| (*q).into()) {
+            Some(q) if q == *r => { j += 1; },
+            Some(q) if q < *r => { return None; },
+            _ => {},
+        }
+    }
+    Some(j)
+}
+
+/// returns the remainder of e if it starts with the run
+fn strip_run<'a,T:Copy+Into<usize>>(run:&[usize], e:&'a [T]) -> Option<&'a [T]> {
+    if e.len() < run.len() || run.iter().zip(e).any(|(r,v)| *r != (*v).into()) { return None; }
+    Some(&e[run.len()..])
+}
+
+/// similarity (numerator, denominator) of a set of len elements, overlap of them being in the
+/// query of nb_query elements (intersection size, or Jaccard similarity)
+fn similarity(overlap:usize, len:usize, nb_query:usize, jaccard:bool) -> (usize,usize) {
+    if !jaccard { return (overlap, 1); }
+    let union = len + nb_query - overlap;
+    if union == 0 { (1,1) } else { (overlap, union) }
+}
+
+/// returns true if the similarity a is greater than b
+fn is_more_similar(a:(usize,usize), b:(usize,usize)) -> bool { a.0 * b.1 > b.0 * a.1 }
+
+/// moves the position in the (sorted) query after the element e. Returns the rest of the
+/// query, and true if e is in the query
+fn advance(query:&[usize], e:usize) -> (&[usize],bool) {
+    let p = query.partition_point(|q| *q < e);
+    let found = query.get(p) == Some(&e);
+    (&query[p+usize::from(found)..], found)
+}
+
+/// adds n to the support of each element (growing the supports if needed)
+fn add_supports<I:IntoIterator<Item=usize>>(supports:&mut Vec<usize>, elements:I, n:usize) {
+    for e in elements {
+        if e >= supports.len() { supports.resize(e+1, 0); }
+        supports[e] += n;
+    }
+}
+
+/// removes n from the support of each element
+fn sub_supports<I:IntoIterator<Item=usize>>(supports:&mut [usize], elements:I, n:usize) {
+    for e in elements { supports[e] -= n; }
+}
+
+/// Structural statistics of a [TrieSetStore].
+#[derive(Debug,Clone,PartialEq)]
+pub struct TrieStats {
+    /// number of stored sets
+    pub nb_sets:usize,
+    /// number of nodes (root included)
+    pub nb_nodes:usize,
+    /// number of nodes at each depth (the root being at depth 0)
+    pub depth_histogram:Vec<usize>,
+    /// average number of children of the internal nodes (0 if the root has no child)
+    pub avg_branching_factor:f64,
+    /// number of elements stored in compressed runs (nodes saved by path compression)
+    pub nb_compressed_elements:usize,
 }
 
 /// Set Trie.
 /// 
 /// Implements a trie data-structure to maintain sets, and perform efficient sub-set/super-set
-/// queries.
+/// queries. Chains of nodes without set and with a single child are compressed into a single
+/// node (Patricia-style), thus long runs of consecutive elements do not create a node each.
+/// Children are stored in a sorted list or in a vector of slots depending on their density,
+/// thus sparse alphabets with large gaps do not waste memory.
+///
+/// push_state and pop_state allow to undo the modifications made since a given point (e.g.
+/// nogoods learned in a subtree of a search, undone on backtrack). Modifications are only
+/// recorded when a state was pushed.
+///
+/// With the serde feature, the trie implements Serialize and Deserialize (deserialized tries
+/// are validated, and their supports recomputed). write_to and read_from use a more compact
+/// binary format. Saved states are not serialized.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "SerializedTrie"))]
 pub struct TrieSetStore {
     /// root node of the tree
-    root: Option<Box<Node>>
+    root: Option<Box<Node>>,
+    /// number of stored sets containing each element
+    supports:Vec<usize>,
+    /// modifications made since the first saved state (set, true if inserted or false if removed)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trail:Vec<(Vec<usize>,bool)>,
+    /// length of the trail at each saved state
+    #[cfg_attr(feature = "serde", serde(skip))]
+    levels:Vec<usize>,
 }
 
 impl<T:Copy+Eq+Into<usize>+From<usize>> SetStore<T> for TrieSetStore {
-    type SubsetIterator = std::vec::IntoIter<Vec<T>>;
-    type SupersetIterator = std::vec::IntoIter<Vec<T>>;
+    type SubsetIterator<'a> = TrieSubsetIterator<'a,T>;
+    type SupersetIterator<'a> = TrieSupersetIterator<'a,T>;
 
     fn insert(&mut self, s:&[T]) -> bool {
-        let mut current_node = &mut **self.root.as_mut().unwrap();
-        for v in s {
-            current_node = current_node.add_child((*v).into());
+        let res = Self::insert_rec(self.root.as_mut().unwrap(), s);
+        if res {
+            add_supports(&mut self.supports, s.iter().map(|e| (*e).into()), 1);
+            self.record(s, true);
         }
-        current_node.add_set()
+        res
     }
 
     fn remove(&mut self, s:&[T]) -> bool {
-        Self::remove_rec(self.root.as_mut().unwrap(), s, 0)
+        let res = Self::remove_rec(self.root.as_mut().unwrap(), s);
+        if res {
+            sub_supports(&mut self.supports, s.iter().map(|e| (*e).into()), 1);
+            self.record(s, false);
+        }
+        res
+    }
+
+    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator<'_> {
+        TrieSubsetIterator::new(self.root.as_ref().unwrap(), s, 0)
+    }
+
+    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator<'_> {
+        TrieSupersetIterator::new(self.root.as_ref().unwrap(), s, usize::MAX)
+    }
+
+    /// Branches containing a forbidden element are pruned.
+    fn find_matching(&self, required:&[T], forbidden:&[T]) -> impl Iterator<Item=Vec<T>> {
+        let mut res = TrieSupersetIterator::new(self.root.as_ref().unwrap(), required, usize::MAX);
+        res.forbidden = forbidden.iter().map(|e| (*e).into()).collect();
+        res.forbidden.sort_unstable();
+        res
+    }
+
+    /// Branches that cannot reach k elements with the rest of the query are pruned.
+    fn find_subsets_with_at_least(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        TrieSubsetIterator::new(self.root.as_ref().unwrap(), s, k)
+    }
+
+    /// Branches having more than k elements outside of the query are pruned.
+    fn find_supersets_with_at_most(&self, s:&[T], k:usize) -> impl Iterator<Item=Vec<T>> {
+        TrieSupersetIterator::new(self.root.as_ref().unwrap(), s, k)
+    }
+
+    fn contains_subset_of(&self, s:&[T]) -> bool {
+        Self::rec_exists_subset(self.root.as_ref().unwrap(), s)
+    }
+
+    fn contains_superset_of(&self, s:&[T]) -> bool {
+        Self::rec_exists_superset(self.root.as_ref().unwrap(), s)
+    }
+
+    fn remove_supersets_of(&mut self, s:&[T]) -> usize {
+        if self.is_recording() {
+            let removed = self.find_supersets(s).map(|set| set.into_iter().map(T::into).collect()).collect();
+            return self.remove_all(removed);
+        }
+        Self::rec_remove_supersets(self.root.as_mut().unwrap(), s, &mut self.supports)
     }
 
-    fn find_subsets(&self, s:&[T]) -> Self::SubsetIterator {
-        Self::rec_find_subsets(
-            self.root.as_ref().unwrap(),
-            s,
-            0,
-            Vec::new()
-        ).into_iter()
+    fn remove_subsets_of(&mut self, s:&[T]) -> usize {
+        if self.is_recording() {
+            let removed = self.find_subsets(s).map(|set| set.into_iter().map(T::into).collect()).collect();
+            return self.remove_all(removed);
+        }
+        Self::rec_remove_subsets(self.root.as_mut().unwrap(), s, &mut self.supports)
+    }
+
+    /// A set having a stored prefix is not minimal: such subtrees are pruned.
+    fn iter_minimal(&self) -> impl Iterator<Item=Vec<T>> {
+        let mut candidates = Vec::new();
+        Self::rec_collect_candidates(self.root.as_ref().unwrap(), true, &mut Vec::new(), &mut candidates);
+        candidates.into_iter().filter(|set| self.count_subsets(set) == 1)
+    }
+
+    /// A set having a stored extension (a set in its subtree) is not maximal: only the
+    /// leaves of the trie are checked.
+    fn iter_maximal(&self) -> impl Iterator<Item=Vec<T>> {
+        let mut candidates = Vec::new();
+        Self::rec_collect_candidates(self.root.as_ref().unwrap(), false, &mut Vec::new(), &mut candidates);
+        candidates.into_iter().filter(|set| self.count_supersets(set) == 1)
+    }
+
+    fn count_subsets(&self, s:&[T]) -> usize {
+        Self::rec_count_subsets(self.root.as_ref().unwrap(), s)
     }
 
-    fn find_supersets(&self, s:&[T]) -> Self::SupersetIterator {
-        Self::rec_find_supersets(
-            self.root.as_ref().unwrap(),
-            s,
-            0,
-            Vec::new()
-        ).into_iter()
+    fn count_supersets(&self, s:&[T]) -> usize {
+        Self::rec_count_supersets(self.root.as_ref().unwrap(), s)
     }
 
+    fn len(&self) -> usize { self.root.as_ref().unwrap().nb_sets }
+
+    /// Branch and bound: subtrees that cannot beat the best set found, even by containing the
+    /// rest of the query, are pruned.
+    fn find_max_overlap(&self, s:&[T]) -> Option<(Vec<T>,usize)> {
+        self.most_similar(s, false).map(|(set,(overlap,_))| (set, overlap))
+    }
+
+    /// Branch and bound: subtrees that cannot beat the best set found, even by containing the
+    /// rest of the query (and no other element), are pruned.
+    fn find_best_jaccard(&self, s:&[T]) -> Option<(Vec<T>,f64)> {
+        self.most_similar(s, true).map(|(set,(num,den))| (set, num as f64 / den as f64))
+    }
+
+    fn support(&self, e:T) -> usize { TrieSetStore::support(self, e.into()) }
+
+    fn union_with(&mut self, other:&Self) -> usize { TrieSetStore::union_with(self, other) }
+
+    fn intersect_with(&mut self, other:&Self) -> usize { TrieSetStore::intersect_with(self, other) }
+
+    fn difference_with(&mut self, other:&Self) -> usize { TrieSetStore::difference_with(self, other) }
+
     fn contains(&self, s:&[T]) -> bool {
-        let mut current_node = self.root.as_ref().unwrap();
-        for v in s {
-            if current_node.is_out_of_bounds((*v).into()) { return false; }
-            match current_node.child((*v).into()) {
+        let mut current_node:&Node = self.root.as_ref().unwrap();
+        let mut rest = s;
+        while let Some(v) = rest.first() {
+            let Some(n) = current_node.children.get((*v).into()) else { return false; };
+            match strip_run(&n.run, &rest[1..]) {
                 None => { return false; },
-                Some(n) => { current_node = n; },
+                Some(r) => { current_node = n; rest = r; },
             }
         }
         current_node.has_set()
     }
 }
 
+/// node to visit during a trie traversal
+#[derive(Debug)]
+struct Frame<'a,T> {
+    /// node to visit
+    node:&'a Node,
+    /// next position in the query
+    index:usize,
+    /// length of the path of the parent node
+    depth:usize,
+    /// label of the edge leading to the node (None for the root)
+    label:Option<T>,
+    /// number of elements of the path not in the query (super-set queries)
+    nb_extra:usize,
+}
+
+impl<'a,T:Copy+From<usize>> Frame<'a,T> {
+    /// updates the path to lead to the node of the frame
+    fn enter(&self, path:&mut Vec<T>) {
+        path.truncate(self.depth);
+        path.extend(self.label);
+        path.extend(self.node.run.iter().map(|v| T::from(*v)));
+    }
+}
+
+/// Lazy iterator over the subsets of a query stored in a [TrieSetStore].
+///
+/// Walks the trie with an explicit stack, and yields one set at a time.
+#[derive(Debug)]
+pub struct TrieSubsetIterator<'a,T> {
+    /// query set
+    query:Vec<T>,
+    /// nodes to visit
+    stack:Vec<Frame<'a,T>>,
+    /// path from the root to the current node
+    path:Vec<T>,
+    /// minimum number of elements of the returned sets
+    min_len:usize,
+}
+
+impl<'a,T:Copy> TrieSubsetIterator<'a,T> {
+    /// creates an iterator over the subsets of the query having at least min_len elements
+    fn new(root:&'a Node, query:&[T], min_len:usize) -> Self {
+        Self {
+            query:query.to_vec(),
+            stack:vec![Frame { node:root, index:0, depth:0, label:None, nb_extra:0 }],
+            path:Vec::new(),
+            min_len,
+        }
+    }
+}
+
+impl<'a,T:Copy+Eq+Into<usize>+From<usize>> Iterator for TrieSubsetIterator<'a,T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        while let Some(frame) = self.stack.pop() {
+            frame.enter(&mut self.path);
+            let node = frame.node;
+            // children are pushed in reverse order to be visited in increasing order
+            for i in (frame.index..self.query.len()).rev() {
+                let e = self.query[i];
+                let v = e.into();
+                if let Some(child) = node.children.get(v) {
+                    // the run of the child must be contained in the rest of the query
+                    let Some(index) = match_subset_run(&child.run, &self.query, i+1) else { continue; };
+                    // the sets below the child have at most this number of elements
+                    let max_len = self.path.len() + 1 + child.run.len() + self.query.len() - index;
+                    if max_len < self.min_len { continue; }
+                    // the label is the stored element (not its position in the query)
+                    self.stack.push(Frame {
+                        node:child, index, depth:self.path.len(), label:Some(e), nb_extra:0
+                    });
+                }
+            }
+            if node.has_set() && self.path.len() >= self.min_len { return Some(self.path.clone()); }
+        }
+        None
+    }
+}
+
+/// Lazy iterator over the supersets of a query stored in a [TrieSetStore].
+///
+/// Walks the trie with an explicit stack, and yields one set at a time.
+#[derive(Debug)]
+pub struct TrieSupersetIterator<'a,T> {
+    /// query set
+    query:Vec<T>,
+    /// nodes to visit
+    stack:Vec<Frame<'a,T>>,
+    /// path from the root to the current node
+    path:Vec<T>,
+    /// maximum number of elements of the returned sets not in the query
+    max_extra:usize,
+    /// sorted elements that the returned sets must not contain
+    forbidden:Vec<usize>,
+}
+
+impl<'a,T:Copy> TrieSupersetIterator<'a,T> {
+    /// creates an iterator over the supersets of the query having at most max_extra
+    /// elements not in the query
+    fn new(root:&'a Node, query:&[T], max_extra:usize) -> Self {
+        Self {
+            query:query.to_vec(),
+            stack:vec![Frame { node:root, index:0, depth:0, label:None, nb_extra:0 }],
+            path:Vec::new(),
+            max_extra,
+            forbidden:Vec::new(),
+        }
+    }
+}
+
+impl<'a,T:Copy+Eq+Into<usize>+From<usize>> Iterator for TrieSupersetIterator<'a,T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        while let Some(frame) = self.stack.pop() {
+            frame.enter(&mut self.path);
+            let node = frame.node;
+            // all the query elements are covered: every set below is a superset.
+            // Otherwise, only children lower or equal than the next query element are explored
+            let (last, covered) = match self.query.get(frame.index) {
+                None => (usize::MAX, true),
+                Some(v) => ((*v).into(), false),
+            };
+            // children are pushed in reverse order to be visited in increasing order
+            for (v,child) in node.children.iter().rev() {
+                if v > last { continue; }
+                let next_index = if v == last { frame.index+1 } else { frame.index };
+                // the run of the child must not skip a query element
+                let Some(index) = match_superset_run(&child.run, &self.query, next_index) else { continue; };
+                // elements of the label and the run that are not in the query
+                let nb_extra = frame.nb_extra + usize::from(v != last) + child.run.len() - (index - next_index);
+                if nb_extra > self.max_extra { continue; }
+                if !self.forbidden.is_empty() && std::iter::once(&v).chain(&child.run)
+                    .any(|e| self.forbidden.binary_search(e).is_ok()) { continue; }
+                self.stack.push(Frame {
+                    node:child, index, depth:self.path.len(), label:Some(v.into()), nb_extra
+                });
+            }
+            if covered && node.has_set() { return Some(self.path.clone()); }
+        }
+        None
+    }
+}
+
+impl<'a> IntoIterator for &'a TrieSetStore {
+    type Item = Vec<usize>;
+    type IntoIter = TrieSupersetIterator<'a,usize>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
 impl Default for TrieSetStore {
     fn default() -> Self {
-        Self { root: Some(Box::new(Node::new())) }
+        Self { root: Some(Box::new(Node::new())), supports:Vec::new(), trail:Vec::new(), levels:Vec::new() }
     }
 }
 
+/// serialized trie, validated before being converted into a [TrieSetStore]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerializedTrie {
+    /// root node of the tree
+    root: Option<Box<Node>>,
+    /// number of stored sets containing each element (ignored: recomputed from the tree)
+    #[serde(rename = "supports")]
+    _supports:Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerializedTrie> for TrieSetStore {
+    type Error = &'static str;
+
+    /// rejects tries without root, whose elements are not strictly increasing along the paths
+    /// (runs and labels), or whose nodes do not count their sets. Recomputes the number of
+    /// children of the dense nodes
+    fn try_from(serialized:SerializedTrie) -> Result<Self, Self::Error> {
+        /// checks the subtree of the node, its elements being at least min
+        fn check_node(node:&mut Node, min:usize) -> Result<(), &'static str> {
+            let mut next = min;
+            for r in &node.run {
+                if *r < next { return Err("unsorted run"); }
+                next = r.checked_add(1).ok_or("element overflow")?;
+            }
+            match &mut node.children {
+                Children::Sparse(list) => {
+                    if list.windows(2).any(|w| w[0].0 >= w[1].0) { return Err("unsorted labels"); }
+                },
+                Children::Dense { offset, slots, len } => {
+                    if offset.checked_add(slots.len()).is_none() { return Err("element overflow"); }
+                    *len = slots.iter().filter(|c| c.is_some()).count();
+                },
+            }
+            if node.children.iter().next().is_some_and(|(v,_)| v < next) { return Err("unsorted labels"); }
+            node.children.adapt();
+            let children:Vec<(usize,&mut Node)> = match &mut node.children {
+                Children::Sparse(list) => list.iter_mut().map(|(l,c)| (*l, &mut **c)).collect(),
+                Children::Dense { offset, slots, .. } => slots.iter_mut().enumerate()
+                    .filter_map(|(i,c)| c.as_deref_mut().map(|child| (i + *offset, child)))
+                    .collect(),
+            };
+            let mut nb_sets = usize::from(node.contains_set);
+            for (v,child) in children {
+                if child.nb_sets == 0 { return Err("empty subtree"); }
+                check_node(child, v.checked_add(1).ok_or("element overflow")?)?;
+                nb_sets += child.nb_sets;
+            }
+            if nb_sets == node.nb_sets { Ok(()) } else { Err("inconsistent number of sets") }
+        }
+        let mut root = serialized.root.ok_or("missing root")?;
+        if !root.run.is_empty() { return Err("the root cannot have a run"); }
+        check_node(&mut root, 0)?;
+        let mut supports = Vec::new();
+        Self::rec_supports(&root, &mut supports, true);
+        Ok(Self { root:Some(root), supports, ..Self::default() })
+    }
+}
+
+
+/// saves and restores the stored sets (see push_state and pop_state)
+impl Backtrackable for TrieSetStore {
+    fn save_level(&mut self) { self.push_state(); }
+
+    fn restore_level(&mut self) -> bool { self.pop_state() }
+
+    fn level(&self) -> usize { self.nb_states() }
+}
 
 impl TrieSetStore {
 
-    fn rec_find_subsets<T:Copy+Eq+Into<usize>+From<usize>>(node:&Node, e:&[T], index:usize, selected:Vec<T>)
-    -> Vec<Vec<T>> {
-        let mut res = Vec::new();
-        if node.contains_set { res.push(selected.clone()); }
-        for i in index..e.len() {
-            let v = e[i];
-            if node.is_out_of_bounds(v.into()) { continue; }
-            match node.child(v.into()) {
-                None => { continue; }
-                Some(child) => {
-                    let mut new_selected = selected.clone();
-                    new_selected.push(i.into());
-                    let mut rec_res = Self::rec_find_subsets(
-                        child, e, i, new_selected.clone()
-                    );
-                    res.append(&mut rec_res);
-                }
+    /// builds a trie from sets (sorted sets of elements, duplicates are ignored).
+    ///
+    /// Sorts the sets lexicographically, and builds the trie top-down: each node is created
+    /// once (with its compressed run), instead of a descent per inserted set.
+    pub fn from_sets<T,S,I>(sets:I) -> Self
+    where T:Copy+Into<usize>, S:AsRef<[T]>, I:IntoIterator<Item=S> {
+        let mut sorted:Vec<Vec<usize>> = sets.into_iter()
+            .map(|s| s.as_ref().iter().map(|e| (*e).into()).collect())
+            .collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let mut supports = Vec::new();
+        for set in &sorted { add_supports(&mut supports, set.iter().copied(), 1); }
+        let root = Self::build_rec(&sorted, 0);
+        Self { root:Some(Box::new(root)), supports, ..Self::default() }
+    }
+
+    /// builds the node of the sorted and distinct sets sharing their first depth elements
+    fn build_rec(sets:&[Vec<usize>], depth:usize) -> Node {
+        let mut node = Node::new();
+        node.nb_sets = sets.len();
+        // the set ending at this node (if any) is the first one
+        let mut rest = sets;
+        if rest.first().is_some_and(|set| set.len() == depth) {
+            node.add_set();
+            rest = &rest[1..];
+        }
+        while let Some(set) = rest.first() {
+            let v = set[depth];
+            let nb_in_child = rest.partition_point(|other| other[depth] == v);
+            let mut child = Self::build_rec(&rest[..nb_in_child], depth+1);
+            child.compress();
+            node.children.insert(v, child);
+            rest = &rest[nb_in_child..];
+        }
+        node
+    }
+
+    /// iterates over the stored sets in lexicographic order
+    pub fn iter<T:Copy+Eq+Into<usize>+From<usize>>(&self) -> TrieSupersetIterator<'_,T> {
+        self.find_supersets(&[])
+    }
+
+    /// returns the first stored set (in lexicographic order) maximizing the similarity with s
+    /// (intersection size or Jaccard similarity), and this similarity
+    fn most_similar<T:Copy+Into<usize>+From<usize>>(&self, s:&[T], jaccard:bool) -> Option<(Vec<T>,(usize,usize))> {
+        let query:Vec<usize> = s.iter().map(|e| (*e).into()).collect();
+        let mut best = None;
+        Self::rec_most_similar(self.root.as_ref().unwrap(), &query, query.len(), &mut Vec::new(), 0, jaccard, &mut best);
+        best.map(|(set,score)| (set.into_iter().map(T::from).collect(), score))
+    }
+
+    /// explores the subtree of the node (path: its path, overlap: number of elements of the
+    /// path in the query, query: elements of the query greater than the path)
+    fn rec_most_similar(
+        node:&Node, query:&[usize], nb_query:usize, path:&mut Vec<usize>, overlap:usize, jaccard:bool,
+        best:&mut Option<(Vec<usize>,(usize,usize))>
+    ) {
+        let bound = similarity(overlap + query.len(), path.len() + query.len(), nb_query, jaccard);
+        if best.as_ref().is_some_and(|(_,b)| !is_more_similar(bound, *b)) { return; }
+        if node.has_set() {
+            let score = similarity(overlap, path.len(), nb_query, jaccard);
+            if best.as_ref().is_none_or(|(_,b)| is_more_similar(score, *b)) { *best = Some((path.clone(), score)); }
+        }
+        for (v,child) in node.children.iter() {
+            let depth = path.len();
+            let (mut rest, found) = advance(query, v);
+            let mut child_overlap = overlap + usize::from(found);
+            path.push(v);
+            for r in &child.run {
+                let (next, in_query) = advance(rest, *r);
+                rest = next;
+                child_overlap += usize::from(in_query);
+                path.push(*r);
             }
+            Self::rec_most_similar(child, rest, nb_query, path, child_overlap, jaccard, best);
+            path.truncate(depth);
         }
-        res
     }
 
-    fn rec_find_supersets<T:Copy+Eq+Into<usize>+From<usize>>(node:&Node, e:&[T], index:usize, selected:Vec<T>)
-    -> Vec<Vec<T>> {
-        let mut res = Vec::new();
-        if index >= e.len() { // find all sets below
-            if node.contains_set { res.push(selected.clone()); }
-            for (i,c) in node.children.iter().enumerate()
-            .filter(|(_,c)| c.is_some()) {
-                let mut new_selected = selected.clone();
-                new_selected.push(T::from(i+node.offset));
-                let mut rec_res = Self::rec_find_supersets(c.as_ref().unwrap(),e,index,new_selected);
-                res.append(&mut rec_res);
+    /// returns the i-th stored set in lexicographic order (None if i >= len).
+    ///
+    /// Descends the trie once, skipping subtrees using their number of sets.
+    pub fn nth<T:From<usize>>(&self, i:usize) -> Option<Vec<T>> {
+        let mut node:&Node = self.root.as_ref().unwrap();
+        if i >= node.nb_sets { return None; }
+        let mut rank = i;
+        let mut path:Vec<usize> = Vec::new();
+        loop {
+            if node.has_set() {
+                if rank == 0 { return Some(path.into_iter().map(T::from).collect()); }
+                rank -= 1;
             }
-        } else { // iterate over all values less or equal than e[index]
-            let v = e[index];
-            for i in 0..v.into()+1 { // search for all elements below the value v (included)
-                if node.is_out_of_bounds(i) { continue; }
-                match node.child(i) {
-                    None => { continue; },
-                    Some(child) => {
-                        let mut new_selected = selected.clone();
-                        new_selected.push(i.into());
-                        let mut rec_res = if i == v.into() {
-                            Self::rec_find_supersets(
-                                child, e, index+1, new_selected.clone()
-                            )
-                        } else {
-                            Self::rec_find_supersets(
-                                child, e, index, new_selected.clone()
-                            )
-                        };
-                        res.append(&mut rec_res);
-                    }
-                }
+            let (v,child) = node.children.iter()
+                .find(|(_,c)| if rank < c.nb_sets { true } else { rank -= c.nb_sets; false })
+                .unwrap();
+            path.push(v);
+            path.extend(&child.run);
+            node = child;
+        }
+    }
+
+    /// draws a stored set uniformly at random (None if the trie is empty)
+    pub fn sample<T:From<usize>,R:Rng+?Sized>(&self, rng:&mut R) -> Option<Vec<T>> {
+        let len = self.root.as_ref().unwrap().nb_sets;
+        if len == 0 { return None; }
+        self.nth(rng.gen_range(0..len))
+    }
+
+    /// draws min(k, len) distinct stored sets uniformly at random
+    pub fn sample_k<T:From<usize>,R:Rng+?Sized>(&self, rng:&mut R, k:usize) -> Vec<Vec<T>> {
+        let len = self.root.as_ref().unwrap().nb_sets;
+        rand::seq::index::sample(rng, len, k.min(len)).into_iter()
+            .filter_map(|i| self.nth(i))
+            .collect()
+    }
+
+    /// adds the sets of other to the trie. Returns the number of inserted sets.
+    ///
+    /// Merges the tries in a single traversal (shared prefixes are walked once, and the
+    /// subtrees missing in the trie are cloned).
+    pub fn union_with(&mut self, other:&Self) -> usize {
+        if self.is_recording() {
+            return other.iter::<usize>().filter(|set| SetStore::insert(self, set)).count();
+        }
+        Self::rec_union(self.root.as_mut().unwrap(), other.root.as_ref().unwrap(), &[], &mut self.supports)
+    }
+
+    /// keeps only the sets that are also stored in other. Returns the number of removed sets.
+    ///
+    /// Walks both tries simultaneously, subtrees missing in other are removed at once.
+    pub fn intersect_with(&mut self, other:&Self) -> usize {
+        if self.is_recording() {
+            let removed = self.iter::<usize>().filter(|set| !SetStore::contains(other, set)).collect();
+            return self.remove_all(removed);
+        }
+        let b = Some((other.root.as_ref().unwrap().as_ref(), &[][..]));
+        Self::rec_filter(self.root.as_mut().unwrap(), b, true, &mut self.supports)
+    }
+
+    /// removes the sets that are stored in other. Returns the number of removed sets.
+    ///
+    /// Walks both tries simultaneously, subtrees missing in other are skipped.
+    pub fn difference_with(&mut self, other:&Self) -> usize {
+        if self.is_recording() {
+            let removed = self.iter::<usize>().filter(|set| SetStore::contains(other, set)).collect();
+            return self.remove_all(removed);
+        }
+        let b = Some((other.root.as_ref().unwrap().as_ref(), &[][..]));
+        Self::rec_filter(self.root.as_mut().unwrap(), b, false, &mut self.supports)
+    }
+
+    /// saves the current state: the sets inserted or removed from now on are restored by
+    /// pop_state
+    pub fn push_state(&mut self) { self.levels.push(self.trail.len()); }
+
+    /// restores the last saved state (undoing the insertions and removals made since then).
+    ///
+    /// Returns false if no state was saved
+    pub fn pop_state(&mut self) -> bool {
+        let Some(level) = self.levels.pop() else { return false; };
+        let root = self.root.as_mut().unwrap();
+        for (set,inserted) in self.trail.split_off(level).into_iter().rev() {
+            if inserted {
+                Self::remove_rec(root, &set);
+                sub_supports(&mut self.supports, set, 1);
+            } else {
+                Self::insert_rec(root, &set);
+                add_supports(&mut self.supports, set, 1);
             }
         }
-        res
+        true
+    }
+
+    /// returns the number of saved states
+    pub fn nb_states(&self) -> usize { self.levels.len() }
+
+    /// returns true if the modifications are recorded (some state was saved)
+    fn is_recording(&self) -> bool { !self.levels.is_empty() }
+
+    /// records a modification of the trie if needed
+    fn record<T:Copy+Into<usize>>(&mut self, s:&[T], inserted:bool) {
+        if self.is_recording() { self.trail.push((s.iter().map(|e| (*e).into()).collect(), inserted)); }
+    }
+
+    /// removes the given sets one by one (thus recording them). Returns the number of removed sets
+    fn remove_all(&mut self, sets:Vec<Vec<usize>>) -> usize {
+        sets.iter().filter(|set| SetStore::remove(self, set)).count()
+    }
+
+    /// writes the trie in a compact binary format (pre-order traversal of the nodes, elements
+    /// being delta-encoded variable-length integers)
+    pub fn write_to<W:Write>(&self, w:&mut W) -> io::Result<()> {
+        write_header(w, BINARY_MAGIC, BINARY_VERSION)?;
+        Self::write_node(w, self.root.as_ref().unwrap(), 0)
+    }
+
+    /// reads a trie written by write_to
+    pub fn read_from<R:Read>(r:&mut R) -> io::Result<Self> {
+        read_header(r, BINARY_MAGIC, BINARY_VERSION)?;
+        let root = Self::read_node(r, 0)?;
+        if !root.run.is_empty() { return Err(invalid_data("the root cannot have a run")); }
+        let mut supports = Vec::new();
+        Self::rec_supports(&root, &mut supports, true);
+        Ok(Self { root:Some(Box::new(root)), supports, ..Self::default() })
+    }
+
+    /// writes the node: its set flag, its run and its children. Elements are written as
+    /// their difference with base (the minimum value of the next element)
+    fn write_node<W:Write>(w:&mut W, node:&Node, base:usize) -> io::Result<()> {
+        /// returns v - base (sets must be sorted)
+        fn delta(v:usize, base:usize) -> io::Result<usize> {
+            v.checked_sub(base).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "sets must be sorted"))
+        }
+        write_varint(w, node.run.len() << 1 | usize::from(node.has_set()))?;
+        let mut next = base;
+        for r in &node.run {
+            write_varint(w, delta(*r, next)?)?;
+            next = r+1;
+        }
+        write_varint(w, node.children.len())?;
+        for (v,child) in node.children.iter() {
+            write_varint(w, delta(v, next)?)?;
+            next = v+1;
+            Self::write_node(w, child, next)?;
+        }
+        Ok(())
+    }
+
+    /// reads a node written by write_node
+    fn read_node<R:Read>(r:&mut R, base:usize) -> io::Result<Node> {
+        /// returns base + delta + 1 (the base of the next element)
+        fn next_base(base:usize, delta:usize) -> io::Result<usize> {
+            base.checked_add(delta).and_then(|v| v.checked_add(1)).ok_or_else(|| invalid_data("element overflow"))
+        }
+        let header = read_varint(r)?;
+        let mut node = Node::new();
+        if header & 1 == 1 {
+            node.add_set();
+            node.nb_sets = 1;
+        }
+        let mut next = base;
+        for _ in 0..header >> 1 {
+            next = next_base(next, read_varint(r)?)?;
+            node.run.push(next-1);
+        }
+        let nb_children = read_varint(r)?;
+        for _ in 0..nb_children {
+            next = next_base(next, read_varint(r)?)?;
+            let child = Self::read_node(r, next)?;
+            if child.nb_sets == 0 { return Err(invalid_data("empty subtree")); }
+            node.nb_sets += child.nb_sets;
+            node.children.insert(next-1, child);
+        }
+        Ok(node)
+    }
+
+    /// returns the number of nodes of the trie (root included)
+    pub fn node_count(&self) -> usize {
+        Self::rec_node_count(self.root.as_ref().unwrap())
+    }
+
+    /// returns the number of stored sets containing the element (maintained incrementally)
+    pub fn support(&self, e:usize) -> usize { self.supports.get(e).copied().unwrap_or(0) }
+
+    /// iterates over the elements of the stored sets (in increasing order) with their support
+    pub fn supports(&self) -> impl Iterator<Item=(usize,usize)> + '_ {
+        self.supports.iter().copied().enumerate().filter(|(_,n)| *n > 0)
+    }
+
+    /// adds (or removes) the elements of the sets of the subtree to the supports (the path
+    /// of the node excluded)
+    fn rec_supports(node:&Node, supports:&mut Vec<usize>, add:bool) {
+        for (v,child) in node.children.iter() {
+            let elements = std::iter::once(v).chain(child.run.iter().copied());
+            if add {
+                add_supports(supports, elements, child.nb_sets);
+            } else {
+                sub_supports(supports, elements, child.nb_sets);
+            }
+            Self::rec_supports(child, supports, add);
+        }
+    }
+
+    /// counts the nodes of the subtree
+    fn rec_node_count(node:&Node) -> usize {
+        1 + node.children.iter().map(|(_,c)| Self::rec_node_count(c)).sum::<usize>()
+    }
+
+    /// returns the structural statistics of the trie (traverses the whole trie)
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats {
+            nb_sets:self.root.as_ref().unwrap().nb_sets,
+            nb_nodes:0,
+            depth_histogram:Vec::new(),
+            avg_branching_factor:0.,
+            nb_compressed_elements:0,
+        };
+        let mut nb_internal_nodes = 0;
+        let mut stack:Vec<(&Node,usize)> = vec![(self.root.as_ref().unwrap(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            stats.nb_nodes += 1;
+            stats.nb_compressed_elements += node.run.len();
+            if stats.depth_histogram.len() <= depth { stats.depth_histogram.push(0); }
+            stats.depth_histogram[depth] += 1;
+            if node.children.len() > 0 { nb_internal_nodes += 1; }
+            stack.extend(node.children.iter().map(|(_,c)| (c, depth+1)));
+        }
+        if nb_internal_nodes > 0 {
+            // every node but the root is the child of an internal node
+            stats.avg_branching_factor = (stats.nb_nodes - 1) as f64 / nb_internal_nodes as f64;
+        }
+        stats
+    }
+
+    /// returns true iff a set below the node is a subset of e
+    fn rec_exists_subset<T:Copy+Into<usize>>(node:&Node, e:&[T]) -> bool {
+        if node.has_set() { return true; }
+        e.iter().enumerate().any(|(i,v)| {
+            let value = (*v).into();
+            node.children.get(value).is_some_and(|child| {
+                match_subset_run(&child.run, e, i+1)
+                    .is_some_and(|j| Self::rec_exists_subset(child, &e[j..]))
+            })
+        })
+    }
+
+    /// returns true iff a set below the node is a superset of e
+    fn rec_exists_superset<T:Copy+Into<usize>>(node:&Node, e:&[T]) -> bool {
+        let Some(v) = e.first() else { return node.nb_sets > 0; };
+        let first = (*v).into();
+        node.children.iter()
+            .take_while(|(l,_)| *l <= first)
+            .any(|(l,child)| {
+                let rest = if l == first { &e[1..] } else { e };
+                match_superset_run(&child.run, rest, 0)
+                    .is_some_and(|j| Self::rec_exists_superset(child, &rest[j..]))
+            })
+    }
+
+    /// collects the sets below the node that may be minimal (no stored prefix) if minimal is
+    /// true, or that may be maximal (no set in their subtree) otherwise
+    fn rec_collect_candidates<T:From<usize>>(node:&Node, minimal:bool, path:&mut Vec<usize>, res:&mut Vec<Vec<T>>) {
+        if node.has_set() && (minimal || node.nb_sets == 1) {
+            res.push(path.iter().map(|v| T::from(*v)).collect());
+        }
+        if node.has_set() && minimal { return; }
+        for (v,child) in node.children.iter() {
+            let depth = path.len();
+            path.push(v);
+            path.extend(&child.run);
+            Self::rec_collect_candidates(child, minimal, path, res);
+            path.truncate(depth);
+        }
+    }
+
+    /// counts the sets below the node that are subsets of e
+    fn rec_count_subsets<T:Copy+Into<usize>>(node:&Node, e:&[T]) -> usize {
+        let nb_children_sets:usize = e.iter().enumerate()
+            .filter_map(|(i,v)| node.children.get((*v).into()).map(|child| (i,child)))
+            .filter_map(|(i,child)| match_subset_run(&child.run, e, i+1).map(|j| (j,child)))
+            .map(|(j,child)| Self::rec_count_subsets(child, &e[j..]))
+            .sum();
+        nb_children_sets + usize::from(node.has_set())
+    }
+
+    /// counts the sets below the node that are supersets of e (uses the number of sets of
+    /// the subtrees once all the elements of e are found)
+    fn rec_count_supersets<T:Copy+Into<usize>>(node:&Node, e:&[T]) -> usize {
+        let Some(v) = e.first() else { return node.nb_sets; };
+        let first = (*v).into();
+        node.children.iter()
+            .take_while(|(l,_)| *l <= first)
+            .map(|(l,child)| {
+                let rest = if l == first { &e[1..] } else { e };
+                match_superset_run(&child.run, rest, 0)
+                    .map_or(0, |j| Self::rec_count_supersets(child, &rest[j..]))
+            })
+            .sum()
     }
 
     /// generates the graphviz representation of the trie.
@@ -231,48 +1155,210 @@ impl TrieSetStore {
         let shape = if node.contains_set { "doublecircle" } else { "circle" };
         let mut res = format!("\t{} [label=\"\",shape=\"{}\"];\n", id, shape);
         let mut current_id = id+1;
-        for (v,child) in node.children.iter().enumerate() {
-            if let Some(c) = child {
-                res += format!("\t{} -> {} [label=\"{}\"];\n",
-                    id, current_id, v+child.as_ref().unwrap().offset
-                ).as_str();
-                let (tmp_str, next_id) = Self::node_to_graphviz(c, current_id);
-                res += tmp_str.as_str();
-                current_id = next_id;
-            }
+        for (v,c) in node.children.iter() {
+            let label:Vec<String> = std::iter::once(v).chain(c.run.iter().copied())
+                .map(|e| e.to_string())
+                .collect();
+            res += format!("\t{} -> {} [label=\"{}\"];\n",
+                id, current_id, label.join(",")
+            ).as_str();
+            let (tmp_str, next_id) = Self::node_to_graphviz(c, current_id);
+            res += tmp_str.as_str();
+            current_id = next_id;
         }
         (res, current_id)
     }
 
-    /// removes e[index:] from node
-    fn remove_rec<T:Copy+Eq+Into<usize>>(node:&mut Node, e:&[T], index:usize) -> bool {
-        if index == e.len() { // right spot is found
-            if node.has_set() { node.remove_set(); true }
-            else { false }
-        } else {
-            // check if the next element exists, and perform a recursive remove
-            if node.is_out_of_bounds(e[index].into()) { return false; }
-            match node.child_mut(e[index].into()) {
-                None => { false }
-                Some(child) => { // if the child exists, recursive call
-                    let res = Self::remove_rec(&mut **child, e, index+1);
-                    if !res { return false; }
-                    // if an element was removed, check its children, and possibly remove it
-                    if child.nb_children() == 0 && !child.has_set() {
-                        node.remove_child(e[index].into());
-                    }
-                    true
-                }
+    /// adds below a the sets of the subtree of b. The path of b is the path of a followed by
+    /// b_rest (the end of the run of b). Returns the number of inserted sets
+    fn rec_union(a:&mut Node, b:&Node, b_rest:&[usize], supports:&mut Vec<usize>) -> usize {
+        let nb_inserted = match b_rest.split_first() {
+            None => {
+                let nb_children_sets:usize = b.children.iter()
+                    .map(|(v,child)| Self::union_child(a, v, child, &child.run, supports))
+                    .sum();
+                nb_children_sets + usize::from(b.has_set() && a.add_set())
+            },
+            Some((v,rest)) => Self::union_child(a, *v, b, rest, supports),
+        };
+        a.nb_sets += nb_inserted;
+        nb_inserted
+    }
+
+    /// adds below a the sets of the subtree of b, reached from a by the label v followed by
+    /// the run. Returns the number of inserted sets
+    fn union_child(a:&mut Node, v:usize, b:&Node, run:&[usize], supports:&mut Vec<usize>) -> usize {
+        match a.children.get_mut(v) {
+            None => {
+                add_supports(supports, std::iter::once(v).chain(run.iter().copied()), b.nb_sets);
+                Self::rec_supports(b, supports, true);
+                let mut child = b.clone();
+                child.run = run.to_vec();
+                a.children.insert(v, child);
+                b.nb_sets
+            },
+            Some(child) => {
+                let p = child.run.iter().zip(run).take_while(|(x,y)| x == y).count();
+                if p < child.run.len() { child.split(p); }
+                let res = Self::rec_union(child, b, &run[p..], supports);
+                add_supports(supports, std::iter::once(v).chain(child.run.iter().copied()), res);
+                child.compress();
+                res
+            },
+        }
+    }
+
+    /// follows the path from the node b, b_rest being the end of the run of b not consumed
+    /// yet. Returns the reached node and the rest of its run (None if the path does not exist)
+    fn descend<'a>(b:&'a Node, b_rest:&'a [usize], path:&[usize]) -> Option<(&'a Node, &'a [usize])> {
+        let (mut node, mut rest) = (b, b_rest);
+        for x in path {
+            match rest.split_first() {
+                Some((r,tail)) => {
+                    if r != x { return None; }
+                    rest = tail;
+                },
+                None => {
+                    node = node.children.get(*x)?;
+                    rest = &node.run;
+                },
             }
         }
+        Some((node, rest))
+    }
+
+    /// removes the sets below a that are stored (keep_common=false) or not stored
+    /// (keep_common=true) below the position b of the other trie (same path as a).
+    /// Returns the number of removed sets
+    fn rec_filter(a:&mut Node, b:Option<(&Node,&[usize])>, keep_common:bool, supports:&mut Vec<usize>) -> usize {
+        let Some((b_node, b_rest)) = b else {
+            // no set of the subtree is stored in the other trie
+            if !keep_common { return 0; }
+            Self::rec_supports(a, supports, false);
+            return a.clear();
+        };
+        let in_other = b_rest.is_empty() && b_node.has_set();
+        let mut nb_removed = 0;
+        if a.has_set() && in_other != keep_common {
+            a.remove_set();
+            nb_removed += 1;
+        }
+        let labels:Vec<usize> = a.children.iter().map(|(l,_)| l).collect();
+        for l in labels {
+            let child = a.children.get_mut(l).unwrap();
+            let position = Self::descend(b_node, b_rest, &[l])
+                .and_then(|(n,r)| Self::descend(n, r, &child.run));
+            let nb_child_removed = Self::rec_filter(child, position, keep_common, supports);
+            sub_supports(supports, std::iter::once(l).chain(child.run.iter().copied()), nb_child_removed);
+            nb_removed += nb_child_removed;
+            child.compress();
+        }
+        if nb_removed > 0 { a.children.retain(|_,c| c.nb_sets > 0); }
+        a.nb_sets -= nb_removed;
+        nb_removed
+    }
+
+    /// inserts e below node. Returns true if the set was inserted
+    fn insert_rec<T:Copy+Into<usize>>(node:&mut Node, e:&[T]) -> bool {
+        let res = match e.first() {
+            None => node.add_set(),
+            Some(v) => {
+                let value = (*v).into();
+                let rest = &e[1..];
+                match node.children.get_mut(value) {
+                    None => {
+                        // new leaf: the rest of the set is compressed in its run
+                        node.children.insert(value, Node::leaf(rest.iter().map(|r| (*r).into()).collect()));
+                        true
+                    },
+                    Some(child) => {
+                        let p = child.run.iter().zip(rest).take_while(|(r,q)| **r == (**q).into()).count();
+                        if p < child.run.len() { child.split(p); }
+                        Self::insert_rec(child, &rest[p..])
+                    },
+                }
+            },
+        };
+        if res { node.nb_sets += 1; }
+        res
+    }
+
+    /// removes the sets below the node that are supersets of e (in a single traversal).
+    /// Children left without sets are removed. Returns the number of removed sets
+    fn rec_remove_supersets<T:Copy+Into<usize>>(node:&mut Node, e:&[T], supports:&mut Vec<usize>) -> usize {
+        // every set of the subtree is a superset
+        let Some(v) = e.first() else {
+            Self::rec_supports(node, supports, false);
+            return node.clear();
+        };
+        let first = (*v).into();
+        let labels:Vec<usize> = node.children.iter().map(|(l,_)| l).take_while(|l| *l <= first).collect();
+        let mut nb_removed = 0;
+        for l in labels {
+            let child = node.children.get_mut(l).unwrap();
+            let rest = if l == first { &e[1..] } else { e };
+            let Some(j) = match_superset_run(&child.run, rest, 0) else { continue; };
+            let nb_child_removed = Self::rec_remove_supersets(child, &rest[j..], supports);
+            sub_supports(supports, std::iter::once(l).chain(child.run.iter().copied()), nb_child_removed);
+            nb_removed += nb_child_removed;
+            child.compress();
+        }
+        if nb_removed > 0 { node.children.retain(|_,c| c.nb_sets > 0); }
+        node.nb_sets -= nb_removed;
+        nb_removed
+    }
+
+    /// removes the sets below the node that are subsets of e (in a single traversal).
+    /// Children left without sets are removed. Returns the number of removed sets
+    fn rec_remove_subsets<T:Copy+Into<usize>>(node:&mut Node, e:&[T], supports:&mut Vec<usize>) -> usize {
+        let mut nb_removed = usize::from(node.has_set());
+        node.remove_set();
+        for (i,v) in e.iter().enumerate() {
+            let Some(child) = node.children.get_mut((*v).into()) else { continue; };
+            let Some(j) = match_subset_run(&child.run, e, i+1) else { continue; };
+            let nb_child_removed = Self::rec_remove_subsets(child, &e[j..], supports);
+            sub_supports(supports, std::iter::once((*v).into()).chain(child.run.iter().copied()), nb_child_removed);
+            nb_removed += nb_child_removed;
+            child.compress();
+        }
+        node.children.retain(|_,c| c.nb_sets > 0);
+        node.nb_sets -= nb_removed;
+        nb_removed
+    }
+
+    /// removes e from node (e follows the run of the node)
+    fn remove_rec<T:Copy+Eq+Into<usize>>(node:&mut Node, e:&[T]) -> bool {
+        let Some(v) = e.first() else { // right spot is found
+            if node.has_set() { node.remove_set(); node.nb_sets -= 1; return true; }
+            return false;
+        };
+        // check if the next element exists, and perform a recursive remove
+        let value = (*v).into();
+        let Some(child) = node.children.get_mut(value) else { return false; };
+        let Some(rest) = strip_run(&child.run, &e[1..]) else { return false; };
+        if !Self::remove_rec(child, rest) { return false; }
+        // if an element was removed, remove the child if its subtree has no set
+        if child.nb_sets == 0 {
+            node.children.remove(value);
+        } else {
+            child.compress();
+        }
+        node.nb_sets -= 1;
+        true
     }
 }
 
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
 
+    use std::ops::ControlFlow;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::set_store::list::ListSetStore;
+
     #[test]
     fn test_insert_emptyset() {
         let mut trie = TrieSetStore::default();
@@ -343,8 +1429,579 @@ pub mod test {
         trie.insert(&b);
         trie.insert(&c);
         assert_eq!(trie.find_supersets(&b).count(), 2);
+        assert_eq!(trie.find_supersets(&c).collect::<Vec<_>>(), vec![vec![1], vec![1,2,3], vec![1,3]]);
         // println!("{}", trie.to_graphviz());
         // println!("{:?}", trie.find_supersets(&b));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_lazy_queries() {
+        let mut trie = TrieSetStore::default();
+        for i in 0..50usize {
+            let s:Vec<usize> = (0..10).filter(|j| (i >> (j % 6)) & 1 == 1).collect();
+            trie.insert(&s);
+        }
+        let query:Vec<usize> = vec![0];
+        let mut supersets = trie.find_supersets(&query);
+        assert!(supersets.next().unwrap().contains(&0));
+        assert!(supersets.all(|s| s.contains(&0)));
+        let empty:Vec<usize> = vec![];
+        assert_eq!(trie.find_subsets(&empty).count(), 1);
+        assert_eq!(trie.find_supersets(&empty).take(3).count(), 3);
+    }
+
+    #[test]
+    fn test_query_results_non_contiguous() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![3,17,42], vec![17,99], vec![42], vec![5,17], vec![]];
+        for s in &sets { trie.insert(s); }
+        let query_subsets:Vec<usize> = vec![3,5,17,42];
+        let subsets:Vec<Vec<usize>> = trie.find_subsets(&query_subsets).collect();
+        assert_eq!(subsets, vec![vec![], vec![3,17,42], vec![5,17], vec![42]]);
+        let query_supersets:Vec<usize> = vec![17];
+        let supersets:Vec<Vec<usize>> = trie.find_supersets(&query_supersets).collect();
+        assert_eq!(supersets, vec![vec![3,17,42], vec![5,17], vec![17,99]]);
+        // every returned set is a stored set
+        let empty:Vec<usize> = vec![];
+        assert!(trie.find_supersets(&empty).all(|s| trie.contains(&s) && sets.contains(&s)));
+    }
+
+    #[test]
+    fn test_existence_queries() {
+        let mut trie = TrieSetStore::default();
+        let a:Vec<usize> = vec![1,3];
+        let b:Vec<usize> = vec![2,5,7];
+        trie.insert(&a);
+        trie.insert(&b);
+        let queries:Vec<Vec<usize>> = vec![vec![], vec![1], vec![1,2,3], vec![1,2,5], vec![5], vec![3,5], vec![2,5,7,9]];
+        for q in &queries {
+            assert_eq!(trie.contains_subset_of(q), trie.find_subsets(q).next().is_some());
+            assert_eq!(trie.contains_superset_of(q), trie.find_supersets(q).next().is_some());
+        }
+        assert!(trie.contains_subset_of(&queries[2]));
+        assert!(!trie.contains_subset_of(&queries[3]));
+        assert!(trie.contains_superset_of(&queries[4]));
+        assert!(!trie.contains_superset_of(&queries[5]));
+        assert!(trie.remove(&a));
+        assert!(trie.remove(&b));
+        assert!(!trie.contains_superset_of(&queries[0]));
+    }
+
+    #[test]
+    fn test_count_queries() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![], vec![1], vec![1,3], vec![2,3], vec![1,2,3,4], vec![3,5]];
+        for set in &sets { trie.insert(set); }
+        assert!(!trie.insert(&sets[2]));
+        let queries:Vec<Vec<usize>> = vec![vec![], vec![1], vec![3], vec![1,3], vec![1,2,3], vec![2,4], vec![6]];
+        for q in &queries {
+            assert_eq!(trie.count_subsets(q), trie.find_subsets(q).count());
+            assert_eq!(trie.count_supersets(q), trie.find_supersets(q).count());
+        }
+        assert_eq!(trie.count_subsets(&queries[4]), 4);
+        assert_eq!(trie.count_supersets(&queries[2]), 4);
+        assert!(trie.remove(&sets[4]));
+        assert_eq!(trie.count_supersets(&queries[5]), 0);
+        assert_eq!(trie.count_supersets(&queries[0]), 5);
+    }
+
+    #[test]
+    fn test_subsumption_insertions() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![1,2,3], vec![2,4], vec![1,2,3,4], vec![2], vec![5]];
+        let empty:Vec<usize> = vec![];
+        // minimal sets
+        assert!(trie.insert_if_minimal(&sets[0], true));
+        assert!(trie.insert_if_minimal(&sets[1], true));
+        assert!(!trie.insert_if_minimal(&sets[2], true));
+        assert!(!trie.insert_if_minimal(&sets[0], true));
+        assert!(trie.insert_if_minimal(&sets[3], false));
+        assert_eq!(trie.count_supersets(&empty), 3);
+        assert!(trie.insert_if_minimal(&sets[4], true));
+        assert!(trie.remove(&sets[0]));
+        assert!(trie.remove(&sets[1]));
+        // maximal sets
+        assert!(!trie.insert_if_maximal(&sets[3], true));
+        assert!(trie.insert_if_maximal(&sets[2], true));
+        assert_eq!(trie.find_supersets(&empty).collect::<Vec<Vec<usize>>>(), vec![sets[2].clone(), sets[4].clone()]);
+        assert!(!trie.insert_if_maximal(&sets[1], false));
+    }
+
+    #[test]
+    fn test_bulk_removals() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![], vec![1,3], vec![1,2,3], vec![2], vec![3,4], vec![2,5]];
+        for set in &sets[..5] { trie.insert(set); }
+        assert_eq!(trie.remove_supersets_of(&sets[3]), 2);
+        assert_eq!(trie.find_supersets(&sets[0]).collect::<Vec<Vec<usize>>>(), vec![sets[0].clone(), sets[1].clone(), sets[4].clone()]);
+        assert_eq!(trie.remove_subsets_of(&sets[2]), 2);
+        assert_eq!(trie.find_supersets(&sets[0]).collect::<Vec<Vec<usize>>>(), vec![sets[4].clone()]);
+        assert_eq!(trie.remove_supersets_of(&sets[0]), 1);
+        assert!(!trie.contains_superset_of(&sets[0]));
+        assert!(trie.insert(&sets[5]));
+        assert_eq!(trie.count_subsets(&sets[5]), 1);
+    }
+
+    #[test]
+    fn test_minimal_maximal_sets() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![1,3], vec![1,2,3], vec![2], vec![3], vec![3,4], vec![1,5], vec![1,5,6]];
+        for set in &sets { trie.insert(set); }
+        let minimal:Vec<Vec<usize>> = trie.iter_minimal().collect();
+        assert_eq!(minimal, vec![sets[5].clone(), sets[2].clone(), sets[3].clone()]);
+        let maximal:Vec<Vec<usize>> = trie.iter_maximal().collect();
+        assert_eq!(maximal, vec![sets[1].clone(), sets[6].clone(), sets[4].clone()]);
+        let mut list = ListSetStore::default();
+        for set in &sets { list.insert(set); }
+        assert_eq!(list.iter_minimal().count(), 3);
+        assert_eq!(list.iter_maximal().collect::<Vec<Vec<usize>>>(), vec![sets[1].clone(), sets[4].clone(), sets[6].clone()]);
+    }
+
+    #[test]
+    fn test_path_compression() {
+        let mut trie = TrieSetStore::default();
+        let long:Vec<usize> = (10..30).collect();
+        let prefix:Vec<usize> = (10..15).collect();
+        let branch:Vec<usize> = vec![10,11,12,40];
+        trie.insert(&long);
+        assert_eq!(trie.node_count(), 2);
+        trie.insert(&prefix);
+        assert_eq!(trie.node_count(), 3);
+        trie.insert(&branch);
+        assert_eq!(trie.node_count(), 5);
+        assert!(trie.contains(&prefix));
+        assert!(!trie.contains(&prefix[..4]));
+        assert_eq!(trie.find_subsets(&long).collect::<Vec<_>>(), vec![prefix.clone(), long.clone()]);
+        let query:Vec<usize> = vec![11,14];
+        assert_eq!(trie.find_supersets(&query).collect::<Vec<_>>(), vec![prefix.clone(), long.clone()]);
+        assert_eq!(trie.count_supersets(&query[..1]), 3);
+        // removing sets restores the compressed chains
+        assert!(trie.remove(&prefix));
+        assert_eq!(trie.node_count(), 4);
+        assert!(trie.remove(&branch));
+        assert_eq!(trie.node_count(), 2);
+        assert_eq!(trie.find_supersets(&query).collect::<Vec<_>>(), vec![long.clone()]);
+    }
+
+    #[test]
+    fn test_random_against_list() {
+        /// random set of close elements (creates long compressed runs)
+        fn random_set(rng:&mut StdRng) -> Vec<usize> {
+            let start = rng.gen_range(0..20);
+            let len = rng.gen_range(0..8);
+            (start..start+len).filter(|_| rng.gen_bool(0.8)).collect()
+        }
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut trie = TrieSetStore::default();
+        let mut list = ListSetStore::default();
+        for step in 0..2000 {
+            let set = random_set(&mut rng);
+            match step % 5 {
+                0 | 1 => { assert_eq!(trie.insert(&set), list.insert(&set)); },
+                2 => { assert_eq!(trie.remove(&set), list.remove(&set)); },
+                3 => {
+                    let prefix = &set[..set.len().min(2)];
+                    assert_eq!(trie.remove_supersets_of(prefix), list.remove_supersets_of(prefix));
+                },
+                _ => { assert_eq!(trie.remove_subsets_of(&set), list.remove_subsets_of(&set)); },
+            }
+            let query = random_set(&mut rng);
+            let mut subsets:Vec<Vec<usize>> = trie.find_subsets(&query).collect();
+            subsets.sort();
+            let mut expected:Vec<Vec<usize>> = list.find_subsets(&query).collect();
+            expected.sort();
+            assert_eq!(subsets, expected);
+            let mut supersets:Vec<Vec<usize>> = trie.find_supersets(&query).collect();
+            supersets.sort();
+            let mut expected_supersets:Vec<Vec<usize>> = list.find_supersets(&query).collect();
+            expected_supersets.sort();
+            assert_eq!(supersets, expected_supersets);
+            assert_eq!(trie.count_subsets(&query), subsets.len());
+            assert_eq!(trie.count_supersets(&query), supersets.len());
+            assert_eq!(trie.contains(&query), list.contains(&query));
+            assert!(trie.supports().eq(list.supports().map(|(e,n)| (*e,n))));
+        }
+        let mut minimal:Vec<Vec<usize>> = trie.iter_minimal().collect();
+        minimal.sort();
+        let mut expected:Vec<Vec<usize>> = list.iter_minimal().collect();
+        expected.sort();
+        assert_eq!(minimal, expected);
+    }
+
+    #[test]
+    fn test_adaptive_children() {
+        let mut trie = TrieSetStore::default();
+        let sparse:Vec<Vec<usize>> = (0..20).map(|i| vec![i*1000, i*1000+1]).collect();
+        for set in &sparse { trie.insert(set); }
+        assert!(matches!(trie.root.as_ref().unwrap().children, Children::Sparse(_)));
+        let dense:Vec<Vec<usize>> = (0..20).map(|i| vec![i]).collect();
+        let mut trie_dense = TrieSetStore::default();
+        for set in &dense { trie_dense.insert(set); }
+        assert!(matches!(trie_dense.root.as_ref().unwrap().children, Children::Dense { .. }));
+        let query:Vec<usize> = vec![3,5,7,1000];
+        assert_eq!(trie_dense.find_subsets(&query).collect::<Vec<_>>(), vec![vec![3], vec![5], vec![7]]);
+        assert_eq!(trie.find_supersets(&query[3..]).collect::<Vec<_>>(), vec![sparse[1].clone()]);
+        // removing most children makes the node sparse again
+        for set in &dense[2..] { assert!(trie_dense.remove(set)); }
+        assert!(matches!(trie_dense.root.as_ref().unwrap().children, Children::Sparse(_)));
+        assert_eq!(trie_dense.find_subsets(&query).count(), 0);
+        assert!(trie_dense.contains(&dense[1]));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut trie = TrieSetStore::default();
+        assert_eq!(trie.stats(), TrieStats {
+            nb_sets:0, nb_nodes:1, depth_histogram:vec![1], avg_branching_factor:0., nb_compressed_elements:0
+        });
+        let sets:Vec<Vec<usize>> = vec![vec![1,2,3], vec![1,2,4], vec![1,5], vec![7]];
+        for set in &sets { trie.insert(set); }
+        assert_eq!(SetStore::<usize>::len(&trie), 4);
+        // root -> {1 (no set), 7}, 1 -> {2 (no set), 5}, 2 -> {3, 4}
+        assert_eq!(trie.stats(), TrieStats {
+            nb_sets:4, nb_nodes:7, depth_histogram:vec![1,2,2,2], avg_branching_factor:2., nb_compressed_elements:0
+        });
+        assert!(trie.remove(&sets[1]));
+        assert!(trie.remove(&sets[2]));
+        let stats = trie.stats();
+        assert_eq!((stats.nb_sets, stats.nb_nodes, stats.nb_compressed_elements), (2, 3, 2));
+        assert_eq!(stats.depth_histogram, vec![1,2]);
+        assert_eq!(trie.node_count(), stats.nb_nodes);
+    }
+
+    #[test]
+    fn test_store_operations() {
+        /// random set of close elements
+        fn random_set(rng:&mut StdRng) -> Vec<usize> {
+            let start = rng.gen_range(0..10);
+            (start..start+6).filter(|_| rng.gen_bool(0.6)).collect()
+        }
+        /// returns the sorted sets of the store
+        fn sorted_sets<S:SetStore<usize>>(store:&S) -> Vec<Vec<usize>> {
+            let mut res:Vec<Vec<usize>> = store.find_supersets(&[]).collect();
+            res.sort();
+            res
+        }
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let (mut trie_a, mut trie_b) = (TrieSetStore::default(), TrieSetStore::default());
+            let (mut list_a, mut list_b) = (ListSetStore::default(), ListSetStore::default());
+            for _ in 0..30 {
+                let a = random_set(&mut rng);
+                let b = random_set(&mut rng);
+                trie_a.insert(&a);
+                list_a.insert(&a);
+                trie_b.insert(&b);
+                list_b.insert(&b);
+                // common sets
+                if rng.gen_bool(0.3) {
+                    trie_a.insert(&b);
+                    list_a.insert(&b);
+                }
+            }
+            let mut union = TrieSetStore::default();
+            union.union_with(&trie_a);
+            let mut list_union = ListSetStore::default();
+            list_union.union_with(&list_a);
+            assert_eq!(union.union_with(&trie_b), list_union.union_with(&list_b));
+            assert_eq!(sorted_sets(&union), sorted_sets(&list_union));
+            // the merged trie is as compact as a trie built by insertions
+            let mut rebuilt = TrieSetStore::default();
+            for set in sorted_sets(&union) { rebuilt.insert(&set); }
+            assert_eq!(union.node_count(), rebuilt.node_count());
+            assert_eq!(SetStore::<usize>::len(&union), list_union.len());
+            let mut intersection = TrieSetStore::default();
+            intersection.union_with(&trie_a);
+            let mut list_intersection = ListSetStore::default();
+            list_intersection.union_with(&list_a);
+            assert_eq!(intersection.intersect_with(&trie_b), list_intersection.intersect_with(&list_b));
+            assert_eq!(sorted_sets(&intersection), sorted_sets(&list_intersection));
+            assert_eq!(trie_a.difference_with(&trie_b), list_a.difference_with(&list_b));
+            assert_eq!(sorted_sets(&trie_a), sorted_sets(&list_a));
+            assert_eq!(SetStore::<usize>::len(&trie_a), list_a.len());
+        }
+    }
+
+    #[test]
+    fn test_lexicographic_iteration() {
+        let mut trie = TrieSetStore::default();
+        let mut list = ListSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![2,3], vec![1,2,3,4,5], vec![], vec![1,3], vec![1,2], vec![12], vec![1,2,3]];
+        for set in &sets {
+            trie.insert(set);
+            list.insert(set);
+        }
+        let mut expected = sets.clone();
+        expected.sort();
+        assert_eq!(trie.iter::<usize>().collect::<Vec<_>>(), expected);
+        assert_eq!((&trie).into_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(list.iter().map(|s| s.to_vec()).collect::<Vec<_>>(), expected);
+        let mut nb_sets = 0;
+        for set in &list {
+            assert!(trie.contains(set));
+            nb_sets += 1;
+        }
+        assert_eq!(nb_sets, sets.len());
+    }
+
+    #[test]
+    fn test_cardinality_constrained_queries() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut trie = TrieSetStore::default();
+        let mut list = ListSetStore::default();
+        for _ in 0..300 {
+            let set:Vec<usize> = (0..12).filter(|_| rng.gen_bool(0.3)).collect();
+            trie.insert(&set);
+            list.insert(&set);
+        }
+        for _ in 0..50 {
+            let query:Vec<usize> = (0..12).filter(|_| rng.gen_bool(0.3)).collect();
+            let k = rng.gen_range(0..4);
+            let supersets:Vec<Vec<usize>> = trie.find_supersets_with_at_most(&query, k).collect();
+            assert!(supersets.iter().all(|s| s.len() <= query.len() + k));
+            let mut expected:Vec<Vec<usize>> = list.find_supersets_with_at_most(&query, k).collect();
+            expected.sort();
+            assert_eq!(supersets, expected);
+            let query_subsets:Vec<usize> = (0..12).filter(|_| rng.gen_bool(0.7)).collect();
+            let subsets:Vec<Vec<usize>> = trie.find_subsets_with_at_least(&query_subsets, k+1).collect();
+            assert!(subsets.iter().all(|s| s.len() > k));
+            let mut expected_subsets:Vec<Vec<usize>> = list.find_subsets_with_at_least(&query_subsets, k+1).collect();
+            expected_subsets.sort();
+            assert_eq!(subsets, expected_subsets);
+        }
+    }
+
+    #[test]
+    fn test_partial_match_queries() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![1,2,3], vec![1,3,5], vec![2,3,4,5,6,7], vec![3], vec![3,8]];
+        for set in &sets { trie.insert(set); }
+        let required:Vec<usize> = vec![3];
+        let forbidden:Vec<usize> = vec![5,8];
+        assert_eq!(trie.find_matching(&required, &forbidden).collect::<Vec<_>>(), vec![sets[0].clone(), sets[3].clone()]);
+        let mut list = ListSetStore::default();
+        for set in &sets { list.insert(set); }
+        assert_eq!(list.find_matching(&required, &forbidden).collect::<Vec<_>>(), vec![sets[0].clone(), sets[3].clone()]);
+        // without forbidden elements, this is a super-set query
+        let empty:Vec<usize> = vec![];
+        assert_eq!(trie.find_matching(&required, &empty).count(), 5);
+        // without required elements, only the forbidden ones are excluded
+        let forbidden_three:Vec<usize> = vec![3];
+        assert_eq!(trie.find_matching(&empty, &forbidden_three).count(), 0);
+        assert_eq!(trie.find_matching(&empty, &forbidden[..1]).count(), 3);
+    }
+
+    #[test]
+    fn test_binary_format() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = vec![vec![], vec![1,2,3], vec![1,2,4,100], vec![5], vec![1000,1001,1002]];
+        for set in &sets { trie.insert(set); }
+        let mut buf:Vec<u8> = Vec::new();
+        trie.write_to(&mut buf).unwrap();
+        let read = TrieSetStore::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.iter::<usize>().collect::<Vec<_>>(), trie.iter::<usize>().collect::<Vec<_>>());
+        assert_eq!(read.stats(), trie.stats());
+        // truncated or corrupted data
+        assert!(TrieSetStore::read_from(&mut &buf[..buf.len()-1]).is_err());
+        assert!(TrieSetStore::read_from(&mut &buf[1..]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut trie = TrieSetStore::default();
+        let sets:Vec<Vec<usize>> = (0..30).map(|i| (i..i+5).filter(|j| j % 3 != 0).collect()).collect();
+        for set in &sets { trie.insert(set); }
+        let bytes = bincode::serialize(&trie).unwrap();
+        let read:TrieSetStore = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(read.iter::<usize>().collect::<Vec<_>>(), trie.iter::<usize>().collect::<Vec<_>>());
+        assert_eq!(SetStore::<usize>::len(&read), SetStore::<usize>::len(&trie));
+        // missing root, or inconsistent number of sets
+        let no_root = bincode::serialize(&(None::<()>, Vec::<usize>::new())).unwrap();
+        assert!(bincode::deserialize::<TrieSetStore>(&no_root).is_err());
+        trie.root.as_mut().unwrap().nb_sets += 1;
+        let inconsistent = bincode::serialize(&trie).unwrap();
+        assert!(bincode::deserialize::<TrieSetStore>(&inconsistent).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_corrupted() {
+        /// serializes the trie after corrupting its root
+        fn corrupted<F:FnOnce(&mut Node)>(sets:&[Vec<usize>], f:F) -> Vec<u8> {
+            let mut trie = TrieSetStore::default();
+            for set in sets { trie.insert(set); }
+            f(trie.root.as_mut().unwrap());
+            bincode::serialize(&trie).unwrap()
+        }
+        let dense:Vec<Vec<usize>> = (0..20).map(|i| vec![i, i+100]).collect();
+        // wrong number of dense children: recomputed
+        let bytes = corrupted(&dense, |root| {
+            if let Children::Dense { len, .. } = &mut root.children { *len = 0; }
+        });
+        let mut read:TrieSetStore = bincode::deserialize(&bytes).unwrap();
+        assert!(read.remove(&[3,103]));
+        assert!(!read.remove(&[3]));
+        assert_eq!(SetStore::<usize>::len(&read), 19);
+        // unsorted sparse labels
+        let sparse:Vec<Vec<usize>> = vec![vec![1,5], vec![2,6], vec![3,7]];
+        let unsorted_labels = corrupted(&sparse, |root| {
+            if let Children::Sparse(list) = &mut root.children { list.swap(0, 2); }
+        });
+        assert!(bincode::deserialize::<TrieSetStore>(&unsorted_labels).is_err());
+        // unsorted run, and run not following the label
+        let unsorted_run = corrupted(&sparse, |root| root.children.get_mut(1).unwrap().run = vec![8,6]);
+        assert!(bincode::deserialize::<TrieSetStore>(&unsorted_run).is_err());
+        let run_before_label = corrupted(&sparse, |root| root.children.get_mut(2).unwrap().run = vec![0]);
+        assert!(bincode::deserialize::<TrieSetStore>(&run_before_label).is_err());
+        // label not following the run
+        let label_before_run = corrupted(&[vec![1,5,9], vec![1,5,10]], |root| root.children.get_mut(1).unwrap().run = vec![12]);
+        assert!(bincode::deserialize::<TrieSetStore>(&label_before_run).is_err());
+    }
+
+    #[test]
+    fn test_trail() {
+        let mut trie = TrieSetStore::default();
+        let empty:Vec<usize> = Vec::new();
+        trie.insert(&[1,2]);
+        trie.insert(&[3]);
+        assert!(!trie.pop_state());
+        trie.push_state();
+        trie.insert(&[1,2,3]);
+        assert!(trie.remove(&[3]));
+        trie.push_state();
+        assert_eq!(trie.nb_states(), 2);
+        assert!(trie.insert_if_minimal(&[1], true)); // evicts {1,2} and {1,2,3}
+        assert_eq!(trie.iter::<usize>().collect::<Vec<_>>(), vec![vec![1]]);
+        assert!(trie.pop_state());
+        assert_eq!(trie.iter::<usize>().collect::<Vec<_>>(), vec![vec![1,2], vec![1,2,3]]);
+        let mut other = TrieSetStore::default();
+        other.insert(&[5]);
+        other.insert(&[1,2]);
+        trie.union_with(&other);
+        trie.difference_with(&other);
+        assert_eq!(trie.remove_subsets_of(&[1,2,3]), 1);
+        assert!(SetStore::<usize>::is_empty(&trie));
+        assert!(trie.pop_state());
+        assert_eq!(trie.nb_states(), 0);
+        assert_eq!(trie.iter::<usize>().collect::<Vec<_>>(), vec![vec![1,2], vec![3]]);
+        assert_eq!(trie.count_supersets(&empty), 2);
+        // without saved state, nothing is recorded
+        trie.insert(&[4]);
+        assert!(trie.trail.is_empty());
+    }
+
+    #[test]
+    fn test_supports() {
+        /// computes the supports from the stored sets
+        fn expected_supports(trie:&TrieSetStore) -> Vec<(usize,usize)> {
+            let mut res = vec![0; 30];
+            for set in trie { for e in set { res[e] += 1; } }
+            res.into_iter().enumerate().filter(|(_,n)| *n > 0).collect()
+        }
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut random_trie = || {
+            let mut trie = TrieSetStore::default();
+            for _ in 0..30 {
+                let set:Vec<usize> = (0..30).filter(|_| rng.gen_bool(0.2)).collect();
+                trie.insert(&set);
+            }
+            trie
+        };
+        let (mut a, b, c) = (random_trie(), random_trie(), random_trie());
+        a.union_with(&b);
+        assert_eq!(a.supports().collect::<Vec<_>>(), expected_supports(&a));
+        a.push_state();
+        a.union_with(&c);
+        a.difference_with(&b);
+        assert_eq!(a.supports().collect::<Vec<_>>(), expected_supports(&a));
+        a.pop_state();
+        a.intersect_with(&c);
+        assert_eq!(a.supports().collect::<Vec<_>>(), expected_supports(&a));
+        a.union_with(&c);
+        a.difference_with(&b);
+        assert_eq!(a.supports().collect::<Vec<_>>(), expected_supports(&a));
+        let mut buf:Vec<u8> = Vec::new();
+        a.write_to(&mut buf).unwrap();
+        let read = TrieSetStore::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(read.supports().collect::<Vec<_>>(), expected_supports(&a));
+        assert_eq!(SetStore::<usize>::support(&read, 31), 0);
+    }
+
+    #[test]
+    fn test_from_sets() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let sets:Vec<Vec<usize>> = (0..300)
+            .map(|_| (0..40).filter(|_| rng.gen_bool(0.1)).collect())
+            .collect();
+        let built = TrieSetStore::from_sets(&sets);
+        let mut inserted = TrieSetStore::default();
+        for set in &sets { inserted.insert(set); }
+        assert_eq!(built.iter::<usize>().collect::<Vec<_>>(), inserted.iter::<usize>().collect::<Vec<_>>());
+        assert_eq!(built.stats(), inserted.stats());
+        assert!(built.supports().eq(inserted.supports()));
+        let empty = TrieSetStore::from_sets(Vec::<Vec<usize>>::new());
+        assert_eq!(empty.node_count(), 1);
+    }
+
+    #[test]
+    fn test_sampling() {
+        let sets:Vec<Vec<usize>> = vec![vec![], vec![1], vec![1,2,3], vec![1,4], vec![2,5,6]];
+        let trie = TrieSetStore::from_sets(&sets);
+        for (i,set) in sets.iter().enumerate() { assert_eq!(trie.nth::<usize>(i).as_ref(), Some(set)); }
+        assert_eq!(trie.nth::<usize>(5), None);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut counts = [0; 5];
+        for _ in 0..5000 {
+            let set:Vec<usize> = trie.sample(&mut rng).unwrap();
+            counts[sets.iter().position(|s| *s == set).unwrap()] += 1;
+        }
+        assert!(counts.iter().all(|c| (800..1200).contains(c)));
+        let mut drawn:Vec<Vec<usize>> = trie.sample_k(&mut rng, 10);
+        drawn.sort();
+        assert_eq!(drawn, sets);
+        assert_eq!(trie.sample_k::<usize,_>(&mut rng, 2).len(), 2);
+        assert_eq!(TrieSetStore::default().sample::<usize,_>(&mut rng), None);
+    }
+
+    #[test]
+    fn test_similarity_queries() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut random_set = || -> Vec<usize> { (0..25).filter(|_| rng.gen_bool(0.3)).collect() };
+        let sets:Vec<Vec<usize>> = (0..200).map(|_| random_set()).collect();
+        let trie = TrieSetStore::from_sets(&sets);
+        let list = ListSetStore::from_sets(&sets);
+        for _ in 0..50 {
+            let query = random_set();
+            let (set, overlap) = trie.find_max_overlap(&query).unwrap();
+            assert_eq!(overlap, list.find_max_overlap(&query).unwrap().1);
+            assert_eq!(set.iter().filter(|e| query.contains(e)).count(), overlap);
+            let (_, similarity) = trie.find_best_jaccard(&query).unwrap();
+            assert!((similarity - list.find_best_jaccard(&query).unwrap().1).abs() < 1e-9);
+        }
+        let mut small = TrieSetStore::default();
+        let empty:Vec<usize> = Vec::new();
+        assert_eq!(small.find_max_overlap(&empty), None);
+        small.insert(&[1,2,3,4]);
+        small.insert(&[2]);
+        assert_eq!(small.find_max_overlap(&[2,3]), Some((vec![1,2,3,4], 2)));
+        assert_eq!(small.find_best_jaccard(&[2,5]), Some((vec![2], 0.5)));
+    }
+
+    #[test]
+    fn test_limited_queries() {
+        let trie = TrieSetStore::from_sets([vec![1usize,2], vec![1,2,3], vec![1,2,4], vec![2,3]]);
+        assert_eq!(trie.find_supersets_limit(&[2], 2), vec![vec![1,2], vec![1,2,3]]);
+        assert_eq!(trie.find_subsets_limit(&[1,2,3,4], 0), Vec::<Vec<usize>>::new());
+        let mut witnesses = Vec::new();
+        let res = trie.for_each_superset(&[2,3], |set:&[usize]| {
+            witnesses.push(set.to_vec());
+            ControlFlow::Break(())
+        });
+        assert!(res.is_break());
+        assert_eq!(witnesses, vec![vec![1,2,3]]);
+        let mut nb_subsets = 0;
+        let _ = trie.for_each_subset(&[1,2,3], |_:&[usize]| { nb_subsets += 1; ControlFlow::<()>::Continue(()) });
+        assert_eq!(nb_subsets, 3);
+    }
+}
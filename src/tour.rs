@@ -0,0 +1,173 @@
+/// implements a tour (cyclic permutation of the cities 0..n) for TSP-like local search.
+///
+/// The tour is stored as an array of cities and the position of each city (array
+/// representation). next, prev and between only compare positions. A 2-opt move reverses the
+/// shorter of the two paths it can reverse, thus the orientation of the tour may change
+/// (the cycle is the same).
+/// If *n* is the number of cities, this data-structure has the following complexities:
+/// - memory: O(n)
+/// - next, prev, between, position: O(1)
+/// - path reversal: O(length of the path)
+/// - 2-opt move: O(min(k, n-k)) where k is the length of the reversed path
+/// - segment move (or-opt): O(n)
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Tour {
+    /// city at each position
+    cities:Vec<usize>,
+    /// position of each city
+    positions:Vec<usize>,
+}
+
+impl Tour {
+    /// creates the tour 0, 1, ..., n-1
+    pub fn new(n:usize) -> Self { Self { cities:(0..n).collect(), positions:(0..n).collect() } }
+
+    /// creates a tour visiting the cities in the given order.
+    /// Panics if the order is not a permutation of 0..n
+    pub fn from_order(order:&[usize]) -> Self {
+        let mut positions = vec![usize::MAX; order.len()];
+        for (p,c) in order.iter().enumerate() {
+            assert!(*c < order.len() && positions[*c] == usize::MAX, "Tour: the order is not a permutation");
+            positions[*c] = p;
+        }
+        Self { cities:order.to_vec(), positions }
+    }
+
+    /// returns the number of cities
+    pub fn len(&self) -> usize { self.cities.len() }
+
+    /// returns true iff the tour has no city
+    pub fn is_empty(&self) -> bool { self.cities.is_empty() }
+
+    /// returns the position of a city
+    pub fn position(&self, city:usize) -> usize { self.positions[city] }
+
+    /// returns the city at a position
+    pub fn city_at(&self, position:usize) -> usize { self.cities[position] }
+
+    /// returns the city following a city
+    pub fn next(&self, city:usize) -> usize {
+        let p = self.positions[city] + 1;
+        self.cities[if p == self.len() { 0 } else { p }]
+    }
+
+    /// returns the city preceding a city
+    pub fn prev(&self, city:usize) -> usize {
+        let p = self.positions[city];
+        self.cities[if p == 0 { self.len() - 1 } else { p - 1 }]
+    }
+
+    /// returns true iff b is on the path from a to c (following next, a and c included)
+    pub fn between(&self, a:usize, b:usize, c:usize) -> bool {
+        let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+        if pa <= pc { pa <= pb && pb <= pc } else { pb >= pa || pb <= pc }
+    }
+
+    /// returns the number of cities on the path from a to b (following next, both included)
+    pub fn path_len(&self, a:usize, b:usize) -> usize {
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        (pb + self.len() - pa) % self.len() + 1
+    }
+
+    /// reverses the path from a to b (following next): next(prev(a)) becomes b, and a becomes
+    /// the city preceding the former next(b). The orientation of the rest of the tour is kept
+    pub fn reverse(&mut self, a:usize, b:usize) {
+        let n = self.len();
+        let (mut i, mut j) = (self.positions[a], self.positions[b]);
+        for _ in 0..self.path_len(a, b)/2 {
+            let (ci, cj) = (self.cities[i], self.cities[j]);
+            self.cities[i] = cj;
+            self.cities[j] = ci;
+            self.positions[cj] = i;
+            self.positions[ci] = j;
+            i = if i + 1 == n { 0 } else { i + 1 };
+            j = if j == 0 { n - 1 } else { j - 1 };
+        }
+    }
+
+    /// 2-opt move: replaces the edges (a, next(a)) and (c, next(c)) by the edges (a, c) and
+    /// (next(a), next(c)). Reverses the shorter path (the orientation of the tour may change)
+    pub fn two_opt(&mut self, a:usize, c:usize) {
+        let (na, nc) = (self.next(a), self.next(c));
+        if self.path_len(na, c) <= self.path_len(nc, a) { self.reverse(na, c); } else { self.reverse(nc, a); }
+    }
+
+    /// or-opt move: moves the path from s to e (following next) between the city after (not in
+    /// the path) and its next city, keeping the orientation of the path
+    pub fn move_segment(&mut self, s:usize, e:usize, after:usize) {
+        debug_assert!(!self.between(s, after, e), "Tour: the destination is in the moved path");
+        if self.prev(s) == after { return; }
+        let ne = self.next(e);
+        // s..e ne..after  →  (reversal) after..ne e..s  →  ne..after s..e
+        self.reverse(s, after);
+        self.reverse(after, ne);
+        self.reverse(e, s);
+    }
+
+    /// returns the cities from the city start (following next)
+    pub fn iter_from(&self, start:usize) -> impl Iterator<Item=usize> + '_ {
+        let p = self.positions[start];
+        self.cities[p..].iter().chain(&self.cities[..p]).copied()
+    }
+
+    /// returns the cities in the order of their positions
+    pub fn order(&self) -> &[usize] { &self.cities }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// returns the undirected edges of a tour
+    fn edges(tour:&Tour) -> Vec<(usize,usize)> {
+        let mut res:Vec<(usize,usize)> = (0..tour.len()).map(|c| (c.min(tour.next(c)), c.max(tour.next(c)))).collect();
+        res.sort_unstable();
+        res
+    }
+
+    #[test]
+    fn navigation() {
+        let tour = Tour::from_order(&[3, 0, 4, 1, 2]);
+        assert_eq!((tour.next(3), tour.prev(3), tour.next(2)), (0, 2, 3));
+        assert!(tour.between(4, 2, 0) && tour.between(1, 3, 0) && !tour.between(1, 0, 2));
+        assert!(tour.between(0, 0, 0));
+        assert_eq!((tour.path_len(4, 0), tour.path_len(0, 0)), (5, 1));
+        assert_eq!(tour.iter_from(1).collect::<Vec<_>>(), vec![1, 2, 3, 0, 4]);
+    }
+
+    #[test]
+    fn reversals() {
+        let mut tour = Tour::new(6);
+        tour.reverse(4, 1); // path 4 5 0 1 (wrapping)
+        assert_eq!(tour.iter_from(2).collect::<Vec<_>>(), vec![2, 3, 1, 0, 5, 4]);
+        let mut moved = Tour::new(8);
+        moved.move_segment(1, 2, 5);
+        assert_eq!(moved.iter_from(0).collect::<Vec<_>>(), vec![0, 3, 4, 5, 1, 2, 6, 7]);
+        moved.move_segment(6, 0, 4); // path 6 7 0 (wrapping)
+        assert_eq!(moved.iter_from(3).collect::<Vec<_>>(), vec![3, 4, 6, 7, 0, 5, 1, 2]);
+    }
+
+    #[test]
+    fn random_two_opt() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let n = 30;
+        let mut tour = Tour::new(n);
+        let mut naive:Vec<usize> = (0..n).collect();
+        for _ in 0..500 {
+            let (a, c) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            if a == c { continue; }
+            // naive: same orientation as the tour, a first, then reverse next(a)..=c
+            let pa = naive.iter().position(|x| *x == a).unwrap();
+            naive.rotate_left(pa);
+            if naive[1] != tour.next(a) { naive[1..].reverse(); }
+            tour.two_opt(a, c);
+            let pc = naive.iter().position(|x| *x == c).unwrap();
+            naive[1..=pc].reverse();
+            assert_eq!(edges(&tour), edges(&Tour::from_order(&naive)));
+            assert!(tour.next(a) == c || tour.prev(a) == c);
+        }
+    }
+}
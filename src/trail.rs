@@ -0,0 +1,166 @@
+/// Data-structure whose state can be saved and restored (backtracking search).
+///
+/// Implemented by the reversible structures of the crate (reversible sparse set, domain,
+/// reversible union-find, trie set store) and by the Trail, so that a solver can save and
+/// restore all of them at once (see save_all and restore_all_to).
+pub trait Backtrackable {
+    /// saves the current state
+    fn save_level(&mut self);
+
+    /// restores the last saved state. Returns false if no level was saved
+    fn restore_level(&mut self) -> bool;
+
+    /// returns the number of saved levels
+    fn level(&self) -> usize;
+
+    /// restores the states until the number of saved levels is level (nothing is done if it
+    /// is already lower or equal)
+    fn restore_to(&mut self, level:usize) {
+        while self.level() > level { self.restore_level(); }
+    }
+}
+
+/// saves a level of every structure
+pub fn save_all(structures:&mut [&mut dyn Backtrackable]) {
+    for s in structures { s.save_level(); }
+}
+
+/// restores every structure until its number of saved levels is level
+pub fn restore_all_to(structures:&mut [&mut dyn Backtrackable], level:usize) {
+    for s in structures { s.restore_to(level); }
+}
+
+/// Trail of saved levels, shared by Reversible cells.
+///
+/// Each saved level has a unique identifier. A cell remembers its previous value together
+/// with the identifier of the level at which it changed. Cells are restored lazily: when a
+/// cell is accessed, the values changed at levels that were restored since then are undone.
+/// Thus, saving or restoring a level is O(1) whatever the number of cells.
+#[derive(Debug,Clone,Default)]
+pub struct Trail {
+    /// identifiers of the saved levels (increasing)
+    ids:Vec<u64>,
+    /// next level identifier
+    next_id:u64,
+}
+
+impl Trail {
+    /// creates a trail without saved levels
+    pub fn new() -> Self { Self::default() }
+
+    /// returns the identifier of the current level (0 if no level was saved)
+    fn current_id(&self) -> u64 { self.ids.last().copied().unwrap_or(0) }
+
+    /// returns true iff the level identifier was not restored
+    fn is_active(&self, id:u64) -> bool { id == 0 || self.ids.binary_search(&id).is_ok() }
+}
+
+impl Backtrackable for Trail {
+    fn save_level(&mut self) {
+        self.next_id += 1;
+        self.ids.push(self.next_id);
+    }
+
+    fn restore_level(&mut self) -> bool { self.ids.pop().is_some() }
+
+    fn level(&self) -> usize { self.ids.len() }
+}
+
+/// Reversible value: its modifications are undone when the levels of its trail are restored.
+/// The cell is not borrowed by the trail: it is given the trail on each access.
+/// - memory: one previous value per level at which the cell changed
+/// - get/set: O(1) amortized (plus O(log levels) to check the level of the last change)
+#[derive(Debug,Clone)]
+pub struct Reversible<T> {
+    /// current value
+    value:T,
+    /// previous values (level identifier at which the value changed, value before it)
+    history:Vec<(u64,T)>,
+}
+
+impl<T:Clone> Reversible<T> {
+    /// creates a cell
+    pub fn new(value:T) -> Self { Self { value, history:Vec::new() } }
+
+    /// returns the value (after undoing the changes made at restored levels)
+    pub fn get(&mut self, trail:&Trail) -> &T {
+        self.sync(trail);
+        &self.value
+    }
+
+    /// changes the value (the previous one is restored with the current level)
+    pub fn set(&mut self, trail:&Trail, value:T) {
+        self.sync(trail);
+        let id = trail.current_id();
+        // the value before the first change at this level is the one to restore
+        if id != 0 && self.history.last().is_none_or(|(last,_)| *last != id) {
+            self.history.push((id, self.value.clone()));
+        }
+        self.value = value;
+    }
+
+    /// undoes the changes made at restored levels
+    fn sync(&mut self, trail:&Trail) {
+        while let Some((id,_)) = self.history.last() {
+            if trail.is_active(*id) { break; }
+            self.value = self.history.pop().unwrap().1;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::set::domain::Domain;
+    use crate::set::reversible_sparse_set::ReversibleSparseSet;
+    use crate::set::union_find::ReversibleUnionFind;
+    use crate::set_store::SetStore;
+    use crate::set_store::set_trie::TrieSetStore;
+
+    #[test]
+    fn test_reversible_cells() {
+        let mut trail = Trail::new();
+        let mut x = Reversible::new(1);
+        let mut y = Reversible::new(String::from("a"));
+        x.set(&trail, 2); // before any level: never undone
+        trail.save_level();
+        x.set(&trail, 3);
+        x.set(&trail, 4);
+        trail.save_level();
+        y.set(&trail, String::from("b"));
+        assert_eq!((*x.get(&trail), y.get(&trail).as_str()), (4, "b"));
+        trail.restore_level();
+        assert_eq!(y.get(&trail), "a");
+        trail.save_level(); // new level: the changes of the restored one must not leak
+        x.set(&trail, 5);
+        trail.restore_to(1);
+        assert_eq!(*x.get(&trail), 4);
+        trail.restore_to(0);
+        assert_eq!(*x.get(&trail), 2);
+        assert!(!trail.restore_level());
+    }
+
+    #[test]
+    fn test_structures_together() {
+        let mut trail = Trail::new();
+        let mut set = ReversibleSparseSet::new(0, 9);
+        let mut domain = Domain::new(0, 9);
+        let mut uf = ReversibleUnionFind::new(4);
+        let mut depth = Reversible::new(0);
+        let mut trie = TrieSetStore::default();
+        save_all(&mut [&mut trail, &mut set, &mut domain, &mut uf, &mut trie]);
+        set.remove(3);
+        domain.remove_below(5);
+        uf.union(0, 1);
+        depth.set(&trail, 1);
+        SetStore::<usize>::insert(&mut trie, &[1, 2]);
+        restore_all_to(&mut [&mut trail, &mut set, &mut domain, &mut uf, &mut trie], 0);
+        assert!(SetStore::<usize>::is_empty(&trie));
+        assert!(set.contains(3));
+        assert_eq!(domain.min(), Some(0));
+        assert!(!uf.same(0, 1));
+        assert_eq!(*depth.get(&trail), 0);
+    }
+}